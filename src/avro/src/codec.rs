@@ -13,16 +13,21 @@ use std::str::FromStr;
 
 #[cfg(feature = "snappy")]
 use byteorder;
+use bzip2::read::BzDecoder;
+use bzip2::write::BzEncoder;
 #[cfg(feature = "snappy")]
 use crc;
 use failure::Error;
 use libflate::deflate::{Decoder, Encoder};
+use serde::{Deserialize, Serialize};
+use xz2::read::XzDecoder;
+use xz2::write::XzEncoder;
 
 use crate::types::{ToAvro, Value};
 use crate::util::DecodeError;
 
 /// The compression codec used to compress blocks.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Codec {
     /// The `Null` codec simply passes through data uncompressed.
     Null,
@@ -35,6 +40,14 @@ pub enum Codec {
     /// compression library. Each compressed block is followed by the 4-byte, big-endian
     /// CRC32 checksum of the uncompressed data in the block.
     Snappy,
+    /// The `Zstandard` codec uses Facebook's [Zstandard](https://facebook.github.io/zstd/)
+    /// compression library.
+    Zstandard,
+    /// The `Bzip2` codec uses the [bzip2](https://sourceware.org/bzip2/) compression library.
+    Bzip2,
+    /// The `Xz` codec uses the [xz](https://tukaani.org/xz/) compression library, which implements
+    /// the LZMA2 algorithm.
+    Xz,
 }
 
 impl ToAvro for Codec {
@@ -45,6 +58,9 @@ impl ToAvro for Codec {
                 Codec::Deflate => "deflate",
                 #[cfg(feature = "snappy")]
                 Codec::Snappy => "snappy",
+                Codec::Zstandard => "zstandard",
+                Codec::Bzip2 => "bzip2",
+                Codec::Xz => "xz",
             }
             .to_owned()
             .into_bytes(),
@@ -61,6 +77,9 @@ impl FromStr for Codec {
             "deflate" => Ok(Codec::Deflate),
             #[cfg(feature = "snappy")]
             "snappy" => Ok(Codec::Snappy),
+            "zstandard" => Ok(Codec::Zstandard),
+            "bzip2" => Ok(Codec::Bzip2),
+            "xz" => Ok(Codec::Xz),
             _ => Err(DecodeError::new("unrecognized codec")),
         }
     }
@@ -90,6 +109,19 @@ impl Codec {
 
                 *stream = encoded;
             }
+            Codec::Zstandard => {
+                *stream = zstd::stream::encode_all(&stream[..], 0)?;
+            }
+            Codec::Bzip2 => {
+                let mut encoder = BzEncoder::new(Vec::new(), bzip2::Compression::default());
+                encoder.write_all(stream)?;
+                *stream = encoder.finish()?;
+            }
+            Codec::Xz => {
+                let mut encoder = XzEncoder::new(Vec::new(), 6);
+                encoder.write_all(stream)?;
+                *stream = encoder.finish()?;
+            }
         };
 
         Ok(())
@@ -128,6 +160,19 @@ impl Codec {
                 }
                 *stream = decoded;
             }
+            Codec::Zstandard => {
+                *stream = zstd::stream::decode_all(&stream[..])?;
+            }
+            Codec::Bzip2 => {
+                let mut decoded = Vec::new();
+                BzDecoder::new(&stream[..]).read_to_end(&mut decoded)?;
+                *stream = decoded;
+            }
+            Codec::Xz => {
+                let mut decoded = Vec::new();
+                XzDecoder::new(&stream[..]).read_to_end(&mut decoded)?;
+                *stream = decoded;
+            }
         };
 
         Ok(())
@@ -172,4 +217,52 @@ mod tests {
         codec.decompress(&mut stream).unwrap();
         assert_eq!(INPUT, stream.as_slice());
     }
+
+    #[test]
+    fn zstandard_compress_and_decompress() {
+        let codec = Codec::Zstandard;
+        let mut stream = INPUT.to_vec();
+        codec.compress(&mut stream).unwrap();
+        assert_ne!(INPUT, stream.as_slice());
+        assert!(INPUT.len() > stream.len());
+        codec.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+    }
+
+    #[test]
+    fn bzip2_compress_and_decompress() {
+        let codec = Codec::Bzip2;
+        let mut stream = INPUT.to_vec();
+        codec.compress(&mut stream).unwrap();
+        assert_ne!(INPUT, stream.as_slice());
+        codec.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+    }
+
+    #[test]
+    fn xz_compress_and_decompress() {
+        let codec = Codec::Xz;
+        let mut stream = INPUT.to_vec();
+        codec.compress(&mut stream).unwrap();
+        assert_ne!(INPUT, stream.as_slice());
+        codec.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+    }
+
+    #[test]
+    fn codec_names_round_trip() {
+        for codec in &[
+            Codec::Null,
+            Codec::Deflate,
+            Codec::Zstandard,
+            Codec::Bzip2,
+            Codec::Xz,
+        ] {
+            let name = match codec.avro() {
+                Value::Bytes(name) => String::from_utf8(name).unwrap(),
+                value => panic!("unexpected codec name {:?}", value),
+            };
+            assert_eq!(Codec::from_str(&name).unwrap(), *codec);
+        }
+    }
 }
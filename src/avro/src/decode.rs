@@ -274,7 +274,7 @@ pub fn decode<'a, R: Read>(schema: SchemaNode<'a>, reader: &'a mut R) -> Result<
         }
         SchemaPiece::Enum { ref symbols, .. } => {
             if let Value::Int(index) = decode_int(reader)? {
-                if index >= 0 && (index as usize) <= symbols.len() {
+                if index >= 0 && (index as usize) < symbols.len() {
                     let symbol = symbols[index as usize].clone();
                     Ok(Value::Enum(index, symbol))
                 } else {
@@ -337,11 +337,17 @@ pub fn decode<'a, R: Read>(schema: SchemaNode<'a>, reader: &'a mut R) -> Result<
                 .into()),
             }
         }
-        SchemaPiece::ResolveEnum { doc: _, symbols } => {
+        SchemaPiece::ResolveEnum {
+            doc: _,
+            symbols,
+            default,
+        } => {
             if let Value::Int(index) = decode_int(reader)? {
-                if index >= 0 && (index as usize) <= symbols.len() {
-                    match symbols[index as usize].clone() {
-                        Some(symbol) => Ok(Value::Enum(index, symbol)), // Todo (brennan) -- should this actually be the index in reader? Does it matter?
+                if index >= 0 && (index as usize) < symbols.len() {
+                    // Symbols that the reader does not know resolve to the
+                    // reader's default symbol, if it has one.
+                    match symbols[index as usize].clone().or_else(|| default.clone()) {
+                        Some((index, symbol)) => Ok(Value::Enum(index as i32, symbol)),
                         None => Err(DecodeError::new(format!(
                             "Enum symbol at index {} in writer schema not found in reader",
                             index
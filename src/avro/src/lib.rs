@@ -159,7 +159,7 @@
 //!
 //! ## Using codecs to compress data
 //!
-//! Avro supports three different compression codecs when encoding data:
+//! Avro supports several different compression codecs when encoding data:
 //!
 //! * **Null**: leaves data uncompressed;
 //! * **Deflate**: writes the data block using the deflate algorithm as specified in RFC 1951, and
@@ -168,6 +168,10 @@
 //! * **Snappy**: uses Google's [Snappy](http://google.github.io/snappy/) compression library. Each
 //! compressed block is followed by the 4-byte, big-endianCRC32 checksum of the uncompressed data in
 //! the block. You must enable the `snappy` feature to use this codec.
+//! * **Zstandard**: uses Facebook's [Zstandard](https://facebook.github.io/zstd/) compression
+//! library.
+//! * **Bzip2**: uses the [bzip2](https://sourceware.org/bzip2/) compression library.
+//! * **Xz**: uses the [xz](https://tukaani.org/xz/) compression library.
 //!
 //! To specify a codec to use to compress data, just specify it while creating a `Writer`:
 //! ```
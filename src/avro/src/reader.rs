@@ -422,6 +422,7 @@ impl<'a> SchemaResolver<'a> {
                 SchemaPiece::Enum {
                     symbols: r_symbols,
                     doc,
+                    default_idx,
                 },
             ) => {
                 let r_map = r_symbols
@@ -431,17 +432,12 @@ impl<'a> SchemaResolver<'a> {
                     .collect::<HashMap<_, _>>();
                 let symbols = w_symbols
                     .iter()
-                    .map(|s| {
-                        if r_map.contains_key(&s) {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
+                    .map(|s| r_map.get(&s).map(|i| (*i, s.clone())))
                     .collect();
                 SchemaPiece::ResolveEnum {
                     doc: doc.clone(),
                     symbols,
+                    default: default_idx.map(|i| (i, r_symbols[i].clone())),
                 }
             }
             (SchemaPiece::Fixed { size: wsz }, SchemaPiece::Fixed { size: rsz }) => {
@@ -745,7 +741,7 @@ pub fn from_avro_datum<R: Read>(schema: &Schema, reader: &mut R) -> Result<Value
 mod tests {
     use super::*;
     use crate::types::{Record, ToAvro};
-    use crate::Reader;
+    use crate::{Reader, Writer};
 
     use std::io::Cursor;
 
@@ -823,6 +819,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reader_codecs() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut record = Record::new(schema.top_node()).unwrap();
+        record.put("a", 27i64);
+        record.put("b", "foo");
+
+        for codec in &[Codec::Deflate, Codec::Zstandard, Codec::Bzip2, Codec::Xz] {
+            let mut writer = Writer::with_codec(schema.clone(), Vec::new(), *codec);
+            writer.append(record.clone()).unwrap();
+            writer.append(record.clone()).unwrap();
+            writer.flush().unwrap();
+            let input = writer.into_inner();
+
+            let reader = Reader::new(&input[..]).unwrap();
+            let values = reader.collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(values, vec![record.clone().avro(), record.clone().avro()]);
+        }
+    }
+
     #[test]
     fn test_reader_invalid_header() {
         let schema = Schema::parse_str(SCHEMA).unwrap();
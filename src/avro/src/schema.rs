@@ -12,6 +12,7 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 
+use chrono::{NaiveDate, NaiveDateTime};
 use digest::Digest;
 use failure::{Error, Fail};
 use log::{debug, warn};
@@ -31,7 +32,7 @@ use std::collections::hash_map::Entry;
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::rc::Rc;
-use types::Value as AvroValue;
+use types::{DecimalValue, Value as AvroValue};
 
 pub fn resolve_schemas(writer_schema: &Schema, reader_schema: &Schema) -> Result<Schema, Error> {
     let r_indices = reader_schema.indices.clone();
@@ -235,6 +236,9 @@ pub enum SchemaPiece {
     Enum {
         doc: Documentation,
         symbols: Vec<String>,
+        /// The index of the symbol to use when resolving a writer's symbol
+        /// that is not in `symbols`, if any.
+        default_idx: Option<usize>,
     },
     /// A `fixed` Avro schema.
     Fixed { size: usize },
@@ -255,10 +259,13 @@ pub enum SchemaPiece {
     /// The two schemas may have different values and the values may be in a different order.
     ResolveEnum {
         doc: Documentation,
-        /// Symbols in the reader schema if they exist in the writer schema,
-        /// or `None` otherwise.
-        symbols: Vec<Option<String>>,
-        // TODO(brennan) - These should support default values
+        /// For each symbol in the writer schema, its index in the reader
+        /// schema and the symbol itself if it exists in the reader schema, or
+        /// `None` otherwise.
+        symbols: Vec<Option<(usize, String)>>,
+        /// The reader's default symbol, and its index, which replaces writer
+        /// symbols that do not exist in the reader schema.
+        default: Option<(usize, String)>,
     },
 }
 
@@ -885,9 +892,29 @@ impl SchemaParser {
                     .ok_or_else(|| ParseSchemaError::new("Unable to parse `symbols` in enum"))
             })?;
 
+        let default_idx = match complex.get("default") {
+            None => None,
+            Some(default) => {
+                let default = default
+                    .as_str()
+                    .ok_or_else(|| ParseSchemaError::new("Unable to parse `default` in enum"))?;
+                let idx = symbols
+                    .iter()
+                    .position(|s: &String| s == default)
+                    .ok_or_else(|| {
+                        ParseSchemaError::new(format!(
+                            "Enum default {} is not among its symbols",
+                            default
+                        ))
+                    })?;
+                Some(idx)
+            }
+        };
+
         Ok(SchemaPiece::Enum {
             doc: complex.doc(),
             symbols,
+            default_idx,
         })
     }
 
@@ -1337,9 +1364,14 @@ impl<'a> SchemaSubtreeDeepCloner<'a> {
                     .collect(),
                 lookup: lookup.clone(),
             },
-            SchemaPiece::Enum { doc, symbols } => SchemaPiece::Enum {
+            SchemaPiece::Enum {
+                doc,
+                symbols,
+                default_idx,
+            } => SchemaPiece::Enum {
                 doc: doc.clone(),
                 symbols: symbols.clone(),
+                default_idx: *default_idx,
             },
             SchemaPiece::Fixed { size } => SchemaPiece::Fixed { size: *size },
             SchemaPiece::ResolveRecord {
@@ -1368,9 +1400,14 @@ impl<'a> SchemaSubtreeDeepCloner<'a> {
                     .collect(),
                 n_reader_fields: *n_reader_fields,
             },
-            SchemaPiece::ResolveEnum { doc, symbols } => SchemaPiece::ResolveEnum {
+            SchemaPiece::ResolveEnum {
+                doc,
+                symbols,
+                default,
+            } => SchemaPiece::ResolveEnum {
                 doc: doc.clone(),
                 symbols: symbols.clone(),
+                default: default.clone(),
             },
         }
     }
@@ -1438,6 +1475,36 @@ impl<'a> SchemaNode<'a> {
                         AvroValue::Float(n.as_f64().unwrap() as f32)
                     }
                     SchemaPiece::Double => AvroValue::Double(n.as_f64().unwrap()),
+                    SchemaPiece::Date => {
+                        let days =
+                            n.as_i64()
+                                .and_then(|i| i32::try_from(i).ok())
+                                .ok_or_else(|| {
+                                    ParseSchemaError(format!("{} is not a 32-bit integer", n))
+                                })?;
+                        let date = NaiveDate::from_ymd(1970, 1, 1)
+                            .checked_add_signed(chrono::Duration::days(days.into()))
+                            .ok_or_else(|| {
+                                ParseSchemaError(format!("Invalid num days from epoch: {}", n))
+                            })?;
+                        AvroValue::Date(date)
+                    }
+                    SchemaPiece::TimestampMilli | SchemaPiece::TimestampMicro => {
+                        let units_per_sec = match piece {
+                            SchemaPiece::TimestampMilli => 1_000,
+                            _ => 1_000_000,
+                        };
+                        let i = n.as_i64().ok_or_else(|| {
+                            ParseSchemaError(format!("{} is not a 64-bit integer", n))
+                        })?;
+                        let seconds = i.div_euclid(units_per_sec);
+                        let nanos = i.rem_euclid(units_per_sec) * (1_000_000_000 / units_per_sec);
+                        let ts = NaiveDateTime::from_timestamp_opt(seconds, nanos as u32)
+                            .ok_or_else(|| {
+                                ParseSchemaError(format!("Invalid timestamp in default: {}", n))
+                            })?;
+                        AvroValue::Timestamp(ts)
+                    }
                     _ => {
                         return Err(ParseSchemaError(format!(
                             "Unexpected number in default: {}",
@@ -1446,7 +1513,36 @@ impl<'a> SchemaNode<'a> {
                     }
                 }
             }
-            (String(s), SchemaPiece::Bytes) => AvroValue::Bytes(s.clone().into_bytes()),
+            (String(s), SchemaPiece::Bytes) => AvroValue::Bytes(json_string_to_bytes(s)?),
+            (
+                String(s),
+                SchemaPiece::Decimal {
+                    precision,
+                    scale,
+                    fixed_size,
+                },
+            ) => {
+                let unscaled = json_string_to_bytes(s)?;
+                if let Some(size) = fixed_size {
+                    if unscaled.len() != *size {
+                        return Err(ParseSchemaError(format!(
+                            "Decimal default {:?} does not have size {}",
+                            s, size
+                        )));
+                    }
+                }
+                AvroValue::Decimal(DecimalValue {
+                    unscaled,
+                    precision: *precision,
+                    scale: *scale,
+                })
+            }
+            (String(s), SchemaPiece::Json) => {
+                let j = serde_json::from_str(s).map_err(|e| {
+                    ParseSchemaError(format!("Invalid JSON in default {:?}: {}", s, e))
+                })?;
+                AvroValue::Json(j)
+            }
             (String(s), SchemaPiece::String) => AvroValue::String(s.clone()),
             (Object(map), SchemaPiece::Record { fields, .. }) => {
                 let field_values = fields
@@ -1486,8 +1582,15 @@ impl<'a> SchemaNode<'a> {
                     .collect::<Result<HashMap<_, _>, ParseSchemaError>>()?;
                 AvroValue::Map(map)
             }
-            (String(s), SchemaPiece::Fixed { size }) if s.len() == *size => {
-                AvroValue::Fixed(*size, s.clone().into_bytes())
+            (String(s), SchemaPiece::Fixed { size }) => {
+                let bytes = json_string_to_bytes(s)?;
+                if bytes.len() != *size {
+                    return Err(ParseSchemaError(format!(
+                        "Fixed default {:?} does not have size {}",
+                        s, size
+                    )));
+                }
+                AvroValue::Fixed(*size, bytes)
             }
             // A default value always matches the first variant of a union
             (json, SchemaPiece::Union(us)) => match us.schemas.first() {
@@ -1507,6 +1610,18 @@ impl<'a> SchemaNode<'a> {
     }
 }
 
+/// Converts the JSON encoding of a `bytes` or `fixed` default value, in which
+/// each character stands for the byte with the same code point, to bytes.
+fn json_string_to_bytes(s: &str) -> Result<Vec<u8>, ParseSchemaError> {
+    s.chars()
+        .map(|c| {
+            u8::try_from(u32::from(c)).map_err(|_| {
+                ParseSchemaError(format!("Invalid character {:?} in bytes default", c))
+            })
+        })
+        .collect()
+}
+
 #[derive(Clone)]
 struct SchemaSerContext<'a> {
     node: SchemaNodeOrNamed<'a>,
@@ -1654,11 +1769,18 @@ impl<'a> Serialize for SchemaSerContext<'a> {
                         )?;
                         map.end()
                     }
-                    SchemaPiece::Enum { symbols, .. } => {
+                    SchemaPiece::Enum {
+                        symbols,
+                        default_idx,
+                        ..
+                    } => {
                         let mut map = serializer.serialize_map(None)?;
                         map.serialize_entry("type", "enum")?;
                         map.serialize_entry("name", &name)?;
                         map.serialize_entry("symbols", symbols)?;
+                        if let Some(default_idx) = default_idx {
+                            map.serialize_entry("default", &symbols[*default_idx])?;
+                        }
                         map.end()
                     }
                     SchemaPiece::Fixed { size } => {
@@ -2000,9 +2122,24 @@ mod tests {
                 "clubs".to_owned(),
                 "hearts".to_owned(),
             ],
+            default_idx: None,
         };
 
         assert_eq!(&expected, schema.top_node().inner);
+
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Suit", "symbols": ["diamonds", "spades"], "default": "spades"}"#,
+        )
+        .unwrap();
+        match schema.top_node().inner {
+            SchemaPiece::Enum { default_idx, .. } => assert_eq!(*default_idx, Some(1)),
+            other => panic!("unexpected schema piece: {:?}", other),
+        }
+
+        let res = Schema::parse_str(
+            r#"{"type": "enum", "name": "Suit", "symbols": ["diamonds", "spades"], "default": "clubs"}"#,
+        );
+        assert!(res.is_err());
     }
 
     #[test]
@@ -10,12 +10,13 @@
 //! Port of https://github.com/apache/avro/blob/master/lang/py/test/test_io.py
 use std::io::Cursor;
 
-use avro::schema::resolve_schemas;
-use avro::{
-    from_avro_datum, to_avro_datum, types::Value, Schema, SchemaResolutionError, ValidationError,
-};
+use chrono::NaiveDate;
 use lazy_static::lazy_static;
 
+use avro::schema::resolve_schemas;
+use avro::types::{DecimalValue, Value};
+use avro::{from_avro_datum, to_avro_datum, Schema, SchemaResolutionError, ValidationError};
+
 lazy_static! {
     static ref SCHEMAS_TO_VALIDATE: Vec<(&'static str, Value)> = vec![
         (r#""null""#, Value::Null),
@@ -50,16 +51,22 @@ lazy_static! {
         (r#""null""#, "null", Value::Null),
         (r#""boolean""#, "true", Value::Boolean(true)),
         (r#""string""#, r#""foo""#, Value::String("foo".to_string())),
-        //(r#""bytes""#, r#""\u00FF\u00FF""#, Value::Bytes(vec![0xff, 0xff])),
+        (r#""bytes""#, r#""\u00FF\u00FF""#, Value::Bytes(vec![0xff, 0xff])),
         (r#""int""#, "5", Value::Int(5)),
         (r#""long""#, "5", Value::Long(5)),
         (r#""float""#, "1.1", Value::Float(1.1)),
         (r#""double""#, "1.1", Value::Double(1.1)),
-        //(r#"{"type": "fixed", "name": "F", "size": 2}"#, r#""\u00FF\u00FF""#, Value::Bytes(vec![0xff, 0xff])),
-        //(r#"{"type": "enum", "name": "F", "symbols": ["FOO", "BAR"]}"#, r#""FOO""#, Value::Enum(0, "FOO".to_string())),
+        (r#"{"type": "fixed", "name": "F", "size": 2}"#, r#""\u00FF\u00FF""#, Value::Fixed(2, vec![0xff, 0xff])),
+        (r#"{"type": "enum", "name": "F", "symbols": ["FOO", "BAR"]}"#, r#""FOO""#, Value::Enum(0, "FOO".to_string())),
         (r#"{"type": "array", "items": "int"}"#, "[1, 2, 3]", Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)])),
         (r#"{"type": "map", "values": "int"}"#, r#"{"a": 1, "b": 2}"#, Value::Map([("a".to_string(), Value::Int(1)), ("b".to_string(), Value::Int(2))].iter().cloned().collect())),
-        //(r#"["int", "null"]"#, "5", Value::Union(Box::new(Value::Int(5)))),
+        // The default value of a union is that of its first variant.
+        (r#"["int", "null"]"#, "5", Value::Union(0, Box::new(Value::Int(5)))),
+        (r#"["null", "int"]"#, "null", Value::Union(0, Box::new(Value::Null))),
+        (r#"{"type": "int", "logicalType": "date"}"#, "18000", Value::Date(NaiveDate::from_ymd(2019, 4, 14))),
+        (r#"{"type": "long", "logicalType": "timestamp-millis"}"#, "-1", Value::Timestamp(NaiveDate::from_ymd(1969, 12, 31).and_hms_milli(23, 59, 59, 999))),
+        (r#"{"type": "long", "logicalType": "timestamp-micros"}"#, "1000001", Value::Timestamp(NaiveDate::from_ymd(1970, 1, 1).and_hms_micro(0, 0, 1, 1))),
+        (r#"{"type": "bytes", "logicalType": "decimal", "precision": 4, "scale": 2}"#, r#""\u0004\u00D2""#, Value::Decimal(DecimalValue { unscaled: vec![0x04, 0xd2], precision: 4, scale: 2 })),
         (r#"{"type": "record", "name": "F", "fields": [{"name": "A", "type": "int"}]}"#, r#"{"A": 5}"#,Value::Record(vec![("A".to_string(), Value::Int(5))])),
     ];
 
@@ -180,6 +187,26 @@ fn test_unknown_symbol() {
     assert!(decoded.is_err());
 }
 
+#[test]
+fn test_unknown_symbol_default() {
+    let writer_schema =
+        Schema::parse_str(r#"{"type": "enum", "name": "Test", "symbols": ["FOO", "BAR"]}"#)
+            .unwrap();
+    let reader_schema = Schema::parse_str(
+        r#"{"type": "enum", "name": "Test", "symbols": ["BAR", "BAZ"], "default": "BAZ"}"#,
+    )
+    .unwrap();
+    let resolved_schema = resolve_schemas(&writer_schema, &reader_schema).unwrap();
+    for (written, read) in &[
+        (Value::Enum(0, "FOO".into()), Value::Enum(1, "BAZ".into())),
+        (Value::Enum(1, "BAR".into()), Value::Enum(0, "BAR".into())),
+    ] {
+        let encoded = to_avro_datum(&writer_schema, written.clone()).unwrap();
+        let decoded = from_avro_datum(&resolved_schema, &mut Cursor::new(encoded)).unwrap();
+        assert_eq!(&decoded, read);
+    }
+}
+
 #[test]
 fn test_default_value() {
     for (field_type, default_json, default_datum) in DEFAULT_VALUE_EXAMPLES.iter() {
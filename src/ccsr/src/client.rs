@@ -35,30 +35,61 @@ impl Client {
         Ok(Schema {
             id,
             raw: res.schema,
+            references: res.references,
         })
     }
 
     /// Gets the latest schema for the specified subject.
     pub async fn get_schema_by_subject(&self, subject: &str) -> Result<Schema, GetBySubjectError> {
+        self.get_subject_version(subject, "latest").await
+    }
+
+    /// Gets the schema with the specified version of the specified subject.
+    pub async fn get_schema_by_subject_and_version(
+        &self,
+        subject: &str,
+        version: i32,
+    ) -> Result<Schema, GetBySubjectError> {
+        self.get_subject_version(subject, &version.to_string())
+            .await
+    }
+
+    async fn get_subject_version(
+        &self,
+        subject: &str,
+        version: &str,
+    ) -> Result<Schema, GetBySubjectError> {
         let mut url = self.url.clone();
-        url.set_path(&format!("/subjects/{}/versions/latest", subject));
+        url.set_path(&format!("/subjects/{}/versions/{}", subject, version));
         let res: GetBySubjectResponse = send_request(self.inner.get(url)).await?;
         Ok(Schema {
             id: res.id,
             raw: res.schema,
+            references: res.references,
         })
     }
 
-    /// Publishes a new schema for the specified subject. The ID of the new
-    /// schema is returned.
+    /// Publishes a new schema of type `schema_type` for the specified
+    /// subject. The ID of the new schema is returned.
     ///
     /// Note that if a schema that is identical to an existing schema for the
     /// same subject is published, the ID of the existing schema will be
     /// returned.
-    pub async fn publish_schema(&self, subject: &str, schema: &str) -> Result<i32, PublishError> {
+    pub async fn publish_schema(
+        &self,
+        subject: &str,
+        schema: &str,
+        schema_type: SchemaType,
+    ) -> Result<i32, PublishError> {
         let mut url = self.url.clone();
         url.set_path(&format!("/subjects/{}/versions", subject));
-        let json = json!({ "schema": schema }).to_string();
+        // Registries that predate support for multiple schema types reject
+        // the `schemaType` field, so only send it when it is required.
+        let json = match schema_type {
+            SchemaType::Avro => json!({ "schema": schema }),
+            _ => json!({ "schema": schema, "schemaType": schema_type.name() }),
+        }
+        .to_string();
         let res: PublishResponse = send_request(self.inner.post(url).body(json)).await?;
         Ok(res.id)
     }
@@ -106,6 +137,25 @@ where
     }
 }
 
+/// The type of a schema stored by a schema registry.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SchemaType {
+    /// An Avro schema, in its JSON representation.
+    Avro,
+    /// A Protobuf schema, as the text of a `.proto` file.
+    Protobuf,
+}
+
+impl SchemaType {
+    /// Returns the name that the schema registry uses for the schema type.
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaType::Avro => "AVRO",
+            SchemaType::Protobuf => "PROTOBUF",
+        }
+    }
+}
+
 /// A schema stored by a schema registry.
 #[derive(Debug, Eq, PartialEq)]
 pub struct Schema {
@@ -113,11 +163,29 @@ pub struct Schema {
     pub id: i32,
     /// The raw text representing the schema.
     pub raw: String,
+    /// The other schemas that the schema refers to, like the files that a
+    /// Protobuf schema imports.
+    pub references: Vec<SchemaReference>,
+}
+
+/// A reference from one schema to another, which names the referenced schema
+/// by subject and version.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize)]
+pub struct SchemaReference {
+    /// The name by which the referring schema refers to the schema, like the
+    /// path of an imported Protobuf file.
+    pub name: String,
+    /// The subject of the referenced schema.
+    pub subject: String,
+    /// The version of the referenced schema within its subject.
+    pub version: i32,
 }
 
 #[derive(Debug, Deserialize)]
 struct GetByIdResponse {
     schema: String,
+    #[serde(default)]
+    references: Vec<SchemaReference>,
 }
 
 /// Errors for schema lookups by ID.
@@ -168,6 +236,8 @@ impl fmt::Display for GetByIdError {
 struct GetBySubjectResponse {
     id: i32,
     schema: String,
+    #[serde(default)]
+    references: Vec<SchemaReference>,
 }
 
 /// Errors for schema lookups by subject.
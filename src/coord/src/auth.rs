@@ -0,0 +1,287 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Password hashing and verification.
+//!
+//! Role passwords are never stored in plain text. Instead, the catalog stores
+//! a SCRAM-SHA-256 verifier for each password, as described in [RFC 5802] and
+//! [RFC 7677]. A verifier contains enough information to check a plain-text
+//! password, or to check the proof that a client produces during a SCRAM
+//! authentication exchange, but not enough information to recover the
+//! password itself.
+//!
+//! Verifiers are serialized in the same format that PostgreSQL uses in its
+//! `pg_authid` catalog:
+//!
+//! ```text
+//! SCRAM-SHA-256$<iterations>:<salt>$<StoredKey>:<ServerKey>
+//! ```
+//!
+//! Unlike PostgreSQL, Materialize does not normalize passwords with SASLprep
+//! before hashing them, so clients that do apply SASLprep may fail to
+//! authenticate with passwords that contain non-ASCII characters.
+//!
+//! [RFC 5802]: https://tools.ietf.org/html/rfc5802
+//! [RFC 7677]: https://tools.ietf.org/html/rfc7677
+
+use std::fmt;
+use std::str::FromStr;
+
+use failure::{bail, format_err};
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+
+/// The number of PBKDF2 iterations to use when hashing a new password. This
+/// matches PostgreSQL's default.
+const ITERATIONS: u32 = 4096;
+
+/// The length, in bytes, of the salt to generate when hashing a new password.
+const SALT_LEN: usize = 16;
+
+/// The length, in bytes, of a SHA-256 digest.
+const KEY_LEN: usize = 32;
+
+/// The name of the SASL mechanism that verifiers support.
+pub const SCRAM_SHA_256: &str = "SCRAM-SHA-256";
+
+/// A SCRAM-SHA-256 password verifier.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ScramVerifier {
+    iterations: u32,
+    salt: Vec<u8>,
+    stored_key: Vec<u8>,
+    server_key: Vec<u8>,
+}
+
+impl ScramVerifier {
+    /// Constructs a verifier for `password` using a randomly-generated salt.
+    pub fn new(password: &str) -> Result<ScramVerifier, failure::Error> {
+        let mut salt = vec![0; SALT_LEN];
+        openssl::rand::rand_bytes(&mut salt)?;
+        ScramVerifier::from_password(password, salt, ITERATIONS)
+    }
+
+    /// Constructs a verifier for `role_name` that does not correspond to any
+    /// password.
+    ///
+    /// A mock verifier is useful for carrying out a SCRAM exchange with a
+    /// client that is attempting to authenticate as a nonexistent role. The
+    /// exchange is guaranteed to fail, but the client cannot distinguish
+    /// that failure from an incorrect password. To that end, the salt is
+    /// derived from `secret` and `role_name`, so that repeated attempts to
+    /// authenticate as the same role are offered the same salt, just as they
+    /// would be if the role existed.
+    pub fn mock(secret: &[u8], role_name: &str) -> Result<ScramVerifier, failure::Error> {
+        let mut salt = hmac(secret, role_name.as_bytes())?;
+        salt.truncate(SALT_LEN);
+        let mut stored_key = vec![0; KEY_LEN];
+        let mut server_key = vec![0; KEY_LEN];
+        openssl::rand::rand_bytes(&mut stored_key)?;
+        openssl::rand::rand_bytes(&mut server_key)?;
+        Ok(ScramVerifier {
+            iterations: ITERATIONS,
+            salt,
+            stored_key,
+            server_key,
+        })
+    }
+
+    fn from_password(
+        password: &str,
+        salt: Vec<u8>,
+        iterations: u32,
+    ) -> Result<ScramVerifier, failure::Error> {
+        let mut salted_password = vec![0; KEY_LEN];
+        openssl::pkcs5::pbkdf2_hmac(
+            password.as_bytes(),
+            &salt,
+            iterations as usize,
+            MessageDigest::sha256(),
+            &mut salted_password,
+        )?;
+        let client_key = hmac(&salted_password, b"Client Key")?;
+        let stored_key = openssl::sha::sha256(&client_key).to_vec();
+        let server_key = hmac(&salted_password, b"Server Key")?;
+        Ok(ScramVerifier {
+            iterations,
+            salt,
+            stored_key,
+            server_key,
+        })
+    }
+
+    /// Returns the number of PBKDF2 iterations that the client must perform.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Returns the salt with which the password was hashed.
+    pub fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    /// Reports whether `password` is the password from which this verifier
+    /// was constructed.
+    pub fn verify_password(&self, password: &str) -> Result<bool, failure::Error> {
+        let candidate = ScramVerifier::from_password(password, self.salt.clone(), self.iterations)?;
+        Ok(openssl::memcmp::eq(&candidate.stored_key, &self.stored_key))
+    }
+
+    /// Checks the `ClientProof` that a client sent during a SCRAM exchange
+    /// whose `AuthMessage` was `auth_message`.
+    ///
+    /// If the proof is valid, returns the `ServerSignature` with which the
+    /// server proves its own identity to the client. Otherwise returns `None`.
+    pub fn verify_proof(
+        &self,
+        auth_message: &[u8],
+        proof: &[u8],
+    ) -> Result<Option<Vec<u8>>, failure::Error> {
+        let client_signature = hmac(&self.stored_key, auth_message)?;
+        if proof.len() != client_signature.len() {
+            return Ok(None);
+        }
+        let client_key: Vec<u8> = proof
+            .iter()
+            .zip(&client_signature)
+            .map(|(p, s)| p ^ s)
+            .collect();
+        let stored_key = openssl::sha::sha256(&client_key);
+        if !openssl::memcmp::eq(&stored_key, &self.stored_key) {
+            return Ok(None);
+        }
+        Ok(Some(hmac(&self.server_key, auth_message)?))
+    }
+}
+
+impl fmt::Debug for ScramVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The keys are deliberately omitted, as they are sensitive.
+        f.debug_struct("ScramVerifier")
+            .field("iterations", &self.iterations)
+            .finish()
+    }
+}
+
+impl fmt::Display for ScramVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}${}:{}${}:{}",
+            SCRAM_SHA_256,
+            self.iterations,
+            openssl::base64::encode_block(&self.salt),
+            openssl::base64::encode_block(&self.stored_key),
+            openssl::base64::encode_block(&self.server_key),
+        )
+    }
+}
+
+impl FromStr for ScramVerifier {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<ScramVerifier, failure::Error> {
+        let parts: Vec<_> = s.split(|c| c == '$' || c == ':').collect();
+        let (iterations, salt, stored_key, server_key) = match parts.as_slice() {
+            [mechanism, iterations, salt, stored_key, server_key]
+                if *mechanism == SCRAM_SHA_256 =>
+            {
+                (iterations, salt, stored_key, server_key)
+            }
+            _ => bail!("invalid SCRAM-SHA-256 verifier"),
+        };
+        let decode = |s: &str| {
+            openssl::base64::decode_block(s)
+                .map_err(|_| format_err!("invalid base64 in SCRAM-SHA-256 verifier"))
+        };
+        let verifier = ScramVerifier {
+            iterations: iterations.parse()?,
+            salt: decode(salt)?,
+            stored_key: decode(stored_key)?,
+            server_key: decode(server_key)?,
+        };
+        if verifier.stored_key.len() != KEY_LEN || verifier.server_key.len() != KEY_LEN {
+            bail!("invalid key length in SCRAM-SHA-256 verifier");
+        }
+        Ok(verifier)
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Result<Vec<u8>, failure::Error> {
+    let key = PKey::hmac(key)?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(data)?;
+    Ok(signer.sign_to_vec()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_password() -> Result<(), failure::Error> {
+        let verifier = ScramVerifier::new("hunter2")?;
+        assert!(verifier.verify_password("hunter2")?);
+        assert!(!verifier.verify_password("hunter3")?);
+        assert!(!ScramVerifier::mock(b"secret", "alice")?.verify_password("hunter2")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mock_salt() -> Result<(), failure::Error> {
+        let alice = ScramVerifier::mock(b"secret", "alice")?;
+        assert_eq!(alice.salt().len(), SALT_LEN);
+        assert_eq!(
+            alice.salt(),
+            ScramVerifier::mock(b"secret", "alice")?.salt()
+        );
+        assert_ne!(alice.salt(), ScramVerifier::mock(b"secret", "bob")?.salt());
+        assert_ne!(alice.salt(), ScramVerifier::mock(b"other", "alice")?.salt());
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<(), failure::Error> {
+        let verifier = ScramVerifier::new("hunter2")?;
+        let roundtripped: ScramVerifier = verifier.to_string().parse()?;
+        assert_eq!(verifier, roundtripped);
+        assert!(roundtripped.verify_password("hunter2")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rfc7677_exchange() -> Result<(), failure::Error> {
+        // The example exchange from RFC 7677, Section 3, in which the user
+        // "user" authenticates with the password "pencil".
+        let verifier: ScramVerifier = "SCRAM-SHA-256$4096:W22ZaJ0SNY7soEsUEjb6gQ==$\
+            WG5d8oPm3OtcPnkdi4Uo7BkeZkBFzpcXkuLmtbsT4qY=:\
+            wfPLwcE6nTWhTAmQ7tl2KeoiWGPlZqQxSrmfPwDl2dU="
+            .parse()?;
+        assert!(verifier.verify_password("pencil")?);
+
+        let auth_message = "n=user,r=rOprNGfwEbeRWgbNEkqO,\
+            r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,s=W22ZaJ0SNY7soEsUEjb6gQ==,i=4096,\
+            c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0";
+        let proof = openssl::base64::decode_block("dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ=")?;
+        let server_signature = verifier.verify_proof(auth_message.as_bytes(), &proof)?;
+        assert_eq!(
+            server_signature.map(|s| openssl::base64::encode_block(&s)),
+            Some("6rriTRBi23WpRR/wtup+mMhUZUn/dB5nLTJRsjl95G4=".into()),
+        );
+
+        let mut bad_proof = proof;
+        bad_proof[0] ^= 1;
+        assert_eq!(
+            verifier.verify_proof(auth_message.as_bytes(), &bad_proof)?,
+            None
+        );
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::iter;
 use std::path::Path;
 use std::sync::{Arc, Mutex, MutexGuard};
@@ -24,11 +24,14 @@ use serde::{Deserialize, Serialize};
 use dataflow_types::{SinkConnector, SinkConnectorBuilder, SourceConnector};
 use expr::{GlobalId, Id, IdHumanizer, OptimizedRelationExpr, ScalarExpr};
 use repr::{RelationDesc, Row};
+use sql::catalog::{ObjectRef, Privilege, PUBLIC_ROLE};
 use sql::names::{DatabaseSpecifier, FullName, PartialName};
 use sql::plan::{Params, Plan, PlanContext};
 use transform::Optimizer;
 
+use crate::auth::ScramVerifier;
 use crate::catalog::error::{Error, ErrorKind};
+use crate::catalog::storage::StoredObject;
 use crate::session::Session;
 
 mod error;
@@ -38,6 +41,9 @@ const SYSTEM_CONN_ID: u32 = 0;
 
 pub const MZ_TEMP_SCHEMA: &str = "mz_temp";
 
+/// The role that owns the objects that are built into the system.
+pub const SYSTEM_ROLE: &str = "mz_system";
+
 /// A `Catalog` keeps track of the SQL objects known to the planner.
 ///
 /// For each object, it keeps track of both forward and reverse dependencies:
@@ -67,6 +73,7 @@ pub struct Catalog {
     indexes: HashMap<GlobalId, Vec<Vec<ScalarExpr>>>,
     ambient_schemas: BTreeMap<String, Schema>,
     temporary_schemas: HashMap<u32, Schema>,
+    roles: BTreeMap<String, Role>,
     storage: Arc<Mutex<storage::Connection>>,
     startup_time: SystemTime,
     nonce: u64,
@@ -78,31 +85,114 @@ pub struct ConnCatalog<'a> {
     conn_id: u32,
     database: String,
     search_path: &'a [&'a str],
+    /// The role on whose behalf the catalog is consulted, or `None` if the
+    /// catalog is consulted on behalf of the system itself or of a trusted
+    /// session, in which case permission checks are skipped.
+    user: Option<String>,
 }
 
 impl ConnCatalog<'_> {
     fn database_spec(&self) -> DatabaseSpecifier {
         DatabaseSpecifier::Name(self.database.clone())
     }
+
+    /// Returns the role whose permissions must be checked, or `None` if
+    /// permission checks are to be skipped.
+    fn checked_role(&self) -> Option<&str> {
+        match &self.user {
+            Some(user) if !self.catalog.is_superuser(user) => Some(user),
+            _ => None,
+        }
+    }
+
+    /// Returns the access control metadata for `object`, along with a
+    /// description of `object` for use in error messages.
+    ///
+    /// System and temporary objects are not subject to access control, and so
+    /// have no access control metadata.
+    fn acl(&self, object: &ObjectRef) -> Result<Option<(&Acl, String)>, Error> {
+        match object {
+            ObjectRef::Database(name) => match self.catalog.by_name.get(name) {
+                Some(database) => Ok(Some((&database.acl, format!("database '{}'", name)))),
+                None => Err(Error::new(ErrorKind::UnknownDatabase(name.clone()))),
+            },
+            ObjectRef::Schema(DatabaseSpecifier::Ambient, _) => Ok(None),
+            ObjectRef::Schema(database_spec, schema_name) => {
+                let schema = self
+                    .catalog
+                    .get_schema(database_spec, schema_name, self.conn_id)?;
+                let description = format!("schema '{}.{}'", database_spec, schema_name);
+                Ok(Some((&schema.acl, description)))
+            }
+            ObjectRef::Item(id) => {
+                let entry = self.catalog.get_by_id(id);
+                if let DatabaseSpecifier::Ambient = entry.name.database {
+                    return Ok(None);
+                }
+                let description = format!("{} '{}'", entry.item.type_string(), entry.name);
+                Ok(Some((&entry.acl, description)))
+            }
+        }
+    }
+}
+
+/// The privileges that have been granted on an object, keyed by grantee.
+pub type Grants = BTreeMap<String, BTreeSet<Privilege>>;
+
+/// The owner of and the privileges granted on a database, schema, or item.
+#[derive(Debug, Clone, Serialize)]
+pub struct Acl {
+    owner: String,
+    grants: Grants,
+}
+
+impl Acl {
+    fn new(owner: String, grants: Grants) -> Acl {
+        Acl { owner, grants }
+    }
+
+    /// Reports whether `role_name` holds `privilege`, either because it owns
+    /// the object, because the privilege was granted to it, or because the
+    /// privilege was granted to all roles.
+    fn allows(&self, role_name: &str, privilege: Privilege) -> bool {
+        self.owner == role_name
+            || [role_name, PUBLIC_ROLE].iter().any(|grantee| {
+                self.grants
+                    .get(*grantee)
+                    .map_or(false, |privileges| privileges.contains(&privilege))
+            })
+    }
+
+    /// Reports whether `role_name` owns the object or has been granted any
+    /// privileges on it.
+    fn mentions(&self, role_name: &str) -> bool {
+        self.owner == role_name || self.grants.contains_key(role_name)
+    }
 }
 
 #[derive(Debug, Serialize)]
 struct Database {
     id: i64,
     schemas: BTreeMap<String, Schema>,
-}
-
-lazy_static! {
-    static ref EMPTY_DATABASE: Database = Database {
-        id: 0,
-        schemas: BTreeMap::new(),
-    };
+    acl: Acl,
 }
 
 #[derive(Debug, Serialize)]
 pub struct Schema {
     id: i64,
     items: BTreeMap<String, GlobalId>,
+    acl: Acl,
+}
+
+/// A role, i.e., a user that clients can connect as.
+#[derive(Debug, Clone)]
+pub struct Role {
+    /// Whether the role bypasses all permission checks.
+    pub superuser: bool,
+    /// Whether clients are permitted to connect as the role.
+    pub login: bool,
+    /// The verifier for the role's password, if the role has a password.
+    pub password: Option<ScramVerifier>,
 }
 
 #[derive(Clone, Debug)]
@@ -111,11 +201,13 @@ pub struct CatalogEntry {
     used_by: Vec<GlobalId>,
     id: GlobalId,
     name: FullName,
+    acl: Acl,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CatalogItem {
     Source(Source),
+    Table(Table),
     View(View),
     Sink(Sink),
     Index(Index),
@@ -129,6 +221,13 @@ pub struct Source {
     pub desc: RelationDesc,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Table {
+    pub create_sql: String,
+    pub plan_cx: PlanContext,
+    pub desc: RelationDesc,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Sink {
     pub create_sql: String,
@@ -167,6 +266,7 @@ impl CatalogItem {
     pub fn type_string(&self) -> &'static str {
         match self {
             CatalogItem::Source(_) => "source",
+            CatalogItem::Table(_) => "table",
             CatalogItem::Sink(_) => "sink",
             CatalogItem::View(_) => "view",
             CatalogItem::Index(_) => "index",
@@ -177,7 +277,7 @@ impl CatalogItem {
     /// upon.
     pub fn uses(&self) -> Vec<GlobalId> {
         match self {
-            CatalogItem::Source(_) => vec![],
+            CatalogItem::Source(_) | CatalogItem::Table(_) => vec![],
             CatalogItem::Sink(sink) => vec![sink.from],
             CatalogItem::View(view) => {
                 let mut out = Vec::new();
@@ -192,7 +292,10 @@ impl CatalogItem {
     /// or if it's actually a real item.
     pub fn is_placeholder(&self) -> bool {
         match self {
-            CatalogItem::Source(_) | CatalogItem::View(_) | CatalogItem::Index(_) => false,
+            CatalogItem::Source(_)
+            | CatalogItem::Table(_)
+            | CatalogItem::View(_)
+            | CatalogItem::Index(_) => false,
             CatalogItem::Sink(s) => match s.connector {
                 SinkConnectorState::Pending(_) => true,
                 SinkConnectorState::Ready(_) => false,
@@ -220,6 +323,7 @@ impl CatalogEntry {
     pub fn desc(&self) -> Result<&RelationDesc, failure::Error> {
         match &self.item {
             CatalogItem::Source(src) => Ok(&src.desc),
+            CatalogItem::Table(table) => Ok(&table.desc),
             CatalogItem::Sink(_) => bail!(
                 "catalog item '{}' is a sink and so cannot be depended upon",
                 self.name
@@ -257,6 +361,11 @@ impl CatalogEntry {
     pub fn used_by(&self) -> &[GlobalId] {
         &self.used_by
     }
+
+    /// Returns the name of the role that owns this catalog entry.
+    pub fn owner(&self) -> &str {
+        &self.acl.owner
+    }
 }
 
 impl Catalog {
@@ -275,6 +384,7 @@ impl Catalog {
             indexes: HashMap::new(),
             ambient_schemas: BTreeMap::new(),
             temporary_schemas: HashMap::new(),
+            roles: BTreeMap::new(),
             storage: Arc::new(Mutex::new(storage)),
             startup_time: SystemTime::now(),
             nonce: rand::random(),
@@ -282,18 +392,19 @@ impl Catalog {
         catalog.create_temporary_schema(SYSTEM_CONN_ID);
 
         let databases = catalog.storage().load_databases()?;
-        for (id, name) in databases {
+        for (id, name, owner, grants) in databases {
             catalog.by_name.insert(
                 name,
                 Database {
                     id,
                     schemas: BTreeMap::new(),
+                    acl: Acl::new(owner, grants),
                 },
             );
         }
 
         let schemas = catalog.storage().load_schemas()?;
-        for (id, database_name, schema_name) in schemas {
+        for (id, database_name, schema_name, owner, grants) in schemas {
             let schemas = match database_name {
                 Some(database_name) => {
                     &mut catalog
@@ -309,6 +420,30 @@ impl Catalog {
                 Schema {
                     id,
                     items: BTreeMap::new(),
+                    acl: Acl::new(owner, grants),
+                },
+            );
+        }
+
+        let roles = catalog.storage().load_roles()?;
+        for (name, superuser, login, password) in roles {
+            let password = match password {
+                None => None,
+                Some(password) => match password.parse() {
+                    Ok(verifier) => Some(verifier),
+                    Err(e) => {
+                        return Err(Error::new(ErrorKind::Corruption {
+                            detail: format!("failed to parse password of role {}: {}", name, e),
+                        }))
+                    }
+                },
+            };
+            catalog.roles.insert(
+                name,
+                Role {
+                    superuser,
+                    login,
+                    password,
                 },
             );
         }
@@ -320,7 +455,7 @@ impl Catalog {
         f(&mut catalog);
 
         let items = catalog.storage().load_items()?;
-        for (id, name, def) in items {
+        for (id, name, def, owner, grants) in items {
             // TODO(benesch): a better way of detecting when a view has depended
             // upon a non-existent logging view. This is fine for now because
             // the only goal is to produce a nicer error message; we'll bail out
@@ -342,7 +477,7 @@ impl Catalog {
                     }))
                 }
             };
-            catalog.insert_item(id, name, item);
+            catalog.insert_entry(id, name, item, Acl::new(owner, grants));
         }
 
         Ok(catalog)
@@ -354,6 +489,11 @@ impl Catalog {
             conn_id: session.conn_id(),
             database: session.database().into(),
             search_path: session.search_path(),
+            user: if session.trusted() {
+                None
+            } else {
+                Some(session.user().into())
+            },
         }
     }
 
@@ -363,6 +503,7 @@ impl Catalog {
             conn_id: SYSTEM_CONN_ID,
             database: "materialize".into(),
             search_path: &[],
+            user: None,
         }
     }
 
@@ -374,6 +515,20 @@ impl Catalog {
         self.storage().allocate_id()
     }
 
+    /// Durably records `updates` to the table identified by `id`.
+    pub fn insert_table_updates(
+        &mut self,
+        id: GlobalId,
+        updates: &[(Row, isize)],
+    ) -> Result<(), Error> {
+        self.storage().insert_table_updates(id, updates)
+    }
+
+    /// Loads the durably recorded contents of the table identified by `id`.
+    pub fn load_table_contents(&mut self, id: GlobalId) -> Result<Vec<(Row, isize)>, Error> {
+        self.storage().consolidate_table_updates(id)
+    }
+
     pub fn resolve_schema(
         &self,
         current_database: &DatabaseSpecifier,
@@ -486,6 +641,38 @@ impl Catalog {
         self.by_name.keys().map(String::as_str)
     }
 
+    /// Returns the named role, if it exists.
+    pub fn get_role(&self, name: &str) -> Option<&Role> {
+        self.roles.get(name)
+    }
+
+    /// Reports whether the named role bypasses all permission checks.
+    ///
+    /// Roles that do not exist are not superusers. In particular, sessions
+    /// whose role is dropped lose any privileges that the role held. Sessions
+    /// that connected as a nonexistent role while authentication was disabled
+    /// are instead marked as trusted; see [`Session::trusted`].
+    pub fn is_superuser(&self, role_name: &str) -> bool {
+        self.roles
+            .get(role_name)
+            .map_or(false, |role| role.superuser)
+    }
+
+    /// Reports whether the named role owns any object or has been granted
+    /// privileges on any object.
+    fn role_in_use(&self, role_name: &str) -> bool {
+        self.by_name.values().any(|database| {
+            database.acl.mentions(role_name)
+                || database
+                    .schemas
+                    .values()
+                    .any(|schema| schema.acl.mentions(role_name))
+        }) || self
+            .by_id
+            .values()
+            .any(|entry| entry.acl.mentions(role_name))
+    }
+
     /// Creates a new schema in the `Catalog` for temporary items
     /// indicated by the TEMPORARY or TEMP keywords.
     pub fn create_temporary_schema(&mut self, conn_id: u32) {
@@ -494,6 +681,7 @@ impl Catalog {
             Schema {
                 id: -1,
                 items: BTreeMap::new(),
+                acl: Acl::new(SYSTEM_ROLE.into(), Grants::new()),
             },
         );
     }
@@ -572,7 +760,12 @@ impl Catalog {
         }
     }
 
+    /// Inserts an item that is built into the system.
     pub fn insert_item(&mut self, id: GlobalId, name: FullName, item: CatalogItem) {
+        self.insert_entry(id, name, item, Acl::new(SYSTEM_ROLE.into(), Grants::new()))
+    }
+
+    fn insert_entry(&mut self, id: GlobalId, name: FullName, item: CatalogItem, acl: Acl) {
         if !item.is_placeholder() {
             info!("create {} {} ({})", item.type_string(), name, id);
         }
@@ -582,6 +775,7 @@ impl Catalog {
             name,
             id,
             used_by: Vec::new(),
+            acl,
         };
         for u in entry.uses() {
             match self.by_id.get_mut(&u) {
@@ -685,6 +879,7 @@ impl Catalog {
             if let Op::CreateItem {
                 id,
                 name,
+                owner: _,
                 item:
                     CatalogItem::View(View {
                         conn_id: Some(conn_id),
@@ -713,16 +908,19 @@ impl Catalog {
             CreateDatabase {
                 id: i64,
                 name: String,
+                owner: String,
             },
             CreateSchema {
                 id: i64,
                 database_name: String,
                 schema_name: String,
+                owner: String,
             },
             CreateItem {
                 id: GlobalId,
                 name: FullName,
                 item: CatalogItem,
+                owner: String,
             },
             DropDatabase {
                 name: String,
@@ -732,6 +930,19 @@ impl Catalog {
                 schema_name: String,
             },
             DropItem(GlobalId),
+            CreateRole {
+                name: String,
+                superuser: bool,
+                login: bool,
+                password: Option<ScramVerifier>,
+            },
+            DropRole {
+                name: String,
+            },
+            UpdatePrivileges {
+                object: ObjectRef,
+                grants: Grants,
+            },
         }
 
         let temporary_ids = self.temporary_ids(&ops)?;
@@ -740,13 +951,15 @@ impl Catalog {
         let mut tx = storage.transaction()?;
         for op in ops {
             actions.push(match op {
-                Op::CreateDatabase { name } => Action::CreateDatabase {
-                    id: tx.insert_database(&name)?,
+                Op::CreateDatabase { name, owner } => Action::CreateDatabase {
+                    id: tx.insert_database(&name, &owner)?,
                     name,
+                    owner,
                 },
                 Op::CreateSchema {
                     database_name,
                     schema_name,
+                    owner,
                 } => {
                     if schema_name.starts_with("mz_") || schema_name.starts_with("pg_") {
                         return Err(Error::new(ErrorKind::UnacceptableSchemaName(schema_name)));
@@ -758,12 +971,18 @@ impl Catalog {
                         }
                     };
                     Action::CreateSchema {
-                        id: tx.insert_schema(database_id, &schema_name)?,
+                        id: tx.insert_schema(database_id, &schema_name, &owner)?,
                         database_name,
                         schema_name,
+                        owner,
                     }
                 }
-                Op::CreateItem { id, name, item } => {
+                Op::CreateItem {
+                    id,
+                    name,
+                    item,
+                    owner,
+                } => {
                     if item.is_temporary() {
                         if name.database != DatabaseSpecifier::Ambient
                             || name.schema != MZ_TEMP_SCHEMA
@@ -789,10 +1008,15 @@ impl Catalog {
                         };
                         let schema_id = tx.load_schema_id(database_id, &name.schema)?;
                         let serialized_item = self.serialize_item(&item);
-                        tx.insert_item(id, schema_id, &name.item, &serialized_item)?;
+                        tx.insert_item(id, schema_id, &name.item, &serialized_item, &owner)?;
                     }
 
-                    Action::CreateItem { id, name, item }
+                    Action::CreateItem {
+                        id,
+                        name,
+                        item,
+                        owner,
+                    }
                 }
                 Op::DropDatabase { name } => {
                     tx.remove_database(&name)?;
@@ -820,6 +1044,63 @@ impl Catalog {
                     }
                     Action::DropItem(id)
                 }
+                Op::CreateRole {
+                    name,
+                    superuser,
+                    login,
+                    password,
+                } => {
+                    if name.starts_with("mz_") || name.starts_with("pg_") || name == PUBLIC_ROLE {
+                        return Err(Error::new(ErrorKind::UnacceptableRoleName(name)));
+                    }
+                    let serialized_password = password.as_ref().map(|p| p.to_string());
+                    tx.insert_role(&name, superuser, login, serialized_password.as_deref())?;
+                    Action::CreateRole {
+                        name,
+                        superuser,
+                        login,
+                        password,
+                    }
+                }
+                Op::DropRole { name } => {
+                    if self.role_in_use(&name) {
+                        return Err(Error::new(ErrorKind::RoleInUse(name)));
+                    }
+                    tx.remove_role(&name)?;
+                    Action::DropRole { name }
+                }
+                Op::Grant {
+                    object,
+                    grantee,
+                    privileges,
+                } => {
+                    let stored_object = Self::stored_object(&tx, &object)?;
+                    let mut grants = tx.load_privileges(&stored_object)?;
+                    grants
+                        .entry(grantee)
+                        .or_insert_with(BTreeSet::new)
+                        .extend(privileges);
+                    tx.update_privileges(&stored_object, &grants)?;
+                    Action::UpdatePrivileges { object, grants }
+                }
+                Op::Revoke {
+                    object,
+                    grantee,
+                    privileges,
+                } => {
+                    let stored_object = Self::stored_object(&tx, &object)?;
+                    let mut grants = tx.load_privileges(&stored_object)?;
+                    if let Some(held) = grants.get_mut(&grantee) {
+                        for privilege in &privileges {
+                            held.remove(privilege);
+                        }
+                        if held.is_empty() {
+                            grants.remove(&grantee);
+                        }
+                    }
+                    tx.update_privileges(&stored_object, &grants)?;
+                    Action::UpdatePrivileges { object, grants }
+                }
             })
         }
         tx.commit()?;
@@ -828,13 +1109,14 @@ impl Catalog {
         Ok(actions
             .into_iter()
             .map(|action| match action {
-                Action::CreateDatabase { id, name } => {
+                Action::CreateDatabase { id, name, owner } => {
                     info!("create database {}", name);
                     self.by_name.insert(
                         name,
                         Database {
                             id,
                             schemas: BTreeMap::new(),
+                            acl: Acl::new(owner, Grants::new()),
                         },
                     );
                     OpStatus::CreatedDatabase
@@ -844,6 +1126,7 @@ impl Catalog {
                     id,
                     database_name,
                     schema_name,
+                    owner,
                 } => {
                     info!("create schema {}.{}", database_name, schema_name);
                     self.by_name
@@ -855,13 +1138,19 @@ impl Catalog {
                             Schema {
                                 id,
                                 items: BTreeMap::new(),
+                                acl: Acl::new(owner, Grants::new()),
                             },
                         );
                     OpStatus::CreatedSchema
                 }
 
-                Action::CreateItem { id, name, item } => {
-                    self.insert_item(id, name, item);
+                Action::CreateItem {
+                    id,
+                    name,
+                    item,
+                    owner,
+                } => {
+                    self.insert_entry(id, name, item, Acl::new(owner, Grants::new()));
                     OpStatus::CreatedItem(id)
                 }
 
@@ -917,16 +1206,76 @@ impl Catalog {
                     }
                     OpStatus::DroppedItem(metadata)
                 }
+
+                Action::CreateRole {
+                    name,
+                    superuser,
+                    login,
+                    password,
+                } => {
+                    info!("create role {}", name);
+                    self.roles.insert(
+                        name,
+                        Role {
+                            superuser,
+                            login,
+                            password,
+                        },
+                    );
+                    OpStatus::CreatedRole
+                }
+
+                Action::DropRole { name } => {
+                    info!("drop role {}", name);
+                    self.roles.remove(&name);
+                    OpStatus::DroppedRole
+                }
+
+                Action::UpdatePrivileges { object, grants } => {
+                    let acl = match &object {
+                        ObjectRef::Database(name) => &mut self.by_name.get_mut(name).unwrap().acl,
+                        ObjectRef::Schema(database_spec, schema_name) => {
+                            &mut self
+                                .get_schema_mut(database_spec, schema_name, SYSTEM_CONN_ID)
+                                .expect("catalog out of sync")
+                                .acl
+                        }
+                        ObjectRef::Item(id) => &mut self.by_id.get_mut(id).unwrap().acl,
+                    };
+                    acl.grants = grants;
+                    OpStatus::UpdatedPrivileges
+                }
             })
             .collect())
     }
 
+    /// Looks up the key under which `object` is stored.
+    fn stored_object(tx: &storage::Transaction, object: &ObjectRef) -> Result<StoredObject, Error> {
+        match object {
+            ObjectRef::Database(name) => Ok(StoredObject::Database(tx.load_database_id(name)?)),
+            ObjectRef::Schema(DatabaseSpecifier::Name(database_name), schema_name) => {
+                let database_id = tx.load_database_id(database_name)?;
+                Ok(StoredObject::Schema(
+                    tx.load_schema_id(database_id, schema_name)?,
+                ))
+            }
+            ObjectRef::Schema(DatabaseSpecifier::Ambient, schema_name) => Err(Error::new(
+                ErrorKind::ReadOnlySystemSchema(schema_name.clone()),
+            )),
+            ObjectRef::Item(id) => Ok(StoredObject::Item(*id)),
+        }
+    }
+
     fn serialize_item(&self, item: &CatalogItem) -> Vec<u8> {
         let item = match item {
             CatalogItem::Source(source) => SerializedCatalogItem::V1 {
                 create_sql: source.create_sql.clone(),
                 eval_env: Some(source.plan_cx.clone().into()),
             },
+            CatalogItem::Table(table) => SerializedCatalogItem::V1 {
+                create_sql: table.create_sql.clone(),
+                eval_env: Some(table.plan_cx.clone().into()),
+            },
             CatalogItem::View(view) => SerializedCatalogItem::V1 {
                 create_sql: view.create_sql.clone(),
                 eval_env: Some(view.plan_cx.clone().into()),
@@ -948,10 +1297,6 @@ impl Catalog {
             create_sql,
             eval_env,
         } = serde_json::from_slice(&bytes)?;
-        let params = Params {
-            datums: Row::pack(&[]),
-            types: vec![],
-        };
         let pcx = match eval_env {
             // Old sources and sinks don't have plan contexts, but it's safe to
             // just give them a default, as they clearly don't depend on the
@@ -959,6 +1304,19 @@ impl Catalog {
             None => PlanContext::default(),
             Some(eval_env) => eval_env.into(),
         };
+        self.parse_item(create_sql, pcx)
+    }
+
+    /// Plans the catalog item described by `create_sql` in the context `pcx`.
+    fn parse_item(
+        &self,
+        create_sql: String,
+        pcx: PlanContext,
+    ) -> Result<CatalogItem, failure::Error> {
+        let params = Params {
+            datums: Row::pack(&[]),
+            types: vec![],
+        };
         let stmt = sql::parse::parse(create_sql)?.into_element();
         let plan = sql::plan::plan(&pcx, &self.for_system_session(), stmt, &params)?;
         Ok(match plan {
@@ -968,6 +1326,11 @@ impl Catalog {
                 connector: source.connector,
                 desc: source.desc,
             }),
+            Plan::CreateTable { table, .. } => CatalogItem::Table(Table {
+                create_sql: table.create_sql,
+                plan_cx: pcx,
+                desc: table.desc,
+            }),
             Plan::CreateView { view, .. } => {
                 let mut optimizer = Optimizer::default();
                 let optimized_expr = optimizer.optimize(view.expr, self.indexes())?;
@@ -1003,6 +1366,161 @@ impl Catalog {
         })
     }
 
+    /// Replaces the definition of the source `id` with the one in
+    /// `create_sql`, which must describe the same columns as the source's
+    /// current definition, followed by any number of new nullable columns.
+    ///
+    /// The items that depend upon the source, directly or indirectly, are
+    /// replanned against its new definition. Returns the definitions that
+    /// were replaced, starting with the source's and followed by those of its
+    /// dependents in order of increasing ID, so that the caller can rebuild
+    /// their dataflows.
+    pub fn update_source(
+        &mut self,
+        id: GlobalId,
+        create_sql: String,
+    ) -> Result<Vec<(GlobalId, CatalogItem)>, failure::Error> {
+        let (name, old_source) = match self.try_get_by_id(id) {
+            Some(entry) => match entry.item() {
+                CatalogItem::Source(source) => (entry.name().clone(), source.clone()),
+                _ => bail!("{} is not a source", entry.name()),
+            },
+            None => bail!("source was dropped while its schema was being refreshed"),
+        };
+        let source = match self.parse_item(create_sql, old_source.plan_cx.clone())? {
+            CatalogItem::Source(source) => source,
+            _ => bail!("catalog entry generated inappropriate plan"),
+        };
+        if source.create_sql == old_source.create_sql {
+            return Ok(vec![]);
+        }
+
+        let mut columns = source.desc.iter();
+        for (i, (old_name, old_typ)) in old_source.desc.iter().enumerate() {
+            match columns.next() {
+                Some((name, typ)) if name == old_name && typ == old_typ => (),
+                _ => bail!(
+                    "cannot refresh the schema of {}: column {} was changed or removed",
+                    name,
+                    old_name.map_or_else(|| format!("#{}", i + 1), |n| n.to_string())
+                ),
+            }
+        }
+        for (col_name, typ) in columns {
+            if !typ.nullable {
+                bail!(
+                    "cannot refresh the schema of {}: new column {} is not nullable",
+                    name,
+                    col_name.map_or_else(|| "?".into(), |n| n.to_string())
+                );
+            }
+        }
+
+        let mut dependents = BTreeSet::new();
+        let mut todo = self.get_by_id(&id).used_by().to_vec();
+        while let Some(dep) = todo.pop() {
+            if dependents.insert(dep) {
+                todo.extend(self.get_by_id(&dep).used_by());
+            }
+        }
+        for dep in &dependents {
+            let entry = self.get_by_id(dep);
+            if let CatalogItem::Sink(_) = entry.item() {
+                bail!(
+                    "cannot refresh the schema of {}: depended upon by sink '{}'",
+                    name,
+                    entry.name()
+                );
+            }
+        }
+
+        // Dependents are planned against the items that they depend upon, so
+        // each must be replaced before the next is replanned. Item IDs are
+        // allocated in increasing order, so an item's dependencies always
+        // have smaller IDs than the item itself.
+        let mut replaced = vec![(
+            id,
+            self.replace_item(id, CatalogItem::Source(source.clone())),
+        )];
+        for dep in dependents {
+            match self.replan_item(dep) {
+                Ok(item) => replaced.push((dep, self.replace_item(dep, item))),
+                Err(e) => {
+                    let dep_name = self.get_by_id(&dep).name().to_string();
+                    self.restore_items(replaced);
+                    bail!(
+                        "cannot refresh the schema of {}: dependent item '{}' is no longer \
+                         valid: {}",
+                        name,
+                        dep_name,
+                        e
+                    );
+                }
+            }
+        }
+
+        // Only the source's definition needs to be stored, as the definitions
+        // of its dependents are unchanged, and they are replanned against the
+        // source's new definition when the catalog is loaded.
+        let serialized_item = self.serialize_item(&CatalogItem::Source(source));
+        let res = self.storage().transaction().and_then(|tx| {
+            tx.update_item(id, &serialized_item)?;
+            Ok(tx.commit()?)
+        });
+        if let Err(e) = res {
+            self.restore_items(replaced);
+            return Err(e.into());
+        }
+        info!("refresh schema of source {} ({})", name, id);
+        Ok(replaced)
+    }
+
+    /// Plans anew the existing item `id`, from its original definition.
+    fn replan_item(&self, id: GlobalId) -> Result<CatalogItem, failure::Error> {
+        let entry = self.get_by_id(&id);
+        let create_sql = sql::catalog::CatalogItem::create_sql(entry).to_owned();
+        let pcx = sql::catalog::CatalogItem::plan_cx(entry).clone();
+        let mut item = self.parse_item(create_sql, pcx)?;
+        if let CatalogItem::View(view) = &mut item {
+            view.conn_id = entry.item().conn_id();
+        }
+        let mut uses = item.uses();
+        let mut old_uses = entry.uses();
+        uses.sort();
+        old_uses.sort();
+        if uses != old_uses {
+            bail!("its dependencies changed");
+        }
+        Ok(item)
+    }
+
+    /// Replaces the definition of the item `id` with `item`, which must
+    /// depend upon the same items, and returns the old definition.
+    fn replace_item(&mut self, id: GlobalId, item: CatalogItem) -> CatalogItem {
+        let entry = self.by_id.get_mut(&id).expect("catalog out of sync");
+        let old_item = std::mem::replace(&mut entry.item, item);
+        if let (CatalogItem::Index(old_index), CatalogItem::Index(index)) = (&old_item, &entry.item)
+        {
+            let indexes = self
+                .indexes
+                .get_mut(&old_index.on)
+                .expect("catalog out of sync");
+            let i = indexes
+                .iter()
+                .position(|keys| keys == &old_index.keys)
+                .expect("catalog out of sync");
+            indexes[i] = index.keys.clone();
+        }
+        old_item
+    }
+
+    /// Undoes the replacements of item definitions in `replaced`.
+    fn restore_items(&mut self, replaced: Vec<(GlobalId, CatalogItem)>) {
+        for (id, item) in replaced.into_iter().rev() {
+            self.replace_item(id, item);
+        }
+    }
+
     /// Iterates over the items in the catalog in order of increasing ID.
     pub fn iter(&self) -> impl Iterator<Item = &CatalogEntry> {
         self.by_id.iter().map(|(_id, entry)| entry)
@@ -1032,15 +1550,18 @@ impl IdHumanizer for Catalog {
 pub enum Op {
     CreateDatabase {
         name: String,
+        owner: String,
     },
     CreateSchema {
         database_name: DatabaseSpecifier,
         schema_name: String,
+        owner: String,
     },
     CreateItem {
         id: GlobalId,
         name: FullName,
         item: CatalogItem,
+        owner: String,
     },
     DropDatabase {
         name: String,
@@ -1053,6 +1574,25 @@ pub enum Op {
     /// IDs come from the output of `plan_remove`; otherwise consistency rules
     /// may be violated.
     DropItem(GlobalId),
+    CreateRole {
+        name: String,
+        superuser: bool,
+        login: bool,
+        password: Option<ScramVerifier>,
+    },
+    DropRole {
+        name: String,
+    },
+    Grant {
+        object: ObjectRef,
+        grantee: String,
+        privileges: Vec<Privilege>,
+    },
+    Revoke {
+        object: ObjectRef,
+        grantee: String,
+        privileges: Vec<Privilege>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -1063,6 +1603,9 @@ pub enum OpStatus {
     DroppedDatabase,
     DroppedSchema,
     DroppedItem(CatalogEntry),
+    CreatedRole,
+    DroppedRole,
+    UpdatedPrivileges,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1117,6 +1660,13 @@ impl sql::catalog::Catalog for ConnCatalog<'_> {
         }
     }
 
+    fn resolve_role(&self, role_name: &str) -> Result<(), failure::Error> {
+        match self.catalog.roles.get(role_name) {
+            Some(_) => Ok(()),
+            None => Err(Error::new(ErrorKind::UnknownRole(role_name.into())).into()),
+        }
+    }
+
     fn resolve_schema(
         &self,
         database: Option<String>,
@@ -1183,6 +1733,36 @@ impl sql::catalog::Catalog for ConnCatalog<'_> {
     fn get_item_by_id(&self, id: &GlobalId) -> &dyn sql::catalog::CatalogItem {
         self.catalog.get_by_id(id)
     }
+
+    fn check_privilege(
+        &self,
+        object: &ObjectRef,
+        privilege: Privilege,
+    ) -> Result<(), failure::Error> {
+        let role = match self.checked_role() {
+            Some(role) => role,
+            None => return Ok(()),
+        };
+        match self.acl(object)? {
+            Some((acl, description)) if !acl.allows(role, privilege) => {
+                Err(Error::new(ErrorKind::PermissionDenied(description)).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_owner(&self, object: &ObjectRef) -> Result<(), failure::Error> {
+        let role = match self.checked_role() {
+            Some(role) => role,
+            None => return Ok(()),
+        };
+        match self.acl(object)? {
+            Some((acl, description)) if acl.owner != role => {
+                Err(Error::new(ErrorKind::MustBeOwner(description)).into())
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl sql::catalog::CatalogItem for CatalogEntry {
@@ -1201,6 +1781,7 @@ impl sql::catalog::CatalogItem for CatalogEntry {
     fn create_sql(&self) -> &str {
         match self.item() {
             CatalogItem::Source(Source { create_sql, .. }) => create_sql,
+            CatalogItem::Table(Table { create_sql, .. }) => create_sql,
             CatalogItem::Sink(Sink { create_sql, .. }) => create_sql,
             CatalogItem::View(View { create_sql, .. }) => create_sql,
             CatalogItem::Index(Index { create_sql, .. }) => create_sql,
@@ -1210,6 +1791,7 @@ impl sql::catalog::CatalogItem for CatalogEntry {
     fn plan_cx(&self) -> &PlanContext {
         match self.item() {
             CatalogItem::Source(Source { plan_cx, .. }) => plan_cx,
+            CatalogItem::Table(Table { plan_cx, .. }) => plan_cx,
             CatalogItem::Sink(Sink { plan_cx, .. }) => plan_cx,
             CatalogItem::View(View { plan_cx, .. }) => plan_cx,
             CatalogItem::Index(Index { plan_cx, .. }) => plan_cx,
@@ -1219,6 +1801,7 @@ impl sql::catalog::CatalogItem for CatalogEntry {
     fn item_type(&self) -> sql::catalog::CatalogItemType {
         match self.item() {
             CatalogItem::Source(_) => sql::catalog::CatalogItemType::Source,
+            CatalogItem::Table(_) => sql::catalog::CatalogItemType::Table,
             CatalogItem::Sink(_) => sql::catalog::CatalogItemType::Sink,
             CatalogItem::View(_) => sql::catalog::CatalogItemType::View,
             CatalogItem::Index(_) => sql::catalog::CatalogItemType::Index,
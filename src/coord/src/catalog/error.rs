@@ -24,15 +24,21 @@ pub(crate) enum ErrorKind {
     UnknownDatabase(String),
     UnknownSchema(String),
     UnknownItem(String),
+    UnknownRole(String),
     DatabaseAlreadyExists(String),
     SchemaAlreadyExists(String),
     ItemAlreadyExists(String),
+    RoleAlreadyExists(String),
     UnacceptableSchemaName(String),
+    UnacceptableRoleName(String),
     ReadOnlySystemSchema(String),
     SchemaNotEmpty(String),
     InvalidTemporaryDependency(String),
     InvalidTemporarySchema,
     UnsatisfiableLoggingDependency { depender_name: String },
+    PermissionDenied(String),
+    MustBeOwner(String),
+    RoleInUse(String),
     Storage(rusqlite::Error),
 }
 
@@ -59,15 +65,21 @@ impl std::error::Error for Error {
             | ErrorKind::UnknownDatabase(_)
             | ErrorKind::UnknownSchema(_)
             | ErrorKind::UnknownItem(_)
+            | ErrorKind::UnknownRole(_)
             | ErrorKind::DatabaseAlreadyExists(_)
             | ErrorKind::SchemaAlreadyExists(_)
             | ErrorKind::ItemAlreadyExists(_)
+            | ErrorKind::RoleAlreadyExists(_)
             | ErrorKind::UnacceptableSchemaName(_)
+            | ErrorKind::UnacceptableRoleName(_)
             | ErrorKind::ReadOnlySystemSchema(_)
             | ErrorKind::SchemaNotEmpty(_)
             | ErrorKind::InvalidTemporaryDependency(_)
             | ErrorKind::InvalidTemporarySchema
-            | ErrorKind::UnsatisfiableLoggingDependency { .. } => None,
+            | ErrorKind::UnsatisfiableLoggingDependency { .. }
+            | ErrorKind::PermissionDenied(_)
+            | ErrorKind::MustBeOwner(_)
+            | ErrorKind::RoleInUse(_) => None,
             ErrorKind::Storage(e) => Some(e),
         }
     }
@@ -81,6 +93,7 @@ impl fmt::Display for Error {
             ErrorKind::UnknownDatabase(name) => write!(f, "unknown database '{}'", name),
             ErrorKind::UnknownSchema(name) => write!(f, "unknown schema '{}'", name),
             ErrorKind::UnknownItem(name) => write!(f, "unknown catalog item '{}'", name),
+            ErrorKind::UnknownRole(name) => write!(f, "unknown role '{}'", name),
             ErrorKind::DatabaseAlreadyExists(name) => {
                 write!(f, "database '{}' already exists", name)
             }
@@ -88,9 +101,11 @@ impl fmt::Display for Error {
             ErrorKind::ItemAlreadyExists(name) => {
                 write!(f, "catalog item '{}' already exists", name)
             }
+            ErrorKind::RoleAlreadyExists(name) => write!(f, "role '{}' already exists", name),
             ErrorKind::UnacceptableSchemaName(name) => {
                 write!(f, "unacceptable schema name '{}'", name)
             }
+            ErrorKind::UnacceptableRoleName(name) => write!(f, "unacceptable role name '{}'", name),
             ErrorKind::ReadOnlySystemSchema(name) => {
                 write!(f, "system schema '{}' cannot be modified", name)
             }
@@ -108,6 +123,13 @@ impl fmt::Display for Error {
                 "catalog item '{}' depends on system logging, but logging is disabled",
                 depender_name
             ),
+            ErrorKind::PermissionDenied(object) => write!(f, "permission denied for {}", object),
+            ErrorKind::MustBeOwner(object) => write!(f, "must be owner of {}", object),
+            ErrorKind::RoleInUse(name) => write!(
+                f,
+                "role '{}' cannot be dropped because it owns objects or holds privileges",
+                name
+            ),
             ErrorKind::Storage(e) => write!(f, "sqlite error: {}", e),
         }
     }
@@ -15,9 +15,11 @@ use serde::{Deserialize, Serialize};
 
 use expr::GlobalId;
 use ore::cast::CastFrom;
+use repr::Row;
 use sql::names::{DatabaseSpecifier, FullName};
 
 use crate::catalog::error::{Error, ErrorKind};
+use crate::catalog::Grants;
 
 const APPLICATION_ID: i32 = 0x1854_47dc;
 
@@ -81,6 +83,53 @@ const MIGRATIONS: &[&str] = &[
         offset blob NOT NULL,
         PRIMARY KEY (sid, vid, pid, timestamp)
     );",
+    // Adds storage for the contents of tables.
+    //
+    // Introduced for v0.4.0.
+    //
+    // Each row records one update to a table. A table's contents are the
+    // accumulation of its updates.
+    "CREATE TABLE table_updates (
+        gid  blob NOT NULL,
+        row  blob NOT NULL,
+        diff integer NOT NULL
+    );
+    CREATE INDEX table_updates_gid_idx ON table_updates (gid);",
+    // Adds storage for roles.
+    //
+    // Introduced for v0.4.0.
+    //
+    // The password column holds a SCRAM-SHA-256 verifier, never a plain-text
+    // password. The default "materialize" role has no password, so it cannot
+    // authenticate when password authentication is enabled until one is set.
+    "CREATE TABLE roles (
+        id        integer PRIMARY KEY,
+        name      text NOT NULL UNIQUE,
+        superuser bool NOT NULL,
+        login     bool NOT NULL,
+        password  text
+    );
+    INSERT INTO roles VALUES (1, 'materialize', true, true, NULL);",
+    // Adds ownership and privileges to databases, schemas, and items.
+    //
+    // Introduced for v0.4.0.
+    //
+    // Existing objects are assigned to the "materialize" role, which was the
+    // only role in prior versions. The privileges column holds a JSON map from
+    // grantee to privileges, or NULL if no privileges have been granted. The
+    // default database and its public schema are opened up to all roles, as
+    // they would be if they were created today.
+    "ALTER TABLE databases ADD COLUMN owner text NOT NULL DEFAULT 'materialize';
+    ALTER TABLE databases ADD COLUMN privileges blob;
+    ALTER TABLE schemas ADD COLUMN owner text NOT NULL DEFAULT 'materialize';
+    ALTER TABLE schemas ADD COLUMN privileges blob;
+    ALTER TABLE items ADD COLUMN owner text NOT NULL DEFAULT 'materialize';
+    ALTER TABLE items ADD COLUMN privileges blob;
+    UPDATE databases SET privileges = CAST('{\"public\":[\"Usage\"]}' AS blob)
+        WHERE name = 'materialize';
+    UPDATE schemas SET privileges = CAST('{\"public\":[\"Create\"]}' AS blob)
+        WHERE database_id = (SELECT id FROM databases WHERE name = 'materialize')
+        AND name = 'public';",
     // Add new migrations here.
     //
     // Migrations should be preceded with a comment of the following form:
@@ -144,21 +193,25 @@ impl Connection {
         Ok(Connection { inner: sqlite })
     }
 
-    pub fn load_databases(&self) -> Result<Vec<(i64, String)>, Error> {
+    pub fn load_databases(&self) -> Result<Vec<(i64, String, String, Grants)>, Error> {
         self.inner
-            .prepare("SELECT id, name FROM databases")?
+            .prepare("SELECT id, name, owner, privileges FROM databases")?
             .query_and_then(params![], |row| -> Result<_, Error> {
                 let id: i64 = row.get(0)?;
                 let name: String = row.get(1)?;
-                Ok((id, name))
+                let owner: String = row.get(2)?;
+                let grants: Option<SqlVal<Grants>> = row.get(3)?;
+                Ok((id, name, owner, grants.map(|g| g.0).unwrap_or_default()))
             })?
             .collect()
     }
 
-    pub fn load_schemas(&self) -> Result<Vec<(i64, Option<String>, String)>, Error> {
+    pub fn load_schemas(
+        &self,
+    ) -> Result<Vec<(i64, Option<String>, String, String, Grants)>, Error> {
         self.inner
             .prepare(
-                "SELECT schemas.id, databases.name, schemas.name
+                "SELECT schemas.id, databases.name, schemas.name, schemas.owner, schemas.privileges
                 FROM schemas
                 LEFT JOIN databases ON schemas.database_id = databases.id",
             )?
@@ -166,15 +219,37 @@ impl Connection {
                 let id: i64 = row.get(0)?;
                 let database_name: Option<String> = row.get(1)?;
                 let schema_name: String = row.get(2)?;
-                Ok((id, database_name, schema_name))
+                let owner: String = row.get(3)?;
+                let grants: Option<SqlVal<Grants>> = row.get(4)?;
+                Ok((
+                    id,
+                    database_name,
+                    schema_name,
+                    owner,
+                    grants.map(|g| g.0).unwrap_or_default(),
+                ))
             })?
             .collect()
     }
 
-    pub fn load_items(&self) -> Result<Vec<(GlobalId, FullName, Vec<u8>)>, Error> {
+    pub fn load_roles(&self) -> Result<Vec<(String, bool, bool, Option<String>)>, Error> {
+        self.inner
+            .prepare("SELECT name, superuser, login, password FROM roles")?
+            .query_and_then(params![], |row| -> Result<_, Error> {
+                let name: String = row.get(0)?;
+                let superuser: bool = row.get(1)?;
+                let login: bool = row.get(2)?;
+                let password: Option<String> = row.get(3)?;
+                Ok((name, superuser, login, password))
+            })?
+            .collect()
+    }
+
+    pub fn load_items(&self) -> Result<Vec<(GlobalId, FullName, Vec<u8>, String, Grants)>, Error> {
         self.inner
             .prepare(
-                "SELECT items.gid, databases.name, schemas.name, items.name, items.definition
+                "SELECT items.gid, databases.name, schemas.name, items.name, items.definition,
+                    items.owner, items.privileges
                 FROM items
                 JOIN schemas ON items.schema_id = schemas.id
                 JOIN databases ON schemas.database_id = databases.id
@@ -186,6 +261,8 @@ impl Connection {
                 let schema: String = row.get(2)?;
                 let item: String = row.get(3)?;
                 let definition: Vec<u8> = row.get(4)?;
+                let owner: String = row.get(5)?;
+                let grants: Option<SqlVal<Grants>> = row.get(6)?;
                 Ok((
                     id.0,
                     FullName {
@@ -194,6 +271,8 @@ impl Connection {
                         item,
                     },
                     definition,
+                    owner,
+                    grants.map(|g| g.0).unwrap_or_default(),
                 ))
             })?
             .collect()
@@ -214,6 +293,47 @@ impl Connection {
         Ok(GlobalId::User(id as u64))
     }
 
+    /// Consolidates the updates recorded for the table identified by `id` and
+    /// returns the table's contents.
+    ///
+    /// The consolidated contents replace the table's existing updates, so that
+    /// the stored updates do not grow without bound across restarts.
+    pub fn consolidate_table_updates(&mut self, id: GlobalId) -> Result<Vec<(Row, isize)>, Error> {
+        let tx = self.inner.transaction()?;
+        let contents = tx
+            .prepare(
+                "SELECT row, sum(diff) FROM table_updates
+                WHERE gid = ?
+                GROUP BY row
+                HAVING sum(diff) != 0",
+            )?
+            .query_and_then(params![SqlVal(&id)], |row| -> Result<_, Error> {
+                let data: SqlVal<Row> = row.get(0)?;
+                let diff: i64 = row.get(1)?;
+                Ok((data.0, diff as isize))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        tx.execute(
+            "DELETE FROM table_updates WHERE gid = ?",
+            params![SqlVal(&id)],
+        )?;
+        insert_table_updates(&tx, id, &contents)?;
+        tx.commit()?;
+        Ok(contents)
+    }
+
+    /// Durably records `updates` to the table identified by `id`.
+    pub fn insert_table_updates(
+        &mut self,
+        id: GlobalId,
+        updates: &[(Row, isize)],
+    ) -> Result<(), Error> {
+        let tx = self.inner.transaction()?;
+        insert_table_updates(&tx, id, updates)?;
+        tx.commit()?;
+        Ok(())
+    }
+
     pub fn transaction(&mut self) -> Result<Transaction, Error> {
         Ok(Transaction {
             inner: self.inner.transaction()?,
@@ -254,11 +374,11 @@ impl Transaction<'_> {
         }
     }
 
-    pub fn insert_database(&mut self, database_name: &str) -> Result<i64, Error> {
+    pub fn insert_database(&mut self, database_name: &str, owner: &str) -> Result<i64, Error> {
         match self
             .inner
-            .prepare_cached("INSERT INTO databases (name) VALUES (?)")?
-            .execute(params![database_name])
+            .prepare_cached("INSERT INTO databases (name, owner) VALUES (?, ?)")?
+            .execute(params![database_name, owner])
         {
             Ok(_) => Ok(self.inner.last_insert_rowid()),
             Err(err) if is_constraint_violation(&err) => Err(Error::new(
@@ -268,11 +388,16 @@ impl Transaction<'_> {
         }
     }
 
-    pub fn insert_schema(&mut self, database_id: i64, schema_name: &str) -> Result<i64, Error> {
+    pub fn insert_schema(
+        &mut self,
+        database_id: i64,
+        schema_name: &str,
+        owner: &str,
+    ) -> Result<i64, Error> {
         match self
             .inner
-            .prepare_cached("INSERT INTO schemas (database_id, name) VALUES (?, ?)")?
-            .execute(params![database_id, schema_name])
+            .prepare_cached("INSERT INTO schemas (database_id, name, owner) VALUES (?, ?, ?)")?
+            .execute(params![database_id, schema_name, owner])
         {
             Ok(_) => Ok(self.inner.last_insert_rowid()),
             Err(err) if is_constraint_violation(&err) => Err(Error::new(
@@ -288,13 +413,15 @@ impl Transaction<'_> {
         schema_id: i64,
         item_name: &str,
         item: &[u8],
+        owner: &str,
     ) -> Result<(), Error> {
         match self
             .inner
             .prepare_cached(
-                "INSERT INTO items (gid, schema_id, name, definition) VALUES (?, ?, ?, ?)",
+                "INSERT INTO items (gid, schema_id, name, definition, owner)
+                VALUES (?, ?, ?, ?, ?)",
             )?
-            .execute(params![SqlVal(&id), schema_id, item_name, item])
+            .execute(params![SqlVal(&id), schema_id, item_name, item, owner])
         {
             Ok(_) => Ok(()),
             Err(err) if is_constraint_violation(&err) => Err(Error::new(
@@ -304,6 +431,87 @@ impl Transaction<'_> {
         }
     }
 
+    /// Replaces the stored definition of the item `id`.
+    pub fn update_item(&self, id: GlobalId, item: &[u8]) -> Result<(), Error> {
+        let n = self
+            .inner
+            .prepare_cached("UPDATE items SET definition = ? WHERE gid = ?")?
+            .execute(params![item, SqlVal(id)])?;
+        assert!(n <= 1);
+        if n == 1 {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::UnknownItem(id.to_string())))
+        }
+    }
+
+    pub fn insert_role(
+        &mut self,
+        role_name: &str,
+        superuser: bool,
+        login: bool,
+        password: Option<&str>,
+    ) -> Result<(), Error> {
+        match self
+            .inner
+            .prepare_cached(
+                "INSERT INTO roles (name, superuser, login, password) VALUES (?, ?, ?, ?)",
+            )?
+            .execute(params![role_name, superuser, login, password])
+        {
+            Ok(_) => Ok(()),
+            Err(err) if is_constraint_violation(&err) => Err(Error::new(
+                ErrorKind::RoleAlreadyExists(role_name.to_owned()),
+            )),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Loads the privileges that have been granted on `object`.
+    pub fn load_privileges(&self, object: &StoredObject) -> Result<Grants, Error> {
+        let res: Result<Option<SqlVal<Grants>>, _> = match object {
+            StoredObject::Database(id) => self
+                .inner
+                .prepare_cached("SELECT privileges FROM databases WHERE id = ?")?
+                .query_row(params![id], |row| row.get(0)),
+            StoredObject::Schema(id) => self
+                .inner
+                .prepare_cached("SELECT privileges FROM schemas WHERE id = ?")?
+                .query_row(params![id], |row| row.get(0)),
+            StoredObject::Item(id) => self
+                .inner
+                .prepare_cached("SELECT privileges FROM items WHERE gid = ?")?
+                .query_row(params![SqlVal(id)], |row| row.get(0)),
+        };
+        match res {
+            Ok(grants) => Ok(grants.map(|g| g.0).unwrap_or_default()),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(Error::new(ErrorKind::Corruption {
+                detail: format!("missing privileges for {:?}", object),
+            })),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Replaces the privileges that have been granted on `object`.
+    pub fn update_privileges(&self, object: &StoredObject, grants: &Grants) -> Result<(), Error> {
+        let grants = SqlVal(grants);
+        match object {
+            StoredObject::Database(id) => self
+                .inner
+                .prepare_cached("UPDATE databases SET privileges = ? WHERE id = ?")?
+                .execute(params![grants, id])?,
+            StoredObject::Schema(id) => self
+                .inner
+                .prepare_cached("UPDATE schemas SET privileges = ? WHERE id = ?")?
+                .execute(params![grants, id])?,
+            StoredObject::Item(id) => self
+                .inner
+                .prepare_cached("UPDATE items SET privileges = ? WHERE gid = ?")?
+                .execute(params![grants, SqlVal(id)])?,
+        };
+        Ok(())
+    }
+
     pub fn remove_database(&self, name: &str) -> Result<(), Error> {
         let n = self
             .inner
@@ -330,12 +538,29 @@ impl Transaction<'_> {
         }
     }
 
+    pub fn remove_role(&self, name: &str) -> Result<(), Error> {
+        let n = self
+            .inner
+            .prepare_cached("DELETE FROM roles WHERE name = ?")?
+            .execute(params![name])?;
+        assert!(n <= 1);
+        if n == 1 {
+            Ok(())
+        } else {
+            Err(Error::new(ErrorKind::UnknownRole(name.to_owned())))
+        }
+    }
+
     pub fn remove_item(&self, id: GlobalId) -> Result<(), Error> {
         let n = self
             .inner
             .prepare_cached("DELETE FROM items WHERE gid = ?")?
             .execute(params![SqlVal(id)])?;
         assert!(n <= 1);
+        // If the item was a table, its contents go with it.
+        self.inner
+            .prepare_cached("DELETE FROM table_updates WHERE gid = ?")?
+            .execute(params![SqlVal(id)])?;
         if n == 1 {
             Ok(())
         } else {
@@ -348,6 +573,28 @@ impl Transaction<'_> {
     }
 }
 
+/// Identifies a database, schema, or item by the key under which it is
+/// stored.
+#[derive(Debug, Clone, Copy)]
+pub enum StoredObject {
+    Database(i64),
+    Schema(i64),
+    Item(GlobalId),
+}
+
+fn insert_table_updates(
+    tx: &rusqlite::Transaction,
+    id: GlobalId,
+    updates: &[(Row, isize)],
+) -> Result<(), Error> {
+    let mut stmt =
+        tx.prepare_cached("INSERT INTO table_updates (gid, row, diff) VALUES (?, ?, ?)")?;
+    for (row, diff) in updates {
+        stmt.execute(params![SqlVal(&id), SqlVal(row), *diff as i64])?;
+    }
+    Ok(())
+}
+
 fn is_constraint_violation(err: &rusqlite::Error) -> bool {
     match err {
         rusqlite::Error::SqliteFailure(err, _) => {
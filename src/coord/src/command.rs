@@ -12,9 +12,13 @@ use std::pin::Pin;
 
 use derivative::Derivative;
 
-use dataflow_types::{PeekResponse, Update};
+use dataflow_types::{PeekResponse, TailEvent};
+use expr::GlobalId;
+use repr::{RelationDesc, Row};
 use sql::ast::Statement;
+use sql::plan::CopyParams;
 
+use crate::auth::ScramVerifier;
 use crate::session::Session;
 
 /// The requests the client can make of a [`Coordinator`](crate::Coordinator).
@@ -23,6 +27,9 @@ pub enum Command {
     /// Notify the coordinator of a new client session.
     Startup {
         session: Session,
+        /// Whether the client proved that it may act as the session's role.
+        /// Clients are not authenticated when authentication is disabled.
+        authenticated: bool,
         tx: futures::channel::oneshot::Sender<Response<Vec<StartupMessage>>>,
     },
 
@@ -44,9 +51,30 @@ pub enum Command {
         tx: futures::channel::oneshot::Sender<Response<ExecuteResponse>>,
     },
 
+    /// Insert rows that the client sent in response to
+    /// [`ExecuteResponse::CopyIn`] into the identified table.
+    ///
+    /// The rows are inserted atomically. The number of rows inserted is sent
+    /// on `tx`.
+    CopyRows {
+        id: GlobalId,
+        rows: Vec<Row>,
+        session: Session,
+        tx: futures::channel::oneshot::Sender<Response<usize>>,
+    },
+
     /// Cancel the query currently running on another connection.
     CancelRequest { conn_id: u32 },
 
+    /// Look up the information required to authenticate a client that wishes
+    /// to connect as the named role.
+    ///
+    /// If the role does not exist, `None` is sent on `tx`.
+    GetLoginInfo {
+        role_name: String,
+        tx: futures::channel::oneshot::Sender<Option<LoginInfo>>,
+    },
+
     /// Dump the catalog to a JSON string.
     DumpCatalog {
         tx: futures::channel::oneshot::Sender<String>,
@@ -64,6 +92,15 @@ pub struct Response<T> {
 
 pub type RowsFuture = Pin<Box<dyn Future<Output = Result<PeekResponse, comm::Error>> + Send>>;
 
+/// The response to [`Command::GetLoginInfo`].
+#[derive(Debug)]
+pub struct LoginInfo {
+    /// Whether clients are permitted to connect as the role.
+    pub login: bool,
+    /// The verifier for the role's password, if the role has a password.
+    pub password: Option<ScramVerifier>,
+}
+
 /// Notifications that may be generated in response to [`Command::Startup`].
 #[derive(Debug)]
 pub enum StartupMessage {
@@ -79,12 +116,38 @@ pub enum ExecuteResponse {
     AbortedTransaction,
     /// The active transaction was committed.
     CommittedTransaction,
+    /// The client should stream rows for the table identified by `id`,
+    /// encoded as described by `params`, and then submit them with
+    /// [`Command::CopyRows`].
+    ///
+    /// `desc` describes the table, and `columns` holds the index of the
+    /// table column that each column of the streamed rows feeds.
+    CopyIn {
+        id: GlobalId,
+        columns: Vec<usize>,
+        desc: RelationDesc,
+        params: CopyParams,
+    },
+    /// Rows will be delivered via the specified future, and should be sent to
+    /// the client encoded as described by `params`.
+    ///
+    /// `desc` describes the rows.
+    CopyOut {
+        #[derivative(Debug = "ignore")]
+        rx: RowsFuture,
+        desc: RelationDesc,
+        params: CopyParams,
+    },
+    /// The requested source was altered.
+    AlteredSource,
     /// The requested database was created.
     CreatedDatabase { existed: bool },
     /// The requested schema was created.
     CreatedSchema { existed: bool },
     /// The requested index was created.
     CreatedIndex { existed: bool },
+    /// The requested role was created.
+    CreatedRole,
     /// The requested sink was created.
     CreatedSink { existed: bool },
     /// The requested source was created.
@@ -109,10 +172,16 @@ pub enum ExecuteResponse {
     DroppedIndex,
     /// The requested sink wqs dropped.
     DroppedSink,
+    /// The requested roles were dropped.
+    DroppedRole,
     /// The provided query was empty.
     EmptyQuery,
+    /// The requested privileges were granted.
+    GrantedPrivileges,
     /// The specified number of rows were inserted into the requested table.
     Inserted(usize),
+    /// The requested privileges were revoked.
+    RevokedPrivileges,
     /// Rows will be delivered via the specified future.
     SendingRows(#[derivative(Debug = "ignore")] RowsFuture),
     /// The specified variable was set to a new value.
@@ -122,7 +191,7 @@ pub enum ExecuteResponse {
     /// Updates to the requested source or view will be streamed to the
     /// contained receiver.
     Tailing {
-        rx: comm::mpsc::Receiver<Vec<Update>>,
+        rx: comm::mpsc::Receiver<Vec<TailEvent>>,
     },
     /// The specified number of rows were updated in the requested table.
     Updated(usize),
@@ -17,7 +17,7 @@
 //! must accumulate to the same value as would an un-compacted trace.
 
 use std::cmp;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::iter;
 use std::os::unix::ffi::OsStringExt;
@@ -37,8 +37,9 @@ use dataflow::logging::materialized::MaterializedEvent;
 use dataflow::{SequencedCommand, WorkerFeedback, WorkerFeedbackWithMeta};
 use dataflow_types::logging::LoggingConfig;
 use dataflow_types::{
-    AvroOcfSinkConnector, DataflowDesc, IndexDesc, KafkaSinkConnector, PeekResponse, PeekWhen,
-    SinkConnector, TailSinkConnector, Timestamp, TimestampSourceUpdate, Update,
+    AvroOcfSinkConnector, Consistency, DataflowDesc, FileSinkConnector, IndexDesc,
+    KafkaSinkConnector, PeekResponse, PeekWhen, SinkConnector, SinkConnectorBuilder,
+    SinkQueueConfig, SourceConnector, TailSinkConnector, Timestamp, TimestampSourceUpdate, Update,
 };
 use expr::{
     GlobalId, Id, IdHumanizer, NullaryFunc, RelationExpr, RowSetFinishing, ScalarExpr,
@@ -48,17 +49,18 @@ use ore::collections::CollectionExt;
 use ore::thread::JoinHandleExt;
 use repr::{ColumnName, Datum, RelationDesc, RelationType, Row, RowPacker};
 use sql::ast::{ExplainOptions, ObjectType, Statement};
-use sql::catalog::Catalog as _;
+use sql::catalog::{Catalog as _, ObjectRef, Privilege, PUBLIC_ROLE};
 use sql::names::{DatabaseSpecifier, FullName};
-use sql::plan::{MutationKind, Params, Plan, PlanContext};
+use sql::plan::{CopyParams, MutationKind, Params, Plan, PlanContext, TailFrom};
 use sql_parser::ast::ExplainStage;
 use transform::Optimizer;
 
+use crate::auth::ScramVerifier;
 use crate::catalog::{self, Catalog, CatalogItem, SinkConnectorState};
 use crate::session::{PreparedStatement, Session};
 use crate::timestamp::{TimestampConfig, TimestampMessage, Timestamper};
 use crate::util::ClientTransmitter;
-use crate::{sink_connector, Command, ExecuteResponse, Response, StartupMessage};
+use crate::{sink_connector, Command, ExecuteResponse, LoginInfo, Response, StartupMessage};
 
 use arrangement_state::{ArrangementFrontiers, Frontiers};
 
@@ -81,6 +83,19 @@ pub enum Message {
         id: GlobalId,
         result: Result<SinkConnector, failure::Error>,
     },
+    SourceSchemaReady {
+        session: Session,
+        tx: ClientTransmitter<ExecuteResponse>,
+        id: GlobalId,
+        result: Result<sql::ast::Statement, failure::Error>,
+    },
+    SendDiffs {
+        session: Session,
+        tx: ClientTransmitter<ExecuteResponse>,
+        id: GlobalId,
+        diffs: Result<Vec<(Row, isize)>, failure::Error>,
+        kind: MutationKind,
+    },
     Shutdown,
 }
 
@@ -218,7 +233,7 @@ where
                 //about how it was built. If we start building multiple sinks and/or indexes
                 //using a single dataflow, we have to make sure the rebuild process re-runs
                 //the same multiple-build dataflow.
-                CatalogItem::Source(_) => {
+                CatalogItem::Source(_) | CatalogItem::Table(_) => {
                     coord.views.insert(id, ViewState::new(false, vec![]));
                 }
                 CatalogItem::View(view) => {
@@ -241,6 +256,18 @@ where
                     coord.handle_sink_connector_ready(id, connector);
                 }
                 CatalogItem::Index(index) => match id {
+                    GlobalId::User(_) if coord.is_table_without_input(&index.on) => {
+                        // The first index on a table is the table's local
+                        // input, which holds the table's data.
+                        let on_name = coord.catalog.get_by_id(&index.on).name().to_string();
+                        coord.create_table_input(on_name, id, &index);
+                        // Restore the table's contents from before the restart.
+                        let contents = coord.catalog.load_table_contents(index.on)?;
+                        if !contents.is_empty() {
+                            let timestamp = coord.get_write_ts();
+                            coord.broadcast_table_updates(index.on, contents, timestamp);
+                        }
+                    }
                     GlobalId::User(_) => coord.create_index_dataflow(name.to_string(), id, index),
                     GlobalId::System(_) => {
                         // TODO(benesch): a smarter way to determine whether this system index
@@ -370,7 +397,14 @@ where
 
         while let Some(msg) = block_on(messages.next()) {
             match msg {
-                Message::Command(Command::Startup { session, tx }) => {
+                Message::Command(Command::Startup {
+                    mut session,
+                    authenticated,
+                    tx,
+                }) => {
+                    if !authenticated && self.catalog.get_role(session.user()).is_none() {
+                        session.set_trusted();
+                    }
                     let mut messages = vec![];
                     let catalog = self.catalog.for_session(&session);
                     if catalog
@@ -477,6 +511,51 @@ where
                     }
                 },
 
+                Message::SourceSchemaReady {
+                    session,
+                    tx,
+                    id,
+                    result,
+                } => {
+                    let result = result.and_then(|stmt| {
+                        // Only the owner may refresh a source's schema, and
+                        // ownership may have changed while the schema was
+                        // being fetched.
+                        if self.catalog.try_get_by_id(id).is_some() {
+                            self.catalog
+                                .for_session(&session)
+                                .check_owner(&ObjectRef::Item(id))?;
+                        }
+                        self.refresh_source_schema(id, stmt)
+                    });
+                    tx.send(result, session);
+                }
+
+                Message::SendDiffs {
+                    session,
+                    tx,
+                    id,
+                    diffs,
+                    kind,
+                } => {
+                    let result = diffs.and_then(|diffs| {
+                        let privilege = match kind {
+                            MutationKind::Insert => Privilege::Insert,
+                            MutationKind::Update => Privilege::Update,
+                            MutationKind::Delete => Privilege::Delete,
+                        };
+                        self.require_privilege(&session, id, privilege)?;
+                        let affected_rows = match kind {
+                            // Each updated row produces both a retraction
+                            // and an addition.
+                            MutationKind::Update => diffs.len() / 2,
+                            MutationKind::Insert | MutationKind::Delete => diffs.len(),
+                        };
+                        self.sequence_send_diffs(id, diffs, affected_rows, kind)
+                    });
+                    tx.send(result, session);
+                }
+
                 Message::Command(Command::Describe {
                     name,
                     stmt,
@@ -487,10 +566,35 @@ where
                     let _ = tx.send(Response { result, session });
                 }
 
+                Message::Command(Command::CopyRows {
+                    id,
+                    rows,
+                    session,
+                    tx,
+                }) => {
+                    let count = rows.len();
+                    let updates = rows.into_iter().map(|row| (row, 1)).collect();
+                    let result = self
+                        .require_privilege(&session, id, Privilege::Insert)
+                        .and_then(|()| {
+                            self.sequence_send_diffs(id, updates, count, MutationKind::Insert)
+                        })
+                        .map(|_| count);
+                    let _ = tx.send(Response { result, session });
+                }
+
                 Message::Command(Command::CancelRequest { conn_id }) => {
                     self.sequence_cancel(conn_id);
                 }
 
+                Message::Command(Command::GetLoginInfo { role_name, tx }) => {
+                    let info = self.catalog.get_role(&role_name).map(|role| LoginInfo {
+                        login: role.login,
+                        password: role.password.clone(),
+                    });
+                    let _ = tx.send(info);
+                }
+
                 Message::Command(Command::DumpCatalog { tx }) => {
                     let _ = tx.send(self.catalog.dump());
                 }
@@ -621,23 +725,26 @@ where
             Plan::CreateDatabase {
                 name,
                 if_not_exists,
-            } => tx.send(self.sequence_create_database(name, if_not_exists), session),
+            } => tx.send(
+                self.sequence_create_database(&session, name, if_not_exists),
+                session,
+            ),
 
             Plan::CreateSchema {
                 database_name,
                 schema_name,
                 if_not_exists,
             } => tx.send(
-                self.sequence_create_schema(database_name, schema_name, if_not_exists),
+                self.sequence_create_schema(&session, database_name, schema_name, if_not_exists),
                 session,
             ),
 
             Plan::CreateTable {
                 name,
-                desc,
+                table,
                 if_not_exists,
             } => tx.send(
-                self.sequence_create_table(pcx, name, desc, if_not_exists),
+                self.sequence_create_table(&session, pcx, name, table, if_not_exists),
                 session,
             ),
 
@@ -647,10 +754,26 @@ where
                 if_not_exists,
                 materialized,
             } => tx.send(
-                self.sequence_create_source(pcx, name, source, if_not_exists, materialized),
+                self.sequence_create_source(
+                    &session,
+                    pcx,
+                    name,
+                    source,
+                    if_not_exists,
+                    materialized,
+                ),
                 session,
             ),
 
+            Plan::AlterSourceRefreshSchema { id, stmt } => self
+                .sequence_alter_source_refresh_schema(
+                    internal_cmd_tx.clone(),
+                    tx,
+                    session,
+                    id,
+                    stmt,
+                ),
+
             Plan::CreateSink {
                 name,
                 sink,
@@ -677,11 +800,11 @@ where
                 if_not_exists,
             } => tx.send(
                 self.sequence_create_view(
+                    &session,
                     pcx,
                     name,
                     view,
                     replace,
-                    session.conn_id(),
                     materialize,
                     if_not_exists,
                 ),
@@ -693,7 +816,17 @@ where
                 index,
                 if_not_exists,
             } => tx.send(
-                self.sequence_create_index(pcx, name, index, if_not_exists),
+                self.sequence_create_index(&session, pcx, name, index, if_not_exists),
+                session,
+            ),
+
+            Plan::CreateRole {
+                name,
+                superuser,
+                login,
+                password,
+            } => tx.send(
+                self.sequence_create_role(&session, name, superuser, login, password),
                 session,
             ),
 
@@ -709,6 +842,20 @@ where
 
             Plan::DropItems { items, ty } => tx.send(self.sequence_drop_items(items, ty), session),
 
+            Plan::DropRoles { names } => {
+                tx.send(self.sequence_drop_roles(&session, names), session)
+            }
+
+            Plan::Grant { objects, grantees } => tx.send(
+                self.sequence_update_privileges(true, objects, grantees),
+                session,
+            ),
+
+            Plan::Revoke { objects, grantees } => tx.send(
+                self.sequence_update_privileges(false, objects, grantees),
+                session,
+            ),
+
             Plan::EmptyQuery => tx.send(Ok(ExecuteResponse::EmptyQuery), session),
 
             Plan::ShowAllVariables => tx.send(self.sequence_show_all_variables(&session), session),
@@ -748,13 +895,20 @@ where
             ),
 
             Plan::Tail {
-                id,
+                from,
                 ts,
+                progress,
                 with_snapshot,
-            } => tx.send(
-                self.sequence_tail(session.conn_id(), id, with_snapshot, ts),
-                session,
-            ),
+            } => {
+                let queue = SinkQueueConfig {
+                    max_bytes: session.tail_max_queued_bytes(),
+                    on_full: session.tail_on_queue_full(),
+                };
+                tx.send(
+                    self.sequence_tail(session.conn_id(), from, progress, with_snapshot, ts, queue),
+                    session,
+                )
+            }
 
             Plan::SendRows(rows) => tx.send(Ok(send_immediate_rows(rows)), session),
 
@@ -785,6 +939,39 @@ where
                 session,
             ),
 
+            Plan::CopyFrom {
+                id,
+                columns,
+                params,
+            } => tx.send(self.sequence_copy_from(id, columns, params), session),
+
+            Plan::CopyTo {
+                source,
+                finishing,
+                desc,
+                params,
+            } => tx.send(
+                self.sequence_copy_to(session.conn_id(), source, finishing, desc, params),
+                session,
+            ),
+
+            Plan::Insert { id, values } => {
+                self.sequence_insert(internal_cmd_tx.clone(), tx, session, id, values)
+            }
+
+            Plan::ReadThenWrite {
+                id,
+                selection,
+                kind,
+            } => self.sequence_read_then_write(
+                internal_cmd_tx.clone(),
+                tx,
+                session,
+                id,
+                selection,
+                kind,
+            ),
+
             Plan::ShowViews {
                 ids,
                 full,
@@ -797,16 +984,67 @@ where
         }
     }
 
+    /// Returns an error if the session's user is not a superuser.
+    ///
+    /// `action` describes the operation that requires superuser privileges,
+    /// for use in the error message.
+    fn require_superuser(&self, session: &Session, action: &str) -> Result<(), failure::Error> {
+        if !session.trusted() && !self.catalog.is_superuser(session.user()) {
+            bail!("permission denied to {}", action);
+        }
+        Ok(())
+    }
+
+    /// Returns an error if the session's user does not hold `privilege` on
+    /// the item `id`.
+    ///
+    /// Privileges are checked when a statement is planned. Statements that
+    /// write to a table only once their rows are available, like `UPDATE` and
+    /// `COPY FROM`, check the privilege again before writing, as it may have
+    /// been revoked in the meantime. Items that have since been dropped are
+    /// left for the caller to report.
+    fn require_privilege(
+        &self,
+        session: &Session,
+        id: GlobalId,
+        privilege: Privilege,
+    ) -> Result<(), failure::Error> {
+        if self.catalog.try_get_by_id(id).is_none() {
+            return Ok(());
+        }
+        self.catalog
+            .for_session(session)
+            .check_privilege(&ObjectRef::Item(id), privilege)
+    }
+
     fn sequence_create_database(
         &mut self,
+        session: &Session,
         name: String,
         if_not_exists: bool,
     ) -> Result<ExecuteResponse, failure::Error> {
+        self.require_superuser(session, "create database")?;
+        // Like PostgreSQL, all roles may use a new database and create objects
+        // in its public schema until the owner revokes those privileges.
         let ops = vec![
-            catalog::Op::CreateDatabase { name: name.clone() },
+            catalog::Op::CreateDatabase {
+                name: name.clone(),
+                owner: session.user().into(),
+            },
             catalog::Op::CreateSchema {
-                database_name: DatabaseSpecifier::Name(name),
+                database_name: DatabaseSpecifier::Name(name.clone()),
                 schema_name: "public".into(),
+                owner: session.user().into(),
+            },
+            catalog::Op::Grant {
+                object: ObjectRef::Database(name.clone()),
+                grantee: PUBLIC_ROLE.into(),
+                privileges: vec![Privilege::Usage],
+            },
+            catalog::Op::Grant {
+                object: ObjectRef::Schema(DatabaseSpecifier::Name(name), "public".into()),
+                grantee: PUBLIC_ROLE.into(),
+                privileges: vec![Privilege::Create],
             },
         ];
         match self.catalog_transact(ops) {
@@ -818,6 +1056,7 @@ where
 
     fn sequence_create_schema(
         &mut self,
+        session: &Session,
         database_name: DatabaseSpecifier,
         schema_name: String,
         if_not_exists: bool,
@@ -825,6 +1064,7 @@ where
         let op = catalog::Op::CreateSchema {
             database_name,
             schema_name,
+            owner: session.user().into(),
         };
         match self.catalog_transact(vec![op]) {
             Ok(_) => Ok(ExecuteResponse::CreatedSchema { existed: false }),
@@ -835,54 +1075,40 @@ where
 
     fn sequence_create_table(
         &mut self,
+        session: &Session,
         pcx: PlanContext,
         name: FullName,
-        desc: RelationDesc,
+        table: sql::plan::Table,
         if_not_exists: bool,
     ) -> Result<ExecuteResponse, failure::Error> {
-        let source_id = self.catalog.allocate_id()?;
-        let source = catalog::Source {
-            create_sql: "TODO (see #2755)".to_string(),
+        let table_id = self.catalog.allocate_id()?;
+        let table = catalog::Table {
+            create_sql: table.create_sql,
             plan_cx: pcx,
-            connector: dataflow_types::SourceConnector::Local,
-            desc,
+            desc: table.desc,
         };
         let index_id = self.catalog.allocate_id()?;
         let mut index_name = name.clone();
         index_name.item += "_primary_idx";
-        let index = auto_generate_primary_idx(
-            index_name.item.clone(),
-            name.clone(),
-            source_id,
-            &source.desc,
-        );
+        let index =
+            auto_generate_primary_idx(index_name.item.clone(), name.clone(), table_id, &table.desc);
         match self.catalog_transact(vec![
             catalog::Op::CreateItem {
-                id: source_id,
+                id: table_id,
                 name: name.clone(),
-                item: CatalogItem::Source(source.clone()),
+                item: CatalogItem::Table(table),
+                owner: session.user().into(),
             },
             catalog::Op::CreateItem {
                 id: index_id,
                 name: index_name,
                 item: CatalogItem::Index(index.clone()),
+                owner: session.user().into(),
             },
         ]) {
             Ok(_) => {
-                self.views.insert(source_id, ViewState::new(false, vec![]));
-                broadcast(
-                    &mut self.broadcast_tx,
-                    SequencedCommand::CreateLocalInput {
-                        name: name.to_string(),
-                        index_id,
-                        index: IndexDesc {
-                            on_id: index.on,
-                            keys: index.keys.clone(),
-                        },
-                        on_type: source.desc.typ().clone(),
-                    },
-                );
-                self.insert_index(index_id, &index, self.logical_compaction_window_ms);
+                self.views.insert(table_id, ViewState::new(false, vec![]));
+                self.create_table_input(name.to_string(), index_id, &index);
                 Ok(ExecuteResponse::CreatedTable { existed: false })
             }
             Err(_) if if_not_exists => Ok(ExecuteResponse::CreatedTable { existed: true }),
@@ -892,6 +1118,7 @@ where
 
     fn sequence_create_source(
         &mut self,
+        session: &Session,
         pcx: PlanContext,
         name: FullName,
         source: sql::plan::Source,
@@ -909,6 +1136,7 @@ where
             id: source_id,
             name: name.clone(),
             item: CatalogItem::Source(source.clone()),
+            owner: session.user().into(),
         }];
         let (index_id, index) = if materialized {
             let mut index_name = name.clone();
@@ -924,6 +1152,7 @@ where
                 id: index_id,
                 name: index_name,
                 item: CatalogItem::Index(index.clone()),
+                owner: session.user().into(),
             });
             (Some(index_id), Some(index))
         } else {
@@ -949,6 +1178,101 @@ where
         }
     }
 
+    fn sequence_alter_source_refresh_schema(
+        &self,
+        mut internal_cmd_tx: futures::channel::mpsc::UnboundedSender<Message>,
+        tx: ClientTransmitter<ExecuteResponse>,
+        session: Session,
+        id: GlobalId,
+        stmt: sql::ast::Statement,
+    ) {
+        // Fetching the latest schemas from the registry could take an
+        // arbitrarily long time, so do it off the main coordinator thread,
+        // just like purification of the original `CREATE SOURCE`.
+        tokio::spawn(async move {
+            let result = sql::pure::purify(stmt).await;
+            internal_cmd_tx
+                .send(Message::SourceSchemaReady {
+                    session,
+                    tx,
+                    id,
+                    result,
+                })
+                .await
+                .expect("sending to internal_cmd_tx cannot fail");
+        });
+    }
+
+    /// Replaces the definition of the source `id` with the one in `stmt`, and
+    /// rebuilds the dataflows of the indexes on the source and on the views
+    /// that depend upon it, so that they pick up the source's new columns.
+    fn refresh_source_schema(
+        &mut self,
+        id: GlobalId,
+        stmt: sql::ast::Statement,
+    ) -> Result<ExecuteResponse, failure::Error> {
+        let replaced = self.catalog.update_source(id, stmt.to_string())?;
+
+        let old_indexes: Vec<_> = replaced
+            .iter()
+            .filter_map(|(id, item)| match item {
+                CatalogItem::Index(index) => Some((*id, index)),
+                _ => None,
+            })
+            .collect();
+        self.drop_indexes(old_indexes);
+
+        // Rebuild in order of increasing ID, as when the catalog is loaded, so
+        // that each view is in place before the indexes that depend upon it.
+        for (id, _) in &replaced {
+            let entry = self.catalog.get_by_id(id);
+            let name = entry.name().to_string();
+            match entry.item().clone() {
+                CatalogItem::View(view) => self.insert_view(*id, &view),
+                CatalogItem::Index(index) => self.create_index_dataflow(name, *id, index),
+                CatalogItem::Source(_) | CatalogItem::Table(_) | CatalogItem::Sink(_) => (),
+            }
+        }
+        Ok(ExecuteResponse::AlteredSource)
+    }
+
+    /// Checks that the sink of `from` can write exactly once across restarts.
+    ///
+    /// A sink that reuses its topic after a restart must assign each update the
+    /// same timestamp that it was assigned before the restart. That only holds
+    /// if every source upstream of the sink takes its timestamps from a
+    /// consistency topic, rather than from the wall clock.
+    fn validate_exactly_once_inputs(&self, from: GlobalId) -> Result<(), failure::Error> {
+        let mut stack = vec![from];
+        let mut seen = HashSet::new();
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            let entry = self.catalog.get_by_id(&id);
+            match entry.item() {
+                CatalogItem::Source(catalog::Source {
+                    connector:
+                        SourceConnector::External {
+                            consistency: Consistency::BringYourOwn(_),
+                            ..
+                        },
+                    ..
+                }) => (),
+                CatalogItem::Source(_) | CatalogItem::Table(_) => bail!(
+                    "reuse_topic requires that every source upstream of the sink use \
+                     BYO consistency, but {} {} does not",
+                    entry.item().type_string(),
+                    entry.name()
+                ),
+                CatalogItem::View(_) | CatalogItem::Index(_) | CatalogItem::Sink(_) => {
+                    stack.extend(entry.uses())
+                }
+            }
+        }
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn sequence_create_sink(
         &mut self,
@@ -962,6 +1286,15 @@ where
         as_of: Option<u64>,
         if_not_exists: bool,
     ) {
+        if let SinkConnectorBuilder::Kafka(builder) = &sink.connector_builder {
+            if builder.reuse_topic {
+                if let Err(e) = self.validate_exactly_once_inputs(sink.from) {
+                    tx.send(Err(e), session);
+                    return;
+                }
+            }
+        }
+
         // First try to allocate an ID. If that fails, we're done.
         let id = match self.catalog.allocate_id() {
             Ok(id) => id,
@@ -980,6 +1313,7 @@ where
         let op = catalog::Op::CreateItem {
             id,
             name,
+            owner: session.user().into(),
             item: CatalogItem::Sink(catalog::Sink {
                 create_sql: sink.create_sql,
                 plan_cx: pcx,
@@ -1028,11 +1362,11 @@ where
     #[allow(clippy::too_many_arguments)]
     fn sequence_create_view(
         &mut self,
+        session: &Session,
         pcx: PlanContext,
         name: FullName,
         view: sql::plan::View,
         replace: Option<GlobalId>,
-        conn_id: u32,
         materialize: bool,
         if_not_exists: bool,
     ) -> Result<ExecuteResponse, failure::Error> {
@@ -1049,12 +1383,17 @@ where
             plan_cx: pcx,
             optimized_expr,
             desc,
-            conn_id: if view.temporary { Some(conn_id) } else { None },
+            conn_id: if view.temporary {
+                Some(session.conn_id())
+            } else {
+                None
+            },
         };
         ops.push(catalog::Op::CreateItem {
             id: view_id,
             name: name.clone(),
             item: CatalogItem::View(view.clone()),
+            owner: session.user().into(),
         });
         let (index_id, index) = if materialize {
             let mut index_name = name.clone();
@@ -1071,6 +1410,7 @@ where
                 id: index_id,
                 name: index_name,
                 item: CatalogItem::Index(index.clone()),
+                owner: session.user().into(),
             });
             (Some(index_id), Some(index))
         } else {
@@ -1098,6 +1438,7 @@ where
 
     fn sequence_create_index(
         &mut self,
+        session: &Session,
         pcx: PlanContext,
         name: FullName,
         index: sql::plan::Index,
@@ -1114,6 +1455,7 @@ where
             id,
             name: name.clone(),
             item: CatalogItem::Index(index.clone()),
+            owner: session.user().into(),
         };
         match self.catalog_transact(vec![op]) {
             Ok(()) => {
@@ -1125,6 +1467,29 @@ where
         }
     }
 
+    fn sequence_create_role(
+        &mut self,
+        session: &Session,
+        name: String,
+        superuser: bool,
+        login: bool,
+        password: Option<String>,
+    ) -> Result<ExecuteResponse, failure::Error> {
+        self.require_superuser(session, "create role")?;
+        let password = match password {
+            None => None,
+            Some(password) => Some(ScramVerifier::new(&password)?),
+        };
+        let op = catalog::Op::CreateRole {
+            name,
+            superuser,
+            login,
+            password,
+        };
+        self.catalog_transact(vec![op])?;
+        Ok(ExecuteResponse::CreatedRole)
+    }
+
     fn sequence_drop_database(&mut self, name: String) -> Result<ExecuteResponse, failure::Error> {
         let ops = self.catalog.drop_database_ops(name);
         self.catalog_transact(ops)?;
@@ -1141,6 +1506,58 @@ where
         Ok(ExecuteResponse::DroppedSchema)
     }
 
+    fn sequence_drop_roles(
+        &mut self,
+        session: &Session,
+        names: Vec<String>,
+    ) -> Result<ExecuteResponse, failure::Error> {
+        self.require_superuser(session, "drop role")?;
+        if names.iter().any(|name| name == session.user()) {
+            bail!("current user cannot be dropped");
+        }
+        let ops = names
+            .into_iter()
+            .map(|name| catalog::Op::DropRole { name })
+            .collect();
+        self.catalog_transact(ops)?;
+        Ok(ExecuteResponse::DroppedRole)
+    }
+
+    fn sequence_update_privileges(
+        &mut self,
+        grant: bool,
+        objects: Vec<(ObjectRef, Vec<Privilege>)>,
+        grantees: Vec<String>,
+    ) -> Result<ExecuteResponse, failure::Error> {
+        let mut ops = vec![];
+        for (object, privileges) in objects {
+            for grantee in &grantees {
+                let object = object.clone();
+                let grantee = grantee.clone();
+                let privileges = privileges.clone();
+                ops.push(if grant {
+                    catalog::Op::Grant {
+                        object,
+                        grantee,
+                        privileges,
+                    }
+                } else {
+                    catalog::Op::Revoke {
+                        object,
+                        grantee,
+                        privileges,
+                    }
+                });
+            }
+        }
+        self.catalog_transact(ops)?;
+        Ok(if grant {
+            ExecuteResponse::GrantedPrivileges
+        } else {
+            ExecuteResponse::RevokedPrivileges
+        })
+    }
+
     fn sequence_drop_items(
         &mut self,
         items: Vec<GlobalId>,
@@ -1359,33 +1776,73 @@ where
     fn sequence_tail(
         &mut self,
         conn_id: u32,
-        source_id: GlobalId,
+        from: TailFrom,
+        progress: bool,
         with_snapshot: bool,
         ts: Option<Timestamp>,
+        queue: SinkQueueConfig,
     ) -> Result<ExecuteResponse, failure::Error> {
-        // Determine the frontier of updates to tail *from*.
-        // Updates greater or equal to this frontier will be produced.
-        let frontier = self.determine_frontier(ts, source_id)?;
-        let sink_name = format!(
-            "tail-source-{}",
-            self.catalog
-                .humanize_id(Id::Global(source_id))
-                .expect("Source id is known to exist in catalog")
-        );
         let sink_id = self.catalog.allocate_id()?;
-        self.active_tails.insert(conn_id, sink_id);
         let (tx, rx) = self.switchboard.mpsc_limited(self.num_timely_workers);
-
-        self.create_sink_dataflow(
-            sink_name,
-            sink_id,
-            source_id,
+        let connector = |frontier| {
             SinkConnector::Tail(TailSinkConnector {
                 tx,
                 frontier,
                 strict: !with_snapshot,
-            }),
-        );
+                progress,
+                queue,
+            })
+        };
+
+        match from {
+            TailFrom::Id(source_id) => {
+                // Determine the frontier of updates to tail *from*.
+                // Updates greater or equal to this frontier will be produced.
+                let frontier = self.determine_frontier(ts, source_id)?;
+                let sink_name = format!(
+                    "tail-source-{}",
+                    self.catalog
+                        .humanize_id(Id::Global(source_id))
+                        .expect("Source id is known to exist in catalog")
+                );
+                self.active_tails.insert(conn_id, sink_id);
+                self.create_sink_dataflow(sink_name, sink_id, source_id, connector(frontier));
+            }
+            TailFrom::Query(source) => {
+                // Tail from the same timestamp that a peek at the query would
+                // observe.
+                let when = match ts {
+                    Some(ts) => PeekWhen::AtTimestamp(ts),
+                    None => PeekWhen::Immediately,
+                };
+                let frontier = Antichain::from_elem(self.determine_timestamp(&source, when)?);
+                let source = self.optimizer.optimize(source, self.catalog.indexes())?;
+
+                // Build a transient view that computes the query in the same
+                // dataflow as the sink, so that dropping the sink when the TAIL
+                // ends tears down the whole dataflow. As with peeks, nothing in
+                // the dataflow layer cares about column names.
+                let typ = source.as_ref().typ();
+                let ncols = typ.column_types.len();
+                let desc =
+                    RelationDesc::new(typ, iter::repeat::<Option<ColumnName>>(None).take(ncols));
+                let view_id = self.catalog.allocate_id()?;
+                let mut dataflow = DataflowDesc::new(format!("tail-temp-view-{}", view_id));
+                dataflow.set_as_of(frontier.clone());
+                let view = catalog::View {
+                    create_sql: "<none>".into(),
+                    plan_cx: PlanContext::default(),
+                    optimized_expr: source,
+                    desc,
+                    conn_id: None,
+                };
+                self.build_view_collection(&view_id, &view, &mut dataflow);
+                dataflow.add_sink_export(sink_id, view_id, view.desc, connector(frontier));
+                self.active_tails.insert(conn_id, sink_id);
+                self.validate_dataflow(&mut dataflow);
+                self.broadcast_dataflow_creation(dataflow);
+            }
+        }
         Ok(ExecuteResponse::Tailing { rx })
     }
 
@@ -1445,7 +1902,86 @@ where
         affected_rows: usize,
         kind: MutationKind,
     ) -> Result<ExecuteResponse, failure::Error> {
+        let entry = match self.catalog.try_get_by_id(id) {
+            Some(entry) => entry,
+            // Another session dropped the table while the diffs were being
+            // computed.
+            None => bail!("table was dropped while the statement was executing"),
+        };
+        let is_table = if let CatalogItem::Table(table) = entry.item() {
+            for (row, diff) in &updates {
+                if *diff < 0 {
+                    continue;
+                }
+                for (datum, (name, typ)) in row.iter().zip(table.desc.iter()) {
+                    if datum == Datum::Null && !typ.nullable {
+                        bail!(
+                            "null value in column \"{}\" violates not-null constraint",
+                            name.map(|n| n.as_str()).unwrap_or("?")
+                        );
+                    }
+                }
+            }
+            true
+        } else {
+            false
+        };
+
+        // Writes to tables must be durable before they are applied, so that
+        // they survive a restart.
+        if is_table {
+            self.catalog.insert_table_updates(id, &updates)?;
+        }
+
         let timestamp = self.get_write_ts();
+        self.broadcast_table_updates(id, updates, timestamp);
+
+        Ok(match kind {
+            MutationKind::Delete => ExecuteResponse::Deleted(affected_rows),
+            MutationKind::Insert => ExecuteResponse::Inserted(affected_rows),
+            MutationKind::Update => ExecuteResponse::Updated(affected_rows),
+        })
+    }
+
+    fn sequence_copy_from(
+        &mut self,
+        id: GlobalId,
+        columns: Vec<usize>,
+        params: CopyParams,
+    ) -> Result<ExecuteResponse, failure::Error> {
+        let desc = self.catalog.get_by_id(&id).desc()?.clone();
+        Ok(ExecuteResponse::CopyIn {
+            id,
+            columns,
+            desc,
+            params,
+        })
+    }
+
+    fn sequence_copy_to(
+        &mut self,
+        conn_id: u32,
+        source: RelationExpr,
+        finishing: RowSetFinishing,
+        desc: RelationDesc,
+        params: CopyParams,
+    ) -> Result<ExecuteResponse, failure::Error> {
+        let rx =
+            match self.sequence_peek(conn_id, source, PeekWhen::Immediately, finishing, true)? {
+                ExecuteResponse::SendingRows(rx) => rx,
+                _ => unreachable!("peeks always send rows"),
+            };
+        Ok(ExecuteResponse::CopyOut { rx, desc, params })
+    }
+
+    /// Sends `updates` to the local input identified by `id`, all at
+    /// `timestamp`.
+    fn broadcast_table_updates(
+        &mut self,
+        id: GlobalId,
+        updates: Vec<(Row, isize)>,
+        timestamp: Timestamp,
+    ) {
         let updates = updates
             .into_iter()
             .map(|(row, diff)| Update {
@@ -1454,17 +1990,121 @@ where
                 timestamp,
             })
             .collect();
-
         broadcast(
             &mut self.broadcast_tx,
             SequencedCommand::Insert { id, updates },
         );
+    }
 
-        Ok(match kind {
-            MutationKind::Delete => ExecuteResponse::Deleted(affected_rows),
-            MutationKind::Insert => ExecuteResponse::Inserted(affected_rows),
-            MutationKind::Update => ExecuteResponse::Updated(affected_rows),
-        })
+    fn sequence_insert(
+        &mut self,
+        internal_cmd_tx: futures::channel::mpsc::UnboundedSender<Message>,
+        tx: ClientTransmitter<ExecuteResponse>,
+        session: Session,
+        id: GlobalId,
+        values: RelationExpr,
+    ) {
+        let values = match self
+            .optimizer
+            .optimize(values, self.catalog.indexes())
+            .map_err(failure::Error::from)
+        {
+            Ok(values) => values,
+            Err(e) => {
+                tx.send(Err(e), session);
+                return;
+            }
+        };
+
+        match values.into_inner() {
+            // Inserting a constant set of rows, like `INSERT ... VALUES`, does
+            // not require reading any data.
+            RelationExpr::Constant { rows, typ: _ } => {
+                let affected_rows = rows.iter().map(|(_, diff)| *diff as usize).sum();
+                tx.send(
+                    self.sequence_send_diffs(id, rows, affected_rows, MutationKind::Insert),
+                    session,
+                )
+            }
+            // Otherwise, like `INSERT ... SELECT`, the values must be
+            // computed by a dataflow before they can be written.
+            values => self.sequence_read_then_write(
+                internal_cmd_tx,
+                tx,
+                session,
+                id,
+                values,
+                MutationKind::Insert,
+            ),
+        }
+    }
+
+    /// Peeks at the rows produced by `selection` and writes the diffs that
+    /// they describe to the table identified by `id`.
+    ///
+    /// NOTE: the read and the write are not atomic. A write that is sequenced
+    /// between the peek and the write will not be observed by the peek.
+    fn sequence_read_then_write(
+        &mut self,
+        mut internal_cmd_tx: futures::channel::mpsc::UnboundedSender<Message>,
+        tx: ClientTransmitter<ExecuteResponse>,
+        session: Session,
+        id: GlobalId,
+        selection: RelationExpr,
+        kind: MutationKind,
+    ) {
+        let arity = self.catalog.get_by_id(&id).desc().unwrap().arity();
+        let finishing = RowSetFinishing {
+            order_by: vec![],
+            limit: None,
+            offset: 0,
+            project: (0..selection.arity()).collect(),
+        };
+        let peek = self.sequence_peek(
+            session.conn_id(),
+            selection,
+            PeekWhen::Immediately,
+            finishing,
+            true,
+        );
+        let rows = match peek {
+            Ok(ExecuteResponse::SendingRows(rows)) => rows,
+            Ok(_) => unreachable!("peeks always send rows"),
+            Err(e) => {
+                tx.send(Err(e), session);
+                return;
+            }
+        };
+
+        tokio::spawn(async move {
+            let diffs = match rows.await {
+                Ok(PeekResponse::Rows(rows)) => Ok(rows
+                    .into_iter()
+                    .flat_map(|row| match kind {
+                        MutationKind::Insert => vec![(row, 1)],
+                        MutationKind::Delete => vec![(row, -1)],
+                        MutationKind::Update => {
+                            let datums: Vec<_> = row.iter().collect();
+                            let (old, new) = datums.split_at(arity);
+                            vec![(Row::pack(old), -1), (Row::pack(new), 1)]
+                        }
+                    })
+                    .collect()),
+                Ok(PeekResponse::Error(e)) => Err(failure::format_err!("{}", e)),
+                Ok(PeekResponse::Canceled) => Err(failure::format_err!("query canceled")),
+                Err(e) => Err(e.into()),
+            };
+            internal_cmd_tx
+                .send(Message::SendDiffs {
+                    session,
+                    tx,
+                    id,
+                    diffs,
+                    kind,
+                })
+                .await
+                .expect("sending to internal_cmd_tx cannot fail");
+        });
     }
 
     fn sequence_show_views(
@@ -1537,7 +2177,7 @@ where
                 catalog::OpStatus::DroppedItem(entry) => {
                     self.report_catalog_update(entry.id(), entry.name().to_string(), false);
                     match entry.item() {
-                        CatalogItem::Source(_) => {
+                        CatalogItem::Source(_) | CatalogItem::Table(_) => {
                             sources_to_drop.push(entry.id());
                             views_to_drop.push(entry.id());
                         }
@@ -1570,6 +2210,16 @@ where
                                         ),
                                     );
                                 }
+                                SinkConnector::File(FileSinkConnector { path, .. }) => {
+                                    broadcast(
+                                        &mut self.broadcast_tx,
+                                        SequencedCommand::AppendLog(MaterializedEvent::FileSink {
+                                            id: entry.id(),
+                                            path: path.clone().into_os_string().into_vec(),
+                                            insert: false,
+                                        }),
+                                    );
+                                }
                                 _ => (),
                             }
                         }
@@ -1639,6 +2289,9 @@ where
                         *id,
                     );
                 }
+                CatalogItem::Table(table) => {
+                    dataflow.add_index_import(*index_id, index_desc, table.desc.typ().clone(), *id);
+                }
                 _ => unreachable!(),
             }
         } else {
@@ -1810,10 +2463,45 @@ where
         self.build_arrangement(&id, index, on_type, dataflow);
     }
 
+    /// Reports whether `id` names a table whose local input has not yet been
+    /// created.
+    fn is_table_without_input(&self, id: &GlobalId) -> bool {
+        match self.catalog.get_by_id(id).item() {
+            CatalogItem::Table(_) => self.views[id].default_idx.is_none(),
+            _ => false,
+        }
+    }
+
+    /// Creates the local input that holds the contents of a table, arranged
+    /// by the table's primary index.
+    fn create_table_input(&mut self, name: String, index_id: GlobalId, index: &catalog::Index) {
+        let on_type = self
+            .catalog
+            .get_by_id(&index.on)
+            .desc()
+            .unwrap()
+            .typ()
+            .clone();
+        broadcast(
+            &mut self.broadcast_tx,
+            SequencedCommand::CreateLocalInput {
+                name,
+                index_id,
+                index: IndexDesc {
+                    on_id: index.on,
+                    keys: index.keys.clone(),
+                },
+                on_type,
+            },
+        );
+        self.insert_index(index_id, index, self.logical_compaction_window_ms);
+    }
+
     fn handle_sink_connector_ready(&mut self, id: GlobalId, connector: SinkConnector) {
         // Update catalog entry with sink connector.
         let entry = self.catalog.get_by_id(&id);
         let name = entry.name().clone();
+        let owner = entry.owner().to_owned();
         let mut sink = match entry.item() {
             CatalogItem::Sink(sink) => sink.clone(),
             _ => unreachable!(),
@@ -1825,6 +2513,7 @@ where
                 id,
                 name: name.clone(),
                 item: CatalogItem::Sink(sink.clone()),
+                owner,
             },
         ];
         self.catalog
@@ -1863,6 +2552,16 @@ where
                     }),
                 );
             }
+            SinkConnector::File(FileSinkConnector { path, .. }) => {
+                broadcast(
+                    &mut self.broadcast_tx,
+                    SequencedCommand::AppendLog(MaterializedEvent::FileSink {
+                        id,
+                        path: path.clone().into_os_string().into_vec(),
+                        insert: true,
+                    }),
+                );
+            }
             _ => (),
         }
         let mut dataflow = DataflowDesc::new(name);
@@ -2096,6 +2795,24 @@ where
             .flat_map(|id| self.find_dependent_indexes(&id))
             .collect();
 
+        // Tables are closed up to the latest write timestamp at the end of
+        // every coordinator step, so a query that reads only from tables can
+        // always be assigned a fresh read timestamp. This ensures that such a
+        // query observes every write that completed before it was issued.
+        let reads_only_tables = !uses_ids.is_empty()
+            && uses_ids
+                .iter()
+                .all(|id| match self.catalog.get_by_id(id).item() {
+                    CatalogItem::Index(index) => match self.catalog.get_by_id(&index.on).item() {
+                        CatalogItem::Table(_) => true,
+                        _ => false,
+                    },
+                    _ => false,
+                });
+        if need_to_determine && reads_only_tables {
+            return Ok(self.get_read_ts());
+        }
+
         // First determine the candidate timestamp, which is either the explicitly requested
         // timestamp, or the latest timestamp known to be immediately available.
         let timestamp = match when {
@@ -2313,25 +3030,20 @@ where
         params: &sql::plan::Params,
     ) -> Result<(PlanContext, sql::plan::Plan), failure::Error> {
         let pcx = PlanContext::default();
-        match sql::plan::plan(
-            &pcx,
-            &self.catalog.for_session(session),
-            stmt.clone(),
-            params,
-        ) {
-            Ok(plan) => Ok((pcx, plan)),
-            Err(err) => match self.symbiosis {
-                Some(ref mut postgres) if postgres.can_handle(&stmt) => {
-                    let plan = block_on(postgres.execute(
-                        &pcx,
-                        &self.catalog.for_session(session),
-                        &stmt,
-                    ))?;
-                    Ok((pcx, plan))
-                }
-                _ => Err(err),
-            },
+
+        // When symbiosis mode is enabled, use Postgres for any statements it
+        // can handle, so that the tables in Postgres stay in sync with the
+        // tables in Materialize.
+        if let Some(ref mut postgres) = self.symbiosis {
+            if postgres.can_handle(&stmt) {
+                let plan =
+                    block_on(postgres.execute(&pcx, &self.catalog.for_session(session), &stmt))?;
+                return Ok((pcx, plan));
+            }
         }
+
+        let plan = sql::plan::plan(&pcx, &self.catalog.for_session(session), stmt, params)?;
+        Ok((pcx, plan))
     }
 
     fn handle_describe(
@@ -2341,16 +3053,13 @@ where
         stmt: Option<Statement>,
     ) -> Result<(), failure::Error> {
         let (desc, param_types) = if let Some(stmt) = stmt.clone() {
-            match sql::plan::describe(&self.catalog.for_session(session), stmt.clone()) {
-                Ok((desc, param_types)) => (desc, param_types),
-                // Describing the query failed. If we're running in symbiosis with
-                // Postgres, see if Postgres can handle it. Note that Postgres
-                // only handles commands that do not return rows, so the
+            match self.symbiosis {
+                // If we're running in symbiosis with Postgres, let Postgres
+                // handle any statements that it can. Note that Postgres only
+                // handles commands that do not return rows, so the
                 // `RelationDesc` is always `None`.
-                Err(err) => match self.symbiosis {
-                    Some(ref postgres) if postgres.can_handle(&stmt) => (None, vec![]),
-                    _ => return Err(err),
-                },
+                Some(ref postgres) if postgres.can_handle(&stmt) => (None, vec![]),
+                _ => sql::plan::describe(&self.catalog.for_session(session), stmt)?,
             }
         } else {
             (None, vec![])
@@ -27,8 +27,9 @@ mod sink_connector;
 mod timestamp;
 mod util;
 
+pub mod auth;
 pub mod session;
 
 pub use self::coord::{dump_catalog, Config, Coordinator};
 pub use self::timestamp::TimestampConfig;
-pub use command::{Command, ExecuteResponse, Response, RowsFuture, StartupMessage};
+pub use command::{Command, ExecuteResponse, LoginInfo, Response, RowsFuture, StartupMessage};
@@ -21,6 +21,7 @@ use std::fmt;
 
 use failure::bail;
 
+use dataflow_types::QueueFullBehavior;
 use repr::{Datum, Row, ScalarType};
 use sql::plan::Params;
 
@@ -82,6 +83,19 @@ const SQL_SAFE_UPDATES: ServerVar<&bool> = ServerVar {
     description: "Prohibits SQL statements that may be overly destructive (CockroachDB).",
 };
 
+const TAIL_MAX_QUEUED_BYTES: ServerVar<&i32> = ServerVar {
+    name: unicase::Ascii::new("tail_max_queued_bytes"),
+    value: &(64 << 20),
+    description: "Limits the bytes of updates that a TAIL buffers for a slow client (Materialize).",
+};
+
+const TAIL_ON_QUEUE_FULL: ServerVar<&str> = ServerVar {
+    name: unicase::Ascii::new("tail_on_queue_full"),
+    value: "block",
+    description:
+        "Sets whether a TAIL whose buffer fills should block, error, or spill (Materialize).",
+};
+
 const TIMEZONE: ServerVar<&str> = ServerVar {
     name: unicase::Ascii::new("TimeZone"),
     value: "UTC",
@@ -90,6 +104,10 @@ const TIMEZONE: ServerVar<&str> = ServerVar {
 
 const DUMMY_CONNECTION_ID: u32 = 0;
 
+/// The role as which dummy sessions run. This is the superuser that is
+/// installed in every catalog.
+const DUMMY_USER: &str = "materialize";
+
 /// A `Session` holds SQL state that is attached to a session.
 pub struct Session {
     application_name: SessionVar<str>,
@@ -100,8 +118,14 @@ pub struct Session {
     search_path: ServerVar<&'static [&'static str]>,
     server_version: ServerVar<&'static str>,
     sql_safe_updates: SessionVar<bool>,
+    tail_max_queued_bytes: SessionVar<i32>,
+    tail_on_queue_full: SessionVar<str>,
     timezone: ServerVar<&'static str>,
     conn_id: u32,
+    /// The name of the role as which the session is connected.
+    user: String,
+    /// Whether the session bypasses permission checks regardless of its role.
+    trusted: bool,
     /// The current state of the the session's transaction
     transaction: TransactionStatus,
     /// A map from statement names to SQL queries
@@ -116,6 +140,8 @@ pub struct Session {
 impl fmt::Debug for Session {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("Session")
+            .field("user", &self.user())
+            .field("trusted", &self.trusted())
             .field("application_name", &self.application_name())
             .field("client_encoding", &self.client_encoding())
             .field("database", &self.database())
@@ -124,6 +150,8 @@ impl fmt::Debug for Session {
             .field("search_path", &self.search_path())
             .field("server_version", &self.server_version())
             .field("sql_safe_updates", &self.sql_safe_updates())
+            .field("tail_max_queued_bytes", &self.tail_max_queued_bytes())
+            .field("tail_on_queue_full", &self.tail_on_queue_full())
             .field("timezone", &self.timezone())
             .field("transaction", &self.transaction())
             .field("prepared_statements", &self.prepared_statements.keys())
@@ -133,8 +161,9 @@ impl fmt::Debug for Session {
 }
 
 impl Session {
-    /// Given a connection id, provides a new session with default values.
-    pub fn new(conn_id: u32) -> Session {
+    /// Given a connection id and the name of the connecting role, provides a
+    /// new session with default values.
+    pub fn new(conn_id: u32, user: String) -> Session {
         assert_ne!(conn_id, DUMMY_CONNECTION_ID);
         Session {
             application_name: SessionVar::new(&APPLICATION_NAME),
@@ -145,8 +174,12 @@ impl Session {
             search_path: SEARCH_PATH,
             server_version: SERVER_VERSION,
             sql_safe_updates: SessionVar::new(&SQL_SAFE_UPDATES),
+            tail_max_queued_bytes: SessionVar::new(&TAIL_MAX_QUEUED_BYTES),
+            tail_on_queue_full: SessionVar::new(&TAIL_ON_QUEUE_FULL),
             timezone: TIMEZONE,
             conn_id,
+            user,
+            trusted: false,
             transaction: TransactionStatus::Idle,
             prepared_statements: HashMap::new(),
             portals: HashMap::new(),
@@ -165,8 +198,12 @@ impl Session {
             search_path: SEARCH_PATH,
             server_version: SERVER_VERSION,
             sql_safe_updates: SessionVar::new(&SQL_SAFE_UPDATES),
+            tail_max_queued_bytes: SessionVar::new(&TAIL_MAX_QUEUED_BYTES),
+            tail_on_queue_full: SessionVar::new(&TAIL_ON_QUEUE_FULL),
             timezone: TIMEZONE,
             conn_id: DUMMY_CONNECTION_ID,
+            user: DUMMY_USER.into(),
+            trusted: false,
             transaction: TransactionStatus::Idle,
             prepared_statements: HashMap::new(),
             portals: HashMap::new(),
@@ -178,6 +215,26 @@ impl Session {
         self.conn_id
     }
 
+    /// Returns the name of the role as which the session is connected.
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// Reports whether the session bypasses permission checks regardless of
+    /// the privileges of its role.
+    ///
+    /// Only sessions that connect as a nonexistent role while authentication
+    /// is disabled are trusted. Such clients could just as well connect as a
+    /// superuser.
+    pub fn trusted(&self) -> bool {
+        self.trusted
+    }
+
+    /// Marks the session as trusted. See [`Session::trusted`].
+    pub fn set_trusted(&mut self) {
+        self.trusted = true;
+    }
+
     /// Returns all configuration parameters and their current values for this
     /// session.
     pub fn vars(&self) -> Vec<&dyn Var> {
@@ -190,6 +247,8 @@ impl Session {
             &self.search_path,
             &self.server_version,
             &self.sql_safe_updates,
+            &self.tail_max_queued_bytes,
+            &self.tail_on_queue_full,
             &self.timezone,
         ]
     }
@@ -233,6 +292,10 @@ impl Session {
             Ok(&self.server_version)
         } else if name == SQL_SAFE_UPDATES.name {
             Ok(&self.sql_safe_updates)
+        } else if name == TAIL_MAX_QUEUED_BYTES.name {
+            Ok(&self.tail_max_queued_bytes)
+        } else if name == TAIL_ON_QUEUE_FULL.name {
+            Ok(&self.tail_on_queue_full)
         } else if name == TIMEZONE.name {
             Ok(&self.timezone)
         } else {
@@ -264,6 +327,22 @@ impl Session {
             bail!("parameter {} is read only", SERVER_VERSION.name);
         } else if name == SQL_SAFE_UPDATES.name {
             self.sql_safe_updates.set(value)
+        } else if name == TAIL_MAX_QUEUED_BYTES.name {
+            match value.parse::<i32>() {
+                Ok(n) if n > 0 => self.tail_max_queued_bytes.set(value),
+                _ => bail!(
+                    "parameter {} requires a positive integer value",
+                    TAIL_MAX_QUEUED_BYTES.name
+                ),
+            }
+        } else if name == TAIL_ON_QUEUE_FULL.name {
+            if QueueFullBehavior::from_name(value).is_none() {
+                bail!(
+                    "parameter {} must be one of block, error, or spill",
+                    TAIL_ON_QUEUE_FULL.name
+                );
+            }
+            self.tail_on_queue_full.set(value)
         } else if name == TIMEZONE.name {
             if unicase::Ascii::new(value) != TIMEZONE.value {
                 bail!(
@@ -319,6 +398,18 @@ impl Session {
         *self.sql_safe_updates.value()
     }
 
+    /// Returns the value of the `tail_max_queued_bytes` configuration
+    /// parameter.
+    pub fn tail_max_queued_bytes(&self) -> usize {
+        *self.tail_max_queued_bytes.value() as usize
+    }
+
+    /// Returns the value of the `tail_on_queue_full` configuration parameter.
+    pub fn tail_on_queue_full(&self) -> QueueFullBehavior {
+        QueueFullBehavior::from_name(self.tail_on_queue_full.value())
+            .expect("tail_on_queue_full is validated when set")
+    }
+
     /// Returns the value of the `timezone` configuration parameter.
     pub fn timezone(&self) -> &'static str {
         self.timezone.value
@@ -7,16 +7,28 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::cmp;
 use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use failure::{bail, format_err, ResultExt};
 use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, TopicReplication};
+use rdkafka::client::DefaultClientContext;
 use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::error::KafkaError;
+use rdkafka::topic_partition_list::Offset;
+use rdkafka::types::RDKafkaError;
+use rdkafka::{Message, TopicPartitionList};
+use url::Url;
 
+use ccsr::SchemaType;
 use dataflow_types::{
-    AvroOcfSinkConnector, AvroOcfSinkConnectorBuilder, KafkaSinkConnector,
-    KafkaSinkConnectorBuilder, SinkConnector, SinkConnectorBuilder, Timestamp,
+    AvroOcfSinkConnector, AvroOcfSinkConnectorBuilder, FileSinkConnector, FileSinkConnectorBuilder,
+    HttpSinkConnector, HttpSinkConnectorBuilder, KafkaSinkConnector, KafkaSinkConnectorBuilder,
+    KafkaSinkFormat, KafkaSinkFormatBuilder, KinesisSinkConnector, KinesisSinkConnectorBuilder,
+    SinkConnector, SinkConnectorBuilder, SinkEnvelope, Timestamp,
 };
 use expr::GlobalId;
 use ore::collections::CollectionExt;
@@ -31,6 +43,9 @@ pub async fn build(
     match builder {
         SinkConnectorBuilder::Kafka(k) => build_kafka(k, with_snapshot, frontier, id).await,
         SinkConnectorBuilder::AvroOcf(a) => build_avro_ocf(a, with_snapshot, frontier, id),
+        SinkConnectorBuilder::File(f) => build_file(f, with_snapshot, frontier, id),
+        SinkConnectorBuilder::Kinesis(k) => build_kinesis(k, with_snapshot, frontier).await,
+        SinkConnectorBuilder::Http(h) => build_http(h, with_snapshot, frontier, id),
     }
 }
 
@@ -40,20 +55,189 @@ async fn build_kafka(
     frontier: Antichain<Timestamp>,
     id: GlobalId,
 ) -> Result<SinkConnector, failure::Error> {
-    let topic = format!("{}-{}-{}", builder.topic_prefix, id, builder.topic_suffix);
+    // Sinks that reuse their topic need to find it again after a restart, so
+    // their topic name must not depend on when Materialize started.
+    let topic = if builder.reuse_topic {
+        format!("{}-{}", builder.topic_prefix, id)
+    } else {
+        format!("{}-{}-{}", builder.topic_prefix, id, builder.topic_suffix)
+    };
 
     // Create Kafka topic with single partition.
     let mut config = ClientConfig::new();
     config.set("bootstrap.servers", &builder.broker_url.to_string());
-    let res = config
+    let admin = config
         .create::<AdminClient<_>>()
-        .expect("creating admin kafka client failed")
+        .expect("creating admin kafka client failed");
+    let mut new_topic = NewTopic::new(
+        &topic,
+        1,
+        TopicReplication::Fixed(builder.replication_factor as i32),
+    );
+    if builder.envelope == SinkEnvelope::Upsert {
+        // Only the latest value for each key is meaningful, so let Kafka
+        // discard the rest.
+        new_topic = new_topic.set("cleanup.policy", "compact");
+    }
+    create_topic(&admin, new_topic, builder.reuse_topic).await?;
+
+    let consistency_topic = if builder.reuse_topic {
+        let consistency_topic = format!("{}-consistency", topic);
+        let new_topic = NewTopic::new(
+            &consistency_topic,
+            1,
+            TopicReplication::Fixed(builder.replication_factor as i32),
+        );
+        create_topic(&admin, new_topic, true).await?;
+        Some(consistency_topic)
+    } else {
+        None
+    };
+
+    let format = match builder.format {
+        KafkaSinkFormatBuilder::Avro {
+            schema_registry_url,
+            value_schema,
+            key_schema,
+        } => {
+            // Publish value schema for the topic.
+            //
+            // TODO(benesch): do we need to delete the Kafka topic if publishing the
+            // schema fails?
+            // TODO(sploiselle): support SSL auth'ed sinks
+            let ccsr = ccsr::ClientConfig::new(schema_registry_url).build();
+            let value_schema_id = ccsr
+                .publish_schema(&format!("{}-value", topic), &value_schema, SchemaType::Avro)
+                .await
+                .with_context(|e| {
+                    format!("unable to publish schema to registry in kafka sink: {}", e)
+                })?;
+
+            let key_schema_id = match key_schema {
+                Some(key_schema) => Some(
+                    ccsr.publish_schema(&format!("{}-key", topic), &key_schema, SchemaType::Avro)
+                        .await
+                        .with_context(|e| {
+                            format!(
+                                "unable to publish key schema to registry in kafka sink: {}",
+                                e
+                            )
+                        })?,
+                ),
+                None => None,
+            };
+
+            let consistency_schema_id = match &consistency_topic {
+                Some(consistency_topic) => Some(
+                    ccsr.publish_schema(
+                        &format!("{}-value", consistency_topic),
+                        &interchange::avro::consistency_schema().canonical_form(),
+                        SchemaType::Avro,
+                    )
+                    .await
+                    .with_context(|e| {
+                        format!(
+                            "unable to publish consistency schema to registry in kafka sink: {}",
+                            e
+                        )
+                    })?,
+                ),
+                None => None,
+            };
+
+            KafkaSinkFormat::Avro {
+                value_schema_id,
+                key_schema_id,
+                consistency_schema_id,
+            }
+        }
+        KafkaSinkFormatBuilder::Protobuf {
+            schema_registry_url,
+            message_name,
+            value_schema,
+            key_schema,
+        } => {
+            let ccsr = ccsr::ClientConfig::new(schema_registry_url).build();
+            let value_schema_id = ccsr
+                .publish_schema(
+                    &format!("{}-value", topic),
+                    &value_schema,
+                    SchemaType::Protobuf,
+                )
+                .await
+                .with_context(|e| {
+                    format!("unable to publish schema to registry in kafka sink: {}", e)
+                })?;
+
+            let key_schema_id = match key_schema {
+                Some(key_schema) => Some(
+                    ccsr.publish_schema(
+                        &format!("{}-key", topic),
+                        &key_schema,
+                        SchemaType::Protobuf,
+                    )
+                    .await
+                    .with_context(|e| {
+                        format!(
+                            "unable to publish key schema to registry in kafka sink: {}",
+                            e
+                        )
+                    })?,
+                ),
+                None => None,
+            };
+
+            KafkaSinkFormat::Protobuf {
+                message_name,
+                value_schema_id,
+                key_schema_id,
+            }
+        }
+        KafkaSinkFormatBuilder::Json => KafkaSinkFormat::Json,
+        KafkaSinkFormatBuilder::Csv { delimiter } => KafkaSinkFormat::Csv { delimiter },
+    };
+
+    let (frontier, strict) = match &consistency_topic {
+        Some(consistency_topic) => {
+            // If the sink has already written some timestamps, resume after
+            // the last of them, regardless of the requested frontier and
+            // snapshot.
+            //
+            // TODO: this is only exact if the sink's inputs assign the same
+            // timestamps to their data as they did before the restart.
+            match last_consistency_timestamp(&builder.broker_url, consistency_topic, &format)? {
+                Some(ts) => (Antichain::from_elem(ts), true),
+                None => (frontier, !with_snapshot),
+            }
+        }
+        None => (frontier, !with_snapshot),
+    };
+
+    Ok(SinkConnector::Kafka(KafkaSinkConnector {
+        format,
+        key_indices: builder.key_indices,
+        envelope: builder.envelope,
+        consistency_topic,
+        topic,
+        url: builder.broker_url,
+        fuel: builder.fuel,
+        queue: builder.queue,
+        frontier,
+        strict,
+    }))
+}
+
+/// Creates a Kafka topic. If `allow_existing` is set, it is not an error for
+/// the topic to already exist.
+async fn create_topic(
+    admin: &AdminClient<DefaultClientContext>,
+    new_topic: NewTopic<'_>,
+    allow_existing: bool,
+) -> Result<(), failure::Error> {
+    let topic = new_topic.name.to_owned();
+    let res = admin
         .create_topics(
-            &[NewTopic::new(
-                &topic,
-                1,
-                TopicReplication::Fixed(builder.replication_factor as i32),
-            )],
+            &[new_topic],
             &AdminOptions::new().request_timeout(Some(Duration::from_secs(5))),
         )
         .await
@@ -66,28 +250,79 @@ async fn build_kafka(
             res.len()
         );
     }
-    res.into_element()
-        .map_err(|(_, e)| format_err!("error creating topic {} for sink: {}", topic, e))?;
-
-    // Publish value schema for the topic.
-    //
-    // TODO(benesch): do we need to delete the Kafka topic if publishing the
-    // schema fails?
-    // TODO(sploiselle): support SSL auth'ed sinks
-    let schema_id = ccsr::ClientConfig::new(builder.schema_registry_url)
-        .build()
-        .publish_schema(&format!("{}-value", topic), &builder.value_schema)
-        .await
-        .with_context(|e| format!("unable to publish schema to registry in kafka sink: {}", e))?;
+    match res.into_element() {
+        Ok(_) => Ok(()),
+        Err((_, RDKafkaError::TopicAlreadyExists)) if allow_existing => Ok(()),
+        Err((_, e)) => bail!("error creating topic {} for sink: {}", topic, e),
+    }
+}
 
-    Ok(SinkConnector::Kafka(KafkaSinkConnector {
-        schema_id,
-        topic,
-        url: builder.broker_url,
-        fuel: builder.fuel,
-        frontier,
-        strict: !with_snapshot,
-    }))
+/// Returns the timestamp of the last record in a Kafka sink's consistency
+/// topic, if there is one.
+///
+/// Only records from committed transactions are considered. As the end of the
+/// topic may consist of transaction markers and aborted records, the topic is
+/// read backwards in exponentially growing windows until a record is found.
+fn last_consistency_timestamp(
+    broker_url: &Url,
+    topic: &str,
+    format: &KafkaSinkFormat,
+) -> Result<Option<Timestamp>, failure::Error> {
+    let mut config = ClientConfig::new();
+    config.set("bootstrap.servers", &broker_url.to_string());
+    config.set("group.id", &format!("materialize-sink-resume-{}", topic));
+    config.set("enable.auto.commit", "false");
+    config.set("enable.partition.eof", "true");
+    config.set("isolation.level", "read_committed");
+    let consumer: BaseConsumer = config
+        .create()
+        .with_context(|e| format!("creating kafka consumer for {}: {}", topic, e))?;
+
+    let timeout = Duration::from_secs(5);
+    let (low, high) = consumer
+        .fetch_watermarks(topic, 0, timeout)
+        .with_context(|e| format!("fetching watermarks for {}: {}", topic, e))?;
+    let mut window = 16;
+    loop {
+        let start = cmp::max(low, high - window);
+        if start >= high {
+            return Ok(None);
+        }
+        let mut tpl = TopicPartitionList::new();
+        tpl.add_partition_offset(topic, 0, Offset::Offset(start));
+        consumer.assign(&tpl)?;
+
+        let mut timestamp = None;
+        while let Some(result) = consumer.poll(timeout) {
+            match result {
+                Ok(message) => {
+                    if let Some(payload) = message.payload() {
+                        timestamp = Some(match format {
+                            KafkaSinkFormat::Avro { .. } => {
+                                interchange::avro::decode_consistency_record(payload)?
+                            }
+                            KafkaSinkFormat::Protobuf { .. } => {
+                                unreachable!("protobuf sinks do not support consistency topics")
+                            }
+                            KafkaSinkFormat::Json => {
+                                interchange::json::decode_consistency_record(payload)?
+                            }
+                            KafkaSinkFormat::Csv { delimiter } => {
+                                interchange::csv::decode_consistency_record(payload, *delimiter)?
+                            }
+                        });
+                    }
+                }
+                Err(KafkaError::PartitionEOF(_)) => break,
+                Err(e) => bail!("reading consistency topic {}: {}", topic, e),
+            }
+        }
+
+        if timestamp.is_some() || start == low {
+            return Ok(timestamp);
+        }
+        window *= 2;
+    }
 }
 
 fn build_avro_ocf(
@@ -96,23 +331,7 @@ fn build_avro_ocf(
     frontier: Antichain<Timestamp>,
     id: GlobalId,
 ) -> Result<SinkConnector, failure::Error> {
-    let mut name = match builder.path.file_stem() {
-        None => bail!(
-            "unable to read file name from path {}",
-            builder.path.display()
-        ),
-        Some(stem) => stem.to_owned(),
-    };
-    name.push("-");
-    name.push(id.to_string());
-    name.push("-");
-    name.push(builder.file_name_suffix);
-    if let Some(extension) = builder.path.extension() {
-        name.push(".");
-        name.push(extension);
-    }
-
-    let path = builder.path.with_file_name(name);
+    let path = sink_file_path(&builder.path, id, builder.file_name_suffix)?;
 
     // Try to create a new sink file
     let _ = OpenOptions::new()
@@ -128,7 +347,124 @@ fn build_avro_ocf(
         })?;
     Ok(SinkConnector::AvroOcf(AvroOcfSinkConnector {
         path,
+        codec: builder.codec,
+        frontier,
+        strict: !with_snapshot,
+    }))
+}
+
+fn build_file(
+    builder: FileSinkConnectorBuilder,
+    with_snapshot: bool,
+    frontier: Antichain<Timestamp>,
+    id: GlobalId,
+) -> Result<SinkConnector, failure::Error> {
+    let path = sink_file_path(&builder.path, id, builder.file_name_suffix)?;
+
+    // Try to create a new sink file
+    let _ = OpenOptions::new()
+        .append(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|e| format_err!("unable to create sink file {} : {}", path.display(), e))?;
+    Ok(SinkConnector::File(FileSinkConnector {
+        path,
+        format: builder.format,
+        rotate_bytes: builder.rotate_bytes,
+        rotate_interval: builder.rotate_interval,
         frontier,
         strict: !with_snapshot,
     }))
 }
+
+async fn build_kinesis(
+    builder: KinesisSinkConnectorBuilder,
+    with_snapshot: bool,
+    frontier: Antichain<Timestamp>,
+) -> Result<SinkConnector, failure::Error> {
+    // Unlike Kafka sinks, Kinesis sinks write to an existing stream, so check
+    // that the stream exists up front rather than failing in the dataflow.
+    let client = aws_util::kinesis::kinesis_client(
+        builder.region.clone(),
+        builder.access_key_id.clone(),
+        builder.secret_access_key.clone(),
+        builder.token.clone(),
+    )
+    .await
+    .map_err(|e| format_err!("creating Kinesis client: {:#}", e))?;
+    aws_util::kinesis::get_shard_ids(&client, &builder.stream_name)
+        .await
+        .map_err(|e| {
+            format_err!(
+                "unable to find Kinesis stream {}: {:#}",
+                builder.stream_name,
+                e
+            )
+        })?;
+
+    Ok(SinkConnector::Kinesis(KinesisSinkConnector {
+        stream_name: builder.stream_name,
+        region: builder.region,
+        access_key_id: builder.access_key_id,
+        secret_access_key: builder.secret_access_key,
+        token: builder.token,
+        format: builder.format,
+        partition_key_indices: builder.partition_key_indices,
+        frontier,
+        strict: !with_snapshot,
+    }))
+}
+
+fn build_http(
+    builder: HttpSinkConnectorBuilder,
+    with_snapshot: bool,
+    frontier: Antichain<Timestamp>,
+    id: GlobalId,
+) -> Result<SinkConnector, failure::Error> {
+    // As with file sinks, the dead letter file is new to the sink and to this
+    // run of Materialize, so that the sink never writes to an existing file.
+    let dead_letter_path = match &builder.dead_letter_path {
+        None => None,
+        Some(path) => {
+            let path = sink_file_path(path, id, builder.file_name_suffix)?;
+            OpenOptions::new()
+                .append(true)
+                .create_new(true)
+                .open(&path)
+                .map_err(|e| {
+                    format_err!(
+                        "unable to create dead letter file {} : {}",
+                        path.display(),
+                        e
+                    )
+                })?;
+            Some(path)
+        }
+    };
+    Ok(SinkConnector::Http(HttpSinkConnector {
+        url: builder.url,
+        dead_letter_path,
+        max_retry: builder.max_retry,
+        queue: builder.queue,
+        frontier,
+        strict: !with_snapshot,
+    }))
+}
+
+/// Makes the path of a sink's file unique to the sink and to this run of
+/// Materialize, by inserting the sink's ID and `suffix` after the file stem.
+fn sink_file_path(path: &Path, id: GlobalId, suffix: String) -> Result<PathBuf, failure::Error> {
+    let mut name = match path.file_stem() {
+        None => bail!("unable to read file name from path {}", path.display()),
+        Some(stem) => stem.to_owned(),
+    };
+    name.push("-");
+    name.push(id.to_string());
+    name.push("-");
+    name.push(suffix);
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    Ok(path.with_file_name(name))
+}
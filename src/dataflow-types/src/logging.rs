@@ -90,6 +90,7 @@ pub enum MaterializedLog {
     Catalog,
     KafkaSinks,
     AvroOcfSinks,
+    FileSinks,
 }
 
 impl LogVariant {
@@ -113,6 +114,7 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::Catalog),
             LogVariant::Materialized(MaterializedLog::KafkaSinks),
             LogVariant::Materialized(MaterializedLog::AvroOcfSinks),
+            LogVariant::Materialized(MaterializedLog::FileSinks),
         ]
     }
 
@@ -141,6 +143,7 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::Catalog) => "mz_catalog_names",
             LogVariant::Materialized(MaterializedLog::KafkaSinks) => "mz_kafka_sinks",
             LogVariant::Materialized(MaterializedLog::AvroOcfSinks) => "mz_avro_ocf_sinks",
+            LogVariant::Materialized(MaterializedLog::FileSinks) => "mz_file_sinks",
         }
     }
 
@@ -165,6 +168,7 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::Catalog) => GlobalId::system(31),
             LogVariant::Materialized(MaterializedLog::KafkaSinks) => GlobalId::system(55),
             LogVariant::Materialized(MaterializedLog::AvroOcfSinks) => GlobalId::system(57),
+            LogVariant::Materialized(MaterializedLog::FileSinks) => GlobalId::system(59),
         }
     }
 
@@ -189,6 +193,7 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::Catalog) => GlobalId::system(32),
             LogVariant::Materialized(MaterializedLog::KafkaSinks) => GlobalId::system(56),
             LogVariant::Materialized(MaterializedLog::AvroOcfSinks) => GlobalId::system(58),
+            LogVariant::Materialized(MaterializedLog::FileSinks) => GlobalId::system(60),
         }
     }
 
@@ -316,6 +321,11 @@ impl LogVariant {
                 .with_nonnull_column("global_id", ScalarType::String)
                 .with_nonnull_column("path", ScalarType::Bytes)
                 .with_key(vec![0]),
+
+            LogVariant::Materialized(MaterializedLog::FileSinks) => RelationDesc::empty()
+                .with_nonnull_column("global_id", ScalarType::String)
+                .with_nonnull_column("path", ScalarType::Bytes)
+                .with_key(vec![0]),
         }
     }
 
@@ -371,6 +381,7 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::Catalog) => vec![],
             LogVariant::Materialized(MaterializedLog::KafkaSinks) => vec![],
             LogVariant::Materialized(MaterializedLog::AvroOcfSinks) => vec![],
+            LogVariant::Materialized(MaterializedLog::FileSinks) => vec![],
         }
     }
 }
@@ -76,6 +76,18 @@ pub struct Update {
     pub diff: isize,
 }
 
+/// An event reported by a tail sink.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TailEvent {
+    /// A change to the tailed relation.
+    Update(Update),
+    /// All changes at timestamps less than the given timestamp have been
+    /// reported.
+    Progress(Timestamp),
+    /// The tail failed, and no further events will be reported.
+    Error(String),
+}
+
 /// A description of view or index to be added to the local context
 /// for a dataflow
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -278,6 +290,7 @@ pub enum DataEncoding {
         regex: Regex,
     },
     Protobuf(ProtobufEncoding),
+    Json(JsonEncoding),
     Bytes,
     Text,
     AvroOcf {
@@ -343,6 +356,7 @@ impl DataEncoding {
             DataEncoding::Protobuf(ProtobufEncoding {
                 descriptors,
                 message_name,
+                confluent_wire_format: _,
             }) => {
                 let d = decode_descriptors(descriptors)?;
                 validate_descriptors(message_name, &d)?
@@ -367,6 +381,16 @@ impl DataEncoding {
                 .fold(key_desc, |desc, i| {
                     desc.with_nonnull_column(format!("column{}", i), ScalarType::String)
                 }),
+            DataEncoding::Json(JsonEncoding { columns }) => {
+                if columns.is_empty() {
+                    key_desc.with_nonnull_column("data", ScalarType::Jsonb)
+                } else {
+                    columns.iter().fold(key_desc, |desc, (name, scalar_type)| {
+                        let ty = ColumnType::new(scalar_type.clone()).nullable(true);
+                        desc.with_column(name.as_str(), ty)
+                    })
+                }
+            }
             DataEncoding::Text => key_desc.with_nonnull_column("text", ScalarType::String),
         })
     }
@@ -379,6 +403,7 @@ impl DataEncoding {
             DataEncoding::Protobuf(_) => "Protobuf",
             DataEncoding::Regex { .. } => "Regex",
             DataEncoding::Csv(_) => "Csv",
+            DataEncoding::Json(_) => "Json",
             DataEncoding::Text => "Text",
         }
     }
@@ -405,6 +430,19 @@ pub struct CsvEncoding {
 pub struct ProtobufEncoding {
     pub descriptors: Vec<u8>,
     pub message_name: String,
+    /// Whether messages are framed in the style of Confluent's Protobuf
+    /// serializer, with a header identifying their schema.
+    pub confluent_wire_format: bool,
+}
+
+/// Encoding in JSON format.
+///
+/// If `columns` is empty, each message is decoded into a single `jsonb`
+/// column. Otherwise each message must be a JSON object, and each named field
+/// of the object is extracted into a column of the corresponding type.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JsonEncoding {
+    pub columns: Vec<(String, ScalarType)>,
 }
 
 /// A source of updates for a relational collection.
@@ -469,6 +507,10 @@ impl ExternalSourceConnector {
     pub fn metadata_columns(&self) -> Vec<(ColumnName, ColumnType)> {
         match self {
             Self::Kafka(_) => vec![("mz_offset".into(), ColumnType::new(ScalarType::Int64))],
+            Self::File(c) if c.glob => vec![
+                ("mz_file_name".into(), ColumnType::new(ScalarType::String)),
+                ("mz_line_no".into(), ColumnType::new(ScalarType::Int64)),
+            ],
             Self::File(_) => vec![("mz_line_no".into(), ColumnType::new(ScalarType::Int64))],
             Self::Kinesis(_) => vec![],
             Self::AvroOcf(_) => vec![("mz_obj_no".into(), ColumnType::new(ScalarType::Int64))],
@@ -570,6 +612,10 @@ pub struct KinesisSourceConnector {
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct FileSourceConnector {
     pub path: PathBuf,
+    /// Whether `path` is a glob pattern, in which case the source reads every
+    /// file that matches it, and with `tail`, every file that comes to match
+    /// it later.
+    pub glob: bool,
     pub tail: bool,
 }
 
@@ -578,23 +624,133 @@ pub enum SinkConnector {
     Kafka(KafkaSinkConnector),
     Tail(TailSinkConnector),
     AvroOcf(AvroOcfSinkConnector),
+    File(FileSinkConnector),
+    Kinesis(KinesisSinkConnector),
+    Http(HttpSinkConnector),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct KafkaSinkConnector {
     pub url: Url,
     pub topic: String,
-    pub schema_id: i32,
+    pub format: KafkaSinkFormat,
+    /// The indices of the columns that form the key, if the sink is keyed.
+    pub key_indices: Option<Vec<usize>>,
+    pub envelope: SinkEnvelope,
+    /// The topic on which the sink marks each timestamp as complete, if the
+    /// sink writes exactly once.
+    ///
+    /// The sink writes the messages for each timestamp in a Kafka
+    /// transaction, along with a record on the consistency topic.
+    pub consistency_topic: Option<String>,
     // Maximum number of records the sink will attempt to send each time it is
     // invoked
     pub fuel: usize,
+    /// Bounds the messages that the sink buffers while Kafka is slow to
+    /// accept them.
+    pub queue: SinkQueueConfig,
     pub frontier: Antichain<Timestamp>,
     pub strict: bool,
 }
 
+/// The format in which a Kafka sink encodes its messages.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum KafkaSinkFormat {
+    /// Avro, with the IDs of the schemas that the sink published to the
+    /// Confluent Schema Registry.
+    Avro {
+        value_schema_id: i32,
+        key_schema_id: Option<i32>,
+        consistency_schema_id: Option<i32>,
+    },
+    /// Protobuf, with the IDs of the schemas that the sink published to the
+    /// Confluent Schema Registry.
+    Protobuf {
+        message_name: String,
+        value_schema_id: i32,
+        key_schema_id: Option<i32>,
+    },
+    /// One JSON object per message.
+    Json,
+    /// One CSV record per message.
+    Csv { delimiter: u8 },
+}
+
+/// The envelope in which a sink writes updates.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum SinkEnvelope {
+    /// Each update is written as a record with `before` and `after` fields,
+    /// in the style of Debezium.
+    Debezium,
+    /// Each update is written as the new value for its key. Deletions are
+    /// written as messages with a null value, i.e., tombstones.
+    Upsert,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct AvroOcfSinkConnector {
     pub path: PathBuf,
+    /// The codec that compresses the blocks of the file.
+    pub codec: avro::Codec,
+    pub frontier: Antichain<Timestamp>,
+    pub strict: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FileSinkConnector {
+    /// The path of the first file the sink writes. Each subsequent file has
+    /// the same path, with a `.N` suffix appended.
+    pub path: PathBuf,
+    pub format: FileSinkFormat,
+    /// The number of bytes after which the sink starts a new file, if any.
+    pub rotate_bytes: Option<u64>,
+    /// The span of timestamps after which the sink starts a new file, if any.
+    pub rotate_interval: Option<Duration>,
+    pub frontier: Antichain<Timestamp>,
+    pub strict: bool,
+}
+
+/// The format in which a file sink writes its change records, one per line.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum FileSinkFormat {
+    Json,
+    Csv { delimiter: u8 },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KinesisSinkConnector {
+    pub stream_name: String,
+    pub region: Region,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub token: Option<String>,
+    pub format: KinesisSinkFormat,
+    /// The indices of the columns from which the sink derives the partition
+    /// key of each record, if any. Otherwise, the partition key is derived
+    /// from the entire row.
+    pub partition_key_indices: Option<Vec<usize>>,
+    pub frontier: Antichain<Timestamp>,
+    pub strict: bool,
+}
+
+/// The format in which a Kinesis sink encodes its change records, one per
+/// Kinesis record.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum KinesisSinkFormat {
+    Json,
+    Csv { delimiter: u8 },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HttpSinkConnector {
+    /// The URL to which the sink POSTs each batch of changes.
+    pub url: Url,
+    /// The file to which the sink appends the batches that it could not
+    /// deliver, if any.
+    pub dead_letter_path: Option<PathBuf>,
+    /// How long the sink retries a batch before giving up on it.
+    pub max_retry: Duration,
+    pub queue: SinkQueueConfig,
     pub frontier: Antichain<Timestamp>,
     pub strict: bool,
 }
@@ -603,7 +759,10 @@ impl SinkConnector {
     pub fn get_frontier(&self) -> Antichain<Timestamp> {
         match self {
             SinkConnector::AvroOcf(avro) => avro.frontier.clone(),
+            SinkConnector::File(file) => file.frontier.clone(),
             SinkConnector::Kafka(kafka) => kafka.frontier.clone(),
+            SinkConnector::Kinesis(kinesis) => kinesis.frontier.clone(),
+            SinkConnector::Http(http) => http.frontier.clone(),
             SinkConnector::Tail(tail) => tail.frontier.clone(),
         }
     }
@@ -611,32 +770,153 @@ impl SinkConnector {
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TailSinkConnector {
-    pub tx: comm::mpsc::Sender<Vec<Update>>,
+    pub tx: comm::mpsc::Sender<Vec<TailEvent>>,
     pub frontier: Antichain<Timestamp>,
     pub strict: bool,
+    /// Whether to report progress events, in addition to updates.
+    pub progress: bool,
+    /// Bounds the events that the sink buffers while the client is slow to
+    /// receive them.
+    pub queue: SinkQueueConfig,
+}
+
+/// Bounds the data that a sink buffers in memory while it waits for its
+/// destination to accept the data.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SinkQueueConfig {
+    /// The maximum number of bytes of data that the sink may buffer in memory.
+    pub max_bytes: usize,
+    /// What the sink does with data that would exceed `max_bytes`.
+    pub on_full: QueueFullBehavior,
+}
+
+impl Default for SinkQueueConfig {
+    fn default() -> Self {
+        SinkQueueConfig {
+            max_bytes: 64 << 20,
+            on_full: QueueFullBehavior::Block,
+        }
+    }
+}
+
+/// What a sink does when its queue of undelivered data is full.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum QueueFullBehavior {
+    /// Prioritize delivering data to the destination until it accepts enough
+    /// data to make room. TAIL and HTTP sinks stall the worker while they
+    /// wait, which stalls every other dataflow on the worker, too.
+    Block,
+    /// Shut down the sink with an error.
+    Error,
+    /// Write the data that does not fit to a temporary file, from which it is
+    /// read back, in order, as the destination catches up.
+    Spill,
+}
+
+impl QueueFullBehavior {
+    /// Parses a behavior from its name, as it appears in SQL.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "block" => Some(QueueFullBehavior::Block),
+            "error" => Some(QueueFullBehavior::Error),
+            "spill" => Some(QueueFullBehavior::Spill),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the behavior, as it appears in SQL.
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueueFullBehavior::Block => "block",
+            QueueFullBehavior::Error => "error",
+            QueueFullBehavior::Spill => "spill",
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum SinkConnectorBuilder {
     Kafka(KafkaSinkConnectorBuilder),
     AvroOcf(AvroOcfSinkConnectorBuilder),
+    File(FileSinkConnectorBuilder),
+    Kinesis(KinesisSinkConnectorBuilder),
+    Http(HttpSinkConnectorBuilder),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct AvroOcfSinkConnectorBuilder {
     pub path: PathBuf,
     pub file_name_suffix: String,
+    pub codec: avro::Codec,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FileSinkConnectorBuilder {
+    pub path: PathBuf,
+    pub file_name_suffix: String,
+    pub format: FileSinkFormat,
+    pub rotate_bytes: Option<u64>,
+    pub rotate_interval: Option<Duration>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KinesisSinkConnectorBuilder {
+    pub stream_name: String,
+    pub region: Region,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub token: Option<String>,
+    pub format: KinesisSinkFormat,
+    pub partition_key_indices: Option<Vec<usize>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HttpSinkConnectorBuilder {
+    pub url: Url,
+    pub dead_letter_path: Option<PathBuf>,
+    pub file_name_suffix: String,
+    pub max_retry: Duration,
+    pub queue: SinkQueueConfig,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct KafkaSinkConnectorBuilder {
     pub broker_url: Url,
-    pub schema_registry_url: Url,
-    pub value_schema: String,
+    pub format: KafkaSinkFormatBuilder,
+    /// The indices of the columns that form the key, if the sink is keyed.
+    pub key_indices: Option<Vec<usize>>,
+    pub envelope: SinkEnvelope,
     pub topic_prefix: String,
     pub topic_suffix: String,
+    /// Whether to write to the same topic across restarts, resuming after the
+    /// last complete timestamp, rather than to a new topic.
+    pub reuse_topic: bool,
     pub replication_factor: u32,
     pub fuel: usize,
+    pub queue: SinkQueueConfig,
+}
+
+/// The format in which a Kafka sink will encode its messages.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum KafkaSinkFormatBuilder {
+    /// Avro, with schemas to be published to a Confluent Schema Registry.
+    Avro {
+        schema_registry_url: Url,
+        value_schema: String,
+        key_schema: Option<String>,
+    },
+    /// Protobuf, with `.proto` schemas to be published to a Confluent Schema
+    /// Registry.
+    Protobuf {
+        schema_registry_url: Url,
+        message_name: String,
+        value_schema: String,
+        key_schema: Option<String>,
+    },
+    Json,
+    Csv {
+        delimiter: u8,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
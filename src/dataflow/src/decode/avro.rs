@@ -102,6 +102,7 @@ impl DecoderState for AvroDecoderState {
         &mut self,
         bytes: &[u8],
         coord: Option<i64>,
+        _: Option<&str>,
         session: &mut PushSession<'a, (Row, Timestamp, Diff)>,
         time: Timestamp,
     ) {
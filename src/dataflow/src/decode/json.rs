@@ -0,0 +1,200 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::mem;
+
+use async_trait::async_trait;
+use failure::{bail, format_err};
+use log::error;
+use serde_json::Value;
+
+use dataflow_types::{Diff, Timestamp};
+use repr::adt::jsonb::JsonbPacker;
+use repr::{strconv, Datum, Row, RowPacker, ScalarType};
+
+use super::{DecoderState, PushSession};
+use crate::metrics::EVENTS_COUNTER;
+
+pub struct JsonDecoderState {
+    /// The columns to extract from each JSON object. If empty, each message
+    /// is decoded into a single `jsonb` datum.
+    columns: Vec<(String, ScalarType)>,
+    packer: RowPacker,
+    events_success: i64,
+    events_error: i64,
+}
+
+impl JsonDecoderState {
+    pub fn new(columns: Vec<(String, ScalarType)>) -> Self {
+        JsonDecoderState {
+            columns,
+            packer: RowPacker::new(),
+            events_success: 0,
+            events_error: 0,
+        }
+    }
+
+    fn decode(
+        &mut self,
+        bytes: &[u8],
+        file_name: Option<&str>,
+        line_no: Option<i64>,
+    ) -> Result<Row, failure::Error> {
+        // The packer is lost if decoding fails partway through a row, so
+        // take it rather than borrowing it.
+        let mut packer = mem::take(&mut self.packer);
+        if self.columns.is_empty() {
+            packer = JsonbPacker::new(packer).pack_slice(bytes)?;
+        } else {
+            let mut object = match serde_json::from_slice::<Value>(bytes)? {
+                Value::Object(object) => object,
+                _ => bail!("expected JSON object"),
+            };
+            for (name, scalar_type) in &self.columns {
+                packer = match object.remove(name) {
+                    None | Some(Value::Null) => {
+                        packer.push(Datum::Null);
+                        packer
+                    }
+                    Some(value) => pack_value(packer, value, scalar_type)
+                        .map_err(|e| format_err!("column \"{}\": {}", name, e))?,
+                };
+            }
+        }
+        if let Some(file_name) = file_name {
+            packer.push(Datum::from(file_name));
+        }
+        if let Some(line_no) = line_no {
+            packer.push(Datum::from(line_no));
+        }
+        let row = packer.finish_and_reuse();
+        self.packer = packer;
+        Ok(row)
+    }
+}
+
+/// Packs `value` into `packer` as a datum of type `scalar_type`.
+///
+/// JSON strings are parsed as if they were the text representation of the
+/// type. Other JSON values are parsed from their JSON text, so that, e.g., a
+/// JSON number can populate an integer column and any JSON value can populate
+/// a string column.
+fn pack_value(
+    mut packer: RowPacker,
+    value: Value,
+    scalar_type: &ScalarType,
+) -> Result<RowPacker, failure::Error> {
+    if let ScalarType::Jsonb = scalar_type {
+        return JsonbPacker::new(packer).pack_serde_json(value);
+    }
+    let text = match value {
+        Value::String(s) => s,
+        value => value.to_string(),
+    };
+    let bytes;
+    let datum = match scalar_type {
+        ScalarType::Bool => Datum::from(strconv::parse_bool(&text)?),
+        ScalarType::Int32 => Datum::from(strconv::parse_int32(&text)?),
+        ScalarType::Int64 => Datum::from(strconv::parse_int64(&text)?),
+        ScalarType::Float32 => Datum::from(strconv::parse_float32(&text)?),
+        ScalarType::Float64 => Datum::from(strconv::parse_float64(&text)?),
+        ScalarType::Decimal(precision, scale) => {
+            Datum::from(strconv::parse_decimal(&text)?.rescale(*scale, *precision)?)
+        }
+        ScalarType::Date => Datum::from(strconv::parse_date(&text)?),
+        ScalarType::Time => Datum::Time(strconv::parse_time(&text)?),
+        ScalarType::Timestamp => Datum::from(strconv::parse_timestamp(&text)?),
+        ScalarType::TimestampTz => Datum::from(strconv::parse_timestamptz(&text)?),
+        ScalarType::Interval => Datum::from(strconv::parse_interval(&text)?),
+        ScalarType::Bytes => {
+            bytes = strconv::parse_bytes(&text)?;
+            Datum::Bytes(&bytes)
+        }
+        ScalarType::String => Datum::String(&text),
+        ScalarType::Jsonb | ScalarType::List(_) | ScalarType::Record { .. } => {
+            unreachable!("JSON source columns are pre-validated")
+        }
+    };
+    packer.push(datum);
+    Ok(packer)
+}
+
+#[async_trait(?Send)]
+impl DecoderState for JsonDecoderState {
+    /// Reset number of success and failures with decoding
+    fn reset_event_count(&mut self) {
+        self.events_success = 0;
+        self.events_error = 0;
+    }
+
+    async fn decode_key(&mut self, bytes: &[u8]) -> Result<Row, String> {
+        match self.decode(bytes, None, None) {
+            Ok(row) => {
+                self.events_success += 1;
+                Ok(row)
+            }
+            Err(err) => {
+                self.events_error += 1;
+                Err(format!("JSON deserialization error: {}", err))
+            }
+        }
+    }
+
+    /// give a session a key-value pair
+    async fn give_key_value<'a>(
+        &mut self,
+        key: Row,
+        bytes: &[u8],
+        line_no: Option<i64>,
+        session: &mut PushSession<'a, (Row, Option<Row>, Timestamp)>,
+        time: Timestamp,
+    ) {
+        match self.decode(bytes, None, line_no) {
+            Ok(row) => {
+                self.events_success += 1;
+                session.give((key, Some(row), time));
+            }
+            Err(err) => {
+                self.events_error += 1;
+                error!("JSON deserialization error: {}", err)
+            }
+        }
+    }
+
+    /// give a session a plain value
+    async fn give_value<'a>(
+        &mut self,
+        bytes: &[u8],
+        line_no: Option<i64>,
+        file_name: Option<&str>,
+        session: &mut PushSession<'a, (Row, Timestamp, Diff)>,
+        time: Timestamp,
+    ) {
+        match self.decode(bytes, file_name, line_no) {
+            Ok(row) => {
+                self.events_success += 1;
+                session.give((row, time, 1));
+            }
+            Err(err) => {
+                self.events_error += 1;
+                error!("JSON deserialization error: {}", err)
+            }
+        }
+    }
+
+    /// Register number of success and failures with decoding
+    fn log_error_count(&self) {
+        if self.events_success > 0 {
+            EVENTS_COUNTER.json.success.inc_by(self.events_success);
+        }
+        if self.events_error > 0 {
+            EVENTS_COUNTER.json.error.inc_by(self.events_error);
+        }
+    }
+}
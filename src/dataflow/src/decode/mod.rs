@@ -26,6 +26,7 @@ use repr::{Row, RowPacker};
 
 mod avro;
 mod csv;
+mod json;
 mod protobuf;
 mod regex;
 
@@ -68,7 +69,7 @@ where
             SourceOutput {
                 value,
                 position: index,
-                key: _,
+                ..
             },
             r,
             d,
@@ -128,11 +129,13 @@ pub trait DecoderState {
         session: &mut PushSession<'a, (Row, Option<Row>, Timestamp)>,
         time: Timestamp,
     );
-    /// give a session a plain value
+    /// give a session a plain value, along with the name of the file it was
+    /// read from, if the source reads more than one file
     async fn give_value<'a>(
         &mut self,
         bytes: &[u8],
         aux_num: Option<i64>,
+        file_name: Option<&str>,
         session: &mut PushSession<'a, (Row, Timestamp, Diff)>,
         time: Timestamp,
     );
@@ -140,8 +143,12 @@ pub trait DecoderState {
     fn log_error_count(&self);
 }
 
-fn pack_with_line_no(datum: Datum, line_no: Option<i64>) -> Row {
-    Row::pack(iter::once(datum).chain(line_no.map(Datum::from)))
+fn pack_with_line_no(datum: Datum, file_name: Option<&str>, line_no: Option<i64>) -> Row {
+    Row::pack(
+        iter::once(datum)
+            .chain(file_name.map(Datum::from))
+            .chain(line_no.map(Datum::from)),
+    )
 }
 
 fn bytes_to_datum(bytes: &[u8]) -> Datum {
@@ -188,7 +195,7 @@ where
     ) {
         session.give((
             key,
-            Some(pack_with_line_no((self.datum_func)(bytes), line_no)),
+            Some(pack_with_line_no((self.datum_func)(bytes), None, line_no)),
             time,
         ));
     }
@@ -198,11 +205,12 @@ where
         &mut self,
         bytes: &[u8],
         line_no: Option<i64>,
+        file_name: Option<&str>,
         session: &mut PushSession<'a, (Row, Timestamp, Diff)>,
         time: Timestamp,
     ) {
         session.give((
-            pack_with_line_no((self.datum_func)(bytes), line_no),
+            pack_with_line_no((self.datum_func)(bytes), file_name, line_no),
             time,
             1,
         ));
@@ -397,12 +405,14 @@ where
                     key: _,
                     value: payload,
                     position: aux_num,
+                    file_name,
                 } in data.iter()
                 {
                     if !payload.is_empty() {
                         block_on(value_decoder_state.give_value(
                             payload,
                             *aux_num,
+                            file_name.as_deref(),
                             &mut session,
                             *cap.time(),
                         ));
@@ -482,9 +492,19 @@ where
             "Internal error: A non-Avro Debezium-envelope source should not have been created."
         ),
         (DataEncoding::Regex { regex }, Envelope::None) => regex_fn(stream, regex, debug_name),
+        (DataEncoding::Json(enc), Envelope::None) => decode_values_inner(
+            stream,
+            json::JsonDecoderState::new(enc.columns),
+            &op_name,
+            SourceOutput::<Vec<u8>, Vec<u8>>::value_contract(),
+        ),
         (DataEncoding::Protobuf(enc), Envelope::None) => decode_values_inner(
             stream,
-            protobuf::ProtobufDecoderState::new(&enc.descriptors, &enc.message_name),
+            protobuf::ProtobufDecoderState::new(
+                &enc.descriptors,
+                &enc.message_name,
+                enc.confluent_wire_format,
+            ),
             &op_name,
             SourceOutput::<Vec<u8>, Vec<u8>>::value_contract(),
         ),
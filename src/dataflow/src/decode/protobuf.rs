@@ -24,11 +24,11 @@ pub struct ProtobufDecoderState {
 }
 
 impl ProtobufDecoderState {
-    pub fn new(descriptors: &[u8], message_name: &str) -> Self {
+    pub fn new(descriptors: &[u8], message_name: &str, confluent_wire_format: bool) -> Self {
         let descriptors = protobuf::decode_descriptors(descriptors)
             .expect("descriptors provided to protobuf source are pre-validated");
         ProtobufDecoderState {
-            decoder: Decoder::new(descriptors, message_name),
+            decoder: Decoder::new(descriptors, message_name, confluent_wire_format),
             events_success: 0,
             events_error: 0,
         }
@@ -87,6 +87,7 @@ impl DecoderState for ProtobufDecoderState {
         &mut self,
         bytes: &[u8],
         _: Option<i64>,
+        _: Option<&str>,
         session: &mut PushSession<'a, (Row, Timestamp, Diff)>,
         time: Timestamp,
     ) {
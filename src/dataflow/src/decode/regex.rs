@@ -37,7 +37,7 @@ where
             move |input, output| {
                 input.for_each(|cap, lines| {
                     let mut session = output.session(&cap);
-                    for SourceOutput {key: _, value: line, position: line_no} in &*lines {
+                    for SourceOutput {key: _, value: line, position: line_no, file_name} in &*lines {
                         let line = match str::from_utf8(&line) {
                             Ok(line) => line,
                             _ => {
@@ -60,7 +60,9 @@ where
                                 |m| Datum::from( m.map(
                                     |m| m.as_str())
                                 )
-                            ).chain(iter::once(line_no.map(Datum::Int64).into()))),
+                            )
+                            .chain(file_name.as_deref().map(Datum::String))
+                            .chain(iter::once(line_no.map(Datum::Int64).into()))),
                             *cap.time(),
                             1,
                         ));
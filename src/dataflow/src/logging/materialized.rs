@@ -52,6 +52,15 @@ pub enum MaterializedEvent {
         /// Globally unique identifier for the source on which the dataflow depends.
         source: GlobalId,
     },
+    /// File sink.
+    FileSink {
+        /// Globally unique identifier for the sink.
+        id: GlobalId,
+        /// Path of the first file written to.
+        path: Vec<u8>,
+        /// True for insertions, false for deletions.
+        insert: bool,
+    },
     /// Kafka sink.
     KafkaSink {
         /// Globally unique identifer for the sink.
@@ -127,6 +136,7 @@ pub fn construct<A: Allocate>(
         let (mut catalog_out, catalog) = demux.new_output();
         let (mut kafka_sinks_out, kafka_sinks) = demux.new_output();
         let (mut avro_ocf_sinks_out, avro_ocf_sinks) = demux.new_output();
+        let (mut file_sinks_out, file_sinks) = demux.new_output();
 
         let mut demux_buffer = Vec::new();
         demux.build(move |_capability| {
@@ -145,6 +155,7 @@ pub fn construct<A: Allocate>(
                 let mut catalog = catalog_out.activate();
                 let mut kafka_sinks = kafka_sinks_out.activate();
                 let mut avro_ocf_sinks = avro_ocf_sinks_out.activate();
+                let mut file_sinks = file_sinks_out.activate();
 
                 input.for_each(|time, data| {
                     data.swap(&mut demux_buffer);
@@ -158,6 +169,7 @@ pub fn construct<A: Allocate>(
                     let mut catalog_session = catalog.session(&time);
                     let mut kafka_sinks_session = kafka_sinks.session(&time);
                     let mut avro_ocf_sinks_session = avro_ocf_sinks.session(&time);
+                    let mut file_sinks_session = file_sinks.session(&time);
 
                     for (time, worker, datum) in demux_buffer.drain(..) {
                         let time_ns = time.as_nanos() as Timestamp;
@@ -258,6 +270,16 @@ pub fn construct<A: Allocate>(
                                     ),
                                 }
                             }
+                            MaterializedEvent::FileSink { id, path, insert } => {
+                                file_sinks_session.give((
+                                    row_packer.pack(&[
+                                        Datum::String(&id.to_string()),
+                                        Datum::Bytes(&path),
+                                    ]),
+                                    time_ms,
+                                    if insert { 1 } else { -1 },
+                                ));
+                            }
                             MaterializedEvent::KafkaSink { id, topic, insert } => {
                                 kafka_sinks_session.give((
                                     row_packer.pack(&[
@@ -392,6 +414,7 @@ pub fn construct<A: Allocate>(
         });
         let kafka_sinks = kafka_sinks.as_collection();
         let avro_ocf_sinks = avro_ocf_sinks.as_collection();
+        let file_sinks = file_sinks.as_collection();
 
         // Duration statistics derive from the non-rounded event times.
         let peek_duration = peek
@@ -495,6 +518,10 @@ pub fn construct<A: Allocate>(
                 LogVariant::Materialized(MaterializedLog::AvroOcfSinks),
                 avro_ocf_sinks,
             ),
+            (
+                LogVariant::Materialized(MaterializedLog::FileSinks),
+                file_sinks,
+            ),
         ];
 
         use differential_dataflow::operators::arrange::arrangement::ArrangeByKey;
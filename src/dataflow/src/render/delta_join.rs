@@ -18,11 +18,13 @@ use expr::{RelationExpr, ScalarExpr};
 use repr::{Datum, Row, RowArena};
 
 use super::context::{ArrangementFlavor, Context};
+use super::let_rec::RenderTimestamp;
 use crate::operator::CollectionExt;
 
 impl<G> Context<G, RelationExpr, Row, Timestamp>
 where
-    G: Scope<Timestamp = Timestamp>,
+    G: Scope,
+    G::Timestamp: RenderTimestamp,
 {
     /// Renders `RelationExpr:Join` using dogs^3 delta query dataflows.
     pub fn render_delta_join<F>(
@@ -0,0 +1,166 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Rendering of recursive bindings in iterative scopes.
+
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::operators::iterate::Variable;
+use differential_dataflow::Collection;
+use timely::dataflow::Scope;
+use timely::order::Product;
+use timely::progress::timestamp::Refines;
+
+use dataflow_types::Timestamp;
+use expr::{Id, RelationExpr};
+use repr::Row;
+
+use crate::render::context::Context;
+
+/// Timestamps of the scopes in which a `RelationExpr` can be rendered.
+///
+/// These are the timestamp of the dataflow itself, and that of the iterative
+/// scopes introduced by `RelationExpr::LetRec`, which may not be nested.
+pub trait RenderTimestamp: timely::progress::Timestamp + Lattice + Refines<Timestamp> {
+    /// Produces a timestamp no greater than `self`, and strictly less where possible.
+    ///
+    /// Delta queries use this to look up arrangements as of just before a change.
+    fn step_back(&self) -> Self;
+
+    /// Renders a `RelationExpr::LetRec` in a scope with this timestamp.
+    fn render_let_rec<G>(
+        context: &mut Context<G, RelationExpr, Row, Timestamp>,
+        relation_expr: &RelationExpr,
+        scope: &mut G,
+        worker_index: usize,
+    ) where
+        G: Scope<Timestamp = Self>;
+}
+
+impl RenderTimestamp for Timestamp {
+    fn step_back(&self) -> Self {
+        self.saturating_sub(1)
+    }
+
+    fn render_let_rec<G>(
+        context: &mut Context<G, RelationExpr, Row, Timestamp>,
+        relation_expr: &RelationExpr,
+        scope: &mut G,
+        worker_index: usize,
+    ) where
+        G: Scope<Timestamp = Self>,
+    {
+        context.render_let_rec(relation_expr, scope, worker_index)
+    }
+}
+
+impl RenderTimestamp for Product<Timestamp, u64> {
+    fn step_back(&self) -> Self {
+        Product::new(self.outer.saturating_sub(1), self.inner.saturating_sub(1))
+    }
+
+    fn render_let_rec<G>(
+        _context: &mut Context<G, RelationExpr, Row, Timestamp>,
+        _relation_expr: &RelationExpr,
+        _scope: &mut G,
+        _worker_index: usize,
+    ) where
+        G: Scope<Timestamp = Self>,
+    {
+        // Planning never places a recursive binding inside the value of another.
+        panic!("Attempt to render nested LetRec");
+    }
+}
+
+impl<G> Context<G, RelationExpr, Row, Timestamp>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    /// Renders `RelationExpr::LetRec` using an iterative scope.
+    ///
+    /// The value is rendered in a fresh context within the iterative scope. The
+    /// collections of the other free `Get`s in the value are imported into it, and the
+    /// recursive `Get`s are bound to a variable that presents the result of the
+    /// previous iteration, starting from an empty collection. Imported arrangements
+    /// are flattened to collections, as their types do not survive entering the scope.
+    pub fn render_let_rec(
+        &mut self,
+        relation_expr: &RelationExpr,
+        scope: &mut G,
+        worker_index: usize,
+    ) {
+        if let RelationExpr::LetRec { id, value, body } = relation_expr {
+            let bind = RelationExpr::Get {
+                id: Id::Local(*id),
+                typ: value.typ(),
+            };
+            if self.has_collection(&bind) {
+                panic!("Inappropriate to re-bind name: {:?}", bind);
+            }
+
+            // `Get`s of the `Let`s within the value are bound by the value itself,
+            // and are rendered along with it.
+            let mut bound = Vec::new();
+            value.visit(&mut |e| {
+                if let RelationExpr::Let { id, .. } = e {
+                    bound.push(Id::Local(*id));
+                }
+            });
+
+            let mut recursive = Vec::new();
+            let mut imports = Vec::new();
+            value.visit(&mut |e| {
+                if let RelationExpr::Get { id: get_id, .. } = e {
+                    let gets = if *get_id == Id::Local(*id) {
+                        &mut recursive
+                    } else if bound.contains(get_id) {
+                        return;
+                    } else {
+                        &mut imports
+                    };
+                    if !gets.contains(e) {
+                        gets.push(e.clone());
+                    }
+                }
+            });
+            let imports = imports
+                .into_iter()
+                .map(|get| match self.collection(&get) {
+                    Some(collections) => (get, collections),
+                    None => panic!("Collection {:?} not pre-loaded", get),
+                })
+                .collect::<Vec<_>>();
+
+            let (oks, errs) = scope.clone().iterative::<u64, _, _>(|inner| {
+                let mut context = Context::<_, _, _, Timestamp>::new();
+                for (get, (oks, errs)) in imports {
+                    context
+                        .collections
+                        .insert(get, (oks.enter(inner), errs.enter(inner)));
+                }
+
+                // Errors in the value are reported once it is left, rather than
+                // being fed back around the loop.
+                let variable = Variable::new(inner, Product::new(Default::default(), 1));
+                for get in recursive {
+                    context
+                        .collections
+                        .insert(get, ((*variable).clone(), Collection::empty(inner)));
+                }
+
+                context.ensure_rendered(value, inner, worker_index);
+                let (oks, errs) = context.collection(value).unwrap();
+                (variable.set(&oks).leave(), errs.leave())
+            });
+
+            self.collections.insert(bind.clone(), (oks, errs));
+            self.ensure_rendered(body, scope, worker_index);
+            self.clone_from_to(body, relation_expr);
+        }
+    }
+}
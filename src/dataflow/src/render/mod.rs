@@ -130,6 +130,7 @@ use ore::iter::IteratorExt;
 use repr::{Datum, RelationType, Row, RowArena};
 
 use self::context::{ArrangementFlavor, Context};
+use self::let_rec::RenderTimestamp;
 use super::sink;
 use super::source;
 use super::source::FileReadStyle;
@@ -147,6 +148,7 @@ mod arrange_by;
 mod context;
 mod delta_join;
 mod join;
+mod let_rec;
 mod reduce;
 mod threshold;
 mod top_k;
@@ -388,7 +390,7 @@ pub(crate) fn build_dataflow<A: Allocate>(
                                 };
 
                                 let ((source, err_source), capability) =
-                                    source::file(source_config, c.path, read_style, ctor);
+                                    source::file(source_config, c.path, false, read_style, ctor);
                                 err_collection = err_collection.concat(
                                     &err_source
                                         .map(DataflowError::SourceError)
@@ -426,7 +428,8 @@ pub(crate) fn build_dataflow<A: Allocate>(
                                         };
                                         let ctor =
                                             |file| Ok(std::io::BufReader::new(file).split(b'\n'));
-                                        source::file(source_config, c.path, read_style, ctor)
+                                        let glob = c.glob;
+                                        source::file(source_config, c.path, glob, read_style, ctor)
                                     }
                                     ExternalSourceConnector::AvroOcf(_) => unreachable!(),
                                 };
@@ -726,6 +729,18 @@ pub(crate) fn build_dataflow<A: Allocate>(
                         sink::avro_ocf(&collection.inner, sink_id, c, sink.from.1);
                         None
                     }
+                    SinkConnector::File(c) => {
+                        sink::file(&collection.inner, sink_id, c, sink.from.1);
+                        None
+                    }
+                    SinkConnector::Kinesis(c) => {
+                        sink::kinesis(&collection.inner, sink_id, c, sink.from.1);
+                        None
+                    }
+                    SinkConnector::Http(c) => {
+                        sink::http(&collection.inner, sink_id, c, sink.from.1);
+                        None
+                    }
                 };
 
                 if let Some(sink_token) = sink_shutdown {
@@ -772,6 +787,7 @@ where
                             key,
                             value: val,
                             position,
+                            ..
                         },
                         time,
                     ) in vector.drain(..)
@@ -812,7 +828,8 @@ where
 
 impl<G> Context<G, RelationExpr, Row, Timestamp>
 where
-    G: Scope<Timestamp = Timestamp>,
+    G: Scope,
+    G::Timestamp: RenderTimestamp,
 {
     /// Ensures the context contains an entry for `relation_expr`.
     ///
@@ -877,6 +894,15 @@ where
                     }
                 }
 
+                RelationExpr::LetRec { .. } => {
+                    <G::Timestamp as RenderTimestamp>::render_let_rec(
+                        self,
+                        relation_expr,
+                        scope,
+                        worker_index,
+                    );
+                }
+
                 RelationExpr::Project { input, outputs } => {
                     self.ensure_rendered(input, scope, worker_index);
                     let outputs = outputs.clone();
@@ -1015,7 +1041,7 @@ where
                             }
                             expr::JoinImplementation::DeltaQuery(_orders) => self
                                 .render_delta_join(input, predicates, scope, worker_index, |t| {
-                                    t.saturating_sub(1)
+                                    t.step_back()
                                 }),
                             expr::JoinImplementation::Unimplemented => {
                                 panic!("Attempt to render unimplemented join");
@@ -1062,7 +1088,7 @@ where
                                 &[],
                                 scope,
                                 worker_index,
-                                |t| t.saturating_sub(1),
+                                |t| t.step_back(),
                             );
                             self.collections.insert(relation_expr.clone(), collection);
                         }
@@ -562,6 +562,6 @@ fn accumulable_hierarchical(func: &AggregateFunc) -> (bool, bool) {
         | AggregateFunc::MinDate
         | AggregateFunc::MinTimestamp
         | AggregateFunc::MinTimestampTz => (false, true),
-        AggregateFunc::JsonbAgg => (false, false),
+        AggregateFunc::JsonbAgg | AggregateFunc::Window { .. } => (false, false),
     }
 }
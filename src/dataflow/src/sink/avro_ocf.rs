@@ -34,7 +34,7 @@ pub fn avro_ocf<G>(
 
     let res = OpenOptions::new().append(true).open(&connector.path);
     let mut avro_writer = match res {
-        Ok(f) => Some(avro::Writer::new(schema.clone(), f)),
+        Ok(f) => Some(avro::Writer::with_codec(schema.clone(), f, connector.codec)),
         Err(e) => {
             error!("creating avro ocf file writer for sink failed: {}", e);
             None
@@ -0,0 +1,198 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use differential_dataflow::hashable::Hashable;
+use log::error;
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::{Scope, Stream};
+
+use dataflow_types::{Diff, FileSinkConnector, FileSinkFormat, Timestamp};
+use expr::GlobalId;
+use repr::{RelationDesc, Row};
+
+use super::util::ChangeEncoder;
+
+/// Writes the updates in `stream` to the files described by `connector`, as
+/// newline-delimited change records.
+///
+/// The updates for each timestamp are written once the timestamp is complete,
+/// in timestamp order. Files are only rotated between timestamps, so that all
+/// of the records for a timestamp appear in the same file.
+pub fn file<G>(
+    stream: &Stream<G, (Row, Timestamp, Diff)>,
+    id: GlobalId,
+    connector: FileSinkConnector,
+    desc: RelationDesc,
+) where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let encoder = match connector.format {
+        FileSinkFormat::Json => ChangeEncoder::json(desc),
+        FileSinkFormat::Csv { delimiter } => ChangeEncoder::csv(desc, delimiter),
+    };
+    let sink_hash = id.hashed();
+
+    let res = OpenOptions::new().append(true).open(&connector.path);
+    let mut writer = match res {
+        Ok(f) => Some(RotatingWriter::new(&connector, f)),
+        Err(e) => {
+            error!("opening file for sink {} failed: {}", id, e);
+            None
+        }
+    };
+    let mut pending = Vec::new();
+
+    stream.sink(
+        Exchange::new(move |_| sink_hash),
+        &format!("file-{}", id),
+        move |input| {
+            input.for_each(|_, rows| {
+                for (row, time, diff) in rows.iter() {
+                    let should_emit = if connector.strict {
+                        connector.frontier.less_than(time)
+                    } else {
+                        connector.frontier.less_equal(time)
+                    };
+                    if should_emit && *diff != 0 {
+                        pending.push((row.clone(), *time, *diff));
+                    }
+                }
+            });
+
+            let writer = match &mut writer {
+                Some(writer) => writer,
+                None => {
+                    pending.clear();
+                    return;
+                }
+            };
+
+            let frontier = input.frontier();
+            let (mut complete, incomplete): (Vec<_>, Vec<_>) = pending
+                .drain(..)
+                .partition(|(_, time, _)| !frontier.less_equal(time));
+            pending = incomplete;
+            if complete.is_empty() {
+                return;
+            }
+            complete.sort_by_key(|(_, time, _)| *time);
+
+            if let Err(e) = write_updates(writer, &encoder, &complete) {
+                error!("writing to file sink {} failed: {}", id, e);
+            }
+        },
+    )
+}
+
+/// Writes `updates`, which must be sorted by time, to `writer`.
+fn write_updates(
+    writer: &mut RotatingWriter,
+    encoder: &Encoder,
+    updates: &[(Row, Timestamp, Diff)],
+) -> Result<(), io::Error> {
+    for (row, time, diff) in updates {
+        writer.start_timestamp(*time)?;
+        let mut record = encoder.encode(row, *time, *diff);
+        record.push(b'\n');
+        // For diffs other than +/- 1, we write repeated copies of the record,
+        // as the record itself captures the polarity of the update.
+        for _ in 0..diff.abs() {
+            writer.write_all(&record)?;
+        }
+    }
+    writer.flush()
+}
+
+/// Writes to a sequence of files, starting a new file between timestamps
+/// once the current file is large enough or spans enough timestamps.
+///
+/// The first file is written at the sink's path, and each subsequent file at
+/// the same path with a `.N` suffix, where `N` counts up from one.
+struct RotatingWriter {
+    path: PathBuf,
+    rotate_bytes: Option<u64>,
+    rotate_interval_ms: Option<u64>,
+    file: BufWriter<File>,
+    /// The number of files started after the first.
+    seq: usize,
+    /// The number of bytes written to the current file.
+    bytes: u64,
+    /// The first and last timestamps written to the current file, if any.
+    timestamps: Option<(Timestamp, Timestamp)>,
+}
+
+impl RotatingWriter {
+    fn new(connector: &FileSinkConnector, file: File) -> Self {
+        RotatingWriter {
+            path: connector.path.clone(),
+            rotate_bytes: connector.rotate_bytes,
+            rotate_interval_ms: connector.rotate_interval.map(|i| i.as_millis() as u64),
+            file: BufWriter::new(file),
+            seq: 0,
+            bytes: 0,
+            timestamps: None,
+        }
+    }
+
+    /// Prepares to write a record at `time`, which must not be less than the
+    /// time of any previous record, rotating the file if necessary.
+    fn start_timestamp(&mut self, time: Timestamp) -> Result<(), io::Error> {
+        let (first, last) = match self.timestamps {
+            None => {
+                self.timestamps = Some((time, time));
+                return Ok(());
+            }
+            Some(timestamps) => timestamps,
+        };
+        if time == last {
+            return Ok(());
+        }
+        let full = self.rotate_bytes.map_or(false, |n| self.bytes >= n)
+            || self
+                .rotate_interval_ms
+                .map_or(false, |ms| time - first >= ms);
+        if full {
+            self.file.flush()?;
+            self.seq += 1;
+            let file = OpenOptions::new()
+                .append(true)
+                .create_new(true)
+                .open(rotated_path(&self.path, self.seq))?;
+            self.file = BufWriter::new(file);
+            self.bytes = 0;
+            self.timestamps = Some((time, time));
+        } else {
+            self.timestamps = Some((first, time));
+        }
+        Ok(())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        self.file.write_all(buf)?;
+        self.bytes += buf.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        self.file.flush()
+    }
+}
+
+/// Returns the path of the file that a sink writing to `path` starts after
+/// `seq` rotations.
+fn rotated_path(path: &Path, seq: usize) -> PathBuf {
+    let mut path = path.to_owned().into_os_string();
+    path.push(format!(".{}", seq));
+    PathBuf::from(path)
+}
@@ -0,0 +1,391 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::cell::RefCell;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::mem;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use differential_dataflow::hashable::Hashable;
+use differential_dataflow::operators::arrange::ShutdownButton;
+use futures::channel::mpsc::{self, Receiver};
+use futures::executor::block_on;
+use futures::sink::SinkExt;
+use futures::stream::StreamExt;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use log::error;
+use prometheus::{
+    register_int_counter_vec, register_uint_gauge_vec, IntCounter, IntCounterVec, UIntGauge,
+    UIntGaugeVec,
+};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::FrontieredInputHandle;
+use timely::dataflow::{Scope, Stream};
+use url::Url;
+
+use dataflow_types::{Diff, HttpSinkConnector, QueueFullBehavior, Timestamp};
+use expr::GlobalId;
+use interchange::avro::DiffPair;
+use interchange::json::Encoder;
+use ore::retry;
+use repr::{RelationDesc, Row};
+
+use super::queue::{QueueMetrics, SinkQueue};
+use super::util::{activator_waker, sink_reschedule};
+
+/// Per-HTTP sink metrics.
+struct SinkMetrics {
+    batches_sent_counter: IntCounter,
+    batches_failed_counter: IntCounter,
+    bytes_queued: UIntGauge,
+    bytes_spilled: UIntGauge,
+}
+
+impl SinkMetrics {
+    fn new(sink_id: &str, worker_id: &str) -> SinkMetrics {
+        lazy_static! {
+            static ref BATCHES_SENT_COUNTER: IntCounterVec = register_int_counter_vec!(
+                "mz_http_sink_batches_sent_total",
+                "The number of batches that this sink delivered",
+                &["sink_id", "worker_id"]
+            )
+            .unwrap();
+            static ref BATCHES_FAILED_COUNTER: IntCounterVec = register_int_counter_vec!(
+                "mz_http_sink_batches_failed_total",
+                "The number of batches that this sink gave up on delivering",
+                &["sink_id", "worker_id"]
+            )
+            .unwrap();
+            static ref BYTES_QUEUED: UIntGaugeVec = register_uint_gauge_vec!(
+                "mz_http_sink_bytes_queued",
+                "The number of bytes of batches that this sink has queued in memory",
+                &["sink_id", "worker_id"]
+            )
+            .unwrap();
+            static ref BYTES_SPILLED: UIntGaugeVec = register_uint_gauge_vec!(
+                "mz_http_sink_bytes_spilled",
+                "The number of bytes of batches that this sink has spilled to disk",
+                &["sink_id", "worker_id"]
+            )
+            .unwrap();
+        }
+        let labels = &[sink_id, worker_id];
+        SinkMetrics {
+            batches_sent_counter: BATCHES_SENT_COUNTER.with_label_values(labels),
+            batches_failed_counter: BATCHES_FAILED_COUNTER.with_label_values(labels),
+            bytes_queued: BYTES_QUEUED.with_label_values(labels),
+            bytes_spilled: BYTES_SPILLED.with_label_values(labels),
+        }
+    }
+}
+
+/// The changes at one timestamp, encoded as the body of a request.
+#[derive(Serialize, Deserialize)]
+struct Batch {
+    timestamp: Timestamp,
+    body: Vec<u8>,
+}
+
+/// POSTs the updates in `stream` to the URL described by `connector`, as JSON
+/// arrays of change records.
+///
+/// The updates for each timestamp are sent in one request once the timestamp
+/// is complete, in timestamp order. The requests are made by a task on the
+/// async runtime, so that a slow server does not stall the worker. A request
+/// that fails is retried with exponential backoff for up to the connector's
+/// retry limit, after which its batch is appended to the connector's dead
+/// letter file, if any, and the sink moves on to the next batch.
+///
+/// Batches are queued until the task is ready to send them. If the queue
+/// outgrows its limit, the sink blocks, shuts down, or spills to disk, as the
+/// connector specifies.
+pub fn http<G>(
+    stream: &Stream<G, (Row, Timestamp, Diff)>,
+    id: GlobalId,
+    connector: HttpSinkConnector,
+    desc: RelationDesc,
+) where
+    G: Scope<Timestamp = Timestamp>,
+{
+    // As with Kafka sinks, exactly one worker sends all of the data, so that
+    // the batches arrive in timestamp order.
+    let sink_hash = id.hashed();
+
+    let name = format!("http-{}", id);
+    let encoder = Encoder::new(desc);
+    let metrics = SinkMetrics::new(&id.to_string(), &stream.scope().index().to_string());
+    let mut queue = SinkQueue::new(
+        connector.queue.clone(),
+        batch_size,
+        QueueMetrics {
+            bytes_queued: metrics.bytes_queued.clone(),
+            bytes_spilled: metrics.bytes_spilled.clone(),
+        },
+    );
+    // The delivery task holds at most one batch beyond the one that it is
+    // sending. The rest wait in the queue, which is subject to its limit.
+    let (tx, rx) = mpsc::channel(0);
+    tokio::spawn(deliver(name.clone(), connector.clone(), rx, metrics));
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let mut pending = Vec::new();
+    let mut vector = Vec::new();
+
+    // Dropping the channel ends the delivery task, so there is no need to hold
+    // on to the shutdown button.
+    let _: ShutdownButton<_> = sink_reschedule(
+        &stream,
+        Exchange::new(move |_| sink_hash),
+        name.clone(),
+        |info| {
+            let waker = activator_waker(stream.scope().sync_activator_for(&info.address[..]));
+            let shutdown_button =
+                ShutdownButton::new(tx.clone(), stream.scope().activator_for(&info.address[..]));
+
+            let ret = move |input: &mut FrontieredInputHandle<_, _, _>| {
+                let mut tx = tx.borrow_mut();
+                let sink = match &mut *tx {
+                    Some(sink) => sink,
+                    None => return false,
+                };
+
+                input.for_each(|_, rows| {
+                    rows.swap(&mut vector);
+                    for (row, time, diff) in vector.drain(..) {
+                        let should_emit = if connector.strict {
+                            connector.frontier.less_than(&time)
+                        } else {
+                            connector.frontier.less_equal(&time)
+                        };
+                        if should_emit && diff != 0 {
+                            pending.push((row, time, diff));
+                        }
+                    }
+                });
+
+                let frontier = input.frontier().frontier();
+                let (mut complete, incomplete): (Vec<_>, Vec<_>) = pending
+                    .drain(..)
+                    .partition(|(_, time, _)| !frontier.less_equal(time));
+                pending = incomplete;
+                complete.sort_by_key(|(_, time, _)| *time);
+
+                for (timestamp, updates) in &complete.into_iter().group_by(|(_, time, _)| *time) {
+                    let mut body = vec![b'['];
+                    for (row, time, diff) in updates {
+                        let record = encode(&encoder, &row, time, diff);
+                        // For diffs other than +/- 1, we send repeated copies
+                        // of the record, as the record itself captures the
+                        // polarity of the update.
+                        for _ in 0..diff.abs() {
+                            if body.len() > 1 {
+                                body.push(b',');
+                            }
+                            body.extend(&record);
+                        }
+                    }
+                    body.push(b']');
+                    if let Err(e) = queue.push_back(Batch { timestamp, body }) {
+                        error!("unable to spill batches in {}: {}", name, e);
+                        *tx = None;
+                        return false;
+                    }
+                }
+
+                if queue.is_full() {
+                    match connector.queue.on_full {
+                        QueueFullBehavior::Block => {
+                            // Wait for the delivery task to make room,
+                            // stalling the worker in the meantime.
+                            while queue.is_full() {
+                                let batch = match queue.pop_front() {
+                                    Ok(batch) => batch.expect("full queue cannot be empty"),
+                                    Err(e) => {
+                                        error!("unable to read spilled batches in {}: {}", name, e);
+                                        *tx = None;
+                                        return false;
+                                    }
+                                };
+                                if block_on(sink.send(batch)).is_err() {
+                                    // The delivery task has gone away.
+                                    *tx = None;
+                                    return false;
+                                }
+                            }
+                        }
+                        QueueFullBehavior::Error => {
+                            error!(
+                                "{} exceeded the limit of {} queued bytes, because the server \
+                                 did not keep up; shutting down sink",
+                                name, connector.queue.max_bytes
+                            );
+                            *tx = None;
+                            return false;
+                        }
+                        // The batch that does not fit is larger than the limit
+                        // on its own. Send it anyway.
+                        QueueFullBehavior::Spill => (),
+                    }
+                }
+
+                // Hand the delivery task as many batches as it is ready to
+                // receive. If it is not ready, the waker reschedules this
+                // operator once it is.
+                let mut cx = Context::from_waker(&waker);
+                while !queue.is_empty() {
+                    match sink.poll_ready(&mut cx) {
+                        Poll::Ready(Ok(())) => (),
+                        Poll::Ready(Err(_)) => {
+                            *tx = None;
+                            return false;
+                        }
+                        Poll::Pending => break,
+                    }
+                    let batch = match queue.pop_front() {
+                        Ok(batch) => batch.expect("non-empty queue has a front"),
+                        Err(e) => {
+                            error!("unable to read spilled batches in {}: {}", name, e);
+                            *tx = None;
+                            return false;
+                        }
+                    };
+                    if sink.start_send(batch).is_err() {
+                        *tx = None;
+                        return false;
+                    }
+                }
+
+                !queue.is_empty()
+            };
+
+            (ret, shutdown_button)
+        },
+    );
+}
+
+fn encode(encoder: &Encoder, row: &Row, time: Timestamp, diff: Diff) -> Vec<u8> {
+    let diff_pair = if diff < 0 {
+        DiffPair {
+            before: Some(row),
+            after: None,
+        }
+    } else {
+        DiffPair {
+            before: None,
+            after: Some(row),
+        }
+    };
+    encoder.encode_diff_pair(diff_pair, time)
+}
+
+/// Sends each batch received on `rx` in turn, until the sink is dropped.
+async fn deliver(
+    name: String,
+    connector: HttpSinkConnector,
+    mut rx: Receiver<Batch>,
+    metrics: SinkMetrics,
+) {
+    let client = Client::new();
+    while let Some(batch) = rx.next().await {
+        let res = retry::retry_for(connector.max_retry, |_| {
+            post(&client, &connector.url, batch.body.clone())
+        })
+        .await;
+        match res {
+            Ok(Ok(())) => metrics.batches_sent_counter.inc(),
+            Ok(Err(e)) | Err(e) => {
+                metrics.batches_failed_counter.inc();
+                dead_letter(&name, &connector, &batch, &e);
+            }
+        }
+    }
+}
+
+/// POSTs `body` to `url`.
+///
+/// Errors that retrying might resolve, like connection failures and server
+/// errors, are returned in the outer result, so that [`retry::retry_for`]
+/// retries them. Errors that it would not, like the server rejecting the
+/// request as malformed, are returned in the inner result.
+async fn post(client: &Client, url: &Url, body: Vec<u8>) -> Result<Result<(), String>, String> {
+    let res = client
+        .post(url.clone())
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await;
+    let status = match res {
+        Ok(res) => res.status(),
+        Err(e) => return Err(e.to_string()),
+    };
+    if status.is_success() {
+        Ok(Ok(()))
+    } else if status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+    {
+        Err(format!("server responded with {}", status))
+    } else {
+        Ok(Err(format!("server responded with {}", status)))
+    }
+}
+
+/// Records that `batch` could not be delivered, by appending it to the
+/// connector's dead letter file as a JSON object with `timestamp`, `error`,
+/// and `records` fields.
+fn dead_letter(name: &str, connector: &HttpSinkConnector, batch: &Batch, error: &str) {
+    let path = match &connector.dead_letter_path {
+        Some(path) => path,
+        None => {
+            error!(
+                "unable to deliver batch at {} from {}, discarding it: {}",
+                batch.timestamp, name, error
+            );
+            return;
+        }
+    };
+    error!(
+        "unable to deliver batch at {} from {}, writing it to {}: {}",
+        batch.timestamp,
+        name,
+        path.display(),
+        error
+    );
+    let mut line = format!(
+        "{{\"timestamp\":{},\"error\":{},\"records\":",
+        batch.timestamp,
+        serde_json::Value::from(error)
+    )
+    .into_bytes();
+    line.extend(&batch.body);
+    line.extend(b"}\n");
+    // The file was created along with the sink.
+    let res = OpenOptions::new()
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(&line));
+    if let Err(e) = res {
+        error!(
+            "unable to write to dead letter file {} for {}: {}",
+            path.display(),
+            name,
+            e
+        );
+    }
+}
+
+/// Estimates the number of bytes of memory that `batch` occupies.
+fn batch_size(batch: &Batch) -> usize {
+    mem::size_of::<Batch>() + batch.body.len()
+}
@@ -8,12 +8,15 @@
 // by the Apache License, Version 2.0.
 
 use std::cell::RefCell;
-use std::collections::VecDeque;
+use std::mem;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::Duration;
 
+use differential_dataflow::consolidation::consolidate;
 use differential_dataflow::hashable::Hashable;
 use differential_dataflow::operators::arrange::ShutdownButton;
 use lazy_static::lazy_static;
@@ -26,17 +29,25 @@ use rdkafka::client::ClientContext;
 use rdkafka::config::ClientConfig;
 use rdkafka::error::{KafkaError, RDKafkaError};
 use rdkafka::message::Message;
-use rdkafka::producer::{BaseRecord, DeliveryResult, ProducerContext, ThreadedProducer};
+use rdkafka::producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer};
 use timely::dataflow::channels::pact::Exchange;
 use timely::dataflow::operators::generic::FrontieredInputHandle;
 use timely::dataflow::{Scope, Stream};
 
-use dataflow_types::{Diff, KafkaSinkConnector, Timestamp};
+use dataflow_types::{
+    Diff, KafkaSinkConnector, KafkaSinkFormat, QueueFullBehavior, SinkEnvelope, Timestamp,
+};
 use expr::GlobalId;
-use interchange::avro::{DiffPair, Encoder};
+use interchange::avro::DiffPair;
 use repr::{RelationDesc, Row};
 
-use super::util::sink_reschedule;
+use super::queue::{QueueMetrics, SinkQueue};
+use super::util::{project_key, sink_reschedule};
+
+/// How long a sink that is blocked on a full queue waits before it is
+/// rescheduled to retry a message that the Kafka producer did not have room
+/// for.
+const BLOCK_RETRY_DELAY: Duration = Duration::from_millis(10);
 
 /// Per-Kafka sink metrics.
 #[derive(Clone)]
@@ -45,6 +56,8 @@ pub struct SinkMetrics {
     message_send_errors_counter: IntCounter,
     message_delivery_errors_counter: IntCounter,
     rows_queued: UIntGauge,
+    bytes_queued: UIntGauge,
+    bytes_spilled: UIntGauge,
     messages_in_flight: UIntGauge,
 }
 
@@ -75,6 +88,18 @@ impl SinkMetrics {
                 &["topic", "sink_id", "worker_id"]
             )
             .unwrap();
+            static ref BYTES_QUEUED: UIntGaugeVec = register_uint_gauge_vec!(
+                "mz_kafka_sink_bytes_queued",
+                "The current number of bytes of rows queued in memory by the Kafka sink operator",
+                &["topic", "sink_id", "worker_id"]
+            )
+            .unwrap();
+            static ref BYTES_SPILLED: UIntGaugeVec = register_uint_gauge_vec!(
+                "mz_kafka_sink_bytes_spilled",
+                "The current number of bytes of rows spilled to disk by the Kafka sink operator",
+                &["topic", "sink_id", "worker_id"]
+            )
+            .unwrap();
             static ref MESSAGES_IN_FLIGHT: UIntGaugeVec = register_uint_gauge_vec!(
                 "mz_kafka_sink_messages_in_flight",
                 "The current number of messages waiting to be delivered by the Kafka producer",
@@ -89,6 +114,8 @@ impl SinkMetrics {
             message_delivery_errors_counter: MESSAGE_DELIVERY_ERRORS_COUNTER
                 .with_label_values(labels),
             rows_queued: ROWS_QUEUED.with_label_values(labels),
+            bytes_queued: BYTES_QUEUED.with_label_values(labels),
+            bytes_spilled: BYTES_SPILLED.with_label_values(labels),
             messages_in_flight: MESSAGES_IN_FLIGHT.with_label_values(labels),
         }
     }
@@ -129,13 +156,20 @@ impl ProducerContext for SinkProducerContext {
     }
 }
 
-// TODO@jldlaughlin: What guarantees does this sink support? #1728
+/// Writes the updates in `stream` to the Kafka topic described by `connector`.
+///
+/// By default, the sink writes each update at least once, as long as
+/// Materialize does not restart. If the connector has a consistency topic, the
+/// sink instead writes the updates for each timestamp in a Kafka transaction,
+/// together with a record on the consistency topic that marks the timestamp as
+/// complete, so that consumers reading committed messages see each timestamp
+/// exactly once.
 pub fn kafka<G>(
     stream: &Stream<G, (Row, Timestamp, Diff)>,
     id: GlobalId,
     connector: KafkaSinkConnector,
     desc: RelationDesc,
-) -> ShutdownButton<ThreadedProducer<SinkProducerContext>>
+) -> ShutdownButton<Arc<ThreadedProducer<SinkProducerContext>>>
 where
     G: Scope<Timestamp = Timestamp>,
 {
@@ -145,19 +179,18 @@ where
     // of also distributing sinks amongst workers
     let sink_hash = id.hashed();
 
-    let encoder = Encoder::new(desc);
+    let encoder = SinkEncoder::new(&connector.format, desc, connector.key_indices.as_deref());
     let mut config = ClientConfig::new();
     config.set("bootstrap.servers", &connector.url.to_string());
 
-    // Increase limits for the Kafka producer's internal buffering of messages
-    // Currently we don't have a great backpressure mechanism to tell indexes or
-    // views to slow down, so the only thing we can do with a message that we
-    // can't immediately send is to put it in a buffer and there's no point
-    // having buffers within the dataflow layer and Kafka
-    // If the sink starts falling behind and the buffers start consuming
-    // too much memory the best thing to do is to drop the sink
-    // Sets the buffer size to be 16 GB (note that this setting is in KB)
-    config.set("queue.buffering.max.kbytes", &format!("{}", 16 << 20));
+    // Bound the Kafka producer's internal buffering of messages by the sink's
+    // queue limit. Once the producer's buffer is full, rows wait in the sink's
+    // own queue, which blocks, fails, or spills once it, too, is full. Note
+    // that this setting is in KB.
+    config.set(
+        "queue.buffering.max.kbytes",
+        &format!("{}", (connector.queue.max_bytes / 1024).max(1)),
+    );
 
     // Set the max messages buffered by the producer at any time to 10MM which
     // is the maximum allowed value
@@ -167,6 +200,12 @@ where
     // TODO(rkhaitan): experiment with different settings for this value to see
     // if it makes a big difference
     config.set("queue.buffering.max.ms", &format!("{}", 10));
+
+    if connector.consistency_topic.is_some() {
+        // The transactional ID must be stable across restarts, so that the
+        // new producer fences out any transactions left open by the old one.
+        config.set("transactional.id", &connector.topic);
+    }
     let sink_metrics = SinkMetrics::new(
         &connector.topic,
         &id.to_string(),
@@ -174,15 +213,43 @@ where
     );
 
     let shutdown = Arc::new(AtomicBool::new(false));
-    let producer = Rc::new(RefCell::new(Some(
-        config
-            .create_with_context::<_, ThreadedProducer<_>>(SinkProducerContext::new(
-                &sink_metrics,
-                &shutdown,
-            ))
-            .expect("creating kafka producer for kafka sinks failed"),
-    )));
-    let mut queue: VecDeque<(Row, Diff)> = VecDeque::new();
+    let producer = config
+        .create_with_context::<_, ThreadedProducer<_>>(SinkProducerContext::new(
+            &sink_metrics,
+            &shutdown,
+        ))
+        .expect("creating kafka producer for kafka sinks failed");
+    if connector.consistency_topic.is_some() {
+        if let Err(e) = producer.init_transactions(Duration::from_secs(60)) {
+            error!(
+                "unable to initialize transactions for kafka sink {}: {}",
+                id, e
+            );
+            shutdown.store(true, Ordering::SeqCst);
+        }
+    }
+    let producer = Rc::new(RefCell::new(Some(Arc::new(producer))));
+    let mut queue = SinkQueue::new(
+        connector.queue.clone(),
+        row_size,
+        QueueMetrics {
+            bytes_queued: sink_metrics.bytes_queued.clone(),
+            bytes_spilled: sink_metrics.bytes_spilled.clone(),
+        },
+    );
+    // Updates that are held back until their timestamp is complete, for sinks
+    // that need to see all of a timestamp's updates at once.
+    let hold_updates =
+        connector.envelope == SinkEnvelope::Upsert || connector.consistency_topic.is_some();
+    let mut pending = Vec::new();
+    // The timestamp of the open transaction and the number of messages
+    // written in it so far, if the sink writes transactionally.
+    let mut transaction: Option<(Timestamp, i64)> = None;
+    // The result of the transaction that is being committed, if any.
+    // Committing waits for Kafka to acknowledge every message in the
+    // transaction, so it happens on a separate thread, to avoid stalling the
+    // worker.
+    let mut committing: Option<mpsc::Receiver<Result<(), KafkaError>>> = None;
     let mut vector = Vec::new();
     let mut encoded_buffer = None;
 
@@ -193,6 +260,9 @@ where
         name.clone(),
         |info| {
             let activator = stream.scope().activator_for(&info.address[..]);
+            let sync_activator = Arc::new(Mutex::new(
+                stream.scope().sync_activator_for(&info.address[..]),
+            ));
             let shutdown_button = ShutdownButton::new(
                 producer.clone(),
                 stream.scope().activator_for(&info.address[..]),
@@ -219,6 +289,7 @@ where
                 // send it over to Kafka. Even though we want to do bounded work
                 // per sink invocation, we still need to remember all inputs as we
                 // receive them.
+                let mut ready = Vec::new();
                 input.for_each(|_, rows| {
                     rows.swap(&mut vector);
 
@@ -230,50 +301,176 @@ where
                         };
 
                         if should_emit {
-                            queue.push_back((row, diff));
+                            if hold_updates {
+                                pending.push((row, time, diff));
+                            } else {
+                                ready.push((row, time, diff));
+                            }
                         }
                     }
                 });
 
+                // The updates for a key at a given timestamp can arrive in any
+                // order, so an upsert sink can only determine the key's new
+                // value once the timestamp is complete. Similarly, a
+                // transactional sink can only commit a timestamp once it has
+                // written all of the timestamp's updates.
+                if !pending.is_empty() {
+                    let frontier = input.frontier();
+                    let (mut complete, incomplete): (Vec<_>, Vec<_>) = pending
+                        .drain(..)
+                        .partition(|(_, time, _)| !frontier.less_equal(time));
+                    pending = incomplete;
+                    match connector.envelope {
+                        SinkEnvelope::Debezium => {
+                            complete.sort_by_key(|(_, time, _)| *time);
+                            ready.extend(complete.into_iter().filter(|(_, _, diff)| *diff != 0));
+                        }
+                        SinkEnvelope::Upsert => {
+                            let key_indices = connector
+                                .key_indices
+                                .as_ref()
+                                .expect("upsert sinks are keyed");
+                            ready.extend(consolidate_upserts(complete, key_indices));
+                        }
+                    }
+                }
+
+                for update in ready {
+                    if let Err(e) = queue.push_back(update) {
+                        error!("unable to spill rows in {}: {}", name, e);
+                        shutdown.store(true, Ordering::SeqCst);
+                        return false;
+                    }
+                }
+
+                // If Kafka is not keeping up, either keep sending until it makes
+                // room, or give up on the sink.
+                let block = match connector.queue.on_full {
+                    QueueFullBehavior::Block => queue.is_full(),
+                    QueueFullBehavior::Error if queue.is_full() => {
+                        error!(
+                            "{} exceeded the limit of {} queued bytes, because Kafka did not \
+                             keep up; shutting down sink",
+                            name, connector.queue.max_bytes
+                        );
+                        shutdown.store(true, Ordering::SeqCst);
+                        return false;
+                    }
+                    QueueFullBehavior::Error | QueueFullBehavior::Spill => false,
+                };
+
+                // No messages can be sent until the previous transaction has
+                // been committed.
+                if let Some(rx) = &committing {
+                    match rx.try_recv() {
+                        Ok(Ok(())) => committing = None,
+                        Ok(Err(e)) => {
+                            error!("unable to commit transaction in {}: {}", name, e);
+                            shutdown.store(true, Ordering::SeqCst);
+                            return false;
+                        }
+                        // The committing thread reschedules the operator once
+                        // the commit completes.
+                        Err(TryRecvError::Empty) => return true,
+                        Err(TryRecvError::Disconnected) => {
+                            error!("transaction commit in {} did not complete", name);
+                            shutdown.store(true, Ordering::SeqCst);
+                            return false;
+                        }
+                    }
+                }
+
                 // Send a bounded number of records to Kafka from the queue. This
                 // loop has explicitly been designed so that each iteration sends
-                // at most one record to Kafka
-                for _ in 0..connector.fuel {
+                // at most one record to Kafka. A blocked sink keeps sending,
+                // regardless of its fuel, until its queue is no longer full.
+                let mut fuel = connector.fuel;
+                while fuel > 0 || (block && queue.is_full()) {
+                    fuel = fuel.saturating_sub(1);
+                    let next = if encoded_buffer.is_some() {
+                        None
+                    } else {
+                        match queue.pop_front() {
+                            Ok(next) => next,
+                            Err(e) => {
+                                error!("unable to read spilled rows in {}: {}", name, e);
+                                shutdown.store(true, Ordering::SeqCst);
+                                return false;
+                            }
+                        }
+                    };
                     let (encoded, count) = if let Some((encoded, count)) = encoded_buffer.take() {
                         // We still need to send more copies of this record.
                         (encoded, count)
-                    } else if let Some((row, diff)) = queue.pop_front() {
-                        // Convert a previously queued (Row, Diff) to a Avro diff
-                        // envelope record
+                    } else if let Some((row, time, diff)) = next {
                         if diff == 0 {
                             // Explicitly refuse to send no-op records
                             continue;
                         };
 
-                        let diff_pair = if diff < 0 {
-                            DiffPair {
-                                before: Some(&row),
-                                after: None,
-                            }
-                        } else {
-                            DiffPair {
-                                before: None,
-                                after: Some(&row),
+                        if connector.consistency_topic.is_some() && transaction.is_none() {
+                            if let Err(e) = producer.begin_transaction() {
+                                error!("unable to begin transaction in {}: {}", name, e);
+                                shutdown.store(true, Ordering::SeqCst);
+                                return false;
                             }
-                        };
+                            transaction = Some((time, 0));
+                        }
 
-                        let buf = encoder.encode_unchecked(connector.schema_id, diff_pair);
-                        // For diffs other than +/- 1, we send repeated copies of the
-                        // Avro record [diff] times. Since the format and envelope
-                        // capture the "polarity" of the update, we need to remember
-                        // how many times to send the data.
-                        (buf, diff.abs())
+                        let key = connector
+                            .key_indices
+                            .as_ref()
+                            .map(|indices| encoder.encode_key(&project_key(&row, indices)));
+
+                        match connector.envelope {
+                            SinkEnvelope::Debezium => {
+                                // Convert a previously queued (Row, Diff) to a diff
+                                // envelope record
+                                let diff_pair = if diff < 0 {
+                                    DiffPair {
+                                        before: Some(&row),
+                                        after: None,
+                                    }
+                                } else {
+                                    DiffPair {
+                                        before: None,
+                                        after: Some(&row),
+                                    }
+                                };
+
+                                let buf = encoder.encode_diff_pair(diff_pair, time);
+                                // For diffs other than +/- 1, we send repeated copies of the
+                                // record [diff] times. Since the format and envelope
+                                // capture the "polarity" of the update, we need to remember
+                                // how many times to send the data.
+                                ((key, Some(buf)), diff.abs())
+                            }
+                            SinkEnvelope::Upsert => {
+                                // An insertion carries the key's new value. A
+                                // retraction means the key was deleted, which
+                                // is signaled with a null payload.
+                                let buf = if diff > 0 {
+                                    Some(encoder.encode_row(&row))
+                                } else {
+                                    None
+                                };
+                                ((key, buf), 1)
+                            }
+                        }
                     } else {
                         // Nothing left for us to do
                         break;
                     };
 
-                    let record = BaseRecord::<&Vec<u8>, _>::to(&connector.topic).payload(&encoded);
+                    let (key, payload) = &encoded;
+                    let mut record = BaseRecord::<Vec<u8>, Vec<u8>>::to(&connector.topic);
+                    if let Some(key) = key {
+                        record = record.key(key);
+                    }
+                    if let Some(payload) = payload {
+                        record = record.payload(payload);
+                    }
                     if let Err((e, _)) = producer.send(record) {
                         sink_metrics.message_send_errors_counter.inc();
                         error!("unable to produce in {}: {}", name, e);
@@ -286,7 +483,15 @@ where
                             // only retries on QueueFull so we will keep that
                             // convention here.
                             encoded_buffer = Some((encoded, count));
-                            activator.activate_after(Duration::from_secs(60));
+                            if block && queue.is_full() {
+                                // The producer delivers messages on its own
+                                // thread, so it will make room soon. Yield to
+                                // the other operators on this worker until it
+                                // does.
+                                activator.activate_after(BLOCK_RETRY_DELAY);
+                            } else {
+                                activator.activate_after(Duration::from_secs(60));
+                            }
                             return true;
                         } else {
                             // We've received an error that is not transient
@@ -297,17 +502,65 @@ where
                         sink_metrics.messages_sent_counter.inc();
                     }
 
-                    // Cache the Avro encoded data if we need to send again and
+                    // Cache the encoded data if we need to send again and
                     // remember how many more times we need to send it
                     if count > 1 {
                         encoded_buffer = Some((encoded, count - 1));
                     }
+
+                    if let Some((time, sent)) = &mut transaction {
+                        *sent += 1;
+                        // Only complete timestamps are queued, so once the
+                        // queue moves on to the next timestamp, this one is
+                        // finished and can be committed.
+                        let next_time = match queue.front() {
+                            Ok(front) => front.map(|(_, t, _)| *t),
+                            Err(e) => {
+                                error!("unable to read spilled rows in {}: {}", name, e);
+                                shutdown.store(true, Ordering::SeqCst);
+                                return false;
+                            }
+                        };
+                        let finished = encoded_buffer.is_none() && next_time != Some(*time);
+                        if finished {
+                            let consistency_topic = connector
+                                .consistency_topic
+                                .as_ref()
+                                .expect("transactional sinks have a consistency topic");
+                            let buf = encoder.encode_consistency_record(*time, *sent);
+                            let record =
+                                BaseRecord::<(), Vec<u8>>::to(consistency_topic).payload(&buf);
+                            if let Err((e, _)) = producer.send(record) {
+                                error!("unable to commit transaction in {}: {}", name, e);
+                                shutdown.store(true, Ordering::SeqCst);
+                                return false;
+                            }
+                            let (tx, rx) = mpsc::channel();
+                            let producer = Arc::clone(producer);
+                            let sync_activator = Arc::clone(&sync_activator);
+                            thread::spawn(move || {
+                                let res = producer.commit_transaction(Duration::from_secs(60));
+                                // The sink may have shut down in the meantime,
+                                // in which case no one is waiting for the result.
+                                let _ = tx.send(res);
+                                let _ = sync_activator.lock().expect("lock poisoned").activate();
+                            });
+                            committing = Some(rx);
+                            transaction = None;
+                            break;
+                        }
+                    }
                 }
 
                 let in_flight = producer.in_flight_count();
 
                 sink_metrics.rows_queued.set(queue.len() as u64);
                 sink_metrics.messages_in_flight.set(in_flight as u64);
+                if committing.is_some() {
+                    // The committing thread reschedules the operator once the
+                    // commit completes.
+                    return true;
+                }
                 if encoded_buffer.is_some() || !queue.is_empty() {
                     // We need timely to reschedule this operator as we have pending
                     // items that we need to send to Kafka
@@ -330,3 +583,224 @@ where
         },
     )
 }
+
+/// Encodes the keys, values, and consistency records of a Kafka sink in the
+/// sink's format.
+enum SinkEncoder {
+    Avro {
+        key_encoder: Option<interchange::avro::Encoder>,
+        encoder: interchange::avro::Encoder,
+        key_schema_id: Option<i32>,
+        value_schema_id: i32,
+        consistency_schema_id: Option<i32>,
+    },
+    Protobuf {
+        key_encoder: Option<interchange::protobuf::Encoder>,
+        encoder: interchange::protobuf::Encoder,
+        key_schema_id: Option<i32>,
+        value_schema_id: i32,
+    },
+    Json {
+        key_encoder: Option<interchange::json::Encoder>,
+        encoder: interchange::json::Encoder,
+    },
+    Csv {
+        key_encoder: Option<interchange::csv::Encoder>,
+        encoder: interchange::csv::Encoder,
+        delimiter: u8,
+    },
+}
+
+impl SinkEncoder {
+    fn new(format: &KafkaSinkFormat, desc: RelationDesc, key_indices: Option<&[usize]>) -> Self {
+        match format {
+            KafkaSinkFormat::Avro {
+                value_schema_id,
+                key_schema_id,
+                consistency_schema_id,
+            } => SinkEncoder::Avro {
+                key_encoder: key_indices
+                    .map(|indices| interchange::avro::Encoder::new_key(&desc, indices)),
+                encoder: interchange::avro::Encoder::new(desc),
+                key_schema_id: *key_schema_id,
+                value_schema_id: *value_schema_id,
+                consistency_schema_id: *consistency_schema_id,
+            },
+            KafkaSinkFormat::Protobuf {
+                message_name,
+                value_schema_id,
+                key_schema_id,
+            } => {
+                const MSG: &str = "protobuf message names are validated during planning";
+                SinkEncoder::Protobuf {
+                    key_encoder: key_indices.map(|indices| {
+                        interchange::protobuf::Encoder::new_key(&desc, indices, message_name)
+                            .expect(MSG)
+                    }),
+                    encoder: interchange::protobuf::Encoder::new(desc, message_name).expect(MSG),
+                    key_schema_id: *key_schema_id,
+                    value_schema_id: *value_schema_id,
+                }
+            }
+            KafkaSinkFormat::Json => SinkEncoder::Json {
+                key_encoder: key_indices
+                    .map(|indices| interchange::json::Encoder::new_key(&desc, indices)),
+                encoder: interchange::json::Encoder::new(desc),
+            },
+            KafkaSinkFormat::Csv { delimiter } => SinkEncoder::Csv {
+                key_encoder: key_indices
+                    .map(|indices| interchange::csv::Encoder::new_key(&desc, indices, *delimiter)),
+                encoder: interchange::csv::Encoder::new(desc, *delimiter),
+                delimiter: *delimiter,
+            },
+        }
+    }
+
+    /// Encodes the key of a keyed sink.
+    fn encode_key(&self, key: &Row) -> Vec<u8> {
+        const MSG: &str = "keyed sinks have a key encoder";
+        match self {
+            SinkEncoder::Avro {
+                key_encoder,
+                key_schema_id,
+                ..
+            } => key_encoder
+                .as_ref()
+                .expect(MSG)
+                .encode_row_unchecked(key_schema_id.expect(MSG), key),
+            SinkEncoder::Protobuf {
+                key_encoder,
+                key_schema_id,
+                ..
+            } => key_encoder
+                .as_ref()
+                .expect(MSG)
+                .encode_row_unchecked(key_schema_id.expect(MSG), key),
+            SinkEncoder::Json { key_encoder, .. } => {
+                key_encoder.as_ref().expect(MSG).encode_row(key)
+            }
+            SinkEncoder::Csv { key_encoder, .. } => {
+                key_encoder.as_ref().expect(MSG).encode_row(key)
+            }
+        }
+    }
+
+    /// Encodes an update for a sink with the Debezium envelope.
+    fn encode_diff_pair(&self, diff_pair: DiffPair<&Row>, timestamp: Timestamp) -> Vec<u8> {
+        match self {
+            SinkEncoder::Avro {
+                encoder,
+                value_schema_id,
+                ..
+            } => encoder.encode_unchecked(*value_schema_id, diff_pair),
+            SinkEncoder::Protobuf {
+                encoder,
+                value_schema_id,
+                ..
+            } => encoder.encode_unchecked(*value_schema_id, diff_pair),
+            SinkEncoder::Json { encoder, .. } => encoder.encode_diff_pair(diff_pair, timestamp),
+            SinkEncoder::Csv { encoder, .. } => match diff_pair {
+                DiffPair {
+                    after: Some(row), ..
+                } => encoder.encode_update(row, timestamp, true),
+                DiffPair {
+                    before: Some(row), ..
+                } => encoder.encode_update(row, timestamp, false),
+                DiffPair { .. } => unreachable!("diff pairs have a before or after row"),
+            },
+        }
+    }
+
+    /// Encodes the new value of a row for a sink with the upsert envelope.
+    fn encode_row(&self, row: &Row) -> Vec<u8> {
+        match self {
+            SinkEncoder::Avro {
+                encoder,
+                value_schema_id,
+                ..
+            } => encoder.encode_row_unchecked(*value_schema_id, row),
+            SinkEncoder::Protobuf {
+                encoder,
+                value_schema_id,
+                ..
+            } => encoder.encode_row_unchecked(*value_schema_id, row),
+            SinkEncoder::Json { encoder, .. } => encoder.encode_row(row),
+            SinkEncoder::Csv { encoder, .. } => encoder.encode_row(row),
+        }
+    }
+
+    /// Encodes a record for the sink's consistency topic.
+    fn encode_consistency_record(&self, timestamp: Timestamp, count: i64) -> Vec<u8> {
+        match self {
+            SinkEncoder::Avro {
+                consistency_schema_id,
+                ..
+            } => interchange::avro::encode_consistency_record(
+                consistency_schema_id.expect("transactional sinks have a consistency schema"),
+                timestamp,
+                count,
+            ),
+            SinkEncoder::Protobuf { .. } => {
+                unreachable!("protobuf sinks do not support consistency topics")
+            }
+            SinkEncoder::Json { .. } => {
+                interchange::json::encode_consistency_record(timestamp, count)
+            }
+            SinkEncoder::Csv { delimiter, .. } => {
+                interchange::csv::encode_consistency_record(timestamp, count, *delimiter)
+            }
+        }
+    }
+}
+
+/// Returns at most one update for each key at each timestamp in `updates`: an
+/// insertion of the key's new value, if it has one, or else a retraction of its
+/// old value, if it was deleted.
+fn consolidate_upserts(
+    updates: Vec<(Row, Timestamp, Diff)>,
+    key_indices: &[usize],
+) -> Vec<(Row, Timestamp, Diff)> {
+    let mut out = Vec::new();
+    let mut updates: Vec<_> = updates
+        .into_iter()
+        .map(|(row, time, diff)| ((time, project_key(&row, key_indices)), (row, diff)))
+        .collect();
+    updates.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut updates = updates.into_iter().peekable();
+    while let Some(((time, key), update)) = updates.next() {
+        let mut rows = vec![update];
+        while updates
+            .peek()
+            .map_or(false, |((t, k), _)| *t == time && *k == key)
+        {
+            rows.push(updates.next().unwrap().1);
+        }
+        consolidate(&mut rows);
+
+        let mut inserts = rows.iter().filter(|(_, diff)| *diff > 0);
+        match inserts.next() {
+            Some((row, diff)) => {
+                if *diff > 1 || inserts.next().is_some() {
+                    error!(
+                        "upsert sink key {:?} has multiple values at time {}; \
+                         emitting only one of them",
+                        key, time
+                    );
+                }
+                out.push((row.clone(), time, 1));
+            }
+            None => {
+                if let Some((row, _)) = rows.into_iter().next() {
+                    out.push((row, time, -1));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Estimates the number of bytes of memory that `update` occupies.
+fn row_size(update: &(Row, Timestamp, Diff)) -> usize {
+    mem::size_of::<(Row, Timestamp, Diff)>() + update.0.data().len()
+}
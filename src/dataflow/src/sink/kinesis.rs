@@ -0,0 +1,308 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use differential_dataflow::hashable::Hashable;
+use differential_dataflow::operators::arrange::ShutdownButton;
+use futures::executor::block_on;
+use lazy_static::lazy_static;
+use log::{error, warn};
+use prometheus::{
+    register_int_counter_vec, register_uint_gauge_vec, IntCounter, IntCounterVec, UIntGauge,
+    UIntGaugeVec,
+};
+use rusoto_core::RusotoError;
+use rusoto_kinesis::{
+    Kinesis, KinesisClient, PutRecordsError, PutRecordsInput, PutRecordsRequestEntry,
+};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::FrontieredInputHandle;
+use timely::dataflow::{Scope, Stream};
+
+use dataflow_types::{Diff, KinesisSinkConnector, KinesisSinkFormat, Timestamp};
+use expr::GlobalId;
+use repr::{RelationDesc, Row};
+
+use super::util::{project_key, sink_reschedule, ChangeEncoder};
+
+/// The maximum number of records that Kinesis accepts in one `PutRecords`
+/// request.
+const MAX_REQUEST_RECORDS: usize = 500;
+
+/// The maximum total size of the data and partition keys of the records in one
+/// `PutRecords` request, in bytes.
+const MAX_REQUEST_BYTES: usize = 5 * 1024 * 1024;
+
+/// How long the sink waits before retrying records that Kinesis did not
+/// accept, e.g., because the stream's throughput limits were exceeded.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Per-Kinesis sink metrics.
+pub struct SinkMetrics {
+    records_sent_counter: IntCounter,
+    request_errors_counter: IntCounter,
+    record_errors_counter: IntCounter,
+    records_queued: UIntGauge,
+}
+
+impl SinkMetrics {
+    fn new(stream_name: &str, sink_id: &str, worker_id: &str) -> SinkMetrics {
+        lazy_static! {
+            static ref RECORDS_SENT_COUNTER: IntCounterVec = register_int_counter_vec!(
+                "mz_kinesis_sink_records_sent_total",
+                "The number of records that Kinesis accepted from this sink",
+                &["stream", "sink_id", "worker_id"]
+            )
+            .unwrap();
+            static ref REQUEST_ERRORS_COUNTER: IntCounterVec = register_int_counter_vec!(
+                "mz_kinesis_sink_request_errors_total",
+                "The number of PutRecords requests from this sink that failed outright",
+                &["stream", "sink_id", "worker_id"]
+            )
+            .unwrap();
+            static ref RECORD_ERRORS_COUNTER: IntCounterVec = register_int_counter_vec!(
+                "mz_kinesis_sink_record_errors_total",
+                "The number of records that Kinesis rejected from an otherwise successful PutRecords request for this sink",
+                &["stream", "sink_id", "worker_id"]
+            )
+            .unwrap();
+            static ref RECORDS_QUEUED: UIntGaugeVec = register_uint_gauge_vec!(
+                "mz_kinesis_sink_records_queued",
+                "The current number of records waiting to be sent by the Kinesis sink operator",
+                &["stream", "sink_id", "worker_id"]
+            )
+            .unwrap();
+        }
+        let labels = &[stream_name, sink_id, worker_id];
+        SinkMetrics {
+            records_sent_counter: RECORDS_SENT_COUNTER.with_label_values(labels),
+            request_errors_counter: REQUEST_ERRORS_COUNTER.with_label_values(labels),
+            record_errors_counter: RECORD_ERRORS_COUNTER.with_label_values(labels),
+            records_queued: RECORDS_QUEUED.with_label_values(labels),
+        }
+    }
+}
+
+/// Writes the updates in `stream` to the Kinesis stream described by
+/// `connector`, one change record per Kinesis record.
+///
+/// The updates for each timestamp are written once the timestamp is complete,
+/// in timestamp order, in batches of up to 500 records per `PutRecords`
+/// request. Records that Kinesis does not accept are retried, which can
+/// reorder them relative to later records in the same shard. If Kinesis
+/// rejects a request for a reason that retrying would not resolve, the sink
+/// stops, rather than skip the request's records and leave a gap.
+///
+/// The partition key of each record is a hash of the columns at the
+/// connector's partition key indices, or of the entire row if there are none,
+/// so all of the updates to a given key land in the same shard.
+pub fn kinesis<G>(
+    stream: &Stream<G, (Row, Timestamp, Diff)>,
+    id: GlobalId,
+    connector: KinesisSinkConnector,
+    desc: RelationDesc,
+) where
+    G: Scope<Timestamp = Timestamp>,
+{
+    // As with Kafka sinks, exactly one worker writes all of the data.
+    let sink_hash = id.hashed();
+
+    let encoder = match connector.format {
+        KinesisSinkFormat::Json => ChangeEncoder::json(desc),
+        KinesisSinkFormat::Csv { delimiter } => ChangeEncoder::csv(desc, delimiter),
+    };
+    let sink_metrics = SinkMetrics::new(
+        &connector.stream_name,
+        &id.to_string(),
+        &stream.scope().index().to_string(),
+    );
+
+    let client = match block_on(aws_util::kinesis::kinesis_client(
+        connector.region.clone(),
+        connector.access_key_id.clone(),
+        connector.secret_access_key.clone(),
+        connector.token.clone(),
+    )) {
+        Ok(client) => Some(client),
+        Err(e) => {
+            error!("creating Kinesis client for sink {} failed: {:#}", id, e);
+            None
+        }
+    };
+    let client = Rc::new(RefCell::new(client));
+    let mut pending = Vec::new();
+    let mut queue: VecDeque<PutRecordsRequestEntry> = VecDeque::new();
+    let mut vector = Vec::new();
+
+    let name = format!("kinesis-{}", id);
+    // The sink holds no resources that must be released promptly when it is
+    // dropped, so there is no need to hold on to its shutdown button.
+    let _: ShutdownButton<KinesisClient> = sink_reschedule(
+        &stream,
+        Exchange::new(move |_| sink_hash),
+        name.clone(),
+        |info| {
+            let activator = stream.scope().activator_for(&info.address[..]);
+            let shutdown_button = ShutdownButton::new(
+                client.clone(),
+                stream.scope().activator_for(&info.address[..]),
+            );
+
+            let ret = move |input: &mut FrontieredInputHandle<_, _, _>| {
+                let mut client = client.borrow_mut();
+                let kinesis_client = match &*client {
+                    Some(client) => client,
+                    None => return false,
+                };
+
+                input.for_each(|_, rows| {
+                    rows.swap(&mut vector);
+                    for (row, time, diff) in vector.drain(..) {
+                        let should_emit = if connector.strict {
+                            connector.frontier.less_than(&time)
+                        } else {
+                            connector.frontier.less_equal(&time)
+                        };
+                        if should_emit && diff != 0 {
+                            pending.push((row, time, diff));
+                        }
+                    }
+                });
+
+                let frontier = input.frontier();
+                let (mut complete, incomplete): (Vec<_>, Vec<_>) = pending
+                    .drain(..)
+                    .partition(|(_, time, _)| !frontier.less_equal(time));
+                pending = incomplete;
+                complete.sort_by_key(|(_, time, _)| *time);
+                for (row, time, diff) in complete {
+                    let partition_key = match &connector.partition_key_indices {
+                        Some(indices) => project_key(&row, indices).hashed(),
+                        None => row.hashed(),
+                    };
+                    let entry = PutRecordsRequestEntry {
+                        data: Bytes::from(encoder.encode(&row, time, diff)),
+                        explicit_hash_key: None,
+                        partition_key: partition_key.to_string(),
+                    };
+                    // For diffs other than +/- 1, we write repeated copies of
+                    // the record, as the record itself captures the polarity
+                    // of the update.
+                    for _ in 0..diff.abs() {
+                        queue.push_back(entry.clone());
+                    }
+                }
+
+                // Send at most one batch per invocation, so that a backlog of
+                // records does not monopolize the worker.
+                let batch = next_batch(&mut queue);
+                if !batch.is_empty() {
+                    let input = PutRecordsInput {
+                        records: batch.clone(),
+                        stream_name: connector.stream_name.clone(),
+                    };
+                    let res = block_on(kinesis_client.put_records(input));
+                    match res {
+                        Ok(output) => {
+                            // Kinesis reports the result of each record in the
+                            // order of the request. Put the records that it
+                            // rejected back at the front of the queue, in
+                            // their original order.
+                            let mut failed = 0;
+                            for (entry, result) in batch.into_iter().zip(output.records).rev() {
+                                if result.error_code.is_some() {
+                                    queue.push_front(entry);
+                                    failed += 1;
+                                } else {
+                                    sink_metrics.records_sent_counter.inc();
+                                }
+                            }
+                            if failed > 0 {
+                                sink_metrics.record_errors_counter.inc_by(failed);
+                                sink_metrics.records_queued.set(queue.len() as u64);
+                                activator.activate_after(RETRY_DELAY);
+                                return true;
+                            }
+                        }
+                        Err(e) => {
+                            sink_metrics.request_errors_counter.inc();
+                            match e {
+                                RusotoError::HttpDispatch(_)
+                                | RusotoError::Service(
+                                    PutRecordsError::ProvisionedThroughputExceeded(_),
+                                ) => {
+                                    warn!("unable to write to {}, will retry: {}", name, e);
+                                    for entry in batch.into_iter().rev() {
+                                        queue.push_front(entry);
+                                    }
+                                    sink_metrics.records_queued.set(queue.len() as u64);
+                                    activator.activate_after(RETRY_DELAY);
+                                    return true;
+                                }
+                                _ => {
+                                    // Any other error is not transient, so
+                                    // retrying would not help. Skipping the
+                                    // batch would leave a gap in the stream,
+                                    // so shut down the sink instead.
+                                    error!(
+                                        "unable to write to {}, shutting down sink: {}",
+                                        name, e
+                                    );
+                                    *client = None;
+                                    pending.clear();
+                                    queue.clear();
+                                    sink_metrics.records_queued.set(0);
+                                    return false;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                sink_metrics.records_queued.set(queue.len() as u64);
+                if !queue.is_empty() {
+                    // We need timely to reschedule this operator as we have
+                    // pending records that we need to send to Kinesis.
+                    activator.activate();
+                    return true;
+                }
+
+                false
+            };
+
+            (ret, shutdown_button)
+        },
+    );
+}
+
+/// Removes the records for the next `PutRecords` request from the front of
+/// `queue`, staying within the limits that Kinesis places on each request.
+fn next_batch(queue: &mut VecDeque<PutRecordsRequestEntry>) -> Vec<PutRecordsRequestEntry> {
+    let mut batch = Vec::new();
+    let mut bytes = 0;
+    while let Some(entry) = queue.front() {
+        let size = entry.data.len() + entry.partition_key.len();
+        // Always take at least one record, so that a record that exceeds the
+        // request size limit on its own does not stall the queue. Kinesis
+        // rejects such a request outright, which shuts down the sink.
+        if batch.len() == MAX_REQUEST_RECORDS
+            || (!batch.is_empty() && bytes + size > MAX_REQUEST_BYTES)
+        {
+            break;
+        }
+        bytes += size;
+        batch.push(queue.pop_front().expect("queue is not empty"));
+    }
+    batch
+}
@@ -8,10 +8,17 @@
 // by the Apache License, Version 2.0.
 
 mod avro_ocf;
+mod file;
+mod http;
 mod kafka;
+mod kinesis;
+mod queue;
 mod tail;
 mod util;
 
 pub use avro_ocf::avro_ocf;
+pub use file::file;
+pub use http::http;
 pub use kafka::kafka;
+pub use kinesis::kinesis;
 pub use tail::tail;
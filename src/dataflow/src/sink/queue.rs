@@ -0,0 +1,222 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
+use log::warn;
+use prometheus::UIntGauge;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+use dataflow_types::{QueueFullBehavior, SinkQueueConfig};
+
+/// A FIFO queue of data that a sink has yet to deliver, which holds at most a
+/// configured number of bytes in memory.
+///
+/// If the queue is configured to spill, data that does not fit in memory is
+/// written to a temporary file and read back, in order, as the in-memory
+/// portion of the queue drains. Otherwise, the queue accepts the data anyway,
+/// and it is up to the sink to notice that the queue [is full] and to block or
+/// shut down as configured.
+///
+/// [is full]: SinkQueue::is_full
+pub struct SinkQueue<T> {
+    config: SinkQueueConfig,
+    size_of: fn(&T) -> usize,
+    metrics: QueueMetrics,
+    memory: VecDeque<T>,
+    memory_bytes: usize,
+    spill: Option<Spill>,
+}
+
+/// Gauges that track the size of a [`SinkQueue`].
+pub struct QueueMetrics {
+    /// The number of bytes of data queued in memory.
+    pub bytes_queued: UIntGauge,
+    /// The number of bytes of data spilled to disk.
+    pub bytes_spilled: UIntGauge,
+}
+
+impl<T> SinkQueue<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Constructs an empty queue, which uses `size_of` to determine how many
+    /// bytes of memory each item occupies.
+    pub fn new(config: SinkQueueConfig, size_of: fn(&T) -> usize, metrics: QueueMetrics) -> Self {
+        SinkQueue {
+            config,
+            size_of,
+            metrics,
+            memory: VecDeque::new(),
+            memory_bytes: 0,
+            spill: None,
+        }
+    }
+
+    /// Adds `item` to the back of the queue.
+    ///
+    /// Only fails if the queue needs to spill to disk and cannot.
+    pub fn push_back(&mut self, item: T) -> Result<(), io::Error> {
+        let size = (self.size_of)(&item);
+        // Once the queue has spilled, every new item must follow the spilled
+        // items, or the queue would reorder them.
+        let spill = self.spill.is_some()
+            || (self.config.on_full == QueueFullBehavior::Spill
+                && !self.memory.is_empty()
+                && self.memory_bytes + size > self.config.max_bytes);
+        if spill {
+            if self.spill.is_none() {
+                self.spill = Some(Spill::new()?);
+            }
+            let spill = self.spill.as_mut().expect("spill file just created");
+            spill.push(&item, size)?;
+            self.metrics.bytes_spilled.set(spill.bytes as u64);
+        } else {
+            self.memory_bytes += size;
+            self.memory.push_back(item);
+            self.metrics.bytes_queued.set(self.memory_bytes as u64);
+        }
+        Ok(())
+    }
+
+    /// Removes the item at the front of the queue, if any.
+    pub fn pop_front(&mut self) -> Result<Option<T>, io::Error> {
+        if self.memory.is_empty() {
+            self.unspill()?;
+        }
+        let item = self.memory.pop_front();
+        if let Some(item) = &item {
+            self.memory_bytes -= (self.size_of)(item);
+            self.metrics.bytes_queued.set(self.memory_bytes as u64);
+        }
+        Ok(item)
+    }
+
+    /// Returns the item at the front of the queue, if any.
+    pub fn front(&mut self) -> Result<Option<&T>, io::Error> {
+        if self.memory.is_empty() {
+            self.unspill()?;
+        }
+        Ok(self.memory.front())
+    }
+
+    /// Removes all items from the queue.
+    pub fn clear(&mut self) {
+        self.memory.clear();
+        self.memory_bytes = 0;
+        self.spill = None;
+        self.metrics.bytes_queued.set(0);
+        self.metrics.bytes_spilled.set(0);
+    }
+
+    /// Returns the number of items in the queue, including any spilled items.
+    pub fn len(&self) -> usize {
+        self.memory.len() + self.spill.as_ref().map_or(0, |spill| spill.len)
+    }
+
+    /// Reports whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reports whether the items in memory exceed the queue's limit.
+    ///
+    /// A queue that spills is only ever full if a single item exceeds the
+    /// limit.
+    pub fn is_full(&self) -> bool {
+        self.memory_bytes > self.config.max_bytes
+    }
+
+    /// Reads spilled items back into memory, up to the queue's limit, but at
+    /// least one item, if there are any.
+    fn unspill(&mut self) -> Result<(), io::Error> {
+        let spill = match &mut self.spill {
+            Some(spill) => spill,
+            None => return Ok(()),
+        };
+        spill.writer.flush()?;
+        while spill.len > 0 {
+            let item: T = bincode::deserialize_from(&mut spill.reader)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let size = (self.size_of)(&item);
+            spill.len -= 1;
+            spill.bytes -= size;
+            self.memory_bytes += size;
+            self.memory.push_back(item);
+            if self.memory_bytes >= self.config.max_bytes {
+                break;
+            }
+        }
+        self.metrics.bytes_queued.set(self.memory_bytes as u64);
+        self.metrics.bytes_spilled.set(spill.bytes as u64);
+        if spill.len == 0 {
+            // Start afresh with an empty file the next time the queue spills,
+            // rather than letting this one grow forever.
+            self.spill = None;
+        }
+        Ok(())
+    }
+}
+
+/// A temporary file of items that did not fit in a [`SinkQueue`]'s memory.
+struct Spill {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    reader: BufReader<File>,
+    /// The number of items in the file that have yet to be read back.
+    len: usize,
+    /// The in-memory size of the items that have yet to be read back.
+    bytes: usize,
+}
+
+impl Spill {
+    fn new() -> Result<Self, io::Error> {
+        let path = std::env::temp_dir().join(format!("mz-sink-spill-{}", Uuid::new_v4()));
+        let writer = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let reader = File::open(&path)?;
+        Ok(Spill {
+            path,
+            writer: BufWriter::new(writer),
+            reader: BufReader::new(reader),
+            len: 0,
+            bytes: 0,
+        })
+    }
+
+    fn push<T>(&mut self, item: &T, size: usize) -> Result<(), io::Error>
+    where
+        T: Serialize,
+    {
+        bincode::serialize_into(&mut self.writer, item)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.len += 1;
+        self.bytes += size;
+        Ok(())
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!(
+                "unable to remove sink spill file {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
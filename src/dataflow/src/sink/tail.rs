@@ -7,17 +7,56 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use timely::dataflow::channels::pact::Pipeline;
-use timely::dataflow::operators::Operator;
-use timely::dataflow::{Scope, Stream};
+use std::cell::RefCell;
+use std::mem;
+use std::rc::Rc;
+use std::task::{Context, Poll};
 
+use differential_dataflow::hashable::Hashable;
+use differential_dataflow::operators::arrange::ShutdownButton;
 use futures::executor::block_on;
 use futures::sink::SinkExt;
+use lazy_static::lazy_static;
+use log::error;
+use prometheus::{register_uint_gauge_vec, UIntGaugeVec};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::FrontieredInputHandle;
+use timely::dataflow::{Scope, Stream};
 
-use dataflow_types::{Diff, TailSinkConnector, Timestamp, Update};
+use dataflow_types::{Diff, QueueFullBehavior, TailEvent, TailSinkConnector, Timestamp, Update};
 use expr::GlobalId;
 use repr::Row;
 
+use super::queue::{QueueMetrics, SinkQueue};
+use super::util::{activator_waker, sink_reschedule};
+
+/// The maximum number of events that the sink sends to the client at once.
+const MAX_BATCH_EVENTS: usize = 1024;
+
+lazy_static! {
+    static ref BYTES_QUEUED: UIntGaugeVec = register_uint_gauge_vec!(
+        "mz_tail_bytes_queued",
+        "The number of bytes of events that a tail has queued in memory for its client",
+        &["sink_id", "worker_id"]
+    )
+    .unwrap();
+    static ref BYTES_SPILLED: UIntGaugeVec = register_uint_gauge_vec!(
+        "mz_tail_bytes_spilled",
+        "The number of bytes of events that a tail has spilled to disk for its client",
+        &["sink_id", "worker_id"]
+    )
+    .unwrap();
+}
+
+/// Sends the updates in `stream` to the client that issued a `TAIL`.
+///
+/// If the connector requests progress events, then whenever the input frontier
+/// advances the sink also reports the new frontier, after all of the updates
+/// at the timestamps that it closes.
+///
+/// Events are queued until the client is ready to receive them, so that a slow
+/// client does not stall the worker. If the queue outgrows its limit, the sink
+/// blocks, fails, or spills to disk, as the connector specifies.
 pub fn tail<G>(
     stream: &Stream<G, (Row, Timestamp, Diff)>,
     id: GlobalId,
@@ -25,30 +64,206 @@ pub fn tail<G>(
 ) where
     G: Scope<Timestamp = Timestamp>,
 {
-    let mut tx = block_on(connector.tx.connect()).expect("tail transmitter failed");
-    stream.sink(Pipeline, &format!("tail-{}", id), move |input| {
-        input.for_each(|_, rows| {
-            let mut results: Vec<Update> = Vec::new();
-            for (row, time, diff) in rows.iter() {
-                let should_emit = if connector.strict {
-                    connector.frontier.less_than(time)
-                } else {
-                    connector.frontier.less_equal(time)
+    let tx = block_on(connector.tx.connect()).expect("tail transmitter failed");
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    // Route all updates to a single worker, so that a progress event cannot
+    // overtake updates that another worker has yet to send.
+    let sink_hash = id.hashed();
+    let sink_id = id.to_string();
+    let worker_id = stream.scope().index().to_string();
+    let labels = &[sink_id.as_str(), worker_id.as_str()];
+    let mut queue = SinkQueue::new(
+        connector.queue.clone(),
+        event_size,
+        QueueMetrics {
+            bytes_queued: BYTES_QUEUED.with_label_values(labels),
+            bytes_spilled: BYTES_SPILLED.with_label_values(labels),
+        },
+    );
+    let mut reported_progress = None;
+    // Whether the sink has failed, in which case it only has to deliver the
+    // error to the client.
+    let mut failed = false;
+    let mut vector = Vec::new();
+
+    let name = format!("tail-{}", id);
+    // The channel to the client closes when the operator is dropped, so there
+    // is no need to hold on to the shutdown button.
+    let _: ShutdownButton<_> = sink_reschedule(
+        &stream,
+        Exchange::new(move |_| sink_hash),
+        name.clone(),
+        |info| {
+            let waker = activator_waker(stream.scope().sync_activator_for(&info.address[..]));
+            let shutdown_button =
+                ShutdownButton::new(tx.clone(), stream.scope().activator_for(&info.address[..]));
+
+            let ret = move |input: &mut FrontieredInputHandle<_, _, _>| {
+                let mut tx = tx.borrow_mut();
+                let sink = match &mut *tx {
+                    Some(sink) => sink,
+                    None => return false,
                 };
-                if should_emit {
-                    results.push(Update {
-                        row: row.clone(),
-                        timestamp: *time,
-                        diff: *diff,
-                    });
+
+                let mut events = Vec::new();
+                input.for_each(|_, rows| {
+                    rows.swap(&mut vector);
+                    if failed {
+                        return;
+                    }
+                    for (row, time, diff) in vector.drain(..) {
+                        let should_emit = if connector.strict {
+                            connector.frontier.less_than(&time)
+                        } else {
+                            connector.frontier.less_equal(&time)
+                        };
+                        if should_emit {
+                            events.push(TailEvent::Update(Update {
+                                row,
+                                timestamp: time,
+                                diff,
+                            }));
+                        }
+                    }
+                });
+
+                if connector.progress && !failed {
+                    // Timestamps are totally ordered, so the frontier has at
+                    // most one element. An empty frontier means that the tail
+                    // is about to end, which the client learns of when the
+                    // stream closes.
+                    if let Some(time) = input.frontier().frontier().iter().next() {
+                        if reported_progress != Some(*time) {
+                            events.push(TailEvent::Progress(*time));
+                            reported_progress = Some(*time);
+                        }
+                    }
+                }
+
+                for event in events {
+                    if let Err(e) = queue.push_back(event) {
+                        // The remaining events must not follow the error.
+                        fail(&mut queue, &mut failed, format!("unable to spill: {}", e));
+                        break;
+                    }
+                }
+
+                if queue.is_full() {
+                    match connector.queue.on_full {
+                        QueueFullBehavior::Block => {
+                            // Wait for the client to make room, stalling the
+                            // worker in the meantime.
+                            while queue.is_full() {
+                                let batch = match next_batch(&mut queue) {
+                                    Ok(batch) => batch,
+                                    Err(e) => {
+                                        error!("unable to read spilled events in {}: {}", name, e);
+                                        *tx = None;
+                                        return false;
+                                    }
+                                };
+                                if block_on(sink.send(batch)).is_err() {
+                                    // The client has gone away.
+                                    *tx = None;
+                                    return false;
+                                }
+                            }
+                        }
+                        QueueFullBehavior::Error => {
+                            let msg = format!(
+                                "tail exceeded the limit of {} queued bytes, because the client \
+                                 did not keep up",
+                                connector.queue.max_bytes
+                            );
+                            fail(&mut queue, &mut failed, msg);
+                        }
+                        // The event that does not fit is larger than the limit
+                        // on its own. Send it anyway.
+                        QueueFullBehavior::Spill => (),
+                    }
                 }
-            }
-
-            // TODO(benesch): this blocks the Timely thread until the send
-            // completes. Hopefully it's just a quick write to a kernel buffer,
-            // but perhaps not if the batch gets too large? We may need to do
-            // something smarter, like offloading to a networking thread.
-            block_on(tx.send(results)).expect("tail send failed");
-        });
-    })
+
+                // Send the client as many events as it is ready to receive. If
+                // it is not ready, the waker reschedules this operator once it
+                // is.
+                let mut cx = Context::from_waker(&waker);
+                while !queue.is_empty() {
+                    match sink.as_mut().poll_ready(&mut cx) {
+                        Poll::Ready(Ok(())) => (),
+                        Poll::Ready(Err(_)) => {
+                            *tx = None;
+                            return false;
+                        }
+                        Poll::Pending => break,
+                    }
+                    let batch = match next_batch(&mut queue) {
+                        Ok(batch) => batch,
+                        Err(e) => {
+                            error!("unable to read spilled events in {}: {}", name, e);
+                            *tx = None;
+                            return false;
+                        }
+                    };
+                    if sink.as_mut().start_send(batch).is_err() {
+                        *tx = None;
+                        return false;
+                    }
+                }
+                let flushed = match sink.as_mut().poll_flush(&mut cx) {
+                    Poll::Ready(Ok(())) => true,
+                    Poll::Ready(Err(_)) => {
+                        *tx = None;
+                        return false;
+                    }
+                    Poll::Pending => false,
+                };
+
+                if failed && flushed && queue.is_empty() {
+                    // The client has received the error, so hang up.
+                    *tx = None;
+                    return false;
+                }
+
+                // Stay alive until the client has received everything.
+                !flushed || !queue.is_empty()
+            };
+
+            (ret, shutdown_button)
+        },
+    );
+}
+
+/// Removes the next batch of events to send to the client from `queue`.
+fn next_batch(queue: &mut SinkQueue<TailEvent>) -> Result<Vec<TailEvent>, std::io::Error> {
+    let mut batch = Vec::new();
+    while batch.len() < MAX_BATCH_EVENTS {
+        match queue.pop_front()? {
+            Some(event) => batch.push(event),
+            None => break,
+        }
+    }
+    Ok(batch)
+}
+
+/// Discards the undelivered events in `queue` in favor of an error that
+/// explains why the tail failed.
+fn fail(queue: &mut SinkQueue<TailEvent>, failed: &mut bool, msg: String) {
+    if *failed {
+        return;
+    }
+    *failed = true;
+    queue.clear();
+    queue
+        .push_back(TailEvent::Error(msg))
+        .expect("pushing to an empty in-memory queue cannot fail");
+}
+
+/// Estimates the number of bytes of memory that `event` occupies.
+fn event_size(event: &TailEvent) -> usize {
+    mem::size_of::<TailEvent>()
+        + match event {
+            TailEvent::Update(update) => update.row.data().len(),
+            TailEvent::Progress(_) => 0,
+            TailEvent::Error(msg) => msg.len(),
+        }
 }
@@ -7,13 +7,22 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+
 use differential_dataflow::operators::arrange::ShutdownButton;
+use futures::task::ArcWake;
 use timely::dataflow::channels::pact::ParallelizationContract;
 use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
 use timely::dataflow::operators::generic::{FrontieredInputHandle, OperatorInfo};
 use timely::dataflow::{Scope, Stream};
+use timely::scheduling::SyncActivator;
 use timely::Data;
 
+use dataflow_types::{Diff, Timestamp};
+use interchange::avro::DiffPair;
+use repr::{RelationDesc, Row};
+
 pub fn sink_reschedule<G: Scope, D, B, L, P, T>(
     s: &Stream<G, D>,
     pact: P,
@@ -40,3 +49,69 @@ where
 
     button
 }
+
+/// Packs the datums of `row` at `indices` into a new row.
+pub fn project_key(row: &Row, indices: &[usize]) -> Row {
+    let datums = row.unpack();
+    Row::pack(indices.iter().map(|i| datums[*i]))
+}
+
+/// Returns a waker that schedules an operator via `activator`, so that the
+/// operator can poll futures without blocking its worker, and be rescheduled
+/// when the futures can make progress.
+pub fn activator_waker(activator: SyncActivator) -> Waker {
+    struct ActivatorWaker(Mutex<SyncActivator>);
+
+    impl ArcWake for ActivatorWaker {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            // The operator may have shut down already, in which case there is
+            // nothing left to wake.
+            let _ = arc_self.0.lock().expect("lock poisoned").activate();
+        }
+    }
+
+    futures::task::waker(Arc::new(ActivatorWaker(Mutex::new(activator))))
+}
+
+/// Encodes updates as change records in the JSON or CSV format that file and
+/// Kinesis sinks share.
+pub enum ChangeEncoder {
+    Json(interchange::json::Encoder),
+    Csv(interchange::csv::Encoder),
+}
+
+impl ChangeEncoder {
+    /// Constructs an encoder for JSON change records of rows described by
+    /// `desc`.
+    pub fn json(desc: RelationDesc) -> Self {
+        ChangeEncoder::Json(interchange::json::Encoder::new(desc))
+    }
+
+    /// Constructs an encoder for CSV change records of rows described by
+    /// `desc`, whose fields are separated by `delimiter`.
+    pub fn csv(desc: RelationDesc, delimiter: u8) -> Self {
+        ChangeEncoder::Csv(interchange::csv::Encoder::new(desc, delimiter))
+    }
+
+    /// Encodes the update of `row` at `time` with `diff` as a single change
+    /// record, which captures the polarity but not the magnitude of `diff`.
+    pub fn encode(&self, row: &Row, time: Timestamp, diff: Diff) -> Vec<u8> {
+        match self {
+            ChangeEncoder::Json(encoder) => {
+                let diff_pair = if diff < 0 {
+                    DiffPair {
+                        before: Some(row),
+                        after: None,
+                    }
+                } else {
+                    DiffPair {
+                        before: None,
+                        after: Some(row),
+                    }
+                };
+                encoder.encode_diff_pair(diff_pair, time)
+            }
+            ChangeEncoder::Csv(encoder) => encoder.encode_update(row, time, diff > 0),
+        }
+    }
+}
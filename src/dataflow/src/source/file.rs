@@ -7,17 +7,21 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
-use std::sync::mpsc::TryRecvError;
-use std::sync::{Arc, Mutex};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+#[cfg(not(target_os = "macos"))]
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, Weak};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use failure::ResultExt;
-use log::error;
+use failure::{format_err, ResultExt};
+use log::{error, warn};
 #[cfg(not(target_os = "macos"))]
 use notify::{RecursiveMode, Watcher};
 use timely::dataflow::operators::Capability;
@@ -43,6 +47,26 @@ pub enum FileReadStyle {
     // TODO: TailFollowName,
 }
 
+/// How often a file source with a glob pattern looks for new matching files.
+const GLOB_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The maximum number of files that a file source with a glob pattern tails at
+/// once. Further matching files are tailed once earlier ones are deleted.
+const MAX_TAILED_FILES: usize = 256;
+
+/// How often tailed files are checked for new data on macOS, where file system
+/// events are not usable for tailing.
+#[cfg(target_os = "macos")]
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A record read by a file source.
+struct FileRecord<Out> {
+    value: Out,
+    /// The name of the file the record was read from and the record's line
+    /// number within that file, if the source reads more than one file.
+    origin: Option<(String, i64)>,
+}
+
 /// Wraps a file, producing a stream that is tailed forever.
 ///
 /// This involves silently swallowing EOFs,
@@ -50,6 +74,9 @@ pub enum FileReadStyle {
 struct ForeverTailedFile<Ev, Handle> {
     rx: std::sync::mpsc::Receiver<Ev>,
     inner: std::fs::File,
+    // Whether to stop at the end of the file once the file has been deleted,
+    // as no new writers can open it.
+    eof_when_deleted: bool,
     // This field only exists to keep the file watcher or timer
     // alive
     _h: Handle,
@@ -62,6 +89,9 @@ impl<Ev, H> Read for ForeverTailedFile<Ev, H> {
             for _ in self.rx.try_iter() {}
             match self.inner.read(buf)? {
                 0 => {
+                    if self.eof_when_deleted && self.inner.metadata()?.nlink() == 0 {
+                        return Ok(0);
+                    }
                     if self.rx.recv().is_ok() {
                         // Notify thinks there might be new data. Go around
                         // the loop again to check.
@@ -116,7 +146,7 @@ pub fn read_file_task<Ctor, I, Out, Err>(
     read_style: FileReadStyle,
     iter_ctor: Ctor,
 ) where
-    I: IntoIterator<Item = Result<Out, Err>> + Send + 'static,
+    I: IntoIterator<Item = Result<Out, Err>>,
     Ctor: FnOnce(Box<dyn Read + Send>) -> Result<I, Err>,
     Err: Into<failure::Error>,
 {
@@ -134,6 +164,22 @@ pub fn read_file_task<Ctor, I, Out, Err>(
         }
     };
 
+    read_open_file(&path, file, tx, activator, read_style, iter_ctor)
+}
+
+/// Blocking logic to read from `file`, which was opened at `path`.
+fn read_open_file<Ctor, I, Out, Err>(
+    path: &Path,
+    file: File,
+    tx: std::sync::mpsc::SyncSender<Result<Out, failure::Error>>,
+    activator: Option<Arc<Mutex<SyncActivator>>>,
+    read_style: FileReadStyle,
+    iter_ctor: Ctor,
+) where
+    I: IntoIterator<Item = Result<Out, Err>>,
+    Ctor: FnOnce(Box<dyn Read + Send>) -> Result<I, Err>,
+    Err: Into<failure::Error>,
+{
     let iter = match read_style {
         FileReadStyle::ReadOnce => iter_ctor(Box::new(file)),
         FileReadStyle::TailFollowFd => {
@@ -167,12 +213,18 @@ pub fn read_file_task<Ctor, I, Out, Err>(
                 let mut w = match notify::RecommendedWatcher::new_raw(notice_tx) {
                     Ok(w) => w,
                     Err(err) => {
-                        error!("file source: failed to create notify watcher: {}", err);
+                        let _ = tx.send(Err(format_err!(
+                            "file source: failed to create notify watcher: {}",
+                            err
+                        )));
                         return;
                     }
                 };
-                if let Err(err) = w.watch(&path, RecursiveMode::NonRecursive) {
-                    error!("file source: failed to add watch: {}", err);
+                if let Err(err) = w.watch(path, RecursiveMode::NonRecursive) {
+                    let _ = tx.send(Err(format_err!(
+                        "file source: failed to add watch: {}",
+                        err
+                    )));
                     return;
                 }
                 (notice_rx, w)
@@ -181,6 +233,7 @@ pub fn read_file_task<Ctor, I, Out, Err>(
             let file = ForeverTailedFile {
                 rx: file_events_stream,
                 inner: file,
+                eof_when_deleted: false,
                 _h: handle,
             };
 
@@ -188,6 +241,20 @@ pub fn read_file_task<Ctor, I, Out, Err>(
         }
     };
 
+    send_iter(path, iter, tx, activator)
+}
+
+/// Sends the records of `iter`, which reads the file at `path`, or the error
+/// that prevented its construction.
+fn send_iter<I, Out, Err>(
+    path: &Path,
+    iter: Result<I, Err>,
+    tx: std::sync::mpsc::SyncSender<Result<Out, failure::Error>>,
+    activator: Option<Arc<Mutex<SyncActivator>>>,
+) where
+    I: IntoIterator<Item = Result<Out, Err>>,
+    Err: Into<failure::Error>,
+{
     match iter.map_err(Into::into).with_context(|e| {
         format!(
             "Failed to obtain records from file at path {}: {}",
@@ -202,6 +269,271 @@ pub fn read_file_task<Ctor, I, Out, Err>(
     };
 }
 
+/// Wakes up the readers of the files that a file source with a glob pattern
+/// tails.
+///
+/// A single watcher observes the directories that contain the tailed files,
+/// rather than each file having a watcher of its own, as the kernel limits
+/// the number of watchers per user.
+struct TailNotifier {
+    #[cfg(not(target_os = "macos"))]
+    watcher: notify::RecommendedWatcher,
+    #[cfg(not(target_os = "macos"))]
+    events: Receiver<notify::RawEvent>,
+    dirs: HashSet<PathBuf>,
+    readers: HashMap<(u64, u64), Sender<()>>,
+}
+
+impl TailNotifier {
+    fn new() -> Result<Self, failure::Error> {
+        #[cfg(not(target_os = "macos"))]
+        let (watcher, events) = {
+            let (events_tx, events) = mpsc::channel();
+            let watcher = notify::RecommendedWatcher::new_raw(events_tx)
+                .with_context(|e| format!("file source: failed to create notify watcher: {}", e))?;
+            (watcher, events)
+        };
+        Ok(TailNotifier {
+            #[cfg(not(target_os = "macos"))]
+            watcher,
+            #[cfg(not(target_os = "macos"))]
+            events,
+            dirs: HashSet::new(),
+            readers: HashMap::new(),
+        })
+    }
+
+    /// Reports whether as many files as allowed are being tailed.
+    fn is_full(&mut self) -> bool {
+        self.notify_readers();
+        self.readers.len() >= MAX_TAILED_FILES
+    }
+
+    /// Registers the reader of the file identified by `id`, which lives in
+    /// `dir`, and returns the receiver of the reader's wakeups.
+    fn add_reader(&mut self, id: (u64, u64), dir: &Path) -> Result<Receiver<()>, failure::Error> {
+        if !self.dirs.contains(dir) {
+            #[cfg(not(target_os = "macos"))]
+            self.watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .with_context(|e| {
+                    format!(
+                        "file source: failed to add watch for {}: {}",
+                        dir.display(),
+                        e
+                    )
+                })?;
+            self.dirs.insert(dir.to_owned());
+        }
+        let (tx, rx) = mpsc::channel();
+        self.readers.insert(id, tx);
+        Ok(rx)
+    }
+
+    /// Wakes up every reader, forgetting the readers that have finished.
+    fn notify_readers(&mut self) {
+        self.readers.retain(|_, tx| tx.send(()).is_ok());
+    }
+
+    /// Wakes up the readers whenever their files may have changed, until
+    /// `timeout` elapses.
+    #[cfg(not(target_os = "macos"))]
+    fn wait(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            match self.events.recv_timeout(timeout) {
+                Ok(_) => {
+                    // Coalesce the events that are already pending.
+                    for _ in self.events.try_iter() {}
+                    self.notify_readers();
+                }
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    error!("file source: notify hung up while tailing files");
+                    thread::sleep(timeout);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Wakes up the readers every `TAIL_POLL_INTERVAL`, until `timeout`
+    /// elapses. See `read_open_file` for why macOS is polled.
+    #[cfg(target_os = "macos")]
+    fn wait(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while let Some(timeout) = deadline.checked_duration_since(Instant::now()) {
+            thread::sleep(timeout.min(TAIL_POLL_INTERVAL));
+            self.notify_readers();
+        }
+    }
+}
+
+/// Blocking logic to read from every file matching a glob pattern, intended
+/// for its own thread.
+///
+/// Files are identified by their device and inode numbers rather than their
+/// names, so that a file that is renamed, e.g. by log rotation, is not read
+/// again under its new name. When tailing, up to `MAX_TAILED_FILES` files are
+/// each tailed on their own thread until they are deleted, and the pattern is
+/// re-evaluated every `GLOB_POLL_INTERVAL` to discover new files for as long
+/// as `alive` can be upgraded. Once a deleted file has been read to its end,
+/// its identity is forgotten, as the file system is free to reuse its inode
+/// number for a new file.
+fn read_glob_task<Ctor, I, Out, Err>(
+    pattern: String,
+    tx: std::sync::mpsc::SyncSender<Result<FileRecord<Out>, failure::Error>>,
+    activator: Option<Arc<Mutex<SyncActivator>>>,
+    read_style: FileReadStyle,
+    iter_ctor: Ctor,
+    alive: Weak<()>,
+) where
+    I: IntoIterator<Item = Result<Out, Err>> + 'static,
+    Ctor: Fn(Box<dyn Read + Send>) -> Result<I, Err> + Clone + Send + 'static,
+    Err: Into<failure::Error> + 'static,
+    Out: Send + 'static,
+{
+    let mut notifier = match read_style {
+        FileReadStyle::ReadOnce => None,
+        FileReadStyle::TailFollowFd => match TailNotifier::new() {
+            Ok(notifier) => Some(notifier),
+            Err(err) => {
+                let _ = tx.send(Err(err));
+                return;
+            }
+        },
+    };
+    // Whether a full notifier has been reported, so that it is not reported
+    // again on every pass over the pattern.
+    let mut reported_full = false;
+    let seen = Arc::new(Mutex::new(HashSet::new()));
+    loop {
+        let paths = match glob::glob(&pattern) {
+            Ok(paths) => paths,
+            Err(err) => {
+                let _ = tx.send(Err(format_err!(
+                    "file source: invalid glob pattern '{}': {}",
+                    pattern,
+                    err
+                )));
+                return;
+            }
+        };
+        for path in paths {
+            let path = match path {
+                Ok(path) => path,
+                Err(err) => {
+                    warn!("file source: unable to read {}", err);
+                    continue;
+                }
+            };
+            if let Some(notifier) = &mut notifier {
+                if notifier.is_full() {
+                    if !reported_full {
+                        warn!(
+                            "file source: already tailing {} files matching '{}'; \
+                             deferring the rest",
+                            MAX_TAILED_FILES, pattern
+                        );
+                        reported_full = true;
+                    }
+                    break;
+                }
+                reported_full = false;
+            }
+            // Identify the file only once it is open, so that the file we
+            // identify is the file we read even if it is concurrently renamed.
+            // Files that disappear in the meantime are simply skipped.
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) => {
+                    warn!("file source: unable to open {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+            let id = match file.metadata() {
+                Ok(m) if m.is_file() => (m.dev(), m.ino()),
+                Ok(_) => continue,
+                Err(err) => {
+                    warn!("file source: unable to stat {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+            if !seen.lock().expect("seen lock poisoned").insert(id) {
+                continue;
+            }
+
+            let file_name = path.to_string_lossy().into_owned();
+            let iter_ctor = {
+                let iter_ctor = iter_ctor.clone();
+                move |reader| {
+                    iter_ctor(reader).map(move |iter| {
+                        iter.into_iter().enumerate().map(move |(i, record)| {
+                            record.map(|value| FileRecord {
+                                value,
+                                origin: Some((file_name.clone(), i as i64 + 1)),
+                            })
+                        })
+                    })
+                }
+            };
+            let tx = tx.clone();
+            let activator = activator.clone();
+            match &mut notifier {
+                None => read_open_file(
+                    &path,
+                    file,
+                    tx,
+                    activator,
+                    FileReadStyle::ReadOnce,
+                    iter_ctor,
+                ),
+                Some(notifier) => {
+                    let dir = match path.parent() {
+                        Some(dir) if dir != Path::new("") => dir,
+                        _ => Path::new("."),
+                    };
+                    let rx = match notifier.add_reader(id, dir) {
+                        Ok(rx) => rx,
+                        Err(err) => {
+                            seen.lock().expect("seen lock poisoned").remove(&id);
+                            let _ = tx.send(Err(err));
+                            continue;
+                        }
+                    };
+                    let seen = seen.clone();
+                    thread::spawn(move || {
+                        let file = ForeverTailedFile {
+                            rx,
+                            inner: file,
+                            eof_when_deleted: true,
+                            _h: (),
+                        };
+                        // Keep a handle to the file, to learn whether it was
+                        // deleted once the tail ends.
+                        let handle = file.inner.try_clone();
+                        send_iter(&path, iter_ctor(Box::new(file)), tx, activator);
+                        if let Ok(m) = handle.and_then(|handle| handle.metadata()) {
+                            if m.nlink() == 0 {
+                                seen.lock().expect("seen lock poisoned").remove(&id);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+
+        match &mut notifier {
+            None => return,
+            Some(notifier) => notifier.wait(GLOB_POLL_INTERVAL),
+        }
+        if alive.upgrade().is_none() {
+            // The source has been dropped.
+            return;
+        }
+    }
+}
+
 /// Timestamp history map is of format [pid1: (ts1, offset1), (ts2, offset2), pid2: (ts1, offset)...].
 /// For a given partition pid, messages in interval [0,offset1] get assigned ts1, all messages in interval [offset1+1,offset2]
 /// get assigned ts2, etc.
@@ -339,6 +671,7 @@ fn find_matching_timestamp(
 pub fn file<G, Ctor, I, Out, Err>(
     config: SourceConfig<G>,
     path: PathBuf,
+    glob: bool,
     read_style: FileReadStyle,
     iter_ctor: Ctor,
 ) -> (
@@ -351,7 +684,7 @@ pub fn file<G, Ctor, I, Out, Err>(
 where
     G: Scope<Timestamp = Timestamp>,
     I: IntoIterator<Item = Result<Out, Err>> + Send + 'static,
-    Ctor: FnOnce(Box<dyn Read + Send>) -> Result<I, Err> + Send + 'static,
+    Ctor: Fn(Box<dyn Read + Send>) -> Result<I, Err> + Clone + Send + 'static,
     Err: Into<failure::Error> + Send + 'static,
     Out: Send + Clone + 'static,
 {
@@ -392,7 +725,7 @@ where
     } = config;
 
     // Buffer placeholder for buffering messages for which we did not have a timestamp
-    let mut buffer: Option<Result<Out, failure::Error>> = None;
+    let mut buffer: Option<Result<FileRecord<Out>, failure::Error>> = None;
     // Index of the last offset that we have already processed (and assigned a timestamp to)
     let mut last_processed_offset = MzOffset { offset: 0 };
     // Index of the current message's offset
@@ -407,12 +740,35 @@ where
     let (stream, capability) = source(id, ts, scope, config.name.clone(), move |info| {
         let activator = scope.activator_for(&info.address[..]);
         let (tx, rx) = std::sync::mpsc::sync_channel(MAX_RECORDS_PER_INVOCATION);
+        // Looking for new files matching a glob pattern stops once this is
+        // dropped along with the operator.
+        let alive = Arc::new(());
         if active {
             let activator = Arc::new(Mutex::new(scope.sync_activator_for(&info.address[..])));
-            thread::spawn(|| read_file_task(path, tx, Some(activator), read_style, iter_ctor));
+            if glob {
+                let pattern = path.to_string_lossy().into_owned();
+                let alive = Arc::downgrade(&alive);
+                thread::spawn(move || {
+                    read_glob_task(pattern, tx, Some(activator), read_style, iter_ctor, alive)
+                });
+            } else {
+                let iter_ctor = move |reader| {
+                    iter_ctor(reader).map(|iter| {
+                        iter.into_iter().map(|record| {
+                            record.map(|value| FileRecord {
+                                value,
+                                origin: None,
+                            })
+                        })
+                    })
+                };
+                thread::spawn(|| read_file_task(path, tx, Some(activator), read_style, iter_ctor));
+            }
         }
         let mut dead = false;
         move |cap, output| {
+            // Hold on to `alive` for as long as the operator exists.
+            let _ = &alive;
             // If nothing else causes us to wake up, do so after a specified amount of time.
             let mut next_activation_duration = HEARTBEAT;
             // Number of records read for this particular activation
@@ -483,11 +839,17 @@ where
                         Some(ts) => {
                             last_processed_offset = current_msg_offset;
                             let ts_cap = cap.delayed(&ts);
-                            output.session(&ts_cap).give(Ok(SourceOutput::new(
-                                vec![],
-                                message,
-                                Some(last_processed_offset.offset),
-                            )));
+                            // Records from glob sources are numbered per file.
+                            let (position, file_name) = match message.origin {
+                                Some((file_name, line_no)) => (line_no, Some(file_name)),
+                                None => (last_processed_offset.offset, None),
+                            };
+                            output.session(&ts_cap).give(Ok(SourceOutput {
+                                key: vec![],
+                                value: message.value,
+                                position: Some(position),
+                                file_name,
+                            }));
 
                             downgrade_capability(
                                 &id,
@@ -90,6 +90,9 @@ where
     pub value: V,
     /// The position in the source, if such a concept exists (e.g., Kafka offset, file line number)
     pub position: Option<i64>,
+    /// The name of the file the record was read from, for file sources that
+    /// read more than one file
+    pub file_name: Option<String>,
 }
 
 impl<K, V> SourceOutput<K, V>
@@ -103,6 +106,7 @@ where
             key,
             value,
             position,
+            file_name: None,
         }
     }
 }
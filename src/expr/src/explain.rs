@@ -122,7 +122,7 @@ impl RelationExpr {
                     | Threshold { .. }
                     | ArrangeBy { .. } => false,
                     Join { .. } | Union { .. } => true,
-                    Let { value, .. } => {
+                    Let { value, .. } | LetRec { value, .. } => {
                         // only the value child goes in a different chain
                         (node.expr as *const RelationExpr) == ((&**value) as *const RelationExpr)
                     }
@@ -142,7 +142,7 @@ impl RelationExpr {
         // assumes LocalId is unique
         let mut local_id_chain: HashMap<&LocalId, usize> = HashMap::new();
         for node in &mut nodes {
-            if let Let { id, value, .. } = node.expr {
+            if let Let { id, value, .. } | LetRec { id, value, .. } = node.expr {
                 local_id_chain.insert(id, expr_chain(value));
             }
         }
@@ -191,6 +191,7 @@ impl RelationExpr {
                     .unwrap(),
                 },
                 Let { id, .. } => write!(pretty, "Let %{}", local_id_chain[id]).unwrap(),
+                LetRec { id, .. } => write!(pretty, "LetRec %{}", local_id_chain[id]).unwrap(),
                 Project { outputs, .. } => {
                     write!(pretty, "Project {}", Bracketed("(", ")", Indices(outputs))).unwrap()
                 }
@@ -20,7 +20,7 @@ mod scalar;
 pub mod explain;
 
 pub use id::{DummyHumanizer, GlobalId, Id, IdHumanizer, LocalId, PartitionId, SourceInstanceId};
-pub use relation::func::{AggregateFunc, TableFunc};
+pub use relation::func::{AggregateFunc, TableFunc, WindowFrame, WindowFunc};
 pub use relation::func::{AnalyzedRegex, CaptureGroupDesc};
 pub use relation::{
     compare_columns, AggregateExpr, ColumnOrder, IdGen, JoinImplementation, RelationExpr,
@@ -9,6 +9,7 @@
 
 #![allow(missing_docs)]
 
+use std::cmp::Ordering;
 use std::fmt;
 use std::iter;
 
@@ -21,6 +22,7 @@ use repr::adt::decimal::Significand;
 use repr::adt::regex::Regex as ReprRegex;
 use repr::{ColumnType, Datum, RelationType, Row, RowArena, ScalarType};
 
+use crate::relation::{compare_columns, ColumnOrder};
 use crate::scalar::func::jsonb_stringify;
 
 // TODO(jamii) be careful about overflow in sum/avg
@@ -387,6 +389,170 @@ where
     Datum::List(datum.unwrap_list())
 }
 
+/// Computes a window function over the rows of one partition.
+///
+/// Each datum is a record of the row itself, a record of the values by which
+/// the partition is ordered, and a record of the arguments to the function.
+/// The result is a list containing, for each row, a record of the row and the
+/// value of the function at that row.
+fn window<'a, I>(
+    func: &WindowFunc,
+    order_by: &[ColumnOrder],
+    datums: I,
+    temp_storage: &'a RowArena,
+) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut rows: Vec<(Datum<'a>, Vec<Datum<'a>>, Vec<Datum<'a>>)> = datums
+        .into_iter()
+        .map(|d| {
+            let mut fields = d.unwrap_list().iter();
+            let row = fields.next().unwrap();
+            let order = fields.next().unwrap().unwrap_list().iter().collect();
+            let args = fields.next().unwrap().unwrap_list().iter().collect();
+            (row, order, args)
+        })
+        .collect();
+    // Break ties by the rows themselves, so that the result does not depend on
+    // the order in which the rows arrived.
+    rows.sort_by(|a, b| compare_columns(order_by, &a.1, &b.1, || a.0.cmp(&b.0)));
+
+    // The index of the first and one past the last peer of each row, along with
+    // the number of distinct peer groups that precede it.
+    let mut peers = Vec::with_capacity(rows.len());
+    let mut start = 0;
+    let mut group = 0;
+    while start < rows.len() {
+        let mut end = start + 1;
+        while end < rows.len()
+            && compare_columns(order_by, &rows[start].1, &rows[end].1, || Ordering::Equal)
+                == Ordering::Equal
+        {
+            end += 1;
+        }
+        peers.extend(iter::repeat((start, end, group)).take(end - start));
+        start = end;
+        group += 1;
+    }
+
+    let mut cached_aggregate: Option<(usize, Datum<'a>)> = None;
+    let values = (0..rows.len()).map(|i| {
+        let (peers_start, peers_end, group) = peers[i];
+        match func {
+            WindowFunc::RowNumber => Datum::Int64(i as i64 + 1),
+            WindowFunc::Rank => Datum::Int64(peers_start as i64 + 1),
+            WindowFunc::DenseRank => Datum::Int64(group as i64 + 1),
+            WindowFunc::Lag | WindowFunc::Lead => {
+                let args = &rows[i].2;
+                let offset = match args[1] {
+                    Datum::Null => return Datum::Null,
+                    offset => i64::from(offset.unwrap_int32()),
+                };
+                let j = if let WindowFunc::Lag = func {
+                    i as i64 - offset
+                } else {
+                    i as i64 + offset
+                };
+                if j >= 0 && j < rows.len() as i64 {
+                    rows[j as usize].2[0]
+                } else {
+                    args[2]
+                }
+            }
+            WindowFunc::FirstValue => rows[0].2[0],
+            WindowFunc::Aggregate { func, frame } => {
+                let end = match frame {
+                    WindowFrame::Partition => rows.len(),
+                    WindowFrame::ThroughPeers => peers_end,
+                    WindowFrame::ThroughRow => i + 1,
+                };
+                match cached_aggregate {
+                    Some((cached_end, value)) if cached_end == end => value,
+                    _ => {
+                        // Collecting the arguments, rather than passing an iterator
+                        // over them, stops the types of `eval` and `window` from
+                        // recursively instantiating one another.
+                        let args: Vec<_> = rows[..end].iter().map(|r| r.2[0]).collect();
+                        let value = func.eval(args, temp_storage);
+                        cached_aggregate = Some((end, value));
+                        value
+                    }
+                }
+            }
+        }
+    });
+    let values: Vec<_> = values.collect();
+
+    temp_storage.make_datum(|packer| {
+        packer.push_list_with(|packer| {
+            for ((row, _, _), value) in rows.iter().zip(values) {
+                packer.push_list(&[*row, value]);
+            }
+        })
+    })
+}
+
+/// A function whose value at a row depends on the other rows of its partition.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
+pub enum WindowFunc {
+    RowNumber,
+    Rank,
+    DenseRank,
+    /// Takes the value, the offset, and the default as arguments.
+    Lag,
+    /// Takes the value, the offset, and the default as arguments.
+    Lead,
+    FirstValue,
+    Aggregate {
+        func: Box<AggregateFunc>,
+        frame: WindowFrame,
+    },
+}
+
+impl WindowFunc {
+    /// The type of the function's value, given the type of its first argument,
+    /// if any.
+    pub fn output_type(&self, arg_type: Option<ScalarType>) -> ScalarType {
+        match self {
+            WindowFunc::RowNumber | WindowFunc::Rank | WindowFunc::DenseRank => ScalarType::Int64,
+            WindowFunc::Lag | WindowFunc::Lead | WindowFunc::FirstValue => {
+                arg_type.expect("window function requires an argument")
+            }
+            WindowFunc::Aggregate { func, .. } => {
+                let arg_type = arg_type.expect("window function requires an argument");
+                func.output_type(ColumnType::new(arg_type)).scalar_type
+            }
+        }
+    }
+}
+
+impl fmt::Display for WindowFunc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WindowFunc::RowNumber => f.write_str("row_number"),
+            WindowFunc::Rank => f.write_str("rank"),
+            WindowFunc::DenseRank => f.write_str("dense_rank"),
+            WindowFunc::Lag => f.write_str("lag"),
+            WindowFunc::Lead => f.write_str("lead"),
+            WindowFunc::FirstValue => f.write_str("first_value"),
+            WindowFunc::Aggregate { func, .. } => write!(f, "{}", func),
+        }
+    }
+}
+
+/// The rows of a partition over which a windowed aggregate is computed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
+pub enum WindowFrame {
+    /// All rows of the partition.
+    Partition,
+    /// The rows from the start of the partition through the last peer of the
+    /// current row.
+    ThroughPeers,
+    /// The rows from the start of the partition through the current row.
+    ThroughRow,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
 pub enum AggregateFunc {
     MaxInt32,
@@ -419,6 +585,12 @@ pub enum AggregateFunc {
     Any,
     All,
     JsonbAgg,
+    /// Computes `func` over a partition ordered by `order_by`. See `window`
+    /// for the representation of its input and output.
+    Window {
+        func: WindowFunc,
+        order_by: Vec<ColumnOrder>,
+    },
 }
 
 impl AggregateFunc {
@@ -457,6 +629,9 @@ impl AggregateFunc {
             AggregateFunc::Any => any(datums),
             AggregateFunc::All => all(datums),
             AggregateFunc::JsonbAgg => jsonb_agg(datums, temp_storage),
+            AggregateFunc::Window { func, order_by } => {
+                window(func, order_by, datums, temp_storage)
+            }
         }
     }
 
@@ -481,6 +656,25 @@ impl AggregateFunc {
             AggregateFunc::Any => ScalarType::Bool,
             AggregateFunc::All => ScalarType::Bool,
             AggregateFunc::JsonbAgg => ScalarType::Jsonb,
+            AggregateFunc::Window { func, .. } => {
+                let mut fields = match input_type.scalar_type {
+                    ScalarType::Record { fields } => fields,
+                    _ => unreachable!("window function input is not a record"),
+                };
+                let arg_type = match fields.pop() {
+                    Some((_, ScalarType::Record { fields })) => {
+                        fields.into_iter().next().map(|(_, typ)| typ)
+                    }
+                    _ => unreachable!("window function arguments are not a record"),
+                };
+                let row_type = fields.swap_remove(0).1;
+                ScalarType::List(Box::new(ScalarType::Record {
+                    fields: vec![
+                        ("row".into(), row_type),
+                        ("value".into(), func.output_type(arg_type)),
+                    ],
+                }))
+            }
             _ => input_type.scalar_type,
         };
         let nullable = match self {
@@ -565,6 +759,14 @@ fn generate_series<'a>(typ: &ScalarType, start: Datum<'a>, stop: Datum<'a>) -> V
     }
 }
 
+fn unnest_list(a: Datum) -> Vec<Row> {
+    let mut row_packer = repr::RowPacker::new();
+    match a {
+        Datum::List(list) => list.iter().map(|e| row_packer.pack(&[e])).collect(),
+        _ => vec![],
+    }
+}
+
 impl fmt::Display for AggregateFunc {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -598,6 +800,7 @@ impl fmt::Display for AggregateFunc {
             AggregateFunc::Any => f.write_str("any"),
             AggregateFunc::All => f.write_str("all"),
             AggregateFunc::JsonbAgg => f.write_str("jsonb_agg"),
+            AggregateFunc::Window { func, .. } => write!(f, "window({})", func),
         }
     }
 }
@@ -679,6 +882,8 @@ pub enum TableFunc {
     // ScalarType is either Int32 or Int64.
     // TODO(justin): should also possibly be Timestamp{,Tz}.
     GenerateSeries(ScalarType),
+    // Produces one row for each element of a list of the given type.
+    UnnestList(ScalarType),
 }
 
 impl TableFunc {
@@ -692,6 +897,7 @@ impl TableFunc {
             TableFunc::RegexpExtract(a) => regexp_extract(datums[0], a).into_iter().collect(),
             TableFunc::CsvExtract(n_cols) => csv_extract(datums[0], *n_cols).into_iter().collect(),
             TableFunc::GenerateSeries(typ) => generate_series(typ, datums[0], datums[1]),
+            TableFunc::UnnestList(_) => unnest_list(datums[0]),
         }
     }
 
@@ -720,6 +926,7 @@ impl TableFunc {
                 .take(*n_cols)
                 .collect(),
             TableFunc::GenerateSeries(typ) => vec![ColumnType::new(typ.clone())],
+            TableFunc::UnnestList(typ) => vec![ColumnType::new(typ.clone()).nullable(true)],
         })
     }
 
@@ -731,6 +938,7 @@ impl TableFunc {
             TableFunc::RegexpExtract(a) => a.capture_groups_len(),
             TableFunc::CsvExtract(n_cols) => *n_cols,
             TableFunc::GenerateSeries(_) => 1,
+            TableFunc::UnnestList(_) => 1,
         }
     }
 }
@@ -748,6 +956,7 @@ impl fmt::Display for TableFunc {
                 f.write_fmt(format_args!("csv_extract({}, _)", n_cols))
             }
             TableFunc::GenerateSeries(_) => f.write_str("generate_series"),
+            TableFunc::UnnestList(_) => f.write_str("unnest_list"),
         }
     }
 }
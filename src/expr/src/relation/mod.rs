@@ -58,6 +58,24 @@ pub enum RelationExpr {
         /// The result of the `Let`, evaluated with `name` bound to `value`.
         body: Box<RelationExpr>,
     },
+    /// Introduce a temporary dataflow that is defined in terms of itself.
+    ///
+    /// Within `value`, `Get` variants of `id` retrieve the result of the
+    /// previous iteration of `value`, starting from the empty collection. The
+    /// value is iterated until it no longer changes, and the result is bound to
+    /// `id` for the evaluation of `body`.
+    ///
+    /// The runtime memory footprint of this operator is proportional to the
+    /// size of its fixed point.
+    LetRec {
+        /// The identifier to be used in `Get` variants to retrieve `value`.
+        id: LocalId,
+        /// The collection to be iterated and bound to `id`.
+        value: Box<RelationExpr>,
+        /// The result of the `LetRec`, evaluated with `id` bound to the fixed
+        /// point of `value`.
+        body: Box<RelationExpr>,
+    },
     /// Project out some columns from a dataflow
     ///
     /// The runtime memory footprint of this operator is zero.
@@ -230,7 +248,7 @@ impl RelationExpr {
                 }
             }
             RelationExpr::Get { typ, .. } => typ.clone(),
-            RelationExpr::Let { body, .. } => body.typ(),
+            RelationExpr::Let { body, .. } | RelationExpr::LetRec { body, .. } => body.typ(),
             RelationExpr::Project { input, outputs } => {
                 let input_typ = input.typ();
                 let mut output_typ = RelationType::new(
@@ -717,7 +735,7 @@ impl RelationExpr {
     {
         match self {
             RelationExpr::Constant { .. } | RelationExpr::Get { .. } => (),
-            RelationExpr::Let { value, body, .. } => {
+            RelationExpr::Let { value, body, .. } | RelationExpr::LetRec { value, body, .. } => {
                 f(value)?;
                 f(body)?;
             }
@@ -794,7 +812,7 @@ impl RelationExpr {
     {
         match self {
             RelationExpr::Constant { .. } | RelationExpr::Get { .. } => (),
-            RelationExpr::Let { value, body, .. } => {
+            RelationExpr::Let { value, body, .. } | RelationExpr::LetRec { value, body, .. } => {
                 f(value)?;
                 f(body)?;
             }
@@ -942,6 +960,11 @@ impl RelationExpr {
                 value: _,
                 body: _,
             }
+            | RelationExpr::LetRec {
+                id: _,
+                value: _,
+                body: _,
+            }
             | RelationExpr::Project {
                 input: _,
                 outputs: _,
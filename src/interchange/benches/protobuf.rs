@@ -26,7 +26,7 @@ fn get_decoder(message_name: &str) -> Decoder {
     file_descriptor_set.set_file(repeated_field);
 
     let descriptors = Descriptors::from_proto(&file_descriptor_set);
-    Decoder::new(descriptors, message_name)
+    Decoder::new(descriptors, message_name, false)
 }
 
 pub fn bench_protobuf(c: &mut Criterion) {
@@ -31,7 +31,9 @@ use repr::adt::decimal::{Significand, MAX_DECIMAL_PRECISION};
 use repr::adt::jsonb::{JsonbPacker, JsonbRef};
 use repr::{ColumnName, ColumnType, Datum, RelationDesc, Row, RowPacker, ScalarType};
 
+use crate::encode::{column_names, key_desc};
 use crate::error::Result;
+use crate::json;
 
 /// Validates an Avro key schema for use as a source.
 ///
@@ -143,6 +145,12 @@ pub fn validate_value_schema(
 }
 
 fn validate_schema_1(schema: SchemaNode) -> Result<Vec<(ColumnName, ColumnType)>> {
+    // The names of the records that enclose the schema currently being
+    // validated, which must not recur within it.
+    let mut seen_avro_nodes = HashSet::new();
+    if let Some(name) = schema.name {
+        seen_avro_nodes.insert(name.to_string());
+    }
     match schema.inner {
         SchemaPiece::Record { fields, .. } => {
             let mut columns = vec![];
@@ -173,13 +181,14 @@ fn validate_schema_1(schema: SchemaNode) -> Result<Vec<(ColumnName, ColumnType)>
                             // If there is more than one variant in the union,
                             // the column's output type is nullable, as this
                             // column will be null whenever it is uninhabited.
-                            let ty = validate_schema_2(node)?;
+                            let ty = validate_schema_2(&mut seen_avro_nodes, node)?;
                             let nullable = vs.len() > 1;
                             columns.push((name.into(), ColumnType::new(ty).nullable(nullable)));
                         }
                     }
                 } else {
-                    let scalar_type = validate_schema_2(schema.step(&f.schema))?;
+                    let scalar_type =
+                        validate_schema_2(&mut seen_avro_nodes, schema.step(&f.schema))?;
                     columns.push((
                         f.name.clone().into(),
                         ColumnType::new(scalar_type).nullable(false),
@@ -192,7 +201,10 @@ fn validate_schema_1(schema: SchemaNode) -> Result<Vec<(ColumnName, ColumnType)>
     }
 }
 
-fn validate_schema_2(schema: SchemaNode) -> Result<ScalarType> {
+fn validate_schema_2(
+    seen_avro_nodes: &mut HashSet<String>,
+    schema: SchemaNode,
+) -> Result<ScalarType> {
     Ok(match schema.inner {
         SchemaPiece::Null => bail!("null outside of union types is not supported"),
         SchemaPiece::Boolean => ScalarType::Bool,
@@ -219,10 +231,64 @@ fn validate_schema_2(schema: SchemaNode) -> Result<ScalarType> {
 
         SchemaPiece::Json => ScalarType::Jsonb,
 
+        SchemaPiece::Array(inner) => ScalarType::List(Box::new(validate_nested_schema(
+            seen_avro_nodes,
+            schema.step(&**inner),
+        )?)),
+        // There is no map type, so maps are represented as JSON objects. Their
+        // values must still be of a type that we understand, as they are
+        // decoded like any other value before being converted to JSON.
+        SchemaPiece::Map(inner) => {
+            validate_nested_schema(seen_avro_nodes, schema.step(&**inner))?;
+            ScalarType::Jsonb
+        }
+        SchemaPiece::Record { fields, .. } => {
+            let name = schema.name.map(|name| name.to_string());
+            if let Some(name) = &name {
+                if !seen_avro_nodes.insert(name.clone()) {
+                    bail!("Recursive types are not supported: {}", name);
+                }
+            }
+            let fields = fields
+                .iter()
+                .map(|f| {
+                    let typ = validate_nested_schema(seen_avro_nodes, schema.step(&f.schema))?;
+                    Ok((f.name.clone().into(), typ))
+                })
+                .collect::<Result<_>>()?;
+            if let Some(name) = &name {
+                seen_avro_nodes.remove(name);
+            }
+            ScalarType::Record { fields }
+        }
+
         _ => bail!("Unsupported type in schema: {:?}", schema.inner),
     })
 }
 
+/// Converts the schema of a value nested within an array, map, or record.
+///
+/// Unlike a top-level field, which becomes one column per variant of its
+/// union, a nested value must have a single type, so the only unions that it
+/// can use are those that make a type nullable.
+fn validate_nested_schema(
+    seen_avro_nodes: &mut HashSet<String>,
+    schema: SchemaNode,
+) -> Result<ScalarType> {
+    match schema.inner {
+        SchemaPiece::Union(us) => {
+            let mut vs = us.variants().iter().filter(|v| !is_null(v));
+            match (vs.next(), vs.next()) {
+                (Some(v), None) => validate_schema_2(seen_avro_nodes, schema.step(v)),
+                _ => bail!(
+                    "Unions within arrays, maps, or records must have exactly one non-null variant"
+                ),
+            }
+        }
+        _ => validate_schema_2(seen_avro_nodes, schema),
+    }
+}
+
 pub fn parse_schema(schema: &str) -> Result<Schema> {
     let schema = serde_json::from_str(schema)?;
     Schema::parse(&schema)
@@ -292,7 +358,7 @@ fn first_mismatched_schema_types<'a>(
     }
 }
 
-fn pack_value(v: Value, mut row: RowPacker, n: SchemaNode) -> Result<RowPacker> {
+fn pack_value(v: Value, row: &mut RowPacker, n: SchemaNode) -> Result<()> {
     match v {
         Value::Null => row.push(Datum::Null),
         Value::Boolean(true) => row.push(Datum::True),
@@ -306,7 +372,7 @@ fn pack_value(v: Value, mut row: RowPacker, n: SchemaNode) -> Result<RowPacker>
         Value::Decimal(DecimalValue { unscaled, .. }) => row.push(Datum::Decimal(
             Significand::from_twos_complement_be(&unscaled)?,
         )),
-        Value::Bytes(b) => row.push(Datum::Bytes(&b)),
+        Value::Bytes(b) | Value::Fixed(_ /* size */, b) => row.push(Datum::Bytes(&b)),
         Value::String(s) | Value::Enum(_ /* idx */, s) => row.push(Datum::String(&s)),
         Value::Union(idx, v) => {
             let mut v = Some(*v);
@@ -319,7 +385,7 @@ fn pack_value(v: Value, mut row: RowPacker, n: SchemaNode) -> Result<RowPacker>
                 {
                     if var_idx == idx {
                         let next = n.step(var_s);
-                        row = pack_value(v.take().unwrap(), row, next)?;
+                        pack_value(v.take().unwrap(), row, next)?;
                     } else {
                         row.push(Datum::Null);
                     }
@@ -328,13 +394,65 @@ fn pack_value(v: Value, mut row: RowPacker, n: SchemaNode) -> Result<RowPacker>
                 unreachable!("Avro value out of sync with schema");
             }
         }
-        Value::Json(j) => row = JsonbPacker::new(row).pack_serde_json(j)?,
-        other @ Value::Fixed(..)
-        | other @ Value::Array(_)
-        | other @ Value::Map(_)
-        | other @ Value::Record(_) => bail!("unsupported avro value: {:?}", other),
+        Value::Json(j) => pack_jsonb(row, j)?,
+        Value::Array(elems) => {
+            let elem_node = if let SchemaPiece::Array(inner) = n.inner {
+                n.step(&**inner)
+            } else {
+                unreachable!("Avro value out of sync with schema");
+            };
+            row.push_list_with(|packer| -> Result<()> {
+                for elem in elems {
+                    pack_value(elem, packer, elem_node)?;
+                }
+                Ok(())
+            })?;
+        }
+        Value::Record(fields) => {
+            let schema_fields = if let SchemaPiece::Record { fields, .. } = n.inner {
+                fields
+            } else {
+                unreachable!("Avro value out of sync with schema");
+            };
+            // Records are represented as lists of their fields' values.
+            row.push_list_with(|packer| -> Result<()> {
+                for ((_, v), f) in fields.into_iter().zip(schema_fields) {
+                    pack_value(v, packer, n.step(&f.schema))?;
+                }
+                Ok(())
+            })?;
+        }
+        Value::Map(entries) => {
+            let value_node = if let SchemaPiece::Map(inner) = n.inner {
+                n.step(&**inner)
+            } else {
+                unreachable!("Avro value out of sync with schema");
+            };
+            // Maps are represented as JSON objects. Decode each value as usual,
+            // then convert it to JSON the same way that a JSON sink would.
+            let value_type = validate_nested_schema(&mut HashSet::new(), value_node)?;
+            let mut object = serde_json::Map::new();
+            let mut packer = RowPacker::new();
+            for (k, v) in entries {
+                pack_value(v, &mut packer, value_node)?;
+                let value = packer.finish_and_reuse();
+                object.insert(k, json::datum_to_json(value.unpack_first(), &value_type));
+            }
+            pack_jsonb(row, serde_json::Value::Object(object))?;
+        }
     };
-    Ok(row)
+    Ok(())
+}
+
+/// Packs `j` into `row` as a jsonb datum.
+fn pack_jsonb(row: &mut RowPacker, j: serde_json::Value) -> Result<()> {
+    // A `JsonbPacker` takes ownership of the packer that it packs into, so
+    // pack into a scratch row and copy the result over.
+    let jsonb = JsonbPacker::new(RowPacker::new())
+        .pack_serde_json(j)?
+        .finish();
+    row.push(jsonb.unpack_first());
+    Ok(())
 }
 
 pub fn extract_nullable_row<'a, I>(v: Value, extra: I, n: SchemaNode) -> Result<Option<Row>>
@@ -369,7 +487,7 @@ where
                 for (i, (_, col)) in fields.into_iter().enumerate() {
                     let f_schema = &schema_fields[i].schema;
                     let f_node = n.step(f_schema);
-                    row = pack_value(col, row, f_node)?;
+                    pack_value(col, &mut row, f_node)?;
                 }
                 for d in extra {
                     row.push(d);
@@ -821,56 +939,21 @@ impl Decoder {
     }
 }
 
-/// Builds an Avro schema that corresponds to `desc`.
+/// Builds the JSON for an Avro record schema named `row` whose fields
+/// correspond to `columns`.
 ///
-/// Requires that all column names in `desc` are present. The returned schema
-/// has some special properties to ease encoding:
+/// The returned schema has some special properties to ease encoding:
 ///
 ///   * Union schemas are only used to represent nullability. The first
 ///     variant is always the null variant, and the second and last variant
 ///     is the non-null variant.
-fn build_schema(columns: &[(ColumnName, ColumnType)]) -> Schema {
+///   * The elements of lists and the fields of records are always nullable,
+///     as Materialize does not track their nullability.
+fn build_row_schema_json(columns: &[(ColumnName, ColumnType)]) -> serde_json::Value {
+    let mut record_count = 0;
     let mut fields = Vec::new();
     for (name, typ) in columns.iter() {
-        let mut field_type = match &typ.scalar_type {
-            ScalarType::Bool => json!("boolean"),
-            ScalarType::Int32 => json!("int"),
-            ScalarType::Int64 => json!("long"),
-            ScalarType::Float32 => json!("float"),
-            ScalarType::Float64 => json!("double"),
-            ScalarType::Decimal(p, s) => json!({
-                "type": "bytes",
-                "logicalType": "decimal",
-                "precision": p,
-                "scale": s,
-            }),
-            ScalarType::Date => json!({
-                "type": "int",
-                "logicalType": "date",
-            }),
-            ScalarType::Time => json!({
-                "type": "long",
-                "logicalType": "time-micros",
-            }),
-            ScalarType::Timestamp | ScalarType::TimestampTz => json!({
-                "type": "long",
-                "connect.name": "io.debezium.time.MicroTimestamp",
-                "logicalType": "timestamp-micros"
-            }),
-            ScalarType::Interval => json!({
-                "type": "fixed",
-                "size": 12,
-                "logicalType": "duration"
-            }),
-            ScalarType::Bytes => json!("bytes"),
-            ScalarType::String => json!("string"),
-            ScalarType::Jsonb => json!({
-                "type": "string",
-                "connect.name": "io.debezium.data.Json",
-            }),
-            ScalarType::List(_t) => unimplemented!("list types"),
-            ScalarType::Record { .. } => unimplemented!("record types"),
-        };
+        let mut field_type = build_type_schema_json(&typ.scalar_type, &mut record_count);
         if typ.nullable {
             field_type = json!(["null", field_type]);
         }
@@ -879,6 +962,83 @@ fn build_schema(columns: &[(ColumnName, ColumnType)]) -> Schema {
             "type": field_type,
         }));
     }
+    json!({
+        "name": "row",
+        "type": "record",
+        "fields": fields,
+    })
+}
+
+/// Builds the JSON for the Avro schema of the non-null values of `typ`.
+///
+/// Avro requires that every record schema have a unique name, so nested
+/// records are named `record1`, `record2`, and so on, using `record_count` to
+/// number them.
+fn build_type_schema_json(typ: &ScalarType, record_count: &mut usize) -> serde_json::Value {
+    match typ {
+        ScalarType::Bool => json!("boolean"),
+        ScalarType::Int32 => json!("int"),
+        ScalarType::Int64 => json!("long"),
+        ScalarType::Float32 => json!("float"),
+        ScalarType::Float64 => json!("double"),
+        ScalarType::Decimal(p, s) => json!({
+            "type": "bytes",
+            "logicalType": "decimal",
+            "precision": p,
+            "scale": s,
+        }),
+        ScalarType::Date => json!({
+            "type": "int",
+            "logicalType": "date",
+        }),
+        ScalarType::Time => json!({
+            "type": "long",
+            "logicalType": "time-micros",
+        }),
+        ScalarType::Timestamp | ScalarType::TimestampTz => json!({
+            "type": "long",
+            "connect.name": "io.debezium.time.MicroTimestamp",
+            "logicalType": "timestamp-micros"
+        }),
+        ScalarType::Interval => json!({
+            "type": "fixed",
+            "size": 12,
+            "logicalType": "duration"
+        }),
+        ScalarType::Bytes => json!("bytes"),
+        ScalarType::String => json!("string"),
+        ScalarType::Jsonb => json!({
+            "type": "string",
+            "connect.name": "io.debezium.data.Json",
+        }),
+        ScalarType::List(elem_type) => json!({
+            "type": "array",
+            "items": ["null", build_type_schema_json(elem_type, record_count)],
+        }),
+        ScalarType::Record { fields } => {
+            *record_count += 1;
+            let name = format!("record{}", record_count);
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(name, typ)| {
+                    json!({
+                        "name": name,
+                        "type": ["null", build_type_schema_json(typ, record_count)],
+                    })
+                })
+                .collect();
+            json!({
+                "name": name,
+                "type": "record",
+                "fields": fields,
+            })
+        }
+    }
+}
+
+/// Builds an Avro schema for a record with `before` and `after` fields, each
+/// of which is an optional record whose fields correspond to `columns`.
+fn build_schema(columns: &[(ColumnName, ColumnType)]) -> Schema {
     let schema = json!({
         "type": "record",
         "name": "envelope",
@@ -887,11 +1047,7 @@ fn build_schema(columns: &[(ColumnName, ColumnType)]) -> Schema {
                 "name": "before",
                 "type": [
                     "null",
-                    {
-                        "name": "row",
-                        "type": "record",
-                        "fields": fields,
-                    }
+                    build_row_schema_json(columns),
                 ]
             },
             {
@@ -906,6 +1062,7 @@ fn build_schema(columns: &[(ColumnName, ColumnType)]) -> Schema {
 /// Manages encoding of Avro-encoded bytes.
 pub struct Encoder {
     columns: Vec<(ColumnName, ColumnType)>,
+    row_schema: Schema,
     writer_schema: Schema,
 }
 
@@ -919,43 +1076,49 @@ impl fmt::Debug for Encoder {
 
 impl Encoder {
     pub fn new(desc: RelationDesc) -> Self {
-        // Invent names for columns that don't have a name.
-        let mut columns: Vec<_> = desc
-            .into_iter()
-            .enumerate()
-            .map(|(i, (name, ty))| match name {
-                None => (ColumnName::from(format!("column{}", i + 1)), ty),
-                Some(name) => (name, ty),
-            })
-            .collect();
-
-        // Deduplicate names.
-        let mut seen = HashSet::new();
-        for (name, _ty) in &mut columns {
-            let stem_len = name.as_str().len();
-            let mut i = 1;
-            while seen.contains(name) {
-                name.as_mut_str().truncate(stem_len);
-                if name.as_str().ends_with(|c: char| c.is_ascii_digit()) {
-                    name.as_mut_str().push('_');
-                }
-                name.as_mut_str().push_str(&i.to_string());
-                i += 1;
-            }
-            seen.insert(name);
-        }
-
+        let columns = column_names(desc);
+        let row_schema =
+            Schema::parse(&build_row_schema_json(&columns)).expect("valid schema constructed");
         let writer_schema = build_schema(&columns);
         Encoder {
             columns,
+            row_schema,
             writer_schema,
         }
     }
 
+    /// Constructs an encoder for the key formed by the columns of `desc` at
+    /// `indices`.
+    pub fn new_key(desc: &RelationDesc, indices: &[usize]) -> Self {
+        Encoder::new(key_desc(desc, indices))
+    }
+
+    /// Returns the schema of the records produced by
+    /// [`Encoder::diff_pair_to_avro`].
     pub fn writer_schema(&self) -> &Schema {
         &self.writer_schema
     }
 
+    /// Returns the schema of the records produced by
+    /// [`Encoder::encode_row_unchecked`], which represent a single row
+    /// without an envelope.
+    pub fn row_schema(&self) -> &Schema {
+        &self.row_schema
+    }
+
+    /// Encodes a single row as a plain Avro record, prefixed with the
+    /// Confluent wire format header.
+    pub fn encode_row_unchecked(&self, schema_id: i32, row: &Row) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.write_u8(0).expect("writing to vec cannot fail");
+        buf.write_i32::<NetworkEndian>(schema_id)
+            .expect("writing to vec cannot fail");
+        let avro = self.row_to_avro(row.unpack());
+        debug_assert!(avro.validate(self.row_schema.top_node()));
+        avro::encode_unchecked(&avro, &self.row_schema, &mut buf);
+        buf
+    }
+
     pub fn encode_unchecked(&self, schema_id: i32, diff_pair: DiffPair<&Row>) -> Vec<u8> {
         let mut buf = Vec::new();
         buf.write_u8(0).expect("writing to vec cannot fail");
@@ -1016,45 +1179,7 @@ impl Encoder {
                 if typ.nullable && datum.is_null() {
                     return (name, Value::Union(0, Box::new(Value::Null)));
                 }
-                let mut val = match &typ.scalar_type {
-                    ScalarType::Bool => Value::Boolean(datum.unwrap_bool()),
-                    ScalarType::Int32 => Value::Int(datum.unwrap_int32()),
-                    ScalarType::Int64 => Value::Long(datum.unwrap_int64()),
-                    ScalarType::Float32 => Value::Float(datum.unwrap_float32()),
-                    ScalarType::Float64 => Value::Double(datum.unwrap_float64()),
-                    ScalarType::Decimal(p, s) => Value::Decimal(DecimalValue {
-                        unscaled: datum.unwrap_decimal().as_i128().to_be_bytes().to_vec(),
-                        precision: (*p).into(),
-                        scale: (*s).into(),
-                    }),
-                    ScalarType::Date => Value::Date(datum.unwrap_date()),
-                    ScalarType::Time => Value::Long({
-                        let time = datum.unwrap_time();
-                        (time.num_seconds_from_midnight() * 1_000_000) as i64
-                            + (time.nanosecond() as i64) / 1_000
-                    }),
-                    ScalarType::Timestamp => Value::Timestamp(datum.unwrap_timestamp()),
-                    ScalarType::TimestampTz => {
-                        Value::Timestamp(datum.unwrap_timestamptz().naive_utc())
-                    }
-                    // This feature isn't actually supported by the Avro Java
-                    // client (https://issues.apache.org/jira/browse/AVRO-2123),
-                    // so no one is likely to be using it, so we're just using
-                    // our own very convenient format.
-                    ScalarType::Interval => Value::Fixed(20, {
-                        let iv = datum.unwrap_interval();
-                        let mut buf = Vec::with_capacity(24);
-                        buf.extend(&iv.months.to_le_bytes());
-                        buf.extend(&iv.duration.to_le_bytes());
-                        debug_assert_eq!(buf.len(), 20);
-                        buf
-                    }),
-                    ScalarType::Bytes => Value::Bytes(Vec::from(datum.unwrap_bytes())),
-                    ScalarType::String => Value::String(datum.unwrap_str().to_owned()),
-                    ScalarType::Jsonb => Value::Json(JsonbRef::from_datum(datum).to_serde_json()),
-                    ScalarType::List(_t) => unimplemented!("list types"),
-                    ScalarType::Record { .. } => unimplemented!("record types"),
-                };
+                let mut val = datum_to_avro(datum, &typ.scalar_type);
                 if typ.nullable {
                     val = Value::Union(1, Box::new(val));
                 }
@@ -1065,6 +1190,121 @@ impl Encoder {
     }
 }
 
+/// Converts a non-null `datum` of type `typ` to an Avro value of the schema
+/// that [`build_type_schema_json`] builds for `typ`.
+fn datum_to_avro(datum: Datum, typ: &ScalarType) -> Value {
+    match typ {
+        ScalarType::Bool => Value::Boolean(datum.unwrap_bool()),
+        ScalarType::Int32 => Value::Int(datum.unwrap_int32()),
+        ScalarType::Int64 => Value::Long(datum.unwrap_int64()),
+        ScalarType::Float32 => Value::Float(datum.unwrap_float32()),
+        ScalarType::Float64 => Value::Double(datum.unwrap_float64()),
+        ScalarType::Decimal(p, s) => Value::Decimal(DecimalValue {
+            unscaled: datum.unwrap_decimal().as_i128().to_be_bytes().to_vec(),
+            precision: (*p).into(),
+            scale: (*s).into(),
+        }),
+        ScalarType::Date => Value::Date(datum.unwrap_date()),
+        ScalarType::Time => Value::Long({
+            let time = datum.unwrap_time();
+            (time.num_seconds_from_midnight() * 1_000_000) as i64
+                + (time.nanosecond() as i64) / 1_000
+        }),
+        ScalarType::Timestamp => Value::Timestamp(datum.unwrap_timestamp()),
+        ScalarType::TimestampTz => Value::Timestamp(datum.unwrap_timestamptz().naive_utc()),
+        // This feature isn't actually supported by the Avro Java
+        // client (https://issues.apache.org/jira/browse/AVRO-2123),
+        // so no one is likely to be using it, so we're just using
+        // our own very convenient format.
+        ScalarType::Interval => Value::Fixed(20, {
+            let iv = datum.unwrap_interval();
+            let mut buf = Vec::with_capacity(24);
+            buf.extend(&iv.months.to_le_bytes());
+            buf.extend(&iv.duration.to_le_bytes());
+            debug_assert_eq!(buf.len(), 20);
+            buf
+        }),
+        ScalarType::Bytes => Value::Bytes(Vec::from(datum.unwrap_bytes())),
+        ScalarType::String => Value::String(datum.unwrap_str().to_owned()),
+        ScalarType::Jsonb => Value::Json(JsonbRef::from_datum(datum).to_serde_json()),
+        ScalarType::List(elem_type) => Value::Array(
+            datum
+                .unwrap_list()
+                .iter()
+                .map(|elem| nullable_datum_to_avro(elem, elem_type))
+                .collect(),
+        ),
+        // Records are represented as lists of their fields' values.
+        ScalarType::Record { fields } => Value::Record(
+            fields
+                .iter()
+                .zip_eq(datum.unwrap_list().iter())
+                .map(|((name, typ), datum)| {
+                    (name.as_str().to_owned(), nullable_datum_to_avro(datum, typ))
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Converts a possibly null `datum` of type `typ` to an Avro union of null and
+/// the schema that [`build_type_schema_json`] builds for `typ`.
+fn nullable_datum_to_avro(datum: Datum, typ: &ScalarType) -> Value {
+    if datum.is_null() {
+        Value::Union(0, Box::new(Value::Null))
+    } else {
+        Value::Union(1, Box::new(datum_to_avro(datum, typ)))
+    }
+}
+
+/// Returns the schema of the records that a Kafka sink writes to its
+/// consistency topic.
+///
+/// Each record states that the sink has written all `count` of its messages
+/// at `timestamp`.
+pub fn consistency_schema() -> Schema {
+    let schema = json!({
+        "type": "record",
+        "name": "consistency",
+        "fields": [
+            {"name": "timestamp", "type": "long"},
+            {"name": "count", "type": "long"},
+        ]
+    });
+    Schema::parse(&schema).expect("valid schema constructed")
+}
+
+/// Encodes a record of [`consistency_schema`], prefixed with the Confluent
+/// wire format header.
+pub fn encode_consistency_record(schema_id: i32, timestamp: u64, count: i64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.write_u8(0).expect("writing to vec cannot fail");
+    buf.write_i32::<NetworkEndian>(schema_id)
+        .expect("writing to vec cannot fail");
+    let avro = Value::Record(vec![
+        ("timestamp".into(), Value::Long(timestamp as i64)),
+        ("count".into(), Value::Long(count)),
+    ]);
+    avro::encode_unchecked(&avro, &consistency_schema(), &mut buf);
+    buf
+}
+
+/// Decodes a record produced by [`encode_consistency_record`], returning its
+/// timestamp.
+pub fn decode_consistency_record(bytes: &[u8]) -> Result<u64> {
+    if bytes.len() < 5 || bytes[0] != 0 {
+        bail!("consistency record is not in the Confluent wire format");
+    }
+    let mut bytes = &bytes[5..];
+    match avro::from_avro_datum(&consistency_schema(), &mut bytes)? {
+        Value::Record(fields) => match fields.into_iter().next() {
+            Some((name, Value::Long(timestamp))) if name == "timestamp" => Ok(timestamp as u64),
+            _ => bail!("consistency record is missing its timestamp"),
+        },
+        _ => bail!("consistency record is not a record"),
+    }
+}
+
 struct SchemaCache {
     cache: HashMap<i32, Option<Schema>>,
     ccsr_client: ccsr::Client,
@@ -1222,4 +1462,221 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_encode_key() -> Result<()> {
+        let desc = RelationDesc::empty()
+            .with_nonnull_column("a", ScalarType::Int64)
+            .with_nonnull_column("b", ScalarType::String);
+        let encoder = Encoder::new_key(&desc, &[1]);
+        assert_eq!(
+            encoder.row_schema().canonical_form(),
+            r#"{"name":"row","type":"record","fields":[{"name":"b","type":"string"}]}"#
+        );
+
+        let row = Row::pack(&[Datum::String("hi")]);
+        assert_eq!(
+            encoder.encode_row_unchecked(7, &row),
+            vec![0, 0, 0, 0, 7, 4, b'h', b'i']
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_nested() -> Result<()> {
+        let record = ScalarType::Record {
+            fields: vec![
+                ("x".into(), ScalarType::Int64),
+                ("y".into(), ScalarType::List(Box::new(ScalarType::String))),
+            ],
+        };
+        let desc = RelationDesc::empty()
+            .with_nonnull_column("l", ScalarType::List(Box::new(ScalarType::Int64)))
+            .with_column("r", ColumnType::new(record.clone()).nullable(true))
+            .with_nonnull_column("rs", ScalarType::List(Box::new(record)));
+        let encoder = Encoder::new(desc);
+
+        let mut packer = RowPacker::new();
+        packer.push_list(&[Datum::Int64(1), Datum::Null]);
+        packer.push_list_with(|packer| {
+            packer.push(Datum::Int64(2));
+            packer.push_list(&[Datum::String("a")]);
+        });
+        packer.push_list_with(|packer| {
+            packer.push_list(&[Datum::Null, Datum::Null]);
+        });
+        let row = packer.finish();
+
+        let avro = encoder.row_to_avro(row.unpack());
+        assert!(avro.validate(encoder.row_schema().top_node()));
+        let null = || Value::Union(0, Box::new(Value::Null));
+        let some = |v| Value::Union(1, Box::new(v));
+        assert_eq!(
+            avro,
+            Value::Record(vec![
+                ("l".into(), Value::Array(vec![some(Value::Long(1)), null()])),
+                (
+                    "r".into(),
+                    some(Value::Record(vec![
+                        ("x".into(), some(Value::Long(2))),
+                        (
+                            "y".into(),
+                            some(Value::Array(vec![some(Value::String("a".into()))]))
+                        ),
+                    ]))
+                ),
+                (
+                    "rs".into(),
+                    Value::Array(vec![some(Value::Record(vec![
+                        ("x".into(), null()),
+                        ("y".into(), null()),
+                    ]))])
+                ),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_nested_schema() -> Result<()> {
+        let schema = r#"{
+            "type": "record",
+            "name": "order",
+            "fields": [
+                {"name": "items", "type": {"type": "array", "items": "long"}},
+                {"name": "tags", "type": {"type": "map", "values": "string"}},
+                {
+                    "name": "address",
+                    "type": {
+                        "type": "record",
+                        "name": "address",
+                        "fields": [
+                            {"name": "city", "type": "string"},
+                            {"name": "zip", "type": ["null", "int"]}
+                        ]
+                    }
+                }
+            ]
+        }"#;
+        assert_eq!(
+            validate_value_schema(schema, EnvelopeType::None)?,
+            vec![
+                (
+                    "items".into(),
+                    ColumnType::new(ScalarType::List(Box::new(ScalarType::Int64)))
+                ),
+                ("tags".into(), ColumnType::new(ScalarType::Jsonb)),
+                (
+                    "address".into(),
+                    ColumnType::new(ScalarType::Record {
+                        fields: vec![
+                            ("city".into(), ScalarType::String),
+                            ("zip".into(), ScalarType::Int32),
+                        ],
+                    })
+                ),
+            ]
+        );
+
+        let schema = r#"{
+            "type": "record",
+            "name": "node",
+            "namespace": "com.example",
+            "fields": [
+                {"name": "children", "type": {"type": "array", "items": "node"}}
+            ]
+        }"#;
+        let err = validate_value_schema(schema, EnvelopeType::None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Recursive types are not supported: com.example.node"
+        );
+
+        let schema = r#"{
+            "type": "record",
+            "name": "row",
+            "fields": [
+                {"name": "a", "type": {"type": "array", "items": ["int", "string"]}}
+            ]
+        }"#;
+        assert!(validate_value_schema(schema, EnvelopeType::None).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_nested_values() -> Result<()> {
+        let schema = parse_schema(
+            r#"{
+                "type": "record",
+                "name": "row",
+                "fields": [
+                    {"name": "a", "type": {"type": "array", "items": ["null", "long"]}},
+                    {"name": "m", "type": {"type": "map", "values": "int"}},
+                    {
+                        "name": "ma",
+                        "type": {"type": "map", "values": {"type": "array", "items": "long"}}
+                    },
+                    {
+                        "name": "r",
+                        "type": {
+                            "type": "record",
+                            "name": "inner",
+                            "fields": [{"name": "b", "type": "string"}]
+                        }
+                    }
+                ]
+            }"#,
+        )?;
+        let value = Value::Record(vec![
+            (
+                "a".into(),
+                Value::Array(vec![
+                    Value::Union(1, Box::new(Value::Long(1))),
+                    Value::Union(0, Box::new(Value::Null)),
+                ]),
+            ),
+            (
+                "m".into(),
+                Value::Map(iter::once(("k".into(), Value::Int(2))).collect()),
+            ),
+            (
+                "ma".into(),
+                Value::Map(
+                    iter::once((
+                        "k".into(),
+                        Value::Array(vec![Value::Long(3), Value::Long(4)]),
+                    ))
+                    .collect(),
+                ),
+            ),
+            (
+                "r".into(),
+                Value::Record(vec![("b".into(), Value::String("hi".into()))]),
+            ),
+        ]);
+
+        let mut expected = RowPacker::new();
+        expected.push_list(&[Datum::Int64(1), Datum::Null]);
+        expected = JsonbPacker::new(expected).pack_serde_json(json!({"k": 2}))?;
+        expected = JsonbPacker::new(expected).pack_serde_json(json!({"k": [3, 4]}))?;
+        expected.push_list(&[Datum::String("hi")]);
+        assert_eq!(
+            extract_row(value, iter::empty(), schema.top_node())?,
+            Some(expected.finish())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_consistency_record_roundtrip() -> Result<()> {
+        let buf = encode_consistency_record(3, 1_590_000_000_123, 42);
+        assert_eq!(&buf[..5], &[0, 0, 0, 0, 3]);
+        assert_eq!(decode_consistency_record(&buf)?, 1_590_000_000_123);
+        assert!(decode_consistency_record(&[1, 2]).is_err());
+        Ok(())
+    }
 }
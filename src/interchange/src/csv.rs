@@ -0,0 +1,155 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! CSV encoding for sinks.
+
+use failure::bail;
+
+use repr::{ColumnType, Datum, RelationDesc, Row};
+
+use crate::encode::{format_datum, key_desc};
+use crate::error::Result;
+
+/// Manages encoding of CSV-encoded bytes.
+///
+/// Each row is encoded as a single CSV record, without a trailing newline.
+/// Values are written in their PostgreSQL text format, and null values are
+/// written as empty, unquoted fields.
+#[derive(Debug)]
+pub struct Encoder {
+    types: Vec<ColumnType>,
+    delimiter: u8,
+}
+
+impl Encoder {
+    pub fn new(desc: RelationDesc, delimiter: u8) -> Self {
+        Encoder {
+            types: desc.typ().column_types.clone(),
+            delimiter,
+        }
+    }
+
+    /// Constructs an encoder for the key formed by the columns of `desc` at
+    /// `indices`.
+    pub fn new_key(desc: &RelationDesc, indices: &[usize], delimiter: u8) -> Self {
+        Encoder::new(key_desc(desc, indices), delimiter)
+    }
+
+    /// Encodes a single row as a CSV record.
+    pub fn encode_row(&self, row: &Row) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.write_row(&mut buf, row);
+        buf
+    }
+
+    /// Encodes a change to a row as a CSV record whose first two fields are
+    /// the timestamp of the change and either `1`, for an insertion, or `-1`,
+    /// for a deletion, followed by the row's fields.
+    pub fn encode_update(&self, row: &Row, timestamp: u64, insert: bool) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend(timestamp.to_string().as_bytes());
+        buf.push(self.delimiter);
+        buf.extend(if insert { &b"1"[..] } else { &b"-1"[..] });
+        buf.push(self.delimiter);
+        self.write_row(&mut buf, row);
+        buf
+    }
+
+    fn write_row(&self, buf: &mut Vec<u8>, row: &Row) {
+        let mut value = String::new();
+        for (i, (typ, datum)) in self.types.iter().zip(row.iter()).enumerate() {
+            if i > 0 {
+                buf.push(self.delimiter);
+            }
+            if datum.is_null() {
+                continue;
+            }
+            value.clear();
+            format_datum(&mut value, datum, &typ.scalar_type);
+            self.write_value(buf, value.as_bytes());
+        }
+    }
+
+    /// Writes a non-null value to `buf`, quoting it if it contains any special
+    /// bytes or could be mistaken for a null value.
+    fn write_value(&self, buf: &mut Vec<u8>, value: &[u8]) {
+        let needs_quotes = value.is_empty()
+            || value
+                .iter()
+                .any(|b| *b == self.delimiter || *b == b'"' || *b == b'\n' || *b == b'\r');
+        if !needs_quotes {
+            buf.extend(value);
+            return;
+        }
+        buf.push(b'"');
+        for b in value {
+            if *b == b'"' {
+                buf.push(b'"');
+            }
+            buf.push(*b);
+        }
+        buf.push(b'"');
+    }
+}
+
+/// Encodes a record for a sink's consistency topic, which states that the sink
+/// has written all `count` of its messages at `timestamp`.
+pub fn encode_consistency_record(timestamp: u64, count: i64, delimiter: u8) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend(timestamp.to_string().as_bytes());
+    buf.push(delimiter);
+    buf.extend(count.to_string().as_bytes());
+    buf
+}
+
+/// Decodes a record produced by [`encode_consistency_record`], returning its
+/// timestamp.
+pub fn decode_consistency_record(bytes: &[u8], delimiter: u8) -> Result<u64> {
+    let timestamp = bytes.split(|b| *b == delimiter).next().unwrap_or_default();
+    match std::str::from_utf8(timestamp)
+        .ok()
+        .and_then(|ts| ts.parse().ok())
+    {
+        Some(timestamp) => Ok(timestamp),
+        None => bail!("consistency record is missing its timestamp"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use repr::ScalarType;
+
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let desc = RelationDesc::empty()
+            .with_nonnull_column("a", ScalarType::Int64)
+            .with_column("b", ColumnType::new(ScalarType::String).nullable(true))
+            .with_column("c", ColumnType::new(ScalarType::String).nullable(true));
+        let encoder = Encoder::new(desc, b',');
+        let row = Row::pack(&[Datum::Int64(1), Datum::String("x,\"y\""), Datum::Null]);
+        assert_eq!(encoder.encode_row(&row), b"1,\"x,\"\"y\"\"\",");
+        assert_eq!(
+            encoder.encode_update(&row, 7, false),
+            b"7,-1,1,\"x,\"\"y\"\"\","
+        );
+
+        let row = Row::pack(&[Datum::Int64(2), Datum::String(""), Datum::Null]);
+        assert_eq!(encoder.encode_row(&row), b"2,\"\",");
+    }
+
+    #[test]
+    fn test_consistency_record_roundtrip() -> Result<()> {
+        let buf = encode_consistency_record(42, 3, b'|');
+        assert_eq!(buf, b"42|3");
+        assert_eq!(decode_consistency_record(&buf, b'|')?, 42);
+        Ok(())
+    }
+}
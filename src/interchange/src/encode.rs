@@ -0,0 +1,68 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Utilities shared by the sink encoders for the various formats.
+
+use std::collections::HashSet;
+
+use repr::{ColumnName, ColumnType, Datum, RelationDesc, ScalarType};
+
+/// Returns the columns of `desc`, with invented names for columns that don't
+/// have a name, and with duplicate names made unique.
+pub(crate) fn column_names(desc: RelationDesc) -> Vec<(ColumnName, ColumnType)> {
+    // Invent names for columns that don't have a name.
+    let mut columns: Vec<_> = desc
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, ty))| match name {
+            None => (ColumnName::from(format!("column{}", i + 1)), ty),
+            Some(name) => (name, ty),
+        })
+        .collect();
+
+    // Deduplicate names.
+    let mut seen = HashSet::new();
+    for (name, _ty) in &mut columns {
+        let stem_len = name.as_str().len();
+        let mut i = 1;
+        while seen.contains(name) {
+            name.as_mut_str().truncate(stem_len);
+            if name.as_str().ends_with(|c: char| c.is_ascii_digit()) {
+                name.as_mut_str().push('_');
+            }
+            name.as_mut_str().push_str(&i.to_string());
+            i += 1;
+        }
+        seen.insert(name);
+    }
+
+    columns
+}
+
+/// Returns the description of the key formed by the columns of `desc` at
+/// `indices`.
+pub(crate) fn key_desc(desc: &RelationDesc, indices: &[usize]) -> RelationDesc {
+    let columns: Vec<_> = desc.iter().collect();
+    indices.iter().fold(RelationDesc::empty(), |key_desc, i| {
+        let (name, typ) = columns[*i];
+        let name = match name {
+            None => ColumnName::from(format!("column{}", i + 1)),
+            Some(name) => name.clone(),
+        };
+        key_desc.with_column(name, typ.clone())
+    })
+}
+
+/// Appends the PostgreSQL text representation of `datum`, a non-null datum of
+/// type `typ`, to `buf`.
+pub(crate) fn format_datum(buf: &mut String, datum: Datum, typ: &ScalarType) {
+    pgrepr::Value::from_datum(datum, typ)
+        .expect("datum is not null")
+        .encode_text(buf);
+}
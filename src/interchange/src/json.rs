@@ -0,0 +1,219 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! JSON encoding for sinks.
+
+use failure::bail;
+use serde_json::{json, Number, Value};
+
+use repr::adt::jsonb::JsonbRef;
+use repr::{ColumnName, ColumnType, Datum, RelationDesc, Row, ScalarType};
+
+use crate::avro::DiffPair;
+use crate::encode::{column_names, format_datum, key_desc};
+use crate::error::Result;
+
+/// Manages encoding of JSON-encoded bytes.
+///
+/// Each row is encoded as a JSON object with one field per column. Booleans,
+/// numbers, and `jsonb` values are represented natively, lists as arrays, and
+/// records as objects; all other values are represented as strings in their
+/// PostgreSQL text format.
+#[derive(Debug)]
+pub struct Encoder {
+    columns: Vec<(ColumnName, ColumnType)>,
+}
+
+impl Encoder {
+    pub fn new(desc: RelationDesc) -> Self {
+        Encoder {
+            columns: column_names(desc),
+        }
+    }
+
+    /// Constructs an encoder for the key formed by the columns of `desc` at
+    /// `indices`.
+    pub fn new_key(desc: &RelationDesc, indices: &[usize]) -> Self {
+        Encoder::new(key_desc(desc, indices))
+    }
+
+    /// Encodes a single row as a JSON object.
+    pub fn encode_row(&self, row: &Row) -> Vec<u8> {
+        let mut buf = String::new();
+        self.write_row(&mut buf, row);
+        buf.into_bytes()
+    }
+
+    /// Encodes a change to a row as a JSON object with `before`, `after`, and
+    /// `timestamp` fields.
+    pub fn encode_diff_pair(&self, diff_pair: DiffPair<&Row>, timestamp: u64) -> Vec<u8> {
+        let mut buf = String::new();
+        buf.push_str("{\"before\":");
+        match diff_pair.before {
+            None => buf.push_str("null"),
+            Some(row) => self.write_row(&mut buf, row),
+        }
+        buf.push_str(",\"after\":");
+        match diff_pair.after {
+            None => buf.push_str("null"),
+            Some(row) => self.write_row(&mut buf, row),
+        }
+        buf.push_str(",\"timestamp\":");
+        buf.push_str(&timestamp.to_string());
+        buf.push('}');
+        buf.into_bytes()
+    }
+
+    /// Writes `row` to `buf` as a JSON object.
+    ///
+    /// The object is written by hand, rather than via `serde_json::Map`, so
+    /// that its fields appear in the same order as the columns.
+    fn write_row(&self, buf: &mut String, row: &Row) {
+        buf.push('{');
+        for (i, ((name, typ), datum)) in self.columns.iter().zip(row.iter()).enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            buf.push_str(&Value::from(name.as_str()).to_string());
+            buf.push(':');
+            buf.push_str(&datum_to_json(datum, &typ.scalar_type).to_string());
+        }
+        buf.push('}');
+    }
+}
+
+pub(crate) fn datum_to_json(datum: Datum, scalar_type: &ScalarType) -> Value {
+    if datum.is_null() {
+        return Value::Null;
+    }
+    match scalar_type {
+        ScalarType::Bool => Value::Bool(datum.unwrap_bool()),
+        ScalarType::Int32 => Value::from(datum.unwrap_int32()),
+        ScalarType::Int64 => Value::from(datum.unwrap_int64()),
+        // JSON numbers cannot represent NaN or infinity, so those values
+        // fall back to their string representation.
+        ScalarType::Float32 => match Number::from_f64(f64::from(datum.unwrap_float32())) {
+            Some(n) => Value::Number(n),
+            None => format_string(datum, scalar_type),
+        },
+        ScalarType::Float64 => match Number::from_f64(datum.unwrap_float64()) {
+            Some(n) => Value::Number(n),
+            None => format_string(datum, scalar_type),
+        },
+        ScalarType::Jsonb => JsonbRef::from_datum(datum).to_serde_json(),
+        ScalarType::List(elem_type) => Value::Array(
+            datum
+                .unwrap_list()
+                .iter()
+                .map(|elem| datum_to_json(elem, elem_type))
+                .collect(),
+        ),
+        // Records are represented as lists of their fields' values.
+        ScalarType::Record { fields } => Value::Object(
+            datum
+                .unwrap_list()
+                .iter()
+                .zip(fields)
+                .map(|(value, (name, typ))| (name.to_string(), datum_to_json(value, typ)))
+                .collect(),
+        ),
+        scalar_type => format_string(datum, scalar_type),
+    }
+}
+
+fn format_string(datum: Datum, scalar_type: &ScalarType) -> Value {
+    let mut buf = String::new();
+    format_datum(&mut buf, datum, scalar_type);
+    Value::String(buf)
+}
+
+/// Encodes a record for a sink's consistency topic, which states that the sink
+/// has written all `count` of its messages at `timestamp`.
+pub fn encode_consistency_record(timestamp: u64, count: i64) -> Vec<u8> {
+    json!({
+        "timestamp": timestamp,
+        "count": count,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Decodes a record produced by [`encode_consistency_record`], returning its
+/// timestamp.
+pub fn decode_consistency_record(bytes: &[u8]) -> Result<u64> {
+    let record: Value = serde_json::from_slice(bytes)?;
+    match record.get("timestamp").and_then(|ts| ts.as_u64()) {
+        Some(timestamp) => Ok(timestamp),
+        None => bail!("consistency record is missing its timestamp"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use repr::adt::decimal::Significand;
+    use repr::RowPacker;
+
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let desc = RelationDesc::empty()
+            .with_nonnull_column("d", ScalarType::Int64)
+            .with_column("b", ColumnType::new(ScalarType::String).nullable(true))
+            .with_nonnull_column("c", ScalarType::Decimal(10, 2))
+            .with_nonnull_column("a", ScalarType::Float64);
+        let encoder = Encoder::new(desc);
+        let row = Row::pack(&[
+            Datum::Int64(1),
+            Datum::Null,
+            Datum::Decimal(Significand::new(1234)),
+            Datum::Float64(f64::NAN.into()),
+        ]);
+        assert_eq!(
+            String::from_utf8(encoder.encode_row(&row)).unwrap(),
+            r#"{"d":1,"b":null,"c":"12.34","a":"NaN"}"#
+        );
+
+        let diff_pair = DiffPair {
+            before: None,
+            after: Some(&row),
+        };
+        assert_eq!(
+            String::from_utf8(encoder.encode_diff_pair(diff_pair, 7)).unwrap(),
+            r#"{"before":null,"after":{"d":1,"b":null,"c":"12.34","a":"NaN"},"timestamp":7}"#
+        );
+    }
+
+    #[test]
+    fn test_encode_nested() {
+        let desc = RelationDesc::empty()
+            .with_nonnull_column("l", ScalarType::List(Box::new(ScalarType::Int64)))
+            .with_nonnull_column(
+                "r",
+                ScalarType::Record {
+                    fields: vec![("x".into(), ScalarType::Int32)],
+                },
+            );
+        let encoder = Encoder::new(desc);
+        let mut packer = RowPacker::new();
+        packer.push_list(&[Datum::Int64(1), Datum::Null]);
+        packer.push_list(&[Datum::Int32(2)]);
+        assert_eq!(
+            String::from_utf8(encoder.encode_row(&packer.finish())).unwrap(),
+            r#"{"l":[1,null],"r":{"x":2}}"#
+        );
+    }
+
+    #[test]
+    fn test_consistency_record_roundtrip() -> Result<()> {
+        let buf = encode_consistency_record(42, 3);
+        assert_eq!(decode_consistency_record(&buf)?, 42);
+        Ok(())
+    }
+}
@@ -12,5 +12,8 @@
 #![deny(missing_debug_implementations)]
 
 pub mod avro;
+pub mod csv;
+mod encode;
 mod error;
+pub mod json;
 pub mod protobuf;
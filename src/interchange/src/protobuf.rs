@@ -7,11 +7,18 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-//! Protobuf source connector
+//! Protobuf source connector and sink encoding
 
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path};
+
+use byteorder::{NetworkEndian, WriteBytesExt};
 use failure::{bail, format_err, ResultExt};
 use num_traits::ToPrimitive;
 use ordered_float::OrderedFloat;
+use protobuf::descriptor::FileDescriptorSet;
+use protobuf::{CodedOutputStream, Message, RepeatedField};
 use serde::de::Deserialize;
 use serde_protobuf::de::Deserializer;
 use serde_protobuf::descriptor::{
@@ -23,6 +30,8 @@ use serde_value::Value as SerdeValue;
 use repr::adt::decimal::Significand;
 use repr::{ColumnType, Datum, DatumList, RelationDesc, RelationType, Row, RowPacker, ScalarType};
 
+use crate::avro::DiffPair;
+use crate::encode::{column_names, format_datum, key_desc};
 use crate::error::Result;
 
 pub mod test_util;
@@ -115,6 +124,49 @@ pub fn decode_descriptors(descriptors: &[u8]) -> Result<Descriptors> {
     Ok(Descriptors::from_proto(&proto))
 }
 
+/// Compiles `schema`, the text of a `.proto` file, into an encoded
+/// `FileDescriptorSet` of the kind that [`decode_descriptors`] accepts.
+///
+/// `imports` holds the path and text of each file that `schema` imports,
+/// directly or indirectly.
+///
+/// The schema is parsed and type checked in process, so, unlike `protoc`'s
+/// output, the descriptors do not include any source code info.
+pub fn compile_proto_schema(schema: &str, imports: &[(String, String)]) -> Result<Vec<u8>> {
+    let dir = tempfile::tempdir().context("creating directory to compile protobuf schema")?;
+    // Keep the imports apart from the schema itself, so that no import can
+    // shadow it.
+    let import_dir = dir.path().join("imports");
+    let main_dir = dir.path().join("main");
+    fs::create_dir(&import_dir).context("creating directory for protobuf imports")?;
+    fs::create_dir(&main_dir).context("creating directory for protobuf schema")?;
+    for (name, text) in imports {
+        let path = Path::new(name);
+        if path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+            bail!("imported protobuf file {} is not a relative path", name);
+        }
+        let path = import_dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("creating directory for protobuf import")?;
+        }
+        fs::write(&path, text).context("writing imported protobuf schema")?;
+    }
+    let input = main_dir.join("schema.proto");
+    fs::write(&input, schema).context("writing protobuf schema")?;
+    let parsed = protobuf_codegen_pure::parse_and_typecheck(
+        &[input.as_path()],
+        &[main_dir.as_path(), import_dir.as_path()],
+    )
+    .map_err(|e| format_err!("compiling protobuf schema: {:?}", e))?;
+    // The parsed files include every file that the schema imports, as
+    // `protoc --include_imports` would.
+    let mut set = FileDescriptorSet::new();
+    set.set_file(RepeatedField::from_vec(parsed.file_descriptors));
+    Ok(set
+        .write_to_bytes()
+        .context("encoding compiled protobuf schema")?)
+}
+
 pub fn validate_descriptors(message_name: &str, descriptors: &Descriptors) -> Result<RelationDesc> {
     let proto_name = proto_message_name(message_name);
     let message = descriptors.message_by_name(&proto_name).ok_or_else(|| {
@@ -153,6 +205,7 @@ pub fn validate_descriptors(message_name: &str, descriptors: &Descriptors) -> Re
 pub struct Decoder {
     descriptors: Descriptors,
     message_name: String,
+    confluent_wire_format: bool,
     packer: RowPacker,
 }
 
@@ -160,17 +213,28 @@ impl Decoder {
     /// Build a decoder from a pre-validated message.
     ///
     /// The message `message_name` must exist in the descriptor set and be
-    /// valid.
-    pub fn new(descriptors: Descriptors, message_name: &str) -> Decoder {
+    /// valid. If `confluent_wire_format` is set, each message is expected to
+    /// carry the header that Confluent's Protobuf serializer writes.
+    pub fn new(
+        descriptors: Descriptors,
+        message_name: &str,
+        confluent_wire_format: bool,
+    ) -> Decoder {
         // TODO: verify that name exists
         Decoder {
             descriptors,
             message_name: proto_message_name(message_name),
+            confluent_wire_format,
             packer: RowPacker::new(),
         }
     }
 
     pub fn decode(&mut self, bytes: &[u8]) -> Result<Option<Row>> {
+        let bytes = if self.confluent_wire_format {
+            strip_confluent_header(bytes)?
+        } else {
+            bytes
+        };
         let input_stream = protobuf::CodedInputStream::from_bytes(bytes);
         let mut deserializer =
             Deserializer::for_named_message(&self.descriptors, &self.message_name, input_stream)
@@ -194,6 +258,60 @@ impl Decoder {
     }
 }
 
+/// Strips the header that Confluent's Protobuf serializer writes before each
+/// message, returning the encoded message itself.
+///
+/// The header consists of a magic byte (0), the 32-bit ID of the message's
+/// schema, and the indexes of the path to the message type within the schema.
+/// The path is written as a count followed by the indexes, as zigzag varints,
+/// except that a lone 0 stands for the first message in the schema. The
+/// decoder always decodes messages as its configured message type, so the
+/// indexes are skipped.
+///
+/// https://docs.confluent.io/current/schema-registry/serdes-develop/index.html#wire-format
+fn strip_confluent_header(bytes: &[u8]) -> Result<&[u8]> {
+    if bytes.len() < 5 {
+        bail!(
+            "Confluent-style protobuf datum is too few bytes: expected at least 5 bytes, got {}",
+            bytes.len()
+        );
+    }
+    let magic = bytes[0];
+    if magic != 0 {
+        bail!(
+            "wrong protobuf serialization magic: expected 0, got {}",
+            magic
+        );
+    }
+    let mut bytes = &bytes[5..];
+    let count = read_zigzag_varint(&mut bytes)?;
+    if count < 0 {
+        bail!("invalid protobuf message index count {}", count);
+    }
+    for _ in 0..count {
+        read_zigzag_varint(&mut bytes)?;
+    }
+    Ok(bytes)
+}
+
+/// Reads a zigzag-encoded varint from the front of `bytes`, advancing `bytes`
+/// past it.
+fn read_zigzag_varint(bytes: &mut &[u8]) -> Result<i64> {
+    let mut n: u64 = 0;
+    for i in 0..10 {
+        let (byte, rest) = match bytes.split_first() {
+            Some(split) => split,
+            None => bail!("protobuf message index ended unexpectedly"),
+        };
+        *bytes = rest;
+        n |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((n >> 1) as i64 ^ -((n & 1) as i64));
+        }
+    }
+    bail!("protobuf message index varint is too long")
+}
+
 fn extract_row_into(
     deserialized_message: SerdeValue,
     descriptors: &Descriptors,
@@ -449,6 +567,206 @@ fn json_nested_from_serde_value(
     Ok(())
 }
 
+/// Manages encoding of Protobuf-encoded bytes for sinks.
+///
+/// Rows are described by a proto2 message with one optional field per column,
+/// so that null values can be represented by omitting their field. Booleans,
+/// integers, floats, strings, and bytes are represented natively; all other
+/// values are represented as strings in their PostgreSQL text format.
+///
+/// Messages are written in the wire format of Confluent's Protobuf
+/// serializer, which prefixes each message with the ID of its schema in the
+/// schema registry.
+#[derive(Debug)]
+pub struct Encoder {
+    message_name: String,
+    columns: Vec<(String, ColumnType)>,
+}
+
+impl Encoder {
+    /// Constructs an encoder for rows described by `desc`, whose messages have
+    /// type `message_name`.
+    pub fn new(desc: RelationDesc, message_name: &str) -> Result<Self> {
+        if !is_proto_identifier(message_name) {
+            bail!(
+                "invalid protobuf message name {:?}: \
+                 must consist of letters, digits, and underscores, and not start with a digit",
+                message_name
+            );
+        }
+        Ok(Encoder {
+            message_name: message_name.into(),
+            columns: proto_columns(desc),
+        })
+    }
+
+    /// Constructs an encoder for the key formed by the columns of `desc` at
+    /// `indices`.
+    pub fn new_key(desc: &RelationDesc, indices: &[usize], message_name: &str) -> Result<Self> {
+        Encoder::new(key_desc(desc, indices), message_name)
+    }
+
+    /// Returns the text of a `.proto` file whose message describes a single
+    /// row.
+    pub fn row_schema(&self) -> String {
+        let mut buf = String::from("syntax = \"proto2\";\n\n");
+        self.write_row_message(&mut buf, &self.message_name, "");
+        buf
+    }
+
+    /// Returns the text of a `.proto` file whose message describes a change to
+    /// a row, with `before` and `after` fields in the style of Debezium.
+    pub fn writer_schema(&self) -> String {
+        let mut buf = String::from("syntax = \"proto2\";\n\n");
+        buf.push_str(&format!("message {} {{\n", self.message_name));
+        self.write_row_message(&mut buf, "Row", "  ");
+        buf.push_str("  optional Row before = 1;\n");
+        buf.push_str("  optional Row after = 2;\n");
+        buf.push_str("}\n");
+        buf
+    }
+
+    fn write_row_message(&self, buf: &mut String, name: &str, indent: &str) {
+        buf.push_str(&format!("{}message {} {{\n", indent, name));
+        for (i, (name, typ)) in self.columns.iter().enumerate() {
+            buf.push_str(&format!(
+                "{}  optional {} {} = {};\n",
+                indent,
+                proto_type_name(&typ.scalar_type),
+                name,
+                i + 1
+            ));
+        }
+        buf.push_str(&format!("{}}}\n", indent));
+    }
+
+    /// Encodes a single row as a message of the type described by
+    /// [`Encoder::row_schema`], whose ID in the schema registry is
+    /// `schema_id`.
+    pub fn encode_row_unchecked(&self, schema_id: i32, row: &Row) -> Vec<u8> {
+        let mut buf = confluent_header(schema_id);
+        buf.extend(self.encode_row_message(row));
+        buf
+    }
+
+    /// Encodes a change to a row as a message of the type described by
+    /// [`Encoder::writer_schema`], whose ID in the schema registry is
+    /// `schema_id`.
+    pub fn encode_unchecked(&self, schema_id: i32, diff_pair: DiffPair<&Row>) -> Vec<u8> {
+        let mut buf = confluent_header(schema_id);
+        {
+            let mut os = CodedOutputStream::vec(&mut buf);
+            if let Some(row) = diff_pair.before {
+                os.write_bytes(1, &self.encode_row_message(row))
+                    .expect("writing to a vec cannot fail");
+            }
+            if let Some(row) = diff_pair.after {
+                os.write_bytes(2, &self.encode_row_message(row))
+                    .expect("writing to a vec cannot fail");
+            }
+            os.flush().expect("writing to a vec cannot fail");
+        }
+        buf
+    }
+
+    /// Encodes the fields of `row`, without a header.
+    fn encode_row_message(&self, row: &Row) -> Vec<u8> {
+        let mut buf = Vec::new();
+        {
+            let mut os = CodedOutputStream::vec(&mut buf);
+            for (i, ((_name, typ), datum)) in self.columns.iter().zip(row.iter()).enumerate() {
+                write_datum(&mut os, (i + 1) as u32, datum, &typ.scalar_type)
+                    .expect("writing to a vec cannot fail");
+            }
+            os.flush().expect("writing to a vec cannot fail");
+        }
+        buf
+    }
+}
+
+/// Returns the header of a message in the wire format of Confluent's Protobuf
+/// serializer: a magic byte (0), the 32-bit ID of the message's schema, and a
+/// single 0, which indicates that the message has the type of the first
+/// message in the schema.
+fn confluent_header(schema_id: i32) -> Vec<u8> {
+    let mut buf = vec![0];
+    buf.write_i32::<NetworkEndian>(schema_id)
+        .expect("writing to a vec cannot fail");
+    buf.push(0);
+    buf
+}
+
+/// Returns the columns of `desc`, with their names made into unique, valid
+/// Protobuf field names.
+fn proto_columns(desc: RelationDesc) -> Vec<(String, ColumnType)> {
+    let mut seen = HashSet::new();
+    column_names(desc)
+        .into_iter()
+        .map(|(name, typ)| {
+            let mut stem: String = name
+                .as_str()
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            if !stem.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                stem.insert(0, '_');
+            }
+            let mut name = stem.clone();
+            let mut i = 1;
+            while seen.contains(&name) {
+                name = format!("{}_{}", stem, i);
+                i += 1;
+            }
+            seen.insert(name.clone());
+            (name, typ)
+        })
+        .collect()
+}
+
+fn is_proto_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn proto_type_name(typ: &ScalarType) -> &'static str {
+    match typ {
+        ScalarType::Bool => "bool",
+        ScalarType::Int32 => "int32",
+        ScalarType::Int64 => "int64",
+        ScalarType::Float32 => "float",
+        ScalarType::Float64 => "double",
+        ScalarType::Bytes => "bytes",
+        _ => "string",
+    }
+}
+
+/// Writes `datum`, of type `typ`, as field `field_number`. Null values are not
+/// written at all.
+fn write_datum(
+    os: &mut CodedOutputStream,
+    field_number: u32,
+    datum: Datum,
+    typ: &ScalarType,
+) -> protobuf::ProtobufResult<()> {
+    if datum.is_null() {
+        return Ok(());
+    }
+    match typ {
+        ScalarType::Bool => os.write_bool(field_number, datum.unwrap_bool()),
+        ScalarType::Int32 => os.write_int32(field_number, datum.unwrap_int32()),
+        ScalarType::Int64 => os.write_int64(field_number, datum.unwrap_int64()),
+        ScalarType::Float32 => os.write_float(field_number, datum.unwrap_float32()),
+        ScalarType::Float64 => os.write_double(field_number, datum.unwrap_float64()),
+        ScalarType::Bytes => os.write_bytes(field_number, datum.unwrap_bytes()),
+        ScalarType::String => os.write_string(field_number, datum.unwrap_str()),
+        _ => {
+            let mut buf = String::new();
+            format_datum(&mut buf, datum, typ);
+            os.write_string(field_number, &buf)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::test_util::gen::fuzz::{
@@ -462,9 +780,12 @@ mod tests {
         Descriptors, FieldDescriptor, FieldLabel, FieldType, InternalFieldType, MessageDescriptor,
     };
 
+    use chrono::NaiveDate;
     use ordered_float::OrderedFloat;
     use repr::adt::decimal::Significand;
-    use repr::{Datum, DatumList, RelationDesc, ScalarType};
+    use repr::{Datum, DatumList, RelationDesc, Row, ScalarType};
+
+    use crate::avro::DiffPair;
 
     fn sanity_check_relation(
         relation: &RelationDesc,
@@ -608,7 +929,7 @@ mod tests {
             &descriptors,
         )
         .expect("Sanity checking descriptors failed");
-        super::Decoder::new(descriptors, message_name)
+        super::Decoder::new(descriptors, message_name, false)
     }
 
     #[test]
@@ -861,4 +1182,141 @@ mod tests {
             assert_eq!(d, Datum::List(DatumList::empty()));
         }
     }
+
+    #[test]
+    fn test_decode_confluent_wire_format() {
+        let mut test_record = TestRecord::new();
+        test_record.set_int_field(1);
+        test_record.set_string_field("one".to_string());
+        let bytes = test_record
+            .write_to_bytes()
+            .expect("test failed to serialize to bytes");
+
+        let decoder = get_decoder(".TestRecord");
+        let mut decoder = super::Decoder::new(decoder.descriptors, ".TestRecord", true);
+
+        // Magic byte, schema ID, and the message index path, once with the
+        // shorthand for the first message and once with an explicit path of
+        // [1].
+        for header in &[&[0, 0, 0, 0, 7, 0][..], &[0, 0, 0, 0, 7, 2, 2][..]] {
+            let mut framed = header.to_vec();
+            framed.extend(&bytes);
+            let row = decoder
+                .decode(&framed)
+                .expect("deserialize protobuf into a row")
+                .unwrap();
+            let datums = row.iter().collect::<Vec<_>>();
+            assert_eq!(datums[0], Datum::Int32(1));
+            assert_eq!(datums[1], Datum::String("one"));
+        }
+
+        let err = decoder.decode(&[1, 0, 0, 0, 7, 0]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wrong protobuf serialization magic: expected 0, got 1"
+        );
+    }
+
+    #[test]
+    fn test_encode() -> Result<(), Error> {
+        let desc = RelationDesc::empty()
+            .with_nonnull_column("a", ScalarType::Int64)
+            .with_column("b c", ScalarType::String.nullable(true))
+            .with_column("b_c", ScalarType::Date.nullable(true));
+        let encoder = super::Encoder::new(desc, "row")?;
+        assert_eq!(
+            encoder.row_schema(),
+            r#"syntax = "proto2";
+
+message row {
+  optional int64 a = 1;
+  optional string b_c = 2;
+  optional string b_c_1 = 3;
+}
+"#
+        );
+        assert_eq!(
+            encoder.writer_schema(),
+            r#"syntax = "proto2";
+
+message row {
+  message Row {
+    optional int64 a = 1;
+    optional string b_c = 2;
+    optional string b_c_1 = 3;
+  }
+  optional Row before = 1;
+  optional Row after = 2;
+}
+"#
+        );
+
+        let row = Row::pack(&[
+            Datum::Int64(1),
+            Datum::String("x"),
+            Datum::Date(NaiveDate::from_ymd(2020, 1, 2)),
+        ]);
+        assert_eq!(
+            encoder.encode_row_unchecked(7, &row),
+            b"\x00\x00\x00\x00\x07\x00\x08\x01\x12\x01x\x1a\x0a2020-01-02".to_vec(),
+        );
+
+        let null_row = Row::pack(&[Datum::Int64(2), Datum::Null, Datum::Null]);
+        assert_eq!(
+            encoder.encode_row_unchecked(7, &null_row),
+            b"\x00\x00\x00\x00\x07\x00\x08\x02".to_vec(),
+        );
+        assert_eq!(
+            encoder.encode_unchecked(
+                7,
+                DiffPair {
+                    before: None,
+                    after: Some(&null_row),
+                }
+            ),
+            b"\x00\x00\x00\x00\x07\x00\x12\x02\x08\x02".to_vec(),
+        );
+
+        let err = super::Encoder::new(RelationDesc::empty(), "1row").unwrap_err();
+        assert!(err.to_string().starts_with("invalid protobuf message name"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compile_with_imports() -> Result<(), Error> {
+        let imports = vec![(
+            "common/money.proto".to_string(),
+            "syntax = \"proto3\"; package common; message Money { int64 cents = 1; }".to_string(),
+        )];
+        let schema = r#"
+            syntax = "proto3";
+            package billing;
+            import "common/money.proto";
+            message Invoice {
+                string id = 1;
+                common.Money total = 2;
+            }
+        "#;
+        let descriptors =
+            super::decode_descriptors(&super::compile_proto_schema(schema, &imports)?)?;
+        let relation = super::validate_descriptors(".billing.Invoice", &descriptors)?;
+        assert_eq!(relation.iter_names().count(), 2);
+        assert!(descriptors.message_by_name(".common.Money").is_some());
+
+        let err = super::compile_proto_schema("syntax = \"proto3\"; message", &[]).unwrap_err();
+        assert!(err.to_string().starts_with("compiling protobuf schema"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compile_rejects_escaping_imports() {
+        for name in &["/etc/passwd", "../schema.proto", "a/../../schema.proto"] {
+            let imports = vec![(name.to_string(), "syntax = \"proto3\";".to_string())];
+            let err = super::compile_proto_schema("syntax = \"proto3\";", &imports).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("imported protobuf file {} is not a relative path", name)
+            );
+        }
+    }
 }
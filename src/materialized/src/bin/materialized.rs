@@ -142,6 +142,12 @@ fn run() -> Result<(), failure::Error> {
         "PATH",
     );
     opts.optopt("", "tls-key", "private key for TLS connections", "PATH");
+    opts.optopt(
+        "",
+        "auth-method",
+        "how clients must authenticate: trust, password, or scram-sha-256 (default trust)",
+        "METHOD",
+    );
 
     // Storage options.
     opts.optopt(
@@ -253,6 +259,7 @@ fn run() -> Result<(), failure::Error> {
             key: key.into(),
         }),
     };
+    let auth_method = popts.opt_get_default("auth-method", pgwire::AuthMethod::Trust)?;
 
     // Configure storage.
     let data_directory = popts.opt_get_default("data-directory", PathBuf::from("mzdata"))?;
@@ -323,6 +330,7 @@ environment:{}",
         timestamp_frequency,
         listen_addr,
         tls,
+        auth_method,
         data_directory: Some(data_directory),
         symbiosis_url,
     })?;
@@ -28,7 +28,7 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use compile_time_run::run_command_str;
-use failure::format_err;
+use failure::{bail, format_err};
 use futures::channel::mpsc;
 use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
 use stream_cancel::{StreamExt as _, Trigger, Tripwire};
@@ -116,6 +116,8 @@ pub struct Config {
     pub listen_addr: Option<SocketAddr>,
     /// TLS encryption configuration.
     pub tls: Option<TlsConfig>,
+    /// The method by which pgwire clients must authenticate.
+    pub auth_method: pgwire::AuthMethod,
 
     // === Storage options. ===
     /// The directory in which `materialized` should store its own metadata.
@@ -173,6 +175,13 @@ pub fn serve(mut config: Config) -> Result<Server, failure::Error> {
         Some(tls_config) => Some(tls_config.acceptor()?),
     };
 
+    // Cleartext passwords are only accepted over TLS, so the password
+    // authentication method is useless without TLS.
+    let auth_method = config.auth_method;
+    if auth_method == pgwire::AuthMethod::Password && tls.is_none() {
+        bail!("the password authentication method requires TLS to be configured");
+    }
+
     // Initialize network listener.
     let listen_addr = config.listen_addr.unwrap_or_else(|| {
         SocketAddr::new(
@@ -216,7 +225,11 @@ pub fn serve(mut config: Config) -> Result<Server, failure::Error> {
             if is_primary {
                 let mut mux = Mux::new();
                 mux.add_handler(switchboard.clone());
-                mux.add_handler(pgwire::Server::new(tls.clone(), cmdq_tx.clone()));
+                mux.add_handler(pgwire::Server::new(
+                    tls.clone(),
+                    auth_method,
+                    cmdq_tx.clone(),
+                ));
                 mux.add_handler(http::Server::new(
                     tls,
                     cmdq_tx,
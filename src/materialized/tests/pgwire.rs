@@ -11,6 +11,7 @@
 
 use std::error::Error;
 use std::fs::File;
+use std::io::{Read, Write};
 use std::path::Path;
 use std::thread;
 use std::time::Duration;
@@ -349,6 +350,193 @@ fn test_persistence() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_table_persistence() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let data_dir = tempfile::tempdir()?;
+    let config = util::Config::default().data_directory(data_dir.path().to_owned());
+
+    fn select_all(client: &mut postgres::Client) -> Result<Vec<(i32, String)>, Box<dyn Error>> {
+        Ok(client
+            .query("SELECT a, b FROM t ORDER BY a", &[])?
+            .into_iter()
+            .map(|row| (row.get(0), row.get(1)))
+            .collect())
+    }
+
+    {
+        let (_server, mut client) = util::start_server(config.clone())?;
+        client.batch_execute(
+            "CREATE TABLE t (a int, b text); \
+             CREATE TABLE dropped (a int); \
+             INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c'); \
+             INSERT INTO dropped VALUES (1); \
+             UPDATE t SET b = 'z' WHERE a = 2; \
+             DELETE FROM t WHERE a = 3; \
+             DROP TABLE dropped;",
+        )?;
+    }
+
+    {
+        let (_server, mut client) = util::start_server(config.clone())?;
+        assert_eq!(
+            select_all(&mut client)?,
+            &[(1, "a".into()), (2, "z".into())]
+        );
+
+        // Writes after a restart are durable too.
+        client.batch_execute(
+            "INSERT INTO t VALUES (4, 'd'); \
+             CREATE TABLE dropped (a int);",
+        )?;
+        assert_eq!(
+            client
+                .query("SELECT count(*) FROM dropped", &[])?
+                .into_iter()
+                .map(|row| row.get(0))
+                .collect::<Vec<i64>>(),
+            &[0]
+        );
+    }
+
+    {
+        let (_server, mut client) = util::start_server(config)?;
+        assert_eq!(
+            select_all(&mut client)?,
+            &[(1, "a".into()), (2, "z".into()), (4, "d".into())]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_copy_from() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let (_server, mut client) = util::start_server(util::Config::default())?;
+    client.batch_execute("CREATE TABLE t (a int, b text, c decimal(10, 2))")?;
+
+    let mut copy_in = |query: &str, data: &[u8]| -> Result<u64, postgres::Error> {
+        let mut writer = client.copy_in(query, &[])?;
+        writer
+            .write_all(data)
+            .expect("writing to buffer cannot fail");
+        writer.finish()
+    };
+
+    assert_eq!(
+        copy_in(
+            "COPY t FROM STDIN",
+            b"1\tescaped\\tab\t1.5\n2\t\\N\t\\N\n3\t\\\\N\t2.125\n\\.\n",
+        )?,
+        3
+    );
+    assert_eq!(
+        copy_in(
+            "COPY t (c, a) FROM STDIN WITH (FORMAT CSV, HEADER)",
+            b"c,a\n,4\n\"0.1\",\"5\"\n",
+        )?,
+        2
+    );
+    assert_eq!(
+        copy_in(
+            "COPY t (b) FROM STDIN WITH (FORMAT CSV, DELIMITER ';')",
+            b"\"multi\nline; \"\"quoted\"\"\"\n",
+        )?,
+        1
+    );
+
+    // A malformed row causes the entire batch to be rejected.
+    match copy_in("COPY t FROM STDIN", b"6\ta\t1.0\n7\tb\n") {
+        Ok(_) => panic!("COPY with missing column succeeded"),
+        Err(err) => {
+            assert_eq!(err.code(), Some(&SqlState::BAD_COPY_FILE_FORMAT));
+            assert!(err.to_string().contains("missing data for column \"c\""));
+        }
+    }
+    match copy_in("COPY t (a) FROM STDIN", b"x\n") {
+        Ok(_) => panic!("COPY with invalid integer succeeded"),
+        Err(err) => assert!(err.to_string().contains("invalid input syntax")),
+    }
+    match copy_in("COPY t (c) FROM STDIN", b"123456789\n") {
+        Ok(_) => panic!("COPY with out-of-range decimal succeeded"),
+        Err(err) => assert!(err.to_string().contains("numeric field overflow")),
+    }
+
+    let rows: Vec<(Option<i32>, Option<String>, Option<String>)> = client
+        .query("SELECT a, b, c::text FROM t ORDER BY a, b", &[])?
+        .into_iter()
+        .map(|row| (row.get(0), row.get(1), row.get(2)))
+        .collect();
+    assert_eq!(
+        rows,
+        &[
+            (Some(1), Some("escaped\tab".into()), Some("1.50".into())),
+            (Some(2), None, None),
+            (Some(3), Some("\\N".into()), Some("2.13".into())),
+            (Some(4), None, None),
+            (Some(5), None, Some("0.10".into())),
+            (None, Some("multi\nline; \"quoted\"".into()), None),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_copy_to() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let (_server, mut client) = util::start_server(util::Config::default())?;
+    client.batch_execute(
+        "CREATE TABLE t (a int, b text); \
+         INSERT INTO t VALUES (1, 'tab\tand, comma'), (2, NULL), (3, '')",
+    )?;
+
+    let mut copy_out = |query: &str| -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut out = vec![];
+        client.copy_out(query, &[])?.read_to_end(&mut out)?;
+        Ok(out)
+    };
+
+    assert_eq!(
+        copy_out("COPY (SELECT * FROM t ORDER BY a) TO STDOUT")?,
+        b"1\ttab\\tand, comma\n2\t\\N\n3\t\n".to_vec()
+    );
+    assert_eq!(
+        copy_out("COPY (SELECT * FROM t ORDER BY a) TO STDOUT WITH (FORMAT CSV, HEADER)")?,
+        b"a,b\n1,\"tab\tand, comma\"\n2,\n3,\"\"\n".to_vec()
+    );
+    assert_eq!(
+        copy_out("COPY (SELECT b FROM t WHERE a = 2) TO STDOUT WITH (NULL 'null')")?,
+        b"null\n".to_vec()
+    );
+    assert_eq!(
+        copy_out("COPY t (a) TO STDOUT BINARY")?.len(),
+        // Signature and header, three rows with one four-byte value each,
+        // and the trailer.
+        19 + 3 * (2 + 4 + 4) + 2
+    );
+    assert_eq!(
+        copy_out("COPY (SELECT 1::int, 'x') TO STDOUT (FORMAT BINARY)")?,
+        b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0\
+          \0\x02\0\0\0\x04\0\0\0\x01\0\0\0\x01x\
+          \xff\xff"
+            .to_vec()
+    );
+
+    match copy_out("COPY t TO STDOUT (FORMAT BINARY, DELIMITER ',')") {
+        Ok(_) => panic!("COPY with conflicting options succeeded"),
+        Err(err) => assert!(err
+            .to_string()
+            .contains("cannot specify DELIMITER in BINARY mode")),
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_tls() -> Result<(), Box<dyn Error>> {
     fn make_tls<F>(configure: F) -> Result<MakeTlsConnector, Box<dyn Error>>
@@ -465,6 +653,303 @@ fn test_tls() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_auth() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let data_dir = tempfile::tempdir()?;
+    let temp_dir = tempfile::tempdir()?;
+    let cert_path = Path::join(temp_dir.path(), "server.crt");
+    let key_path = Path::join(temp_dir.path(), "server.key");
+    util::generate_certs(&cert_path, &key_path)?;
+
+    let make_nonverifying_tls = || -> Result<MakeTlsConnector, Box<dyn Error>> {
+        let mut connector_builder = SslConnector::builder(SslMethod::tls())?;
+        connector_builder.set_verify(SslVerifyMode::NONE);
+        Ok(MakeTlsConnector::new(connector_builder.build()))
+    };
+
+    let assert_code = |res: Result<postgres::Client, postgres::Error>, code: &SqlState| match res {
+        Ok(_) => panic!("expected connection to fail"),
+        Err(e) => assert_eq!(e.code(), Some(code)),
+    };
+
+    // Create some roles while authentication is disabled.
+    {
+        let config = util::Config::default().data_directory(data_dir.path());
+        let (_server, mut client) = util::start_server(config)?;
+        client.batch_execute(
+            "CREATE USER alice PASSWORD 'hunter2'; \
+             CREATE USER bob; \
+             CREATE ROLE carol PASSWORD 'hunter2';",
+        )?;
+    }
+
+    // Test SCRAM-SHA-256 authentication.
+    {
+        let config = util::Config::default()
+            .data_directory(data_dir.path())
+            .auth_method(pgwire::AuthMethod::ScramSha256);
+        let server = util::start_server_without_client(config)?;
+
+        let mut client = server
+            .pg_config()
+            .user("alice")
+            .password("hunter2")
+            .connect(postgres::NoTls)?;
+        assert_eq!(client.query_one("SELECT 1", &[])?.get::<_, i32>(0), 1);
+
+        // Only superusers may manage roles.
+        for (sql, message) in &[
+            ("CREATE ROLE eve", "permission denied to create role"),
+            ("DROP ROLE bob", "permission denied to drop role"),
+        ] {
+            match client.batch_execute(sql) {
+                Ok(()) => panic!("expected {:?} to fail", sql),
+                Err(e) => assert_eq!(e.as_db_error().map(|e| e.message()), Some(*message)),
+            }
+        }
+
+        assert_code(
+            server
+                .pg_config()
+                .user("alice")
+                .password("hunter3")
+                .connect(postgres::NoTls),
+            &SqlState::INVALID_PASSWORD,
+        );
+
+        // A nonexistent role and a role without a password must be
+        // indistinguishable from an incorrect password.
+        for user in &["bob", "dave"] {
+            assert_code(
+                server
+                    .pg_config()
+                    .user(user)
+                    .password("hunter2")
+                    .connect(postgres::NoTls),
+                &SqlState::INVALID_PASSWORD,
+            );
+        }
+
+        // Roles created with CREATE ROLE cannot log in by default.
+        assert_code(
+            server
+                .pg_config()
+                .user("carol")
+                .password("hunter2")
+                .connect(postgres::NoTls),
+            &SqlState::INVALID_AUTHORIZATION_SPECIFICATION,
+        );
+    }
+
+    // Test cleartext password authentication.
+    {
+        let config = util::Config::default()
+            .data_directory(data_dir.path())
+            .enable_tls(cert_path, key_path)
+            .auth_method(pgwire::AuthMethod::Password);
+        let server = util::start_server_without_client(config)?;
+
+        let mut client = server
+            .pg_config()
+            .user("alice")
+            .password("hunter2")
+            .ssl_mode(SslMode::Require)
+            .connect(make_nonverifying_tls()?)?;
+        assert_eq!(client.query_one("SELECT 1", &[])?.get::<_, i32>(0), 1);
+
+        assert_code(
+            server
+                .pg_config()
+                .user("alice")
+                .password("hunter3")
+                .ssl_mode(SslMode::Require)
+                .connect(make_nonverifying_tls()?),
+            &SqlState::INVALID_PASSWORD,
+        );
+
+        // Cleartext passwords must not be sent over unencrypted connections.
+        assert_code(
+            server
+                .pg_config()
+                .user("alice")
+                .password("hunter2")
+                .ssl_mode(SslMode::Disable)
+                .connect(make_nonverifying_tls()?),
+            &SqlState::INVALID_AUTHORIZATION_SPECIFICATION,
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_privileges() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let (server, mut admin) = util::start_server(util::Config::default())?;
+    admin.batch_execute(
+        "CREATE USER alice; \
+         CREATE USER bob; \
+         CREATE TABLE t (a int); \
+         INSERT INTO t VALUES (1); \
+         CREATE VIEW v AS SELECT a FROM t;",
+    )?;
+
+    let mut alice = server.pg_config().user("alice").connect(postgres::NoTls)?;
+    let mut bob = server.pg_config().user("bob").connect(postgres::NoTls)?;
+
+    let assert_err =
+        |client: &mut postgres::Client, sql: &str, message: &str| match client.batch_execute(sql) {
+            Ok(()) => panic!("expected {:?} to fail", sql),
+            Err(e) => {
+                let e = e.as_db_error().expect("expected a database error");
+                assert_eq!(e.message(), message);
+            }
+        };
+
+    // Only the owner may read from or write to an object by default.
+    assert_err(
+        &mut alice,
+        "SELECT * FROM t",
+        "permission denied for table 'materialize.public.t'",
+    );
+    assert_err(
+        &mut alice,
+        "INSERT INTO t VALUES (2)",
+        "permission denied for table 'materialize.public.t'",
+    );
+    assert_err(
+        &mut alice,
+        "SHOW COLUMNS FROM t",
+        "permission denied for table 'materialize.public.t'",
+    );
+    assert_err(
+        &mut alice,
+        "SHOW CREATE VIEW v",
+        "permission denied for view 'materialize.public.v'",
+    );
+
+    // Writes to a table require the corresponding privilege, and mutations
+    // that filter rows additionally require SELECT.
+    admin.batch_execute("GRANT INSERT, DELETE ON t TO alice")?;
+    alice.batch_execute("INSERT INTO t VALUES (2)")?;
+    assert_err(
+        &mut alice,
+        "UPDATE t SET a = 3",
+        "permission denied for table 'materialize.public.t'",
+    );
+    assert_err(
+        &mut alice,
+        "DELETE FROM t WHERE a = 2",
+        "permission denied for table 'materialize.public.t'",
+    );
+    admin.batch_execute("GRANT SELECT ON t TO alice")?;
+    alice.batch_execute("DELETE FROM t WHERE a = 2")?;
+    assert_eq!(
+        alice
+            .query_one("SELECT count(*) FROM t", &[])?
+            .get::<_, i64>(0),
+        1
+    );
+    assert_eq!(alice.query("SHOW COLUMNS FROM t", &[])?.len(), 1);
+    admin.batch_execute("REVOKE ALL ON t FROM alice")?;
+
+    // Granting SELECT on a view does not require SELECT on the objects that
+    // the view references.
+    admin.batch_execute("GRANT SELECT ON v TO alice")?;
+    assert_eq!(alice.query_one("SELECT a FROM v", &[])?.get::<_, i32>(0), 1);
+    assert_err(
+        &mut bob,
+        "SELECT * FROM v",
+        "permission denied for view 'materialize.public.v'",
+    );
+
+    // All roles may create objects in the public schema of the default
+    // database, and own the objects that they create.
+    alice.batch_execute("CREATE VIEW w AS SELECT a + 1 AS b FROM v")?;
+    assert_err(
+        &mut bob,
+        "SELECT * FROM w",
+        "permission denied for view 'materialize.public.w'",
+    );
+    assert_err(
+        &mut bob,
+        "GRANT SELECT ON w TO bob",
+        "must be owner of view 'materialize.public.w'",
+    );
+    alice.batch_execute("GRANT SELECT ON w TO PUBLIC")?;
+    assert_eq!(bob.query_one("SELECT b FROM w", &[])?.get::<_, i32>(0), 2);
+    assert_err(
+        &mut bob,
+        "DROP VIEW w",
+        "must be owner of view 'materialize.public.w'",
+    );
+
+    // Revoking a privilege takes effect immediately.
+    admin.batch_execute("REVOKE ALL ON v FROM alice")?;
+    assert_err(
+        &mut alice,
+        "SELECT * FROM v",
+        "permission denied for view 'materialize.public.v'",
+    );
+
+    // Roles that own objects cannot be dropped.
+    assert_err(
+        &mut admin,
+        "DROP ROLE alice",
+        "role 'alice' cannot be dropped because it owns objects or holds privileges",
+    );
+
+    admin.batch_execute("REVOKE CREATE ON SCHEMA materialize.public FROM PUBLIC")?;
+    assert_err(
+        &mut bob,
+        "CREATE VIEW x AS SELECT 1",
+        "permission denied for schema 'materialize.public'",
+    );
+
+    // Creating databases and managing roles requires superuser privileges.
+    assert_err(
+        &mut alice,
+        "CREATE DATABASE d",
+        "permission denied to create database",
+    );
+    assert_err(
+        &mut alice,
+        "CREATE ROLE carol",
+        "permission denied to create role",
+    );
+    assert_err(
+        &mut alice,
+        "DROP ROLE bob",
+        "permission denied to drop role",
+    );
+
+    // A session whose role is dropped loses the role's privileges, including
+    // superuser status.
+    admin.batch_execute("CREATE USER dave SUPERUSER")?;
+    let mut dave = server.pg_config().user("dave").connect(postgres::NoTls)?;
+    assert_eq!(
+        dave.query_one("SELECT count(*) FROM t", &[])?
+            .get::<_, i64>(0),
+        1
+    );
+    admin.batch_execute("DROP ROLE dave")?;
+    assert_err(
+        &mut dave,
+        "SELECT * FROM t",
+        "permission denied for table 'materialize.public.t'",
+    );
+    assert_err(
+        &mut dave,
+        "CREATE ROLE eve",
+        "permission denied to create role",
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_record_types() -> Result<(), Box<dyn Error>> {
     ore::test::init_logging();
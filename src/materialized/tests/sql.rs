@@ -15,14 +15,15 @@
 
 use std::error::Error;
 use std::fs::{self, File};
-use std::io::{BufRead, Write};
-use std::net::TcpListener;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
 use std::path::Path;
 use std::str;
 use std::thread;
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use serde_json::json;
 
 pub mod util;
 
@@ -240,6 +241,46 @@ fn test_tail_empty_upper_frontier() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_tail_queue_full() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default();
+    let (_server, mut client) = util::start_server(config)?;
+
+    client.batch_execute(
+        "CREATE MATERIALIZED VIEW foo AS SELECT * FROM generate_series(1, 1000);
+         SET tail_max_queued_bytes = 1",
+    )?;
+    // All records should be read into view before we start tailing.
+    thread::sleep(Duration::from_millis(100));
+
+    // A tail that errors once its queue is full delivers nothing but the
+    // error.
+    client.batch_execute("SET tail_on_queue_full = 'error'")?;
+    let mut tail_reader = client.copy_out("TAIL foo")?.split(b'\n');
+    let err = match tail_reader.next().transpose() {
+        Ok(Some(_)) => panic!("tail unexpectedly delivered an update"),
+        Ok(None) => panic!("tail unexpectedly succeeded"),
+        Err(e) => e,
+    };
+    assert!(err
+        .to_string()
+        .contains("tail exceeded the limit of 1 queued bytes"));
+    drop(tail_reader);
+
+    // A tail that spills once its queue is full delivers every update.
+    client.batch_execute("SET tail_on_queue_full = 'spill'")?;
+    let mut tail_reader = client.copy_out("TAIL foo")?.split(b'\n');
+    let mut count = 0;
+    while let Some(_value) = tail_reader.next().transpose()? {
+        count += 1;
+    }
+    assert_eq!(1000, count);
+
+    Ok(())
+}
+
 #[test]
 fn test_tail_unmaterialized() -> Result<(), Box<dyn Error>> {
     ore::test::init_logging();
@@ -295,6 +336,119 @@ fn test_tail_unmaterialized() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_tail_progress() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default();
+    let (_server, mut client) = util::start_server(config)?;
+
+    let temp_dir = tempfile::tempdir()?;
+    let path = Path::join(temp_dir.path(), "dynamic.csv");
+    let mut file = File::create(&path)?;
+    let mut append = |data| -> Result<_, Box<dyn Error>> {
+        file.write_all(data)?;
+        file.sync_all()?;
+        Ok(())
+    };
+
+    client.batch_execute(&*format!(
+        "CREATE MATERIALIZED SOURCE dynamic_csv FROM FILE '{}' WITH (tail = true)
+         FORMAT CSV WITH 3 COLUMNS",
+        path.display()
+    ))?;
+
+    let cancel_token = client.cancel_token();
+    let mut tail_reader = client
+        .copy_out("TAIL dynamic_csv WITH (PROGRESS)")?
+        .split(b'\n');
+
+    fn parse_line(line: &[u8]) -> Result<(bool, u64), Box<dyn Error>> {
+        let line = str::from_utf8(line)?;
+        let ts = line.split_whitespace().last().unwrap().parse()?;
+        Ok((line.starts_with("Progress: "), ts))
+    }
+
+    append(b"City 1,ST,00001\n")?;
+    let mut closed = 0;
+    let update_ts = loop {
+        let line = tail_reader.next().unwrap()?;
+        match parse_line(&line)? {
+            (true, ts) => {
+                assert!(ts >= closed);
+                closed = ts;
+            }
+            (false, ts) => {
+                assert!(line.starts_with(&b"City 1\tST\t00001\t1\tDiff: 1 at "[..]));
+                // The update must not occur at a timestamp that was already
+                // reported as closed.
+                assert!(ts >= closed);
+                break ts;
+            }
+        }
+    };
+
+    // As time passes, a progress message must eventually close the
+    // timestamp of the update. No other updates can arrive in the meantime.
+    loop {
+        let line = tail_reader.next().unwrap()?;
+        let (is_progress, ts) = parse_line(&line)?;
+        assert!(is_progress);
+        if ts > update_ts {
+            break;
+        }
+    }
+
+    cancel_token.cancel_query(postgres::NoTls)?;
+    Ok(())
+}
+
+#[test]
+fn test_tail_query() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default();
+    let (_server, mut client) = util::start_server(config)?;
+
+    let temp_dir = tempfile::tempdir()?;
+    let path = Path::join(temp_dir.path(), "dynamic.csv");
+    let mut file = File::create(&path)?;
+    let mut append = |data| -> Result<_, Box<dyn Error>> {
+        file.write_all(data)?;
+        file.sync_all()?;
+        Ok(())
+    };
+
+    client.batch_execute(&*format!(
+        "CREATE MATERIALIZED SOURCE dynamic_csv FROM FILE '{}' WITH (tail = true)
+         FORMAT CSV WITH 3 COLUMNS",
+        path.display()
+    ))?;
+
+    // Tailing an ad-hoc query must not require creating a view first.
+    let cancel_token = client.cancel_token();
+    let mut tail_reader = client
+        .copy_out("TAIL (SELECT column1 FROM dynamic_csv WHERE column3 <> '00002')")?
+        .split(b'\n');
+
+    append(b"City 1,ST,00001\n")?;
+    assert!(tail_reader
+        .next()
+        .unwrap()?
+        .starts_with(&b"City 1\tDiff: 1 at "[..]));
+
+    append(b"City 2,ST,00002\nCity 3,ST,00003\n")?;
+    assert!(tail_reader
+        .next()
+        .unwrap()?
+        .starts_with(&b"City 3\tDiff: 1 at "[..]));
+
+    cancel_token.cancel_query(postgres::NoTls)?;
+    assert!(tail_reader.next().is_none());
+
+    Ok(())
+}
+
 // Tests that a client that launches a non-terminating TAIL and disconnects
 // does not keep the server alive forever.
 #[test]
@@ -324,6 +478,153 @@ fn test_tail_shutdown() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Reads one HTTP request from `stream`, responds with `status`, and returns
+/// the request's body as JSON.
+fn respond(mut stream: TcpStream, status: &str) -> Result<serde_json::Value, Box<dyn Error>> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.trim_end().splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(""), None) => break,
+            (Some(name), Some(value)) if name.eq_ignore_ascii_case("content-length") => {
+                content_length = value.trim().parse()?;
+            }
+            _ => (),
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    )?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[test]
+fn test_http_sink() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let listener = TcpListener::bind("localhost:0")?;
+    let port = listener.local_addr()?.port();
+    let temp_dir = tempfile::tempdir()?;
+    let dead_letter_path = Path::join(temp_dir.path(), "dead.log");
+
+    let (_server, mut client) = util::start_server(util::Config::default())?;
+    client.batch_execute("CREATE TABLE t (a int)")?;
+    client.batch_execute("CREATE MATERIALIZED VIEW v AS SELECT * FROM t")?;
+    client.batch_execute(&*format!(
+        "CREATE SINK snk FROM v INTO HTTP 'http://localhost:{}/alerts'
+         WITH (dead_letter_path = '{}', max_retry_ms = 5000)
+         FORMAT JSON",
+        port,
+        dead_letter_path.display()
+    ))?;
+
+    // Each timestamp is delivered in its own request.
+    client.execute("INSERT INTO t VALUES (1)", &[])?;
+    let body = respond(listener.accept()?.0, "200 OK")?;
+    assert_eq!(body[0]["before"], serde_json::Value::Null);
+    assert_eq!(body[0]["after"], json!({"a": 1}));
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    // A server error is retried.
+    client.execute("INSERT INTO t VALUES (2)", &[])?;
+    let body = respond(listener.accept()?.0, "503 Service Unavailable")?;
+    assert_eq!(body[0]["after"], json!({"a": 2}));
+    let retried_body = respond(listener.accept()?.0, "200 OK")?;
+    assert_eq!(body, retried_body);
+
+    // A client error is not retried, and the batch goes to the dead letter
+    // file.
+    client.execute("INSERT INTO t VALUES (3)", &[])?;
+    let body = respond(listener.accept()?.0, "400 Bad Request")?;
+    assert_eq!(body[0]["after"], json!({"a": 3}));
+    let mut dead_letters = String::new();
+    for _ in 0..50 {
+        dead_letters = read_dead_letters(temp_dir.path())?;
+        if !dead_letters.is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    let dead_letters: Vec<serde_json::Value> = dead_letters
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0]["records"], body);
+    assert!(dead_letters[0]["error"].as_str().unwrap().contains("400"));
+
+    Ok(())
+}
+
+#[test]
+fn test_http_sink_short_retry() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    // Simulate a server that fails every request.
+    let listener = TcpListener::bind("localhost:0")?;
+    let port = listener.local_addr()?.port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let _ = respond(stream.unwrap(), "503 Service Unavailable");
+        }
+    });
+    let temp_dir = tempfile::tempdir()?;
+    let dead_letter_path = Path::join(temp_dir.path(), "dead.log");
+
+    // A retry limit shorter than the initial backoff gives up on a batch
+    // after a single retry.
+    let (_server, mut client) = util::start_server(util::Config::default())?;
+    client.batch_execute("CREATE TABLE t (a int)")?;
+    client.batch_execute("CREATE MATERIALIZED VIEW v AS SELECT * FROM t")?;
+    client.batch_execute(&*format!(
+        "CREATE SINK snk FROM v INTO HTTP 'http://localhost:{}/alerts'
+         WITH (dead_letter_path = '{}', max_retry_ms = 10)
+         FORMAT JSON",
+        port,
+        dead_letter_path.display()
+    ))?;
+
+    client.execute("INSERT INTO t VALUES (1)", &[])?;
+    client.execute("INSERT INTO t VALUES (2)", &[])?;
+    let mut dead_letters = String::new();
+    for _ in 0..50 {
+        dead_letters = read_dead_letters(temp_dir.path())?;
+        if dead_letters.lines().count() == 2 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    let dead_letters: Vec<serde_json::Value> = dead_letters
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(dead_letters.len(), 2);
+    assert_eq!(dead_letters[0]["records"][0]["after"], json!({"a": 1}));
+    assert_eq!(dead_letters[1]["records"][0]["after"], json!({"a": 2}));
+    assert!(dead_letters[0]["error"].as_str().unwrap().contains("503"));
+
+    // The server is still responsive.
+    let answer: i32 = client.query_one("SELECT 1 + 1", &[])?.get(0);
+    assert_eq!(answer, 2);
+
+    Ok(())
+}
+
+/// Reads the dead letter file of the only HTTP sink that writes to `dir`,
+/// whose name Materialize makes unique to the sink.
+fn read_dead_letters(dir: &Path) -> Result<String, Box<dyn Error>> {
+    let mut paths = fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(paths.len(), 1);
+    Ok(fs::read_to_string(paths.remove(0).path())?)
+}
+
 // Tests that temporary views created by one connection cannot be viewed
 // by another connection.
 #[test]
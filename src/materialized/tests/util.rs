@@ -26,6 +26,7 @@ pub struct Config {
     data_directory: Option<PathBuf>,
     logging_granularity: Option<Duration>,
     tls: Option<materialized::TlsConfig>,
+    auth_method: pgwire::AuthMethod,
 }
 
 impl Default for Config {
@@ -34,6 +35,7 @@ impl Default for Config {
             data_directory: None,
             logging_granularity: Some(Duration::from_millis(10)),
             tls: None,
+            auth_method: pgwire::AuthMethod::Trust,
         }
     }
 }
@@ -60,10 +62,23 @@ impl Config {
         });
         self
     }
+
+    pub fn auth_method(mut self, auth_method: pgwire::AuthMethod) -> Self {
+        self.auth_method = auth_method;
+        self
+    }
 }
 
 pub fn start_server(config: Config) -> Result<(Server, postgres::Client), Box<dyn Error>> {
-    let server = Server(materialized::serve(materialized::Config {
+    let server = start_server_without_client(config)?;
+    let client = server.connect()?;
+    Ok((server, client))
+}
+
+/// Like [`start_server`], but does not connect a client, for tests in which
+/// the default client would be unable to authenticate.
+pub fn start_server_without_client(config: Config) -> Result<Server, Box<dyn Error>> {
+    Ok(Server(materialized::serve(materialized::Config {
         logging_granularity: config.logging_granularity,
         timestamp_frequency: Duration::from_millis(10),
         logical_compaction_window: None,
@@ -74,9 +89,8 @@ pub fn start_server(config: Config) -> Result<(Server, postgres::Client), Box<dy
         symbiosis_url: None,
         listen_addr: None,
         tls: config.tls,
-    })?);
-    let client = server.connect()?;
-    Ok((server, client))
+        auth_method: config.auth_method,
+    })?))
 }
 
 pub struct Server(materialized::Server);
@@ -38,7 +38,10 @@ where
 {
     let mut state = RetryState {
         i: 0,
-        next_backoff: Some(Duration::from_millis(125)),
+        next_backoff: match cmp::min(Duration::from_millis(125), max_sleep) {
+            ZERO_DURATION => None,
+            b => Some(b),
+        },
     };
     let mut total_backoff = ZERO_DURATION;
     loop {
@@ -59,3 +62,25 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::runtime::Runtime;
+    use tokio::time::Duration;
+
+    use super::retry_for;
+
+    #[test]
+    fn test_retry_for_short_max_sleep() {
+        let mut runtime = Runtime::new().unwrap();
+        for (max_sleep_ms, expected_tries) in &[(0, 1), (1, 2), (50, 2), (125, 2), (200, 3)] {
+            let mut tries = 0;
+            let res = runtime.block_on(retry_for(Duration::from_millis(*max_sleep_ms), |_| {
+                tries += 1;
+                async { Err::<(), _>("failed") }
+            }));
+            assert_eq!(res, Err("failed"));
+            assert_eq!(tries, *expected_tries, "max_sleep_ms = {}", max_sleep_ms);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Client authentication.
+//!
+//! The heavy lifting of password verification is performed by
+//! [`coord::auth::ScramVerifier`]. This module is concerned only with
+//! the pgwire-specific framing of the [SCRAM-SHA-256] exchange.
+//!
+//! [SCRAM-SHA-256]: https://www.postgresql.org/docs/11/sasl-authentication.html
+
+use std::fmt;
+use std::str::FromStr;
+
+use failure::bail;
+use rand::Rng;
+
+/// The number of random bytes in the server's portion of a SCRAM nonce. This
+/// matches PostgreSQL.
+const SERVER_NONCE_LEN: usize = 18;
+
+/// The number of random bytes in the secret from which the salts of mock
+/// SCRAM verifiers are derived.
+const MOCK_SECRET_LEN: usize = 32;
+
+/// The method by which clients must authenticate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMethod {
+    /// Clients are admitted without any credential check.
+    Trust,
+    /// Clients must send the role's password in plain text. Only permitted on
+    /// TLS-encrypted connections.
+    Password,
+    /// Clients must prove knowledge of the role's password via a
+    /// SCRAM-SHA-256 exchange.
+    ScramSha256,
+}
+
+impl Default for AuthMethod {
+    fn default() -> AuthMethod {
+        AuthMethod::Trust
+    }
+}
+
+impl FromStr for AuthMethod {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<AuthMethod, failure::Error> {
+        match s {
+            "trust" => Ok(AuthMethod::Trust),
+            "password" => Ok(AuthMethod::Password),
+            "scram-sha-256" => Ok(AuthMethod::ScramSha256),
+            _ => bail!(
+                "unknown authentication method '{}'; \
+                 expected 'trust', 'password', or 'scram-sha-256'",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for AuthMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            AuthMethod::Trust => "trust",
+            AuthMethod::Password => "password",
+            AuthMethod::ScramSha256 => "scram-sha-256",
+        })
+    }
+}
+
+/// The parsed `client-first-message` of a SCRAM exchange.
+#[derive(Debug)]
+pub struct ClientFirstMessage {
+    /// The GS2 header, which the client must echo in its final message.
+    pub gs2_header: String,
+    /// The message without the GS2 header, which forms the first part of the
+    /// `AuthMessage`.
+    pub bare: String,
+    /// The client's nonce.
+    pub nonce: String,
+}
+
+impl ClientFirstMessage {
+    /// Parses a `client-first-message`.
+    ///
+    /// Channel binding and authorization identities are not supported. The
+    /// username in the message is ignored, as PostgreSQL clients send the
+    /// username in the startup message instead.
+    pub fn parse(data: &[u8]) -> Result<ClientFirstMessage, String> {
+        let data = std::str::from_utf8(data).map_err(|_| "invalid SCRAM message encoding")?;
+        let mut parts = data.splitn(3, ',');
+        match parts.next() {
+            Some("n") | Some("y") => (),
+            Some(p) if p.starts_with("p=") => {
+                return Err("SCRAM channel binding is not supported".into())
+            }
+            _ => return Err("malformed SCRAM message: invalid channel binding flag".into()),
+        }
+        match parts.next() {
+            Some("") => (),
+            Some(_) => {
+                return Err("client uses authorization identity, but it is not supported".into())
+            }
+            None => return Err("malformed SCRAM message: missing authorization identity".into()),
+        }
+        let bare = parts.next().unwrap_or("");
+        let gs2_header = data[..data.len() - bare.len()].to_owned();
+        let mut attrs = bare.split(',');
+        match attrs.next() {
+            Some(user) if user.starts_with("n=") => (),
+            _ => return Err("malformed SCRAM message: expected username attribute".into()),
+        }
+        let nonce = match attrs.next() {
+            Some(nonce) if nonce.starts_with("r=") && nonce.len() > 2 => nonce[2..].to_owned(),
+            _ => return Err("malformed SCRAM message: expected nonce attribute".into()),
+        };
+        Ok(ClientFirstMessage {
+            gs2_header,
+            bare: bare.to_owned(),
+            nonce,
+        })
+    }
+}
+
+/// Generates the server's portion of a SCRAM nonce.
+pub fn generate_server_nonce() -> String {
+    let mut rng = rand::thread_rng();
+    let bytes: Vec<u8> = (0..SERVER_NONCE_LEN).map(|_| rng.gen()).collect();
+    openssl::base64::encode_block(&bytes)
+}
+
+/// Generates the secret from which the server derives the salts of mock SCRAM
+/// verifiers, as described in [`coord::auth::ScramVerifier::mock`].
+pub fn generate_mock_secret() -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..MOCK_SECRET_LEN).map(|_| rng.gen()).collect()
+}
+
+/// The parsed `client-final-message` of a SCRAM exchange.
+#[derive(Debug)]
+pub struct ClientFinalMessage {
+    /// The message without the trailing proof attribute, which forms the last
+    /// part of the `AuthMessage`.
+    pub without_proof: String,
+    /// The client's proof.
+    pub proof: Vec<u8>,
+}
+
+impl ClientFinalMessage {
+    /// Parses a `client-final-message`, verifying that the channel binding and
+    /// nonce match those that were negotiated earlier in the exchange.
+    pub fn parse(data: &[u8], gs2_header: &str, nonce: &str) -> Result<ClientFinalMessage, String> {
+        let data = std::str::from_utf8(data).map_err(|_| "invalid SCRAM message encoding")?;
+        let (without_proof, proof) = match data.rfind(",p=") {
+            Some(i) => (&data[..i], &data[i + 3..]),
+            None => return Err("malformed SCRAM message: expected proof attribute".into()),
+        };
+        let mut attrs = without_proof.split(',');
+        match attrs.next() {
+            Some(c) if c.starts_with("c=") => {
+                if openssl::base64::decode_block(&c[2..]).ok().as_deref()
+                    != Some(gs2_header.as_bytes())
+                {
+                    return Err("SCRAM channel binding check failed".into());
+                }
+            }
+            _ => return Err("malformed SCRAM message: expected channel binding attribute".into()),
+        }
+        match attrs.next() {
+            Some(r) if r.starts_with("r=") && &r[2..] == nonce => (),
+            _ => return Err("SCRAM nonce mismatch".into()),
+        }
+        let proof = openssl::base64::decode_block(proof)
+            .map_err(|_| "malformed SCRAM message: invalid proof")?;
+        Ok(ClientFinalMessage {
+            without_proof: without_proof.to_owned(),
+            proof,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auth_method_roundtrip() -> Result<(), failure::Error> {
+        for method in &[
+            AuthMethod::Trust,
+            AuthMethod::Password,
+            AuthMethod::ScramSha256,
+        ] {
+            assert_eq!(*method, method.to_string().parse()?);
+        }
+        assert!("md5".parse::<AuthMethod>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_scram_messages() -> Result<(), String> {
+        // The example exchange from RFC 7677, Section 3.
+        let first = ClientFirstMessage::parse(b"n,,n=user,r=rOprNGfwEbeRWgbNEkqO")?;
+        assert_eq!(first.gs2_header, "n,,");
+        assert_eq!(first.bare, "n=user,r=rOprNGfwEbeRWgbNEkqO");
+        assert_eq!(first.nonce, "rOprNGfwEbeRWgbNEkqO");
+
+        let nonce = "rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0";
+        let last = ClientFinalMessage::parse(
+            b"c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0,\
+              p=dHzbZapWIk4jUhN+Ute9ytag9zjfMHgsqmmiz7AndVQ=",
+            &first.gs2_header,
+            nonce,
+        )?;
+        assert_eq!(
+            last.without_proof,
+            "c=biws,r=rOprNGfwEbeRWgbNEkqO%hvYDpWUa2RaTCAfuxFIlj)hNlF$k0"
+        );
+        assert_eq!(last.proof.len(), 32);
+
+        assert!(ClientFirstMessage::parse(b"p=tls-server-end-point,,n=user,r=abc").is_err());
+        assert!(ClientFirstMessage::parse(b"n,a=admin,n=user,r=abc").is_err());
+        assert!(ClientFirstMessage::parse(b"n,,r=abc").is_err());
+        assert!(ClientFinalMessage::parse(b"c=biws,r=wrong,p=AAAA", "n,,", nonce).is_err());
+        assert!(ClientFinalMessage::parse(b"c=eSws,r=abc,p=AAAA", "n,,", "abc").is_err());
+        Ok(())
+    }
+}
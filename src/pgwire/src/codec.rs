@@ -134,7 +134,11 @@ impl Encoder<BackendMessage> for Codec {
     fn encode(&mut self, msg: BackendMessage, dst: &mut BytesMut) -> Result<(), io::Error> {
         // Write type byte.
         let byte = match msg {
-            BackendMessage::AuthenticationOk => b'R',
+            BackendMessage::AuthenticationOk
+            | BackendMessage::AuthenticationCleartextPassword
+            | BackendMessage::AuthenticationSASL { .. }
+            | BackendMessage::AuthenticationSASLContinue(_)
+            | BackendMessage::AuthenticationSASLFinal(_) => b'R',
             BackendMessage::RowDescription(_) => b'T',
             BackendMessage::DataRow(_) => b'D',
             BackendMessage::CommandComplete { .. } => b'C',
@@ -150,6 +154,7 @@ impl Encoder<BackendMessage> for Codec {
             BackendMessage::CloseComplete => b'3',
             BackendMessage::NoticeResponse { .. } => b'N',
             BackendMessage::ErrorResponse { .. } => b'E',
+            BackendMessage::CopyInResponse { .. } => b'G',
             BackendMessage::CopyOutResponse { .. } => b'H',
             BackendMessage::CopyData(_) => b'd',
             BackendMessage::CopyDone => b'c',
@@ -162,7 +167,11 @@ impl Encoder<BackendMessage> for Codec {
 
         // Write message contents.
         match msg {
-            BackendMessage::CopyOutResponse {
+            BackendMessage::CopyInResponse {
+                overall_format,
+                column_formats,
+            }
+            | BackendMessage::CopyOutResponse {
                 overall_format,
                 column_formats,
             } => {
@@ -179,6 +188,24 @@ impl Encoder<BackendMessage> for Codec {
             BackendMessage::AuthenticationOk => {
                 dst.put_u32(0);
             }
+            BackendMessage::AuthenticationCleartextPassword => {
+                dst.put_u32(3);
+            }
+            BackendMessage::AuthenticationSASL { mechanisms } => {
+                dst.put_u32(10);
+                for mechanism in mechanisms {
+                    dst.put_string(mechanism);
+                }
+                dst.put_u8(b'\0');
+            }
+            BackendMessage::AuthenticationSASLContinue(data) => {
+                dst.put_u32(11);
+                dst.put_slice(&data);
+            }
+            BackendMessage::AuthenticationSASLFinal(data) => {
+                dst.put_u32(12);
+                dst.put_slice(&data);
+            }
             BackendMessage::RowDescription(fields) => {
                 dst.put_u16(fields.len() as u16);
                 for f in &fields {
@@ -383,9 +410,17 @@ impl Decoder for Codec {
                         b'S' => decode_sync(buf)?,
                         b'C' => decode_close(buf)?,
 
+                        // Copy-in flow.
+                        b'd' => decode_copy_data(buf)?,
+                        b'c' => decode_copy_done(buf)?,
+                        b'f' => decode_copy_fail(buf)?,
+
                         // Termination.
                         b'X' => decode_terminate(buf)?,
 
+                        // Authentication.
+                        b'p' => decode_password(buf)?,
+
                         // Invalid.
                         _ => {
                             return Err(io::Error::new(
@@ -408,6 +443,36 @@ fn decode_terminate(mut _buf: Cursor) -> Result<FrontendMessage, io::Error> {
     Ok(FrontendMessage::Terminate)
 }
 
+fn decode_password(buf: Cursor) -> Result<FrontendMessage, io::Error> {
+    Ok(FrontendMessage::Password {
+        buf: buf.buf.to_vec(),
+    })
+}
+
+/// Decodes the contents of a [`FrontendMessage::Password`] that was sent in
+/// response to an [`BackendMessage::AuthenticationCleartextPassword`] request.
+pub fn decode_cleartext_password(buf: &[u8]) -> Result<String, io::Error> {
+    let mut buf = Cursor::new(buf);
+    Ok(buf.read_cstr()?.to_owned())
+}
+
+/// Decodes the contents of a [`FrontendMessage::Password`] that was sent in
+/// response to an [`BackendMessage::AuthenticationSASL`] request.
+///
+/// Returns the name of the SASL mechanism that the client selected and the
+/// mechanism-specific initial response.
+pub fn decode_sasl_initial_response(buf: &[u8]) -> Result<(String, Vec<u8>), io::Error> {
+    let mut buf = Cursor::new(buf);
+    let mechanism = buf.read_cstr()?.to_owned();
+    let len = buf.read_i32()?;
+    let data = if len < 0 {
+        vec![]
+    } else {
+        buf.read_bytes(len as usize)?.to_vec()
+    };
+    Ok((mechanism, data))
+}
+
 fn decode_query(mut buf: Cursor) -> Result<FrontendMessage, io::Error> {
     Ok(FrontendMessage::Query {
         sql: buf.read_cstr()?.to_string(),
@@ -528,6 +593,21 @@ fn decode_sync(mut _buf: Cursor) -> Result<FrontendMessage, io::Error> {
     Ok(FrontendMessage::Sync)
 }
 
+fn decode_copy_data(buf: Cursor) -> Result<FrontendMessage, io::Error> {
+    Ok(FrontendMessage::CopyData(buf.buf.to_vec()))
+}
+
+fn decode_copy_done(mut _buf: Cursor) -> Result<FrontendMessage, io::Error> {
+    // Nothing more to decode.
+    Ok(FrontendMessage::CopyDone)
+}
+
+fn decode_copy_fail(mut buf: Cursor) -> Result<FrontendMessage, io::Error> {
+    Ok(FrontendMessage::CopyFail {
+        message: buf.read_cstr()?.to_owned(),
+    })
+}
+
 /// Decodes data within pgwire messages.
 ///
 /// The API provided is very similar to [`bytes::Buf`], but operations return
@@ -619,6 +699,16 @@ impl<'a> Cursor<'a> {
         Ok(val)
     }
 
+    /// Reads the next `n` bytes, advancing the cursor by `n` bytes.
+    fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], io::Error> {
+        if self.buf.len() < n {
+            return Err(input_err("not enough buffer for byte array"));
+        }
+        let val = &self.buf[..n];
+        self.advance(n);
+        Ok(val)
+    }
+
     /// Reads the next 16-bit format code, advancing the cursor by two bytes.
     fn read_format(&mut self) -> Result<pgrepr::Format, io::Error> {
         match self.read_i16()? {
@@ -0,0 +1,516 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Encoding and decoding of the data that `COPY` statements transfer.
+//!
+//! The text, CSV, and binary formats are described in the [PostgreSQL `COPY`
+//! documentation][copy].
+//!
+//! [copy]: https://www.postgresql.org/docs/11/sql-copy.html
+
+use std::cmp::Ordering;
+
+use byteorder::{ByteOrder, NetworkEndian};
+use bytes::BytesMut;
+
+use repr::{Datum, RelationDesc, RelationType, Row, RowArena, ScalarType};
+use sql::plan::{CopyFormat, CopyParams};
+
+/// A row of raw values, where `None` represents a null value.
+type RawRow = Vec<Option<Vec<u8>>>;
+
+/// The marker that may terminate the data in the text and CSV formats.
+const END_OF_DATA: &[u8] = b"\\.";
+
+/// The signature that begins data in the binary format, followed by a 32-bit
+/// flags field and a 32-bit header extension length, both of which are zero.
+const BINARY_SIGNATURE: &[u8] = b"PGCOPY\n\xff\r\n\0";
+
+/// Returns the data, if any, that precedes the first row when the rows
+/// described by `desc` are copied out: the signature in the binary format, or
+/// the column names if a header was requested in the CSV format.
+pub fn encode_copy_header(desc: &RelationDesc, params: &CopyParams) -> Option<Vec<u8>> {
+    match params.format {
+        CopyFormat::Binary => {
+            let mut out = BINARY_SIGNATURE.to_vec();
+            out.extend(&[0; 8]);
+            Some(out)
+        }
+        CopyFormat::Csv if params.header => {
+            let mut out = vec![];
+            for (i, name) in desc.iter_names().enumerate() {
+                if i > 0 {
+                    out.push(params.delimiter);
+                }
+                let name = name.map(|n| n.as_str()).unwrap_or("?column?");
+                encode_csv_value(name.as_bytes(), params, &mut out);
+            }
+            out.push(b'\n');
+            Some(out)
+        }
+        CopyFormat::Text | CopyFormat::Csv => None,
+    }
+}
+
+/// Encodes one row of type `typ` for `COPY ... TO STDOUT`.
+pub fn encode_copy_row(row: Row, typ: &RelationType, params: &CopyParams) -> Vec<u8> {
+    let mut out = vec![];
+    let mut buf = BytesMut::new();
+    let values = pgrepr::values_from_row(row, typ);
+    if params.format == CopyFormat::Binary {
+        let mut len = [0; 2];
+        NetworkEndian::write_i16(&mut len, values.len() as i16);
+        out.extend(&len);
+    }
+    for (i, (value, column_type)) in values.into_iter().zip(&typ.column_types).enumerate() {
+        buf.clear();
+        match params.format {
+            CopyFormat::Text | CopyFormat::Csv => {
+                if i > 0 {
+                    out.push(params.delimiter);
+                }
+                match value {
+                    None => out.extend(params.null.as_bytes()),
+                    Some(value) => {
+                        value.encode_text(&mut buf);
+                        if params.format == CopyFormat::Text {
+                            encode_text_value(&buf, params, &mut out);
+                        } else {
+                            encode_csv_value(&buf, params, &mut out);
+                        }
+                    }
+                }
+            }
+            CopyFormat::Binary => {
+                let mut len = [0; 4];
+                match value {
+                    None => NetworkEndian::write_i32(&mut len, -1),
+                    Some(value) => {
+                        value
+                            .encode_binary(&pgrepr::Type::from(&column_type.scalar_type), &mut buf);
+                        NetworkEndian::write_i32(&mut len, buf.len() as i32);
+                    }
+                }
+                out.extend(&len);
+                out.extend(&buf);
+            }
+        }
+    }
+    if params.format != CopyFormat::Binary {
+        out.push(b'\n');
+    }
+    out
+}
+
+/// Returns the data, if any, that follows the last row when rows are copied
+/// out.
+pub fn encode_copy_trailer(params: &CopyParams) -> Option<Vec<u8>> {
+    match params.format {
+        CopyFormat::Binary => Some((-1_i16).to_be_bytes().to_vec()),
+        CopyFormat::Text | CopyFormat::Csv => None,
+    }
+}
+
+/// Writes a value in the text format to `out`, escaping any bytes that would
+/// otherwise be misinterpreted.
+fn encode_text_value(value: &[u8], params: &CopyParams, out: &mut Vec<u8>) {
+    for b in value {
+        match b {
+            b'\\' => out.extend(b"\\\\"),
+            b'\n' => out.extend(b"\\n"),
+            b'\r' => out.extend(b"\\r"),
+            b'\t' => out.extend(b"\\t"),
+            8 => out.extend(b"\\b"),
+            11 => out.extend(b"\\v"),
+            12 => out.extend(b"\\f"),
+            b if *b == params.delimiter => {
+                out.push(b'\\');
+                out.push(*b);
+            }
+            b => out.push(*b),
+        }
+    }
+}
+
+/// Writes a value in the CSV format to `out`, quoting it if it contains any
+/// special bytes or could be mistaken for a null value.
+fn encode_csv_value(value: &[u8], params: &CopyParams, out: &mut Vec<u8>) {
+    let needs_quotes = value == params.null.as_bytes()
+        || value == END_OF_DATA
+        || value
+            .iter()
+            .any(|b| *b == params.delimiter || *b == params.quote || *b == b'\n' || *b == b'\r');
+    if !needs_quotes {
+        out.extend(value);
+        return;
+    }
+    out.push(params.quote);
+    for b in value {
+        if *b == params.quote || *b == params.escape {
+            out.push(params.escape);
+        }
+        out.push(*b);
+    }
+    out.push(params.quote);
+}
+
+/// Decodes the rows in `data` into rows of the table described by `desc`.
+///
+/// `columns` holds the index of the table column that each column of the
+/// data feeds. Table columns that are not fed by the data are null.
+pub fn decode_copy_rows(
+    data: &[u8],
+    desc: &RelationDesc,
+    columns: &[usize],
+    params: &CopyParams,
+) -> Result<Vec<Row>, String> {
+    let raw_rows = match params.format {
+        CopyFormat::Text => split_text(data, params),
+        CopyFormat::Csv => split_csv(data, params)?,
+        CopyFormat::Binary => unreachable!("COPY FROM does not support the binary format"),
+    };
+    let column_types = &desc.typ().column_types;
+    let column_names: Vec<_> = desc
+        .iter_names()
+        .map(|name| name.map(|n| n.as_str()).unwrap_or("?"))
+        .collect();
+    let first_line = if params.header { 2 } else { 1 };
+
+    let mut rows = Vec::with_capacity(raw_rows.len());
+    for (line, raw_row) in (first_line..).zip(raw_rows) {
+        match raw_row.len().cmp(&columns.len()) {
+            Ordering::Less => {
+                return Err(format!(
+                    "line {}: missing data for column \"{}\"",
+                    line,
+                    column_names[columns[raw_row.len()]]
+                ))
+            }
+            Ordering::Greater => {
+                return Err(format!(
+                    "line {}: extra data after last expected column",
+                    line
+                ))
+            }
+            Ordering::Equal => (),
+        }
+        let arena = RowArena::new();
+        let mut datums = vec![Datum::Null; column_types.len()];
+        for (raw, i) in raw_row.into_iter().zip(columns) {
+            let raw = match raw {
+                Some(raw) => raw,
+                None => continue,
+            };
+            let scalar_type = &column_types[*i].scalar_type;
+            let typ = pgrepr::Type::from(scalar_type);
+            let value = pgrepr::Value::decode_text(&typ, &raw)
+                .map_err(|e| format!("line {}, column \"{}\": {}", line, column_names[*i], e))?;
+            datums[*i] = match (value.into_datum(&arena, &typ), scalar_type) {
+                ((Datum::Decimal(d), ScalarType::Decimal(_, s1)), ScalarType::Decimal(p, s2)) => {
+                    let d = d.with_scale(s1).rescale(*s2, *p).map_err(|e| {
+                        format!("line {}, column \"{}\": {}", line, column_names[*i], e)
+                    })?;
+                    Datum::Decimal(d)
+                }
+                ((datum, _), _) => datum,
+            };
+        }
+        rows.push(Row::pack(datums));
+    }
+    Ok(rows)
+}
+
+/// Splits data in the text format into rows of unescaped values.
+fn split_text(data: &[u8], params: &CopyParams) -> Vec<RawRow> {
+    let mut rows = vec![];
+    for line in lines(data) {
+        if line == END_OF_DATA {
+            break;
+        }
+        let mut row = vec![];
+        let mut start = 0;
+        let mut i = 0;
+        while i <= line.len() {
+            if i == line.len() || line[i] == params.delimiter {
+                let raw = &line[start..i];
+                // Null values are recognized before backslash escapes are
+                // processed, so that an escaped null string is not null.
+                row.push(if raw == params.null.as_bytes() {
+                    None
+                } else {
+                    Some(unescape_text(raw))
+                });
+                start = i + 1;
+            } else if line[i] == b'\\' {
+                // Skip the escaped byte, which might be the delimiter.
+                i += 1;
+            }
+            i += 1;
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+/// Splits `data` into lines, stripping the line terminators. Both `\n` and
+/// `\r\n` terminate lines.
+fn lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let data = match data.last() {
+        Some(b'\n') => &data[..data.len() - 1],
+        _ => data,
+    };
+    let empty = data.is_empty();
+    data.split(|b| *b == b'\n')
+        .map(|line| match line.last() {
+            Some(b'\r') => &line[..line.len() - 1],
+            _ => line,
+        })
+        .filter(move |_| !empty)
+}
+
+/// Processes the backslash escapes in a value in the text format.
+fn unescape_text(raw: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] != b'\\' || i + 1 == raw.len() {
+            out.push(raw[i]);
+            i += 1;
+            continue;
+        }
+        i += 1;
+        match raw[i] {
+            b'b' => out.push(8),
+            b'f' => out.push(12),
+            b'n' => out.push(b'\n'),
+            b'r' => out.push(b'\r'),
+            b't' => out.push(b'\t'),
+            b'v' => out.push(11),
+            b'0'..=b'7' => {
+                let n = digits(&raw[i..], 3, 8);
+                out.push(parse_digits(&raw[i..i + n], 8));
+                i += n - 1;
+            }
+            b'x' if digits(&raw[i + 1..], 2, 16) > 0 => {
+                let n = digits(&raw[i + 1..], 2, 16);
+                out.push(parse_digits(&raw[i + 1..i + 1 + n], 16));
+                i += n;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Returns the number of leading bytes of `raw`, up to `max`, that are
+/// digits in the specified `radix`.
+fn digits(raw: &[u8], max: usize, radix: u32) -> usize {
+    raw.iter()
+        .take(max)
+        .take_while(|b| (**b as char).is_digit(radix))
+        .count()
+}
+
+fn parse_digits(digits: &[u8], radix: u32) -> u8 {
+    digits.iter().fold(0, |n, b| {
+        let digit = (*b as char)
+            .to_digit(radix)
+            .expect("caller validated digits");
+        // Octal escapes above \377 wrap, as they do in PostgreSQL.
+        (n as u32 * radix + digit) as u8
+    })
+}
+
+/// Splits data in the CSV format into rows of unquoted values.
+///
+/// Quoted values may span multiple lines. A value is null only if it was not
+/// quoted and matches the null string.
+fn split_csv(data: &[u8], params: &CopyParams) -> Result<Vec<RawRow>, String> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut value = vec![];
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut line_start = true;
+
+    let mut i = 0;
+    while i < data.len() {
+        if line_start {
+            let rest = &data[i..];
+            if rest.starts_with(END_OF_DATA) {
+                let after = &rest[END_OF_DATA.len()..];
+                if after.is_empty() || after.starts_with(b"\n") || after.starts_with(b"\r\n") {
+                    break;
+                }
+            }
+            line_start = false;
+        }
+
+        let b = data[i];
+        if in_quotes {
+            let next = data.get(i + 1).copied();
+            if b == params.escape
+                && (next == Some(params.quote) || next == Some(params.escape))
+                && (params.escape != params.quote || next == Some(params.quote))
+            {
+                value.push(next.unwrap());
+                i += 1;
+            } else if b == params.quote {
+                in_quotes = false;
+            } else {
+                value.push(b);
+            }
+        } else if b == params.quote {
+            in_quotes = true;
+            quoted = true;
+        } else if b == params.delimiter {
+            row.push(finish_csv_value(&mut value, &mut quoted, params));
+        } else if b == b'\n' || (b == b'\r' && data.get(i + 1) == Some(&b'\n')) {
+            if b == b'\r' {
+                i += 1;
+            }
+            row.push(finish_csv_value(&mut value, &mut quoted, params));
+            rows.push(std::mem::take(&mut row));
+            line_start = true;
+        } else {
+            value.push(b);
+        }
+        i += 1;
+    }
+
+    if in_quotes {
+        return Err("unterminated CSV quoted field".into());
+    }
+    if !line_start {
+        row.push(finish_csv_value(&mut value, &mut quoted, params));
+        rows.push(row);
+    }
+    if params.header && !rows.is_empty() {
+        rows.remove(0);
+    }
+    Ok(rows)
+}
+
+fn finish_csv_value(
+    value: &mut Vec<u8>,
+    quoted: &mut bool,
+    params: &CopyParams,
+) -> Option<Vec<u8>> {
+    let value = std::mem::take(value);
+    if !std::mem::take(quoted) && value == params.null.as_bytes() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use repr::ColumnType;
+
+    use super::*;
+
+    fn params(format: CopyFormat) -> CopyParams {
+        let csv = format == CopyFormat::Csv;
+        CopyParams {
+            format,
+            delimiter: if csv { b',' } else { b'\t' },
+            null: if csv { "".into() } else { "\\N".into() },
+            header: false,
+            quote: b'"',
+            escape: b'"',
+        }
+    }
+
+    fn strings(rows: Vec<RawRow>) -> Vec<Vec<Option<String>>> {
+        rows.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|v| v.map(|v| String::from_utf8(v).unwrap()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn row(values: &[Option<&str>]) -> Vec<Option<String>> {
+        values.iter().map(|v| v.map(String::from)).collect()
+    }
+
+    #[test]
+    fn test_split_text() {
+        let params = params(CopyFormat::Text);
+        let data = b"1\ta\\tb\t\\N\n2\t\\\\N\t\\x41\\101\n\n3\t\\.\tc\r\n\\.\nignored\n";
+        assert_eq!(
+            strings(split_text(data, &params)),
+            vec![
+                row(&[Some("1"), Some("a\tb"), None]),
+                row(&[Some("2"), Some("\\N"), Some("AA")]),
+                row(&[Some("")]),
+                row(&[Some("3"), Some("."), Some("c")]),
+            ]
+        );
+        assert!(split_text(b"", &params).is_empty());
+    }
+
+    #[test]
+    fn test_split_csv() -> Result<(), String> {
+        let mut params = params(CopyFormat::Csv);
+        params.header = true;
+        let data = b"a,b,c\n1,\"x,\"\"y\"\"\",\n2,\"\",\"multi\r\nline\"\r\n3,NULL,\"\\.\"";
+        assert_eq!(
+            strings(split_csv(data, &params)?),
+            vec![
+                row(&[Some("1"), Some("x,\"y\""), None]),
+                row(&[Some("2"), Some(""), Some("multi\r\nline")]),
+                row(&[Some("3"), Some("NULL"), Some("\\.")]),
+            ]
+        );
+
+        params.header = false;
+        params.null = "NULL".into();
+        params.escape = b'\\';
+        let data = b"\"a\\\"b\\\\\",NULL,\"NULL\"\n\\.\n4,5,6\n";
+        assert_eq!(
+            strings(split_csv(data, &params)?),
+            vec![row(&[Some("a\"b\\"), None, Some("NULL")])]
+        );
+
+        assert!(split_csv(b"1,\"unterminated\n", &params).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_copy_row() {
+        let typ = RelationType::new(vec![
+            ColumnType::new(ScalarType::Int32),
+            ColumnType::new(ScalarType::String).nullable(true),
+            ColumnType::new(ScalarType::String).nullable(true),
+        ]);
+        let row = Row::pack(&[Datum::Int32(1), Datum::String("a\tb,\"c\"\n"), Datum::Null]);
+        let encode = |params| String::from_utf8(encode_copy_row(row.clone(), &typ, &params));
+
+        assert_eq!(
+            encode(params(CopyFormat::Text)).unwrap(),
+            "1\ta\\tb,\"c\"\\n\t\\N\n"
+        );
+        assert_eq!(
+            encode(params(CopyFormat::Csv)).unwrap(),
+            "1,\"a\tb,\"\"c\"\"\n\",\n"
+        );
+
+        let row = Row::pack(&[Datum::Int32(2), Datum::String(""), Datum::String("\\.")]);
+        let encode = |params| String::from_utf8(encode_copy_row(row.clone(), &typ, &params));
+        assert_eq!(encode(params(CopyFormat::Csv)).unwrap(), "2,\"\",\"\\.\"\n");
+        assert_eq!(
+            encode_copy_row(row, &typ, &params(CopyFormat::Binary)),
+            b"\0\x03\0\0\0\x04\0\0\0\x02\0\0\0\0\0\0\0\x02\\.".to_vec()
+        );
+    }
+}
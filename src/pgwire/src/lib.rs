@@ -20,13 +20,16 @@
 //!   * [CockroachDB pgwire implementation](https://github.com/cockroachdb/cockroach/tree/master/pkg/sql/pgwire)
 //!   * ["Postgres on the wire" PGCon talk](https://www.pgcon.org/2014/schedule/attachments/330_postgres-for-the-wire.pdf)
 
+mod auth;
 mod codec;
+mod copy;
 mod id_alloc;
 mod message;
 mod protocol;
 mod secrets;
 mod server;
 
+pub use auth::AuthMethod;
 pub use codec::Codec;
 pub use protocol::match_handshake;
 pub use server::Server;
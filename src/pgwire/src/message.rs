@@ -11,7 +11,7 @@ use bytes::BytesMut;
 use postgres::error::SqlState;
 
 use coord::session::TransactionStatus as CoordTransactionStatus;
-use dataflow_types::Update;
+use dataflow_types::{TailEvent, Update};
 use repr::{ColumnName, RelationDesc, RelationType, ScalarType};
 
 // Pgwire protocol versions are represented as 32-bit integers, where the
@@ -210,6 +210,29 @@ pub enum FrontendMessage {
 
     /// Terminate a connection.
     Terminate,
+
+    /// Data sent as part of a `COPY ... FROM STDIN` operation.
+    CopyData(Vec<u8>),
+
+    /// Finish a `COPY ... FROM STDIN` operation.
+    CopyDone,
+
+    /// Abort a `COPY ... FROM STDIN` operation.
+    CopyFail {
+        /// The reason for the failure, as reported by the client.
+        message: String,
+    },
+
+    /// A response to an authentication request.
+    ///
+    /// The interpretation of the contents depends on the authentication
+    /// request that the backend made: it may be a cleartext password, an
+    /// initial SASL response, or a subsequent SASL response. Since the message
+    /// type does not indicate which, the contents are left undecoded here.
+    Password {
+        /// The undecoded contents of the message.
+        buf: Vec<u8>,
+    },
 }
 
 impl FrontendMessage {
@@ -226,6 +249,10 @@ impl FrontendMessage {
             FrontendMessage::CloseStatement { .. } => "close_statement",
             FrontendMessage::ClosePortal { .. } => "close_portal",
             FrontendMessage::Terminate => "terminate",
+            FrontendMessage::CopyData(_) => "copy_data",
+            FrontendMessage::CopyDone => "copy_done",
+            FrontendMessage::CopyFail { .. } => "copy_fail",
+            FrontendMessage::Password { .. } => "password",
         }
     }
 }
@@ -236,6 +263,12 @@ impl FrontendMessage {
 #[derive(Debug)]
 pub enum BackendMessage {
     AuthenticationOk,
+    AuthenticationCleartextPassword,
+    AuthenticationSASL {
+        mechanisms: Vec<&'static str>,
+    },
+    AuthenticationSASLContinue(Vec<u8>),
+    AuthenticationSASLFinal(Vec<u8>),
     CommandComplete {
         tag: String,
     },
@@ -267,6 +300,10 @@ pub enum BackendMessage {
         message: String,
         detail: Option<String>,
     },
+    CopyInResponse {
+        overall_format: pgrepr::Format,
+        column_formats: Vec<pgrepr::Format>,
+    },
     CopyOutResponse {
         overall_format: pgrepr::Format,
         column_formats: Vec<pgrepr::Format>,
@@ -316,7 +353,21 @@ pub struct FieldDescription {
     pub format: pgrepr::Format,
 }
 
-pub fn encode_update(update: Update, typ: &RelationType) -> Vec<u8> {
+/// Encodes an event reported by a `TAIL` as a line of `COPY` data.
+///
+/// An update is encoded as its row's tab-separated values, followed by its
+/// diff and timestamp. A progress event is encoded as `Progress: ` followed by
+/// the timestamp below which all updates have been reported. An error is never
+/// encoded as data, as it ends the `COPY` with an error response instead.
+pub fn encode_update(event: TailEvent, typ: &RelationType) -> Vec<u8> {
+    match event {
+        TailEvent::Update(update) => encode_row_update(update, typ),
+        TailEvent::Progress(timestamp) => format!("Progress: {}\n", timestamp).into_bytes(),
+        TailEvent::Error(_) => unreachable!("tail errors are sent as error responses"),
+    }
+}
+
+fn encode_row_update(update: Update, typ: &RelationType) -> Vec<u8> {
     let mut out = Vec::new();
     let mut buf = BytesMut::new();
     for field in pgrepr::values_from_row(update.row, typ) {
@@ -23,14 +23,19 @@ use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::time::{self, Duration};
 use tokio_util::codec::Framed;
 
+use coord::auth::{ScramVerifier, SCRAM_SHA_256};
 use coord::session::Session;
-use coord::{ExecuteResponse, StartupMessage};
-use dataflow_types::{PeekResponse, Update};
+use coord::{ExecuteResponse, LoginInfo, StartupMessage};
+use dataflow_types::{PeekResponse, TailEvent};
+use expr::GlobalId;
 use ore::future::OreSinkExt;
 use repr::{Datum, RelationDesc, Row, RowArena};
 use sql::ast::Statement;
+use sql::plan::{CopyFormat, CopyParams};
 
-use crate::codec::Codec;
+use crate::auth::{self, AuthMethod, ClientFinalMessage, ClientFirstMessage};
+use crate::codec::{self, Codec};
+use crate::copy;
 use crate::message::{
     self, BackendMessage, ErrorSeverity, FrontendMessage, NoticeSeverity, VERSIONS, VERSION_3,
 };
@@ -87,6 +92,9 @@ pub struct StateMachine<'a, A> {
     pub conn: &'a mut sink::Buffer<Framed<A, Codec>, BackendMessage>,
     pub conn_id: u32,
     pub secret_key: u32,
+    pub encrypted: bool,
+    pub auth_method: AuthMethod,
+    pub mock_auth_secret: &'a [u8],
     pub cmdq_tx: futures::channel::mpsc::UnboundedSender<coord::Command>,
 }
 
@@ -96,11 +104,10 @@ where
 {
     pub async fn start(
         &mut self,
-        session: Session,
         version: i32,
         params: Vec<(String, String)>,
     ) -> Result<(), comm::Error> {
-        let mut state = self.startup(session, version, params).await?;
+        let mut state = self.startup(version, params).await?;
 
         loop {
             state = match state.take() {
@@ -158,6 +165,19 @@ where
             Some(FrontendMessage::Flush) => self.flush(session).await?,
             Some(FrontendMessage::Sync) => self.sync(session).await?,
             Some(FrontendMessage::Terminate) => State::Done,
+            // A client may continue sending data for a `COPY ... FROM STDIN`
+            // statement that has already failed. PostgreSQL silently ignores
+            // such data, and so do we.
+            Some(FrontendMessage::CopyData(_))
+            | Some(FrontendMessage::CopyDone)
+            | Some(FrontendMessage::CopyFail { .. }) => State::Ready(session),
+            Some(FrontendMessage::Password { .. }) => {
+                self.fatal(
+                    SqlState::PROTOCOL_VIOLATION,
+                    "unexpected password message after authentication",
+                )
+                .await?
+            }
             None => State::Done,
         };
 
@@ -182,7 +202,6 @@ where
 
     async fn startup(
         &mut self,
-        mut session: Session,
         version: i32,
         params: Vec<(String, String)>,
     ) -> Result<State, comm::Error> {
@@ -195,13 +214,33 @@ where
                 .await;
         }
 
+        let user = match params.iter().find(|(name, _)| name == "user") {
+            Some((_, user)) => user.clone(),
+            None => {
+                return self
+                    .fatal(
+                        SqlState::INVALID_AUTHORIZATION_SPECIFICATION,
+                        "no PostgreSQL user name specified in startup packet",
+                    )
+                    .await;
+            }
+        };
+        if !self.authenticate(&user).await? {
+            return Ok(State::Done);
+        }
+
+        let mut session = Session::new(self.conn_id, user);
         for (name, value) in params {
             let _ = session.set(&name, &value);
         }
 
         let (tx, rx) = futures::channel::oneshot::channel();
         self.cmdq_tx
-            .send(coord::Command::Startup { session, tx })
+            .send(coord::Command::Startup {
+                session,
+                authenticated: self.auth_method != AuthMethod::Trust,
+                tx,
+            })
             .await?;
         let (notices, session) = match rx.await? {
             coord::Response {
@@ -257,6 +296,211 @@ where
         self.flush(session).await
     }
 
+    /// Authenticates the client as the role named `user`, according to the
+    /// configured authentication method.
+    ///
+    /// Returns whether authentication succeeded. If it did not, the client has
+    /// already been sent a fatal error, or has hung up. Note that under the
+    /// trust method, the role is not required to exist.
+    async fn authenticate(&mut self, user: &str) -> Result<bool, comm::Error> {
+        let info = match self.auth_method {
+            AuthMethod::Trust => return Ok(true),
+            AuthMethod::Password => self.authenticate_password(user).await?,
+            AuthMethod::ScramSha256 => self.authenticate_scram(user).await?,
+        };
+        match info {
+            None => Ok(false),
+            Some(info) if !info.login => {
+                self.fatal(
+                    SqlState::INVALID_AUTHORIZATION_SPECIFICATION,
+                    format!("role \"{}\" is not permitted to log in", user),
+                )
+                .await?;
+                Ok(false)
+            }
+            Some(_) => Ok(true),
+        }
+    }
+
+    /// Checks a cleartext password sent by the client. Cleartext passwords are
+    /// only accepted over TLS-encrypted connections.
+    async fn authenticate_password(
+        &mut self,
+        user: &str,
+    ) -> Result<Option<LoginInfo>, comm::Error> {
+        if !self.encrypted {
+            return self
+                .auth_failed(
+                    SqlState::INVALID_AUTHORIZATION_SPECIFICATION,
+                    "password authentication requires a TLS-encrypted connection",
+                )
+                .await;
+        }
+
+        self.conn
+            .send(BackendMessage::AuthenticationCleartextPassword)
+            .await?;
+        let password = match self.recv_password().await? {
+            Some(buf) => match codec::decode_cleartext_password(&buf) {
+                Ok(password) => password,
+                Err(err) => {
+                    return self
+                        .auth_failed(SqlState::PROTOCOL_VIOLATION, err.to_string())
+                        .await
+                }
+            },
+            None => return Ok(None),
+        };
+
+        let info = self.login_info(user).await?;
+        let verified = match info.as_ref().and_then(|info| info.password.as_ref()) {
+            Some(verifier) => match verifier.verify_password(&password) {
+                Ok(verified) => verified,
+                Err(err) => {
+                    return self
+                        .auth_failed(SqlState::INTERNAL_ERROR, err.to_string())
+                        .await
+                }
+            },
+            None => false,
+        };
+        if !verified {
+            return self.password_failed(user).await;
+        }
+        Ok(info)
+    }
+
+    /// Carries out a SCRAM-SHA-256 exchange with the client.
+    async fn authenticate_scram(&mut self, user: &str) -> Result<Option<LoginInfo>, comm::Error> {
+        self.conn
+            .send(BackendMessage::AuthenticationSASL {
+                mechanisms: vec![SCRAM_SHA_256],
+            })
+            .await?;
+        let client_first = match self.recv_password().await? {
+            Some(buf) => match codec::decode_sasl_initial_response(&buf) {
+                Ok((mechanism, data)) if mechanism == SCRAM_SHA_256 => {
+                    ClientFirstMessage::parse(&data)
+                }
+                Ok((mechanism, _)) => Err(format!(
+                    "client selected an invalid SASL authentication mechanism: {}",
+                    mechanism
+                )),
+                Err(err) => Err(err.to_string()),
+            },
+            None => return Ok(None),
+        };
+        let client_first = match client_first {
+            Ok(client_first) => client_first,
+            Err(msg) => return self.auth_failed(SqlState::PROTOCOL_VIOLATION, msg).await,
+        };
+
+        // If the role does not exist or has no password, carry on with the
+        // exchange using a mock verifier, so that the client cannot
+        // distinguish a nonexistent role from an incorrect password.
+        let info = self.login_info(user).await?;
+        let verifier = match info.as_ref().and_then(|info| info.password.clone()) {
+            Some(verifier) => verifier,
+            None => match ScramVerifier::mock(self.mock_auth_secret, user) {
+                Ok(verifier) => verifier,
+                Err(err) => {
+                    return self
+                        .auth_failed(SqlState::INTERNAL_ERROR, err.to_string())
+                        .await
+                }
+            },
+        };
+
+        let nonce = format!("{}{}", client_first.nonce, auth::generate_server_nonce());
+        let server_first = format!(
+            "r={},s={},i={}",
+            nonce,
+            openssl::base64::encode_block(verifier.salt()),
+            verifier.iterations()
+        );
+        self.conn
+            .send(BackendMessage::AuthenticationSASLContinue(
+                server_first.clone().into_bytes(),
+            ))
+            .await?;
+        let client_final = match self.recv_password().await? {
+            Some(buf) => ClientFinalMessage::parse(&buf, &client_first.gs2_header, &nonce),
+            None => return Ok(None),
+        };
+        let client_final = match client_final {
+            Ok(client_final) => client_final,
+            Err(msg) => return self.auth_failed(SqlState::PROTOCOL_VIOLATION, msg).await,
+        };
+
+        let auth_message = format!(
+            "{},{},{}",
+            client_first.bare, server_first, client_final.without_proof
+        );
+        match verifier.verify_proof(auth_message.as_bytes(), &client_final.proof) {
+            Ok(Some(server_signature)) => {
+                let server_final =
+                    format!("v={}", openssl::base64::encode_block(&server_signature));
+                self.send(BackendMessage::AuthenticationSASLFinal(
+                    server_final.into_bytes(),
+                ))
+                .await?;
+                Ok(info)
+            }
+            Ok(None) => self.password_failed(user).await,
+            Err(err) => {
+                self.auth_failed(SqlState::INTERNAL_ERROR, err.to_string())
+                    .await
+            }
+        }
+    }
+
+    /// Receives the client's response to an authentication request.
+    ///
+    /// Returns `None` if the client hung up instead of responding, as clients
+    /// commonly do in order to prompt the user for a password.
+    async fn recv_password(&mut self) -> Result<Option<Vec<u8>>, comm::Error> {
+        match self.recv().await? {
+            Some(FrontendMessage::Password { buf }) => Ok(Some(buf)),
+            Some(message) => {
+                self.fatal(
+                    SqlState::PROTOCOL_VIOLATION,
+                    format!("expected password response, got {}", message.name()),
+                )
+                .await?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn login_info(&mut self, user: &str) -> Result<Option<LoginInfo>, comm::Error> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        self.cmdq_tx
+            .send(coord::Command::GetLoginInfo {
+                role_name: user.into(),
+                tx,
+            })
+            .await?;
+        Ok(rx.await?)
+    }
+
+    async fn password_failed(&mut self, user: &str) -> Result<Option<LoginInfo>, comm::Error> {
+        self.auth_failed(
+            SqlState::INVALID_PASSWORD,
+            format!("password authentication failed for user \"{}\"", user),
+        )
+        .await
+    }
+
+    async fn auth_failed(
+        &mut self,
+        code: SqlState,
+        message: impl Into<String>,
+    ) -> Result<Option<LoginInfo>, comm::Error> {
+        self.fatal(code, message).await?;
+        Ok(None)
+    }
+
     async fn one_query(&mut self, session: Session, stmt: Statement) -> Result<State, comm::Error> {
         let stmt_name = String::from("");
         let portal_name = String::from("");
@@ -690,6 +934,7 @@ where
         }
 
         match response {
+            ExecuteResponse::AlteredSource => command_complete!("ALTER SOURCE"),
             ExecuteResponse::CreatedDatabase { existed } => {
                 created!(existed, SqlState::DUPLICATE_DATABASE, "database")
             }
@@ -711,6 +956,7 @@ where
             ExecuteResponse::CreatedView { existed } => {
                 created!(existed, SqlState::DUPLICATE_OBJECT, "view")
             }
+            ExecuteResponse::CreatedRole => command_complete!("CREATE ROLE"),
             ExecuteResponse::Deleted(n) => command_complete!("DELETE {}", n),
             ExecuteResponse::DroppedDatabase => command_complete!("DROP DATABASE"),
             ExecuteResponse::DroppedSchema => command_complete!("DROP SCHEMA"),
@@ -719,6 +965,9 @@ where
             ExecuteResponse::DroppedSink => command_complete!("DROP SINK"),
             ExecuteResponse::DroppedTable => command_complete!("DROP TABLE"),
             ExecuteResponse::DroppedView => command_complete!("DROP VIEW"),
+            ExecuteResponse::DroppedRole => command_complete!("DROP ROLE"),
+            ExecuteResponse::GrantedPrivileges => command_complete!("GRANT"),
+            ExecuteResponse::RevokedPrivileges => command_complete!("REVOKE"),
             ExecuteResponse::EmptyQuery => {
                 self.send(BackendMessage::EmptyQueryResponse).await?;
                 Ok(State::Ready(session))
@@ -770,6 +1019,26 @@ where
             }
             ExecuteResponse::StartedTransaction => command_complete!("BEGIN"),
             ExecuteResponse::CommittedTransaction => command_complete!("COMMIT"),
+            ExecuteResponse::CopyIn {
+                id,
+                columns,
+                desc,
+                params,
+            } => self.copy_from(session, id, columns, desc, params).await,
+            ExecuteResponse::CopyOut { rx, desc, params } => match rx.await? {
+                PeekResponse::Canceled => {
+                    self.error(
+                        session,
+                        SqlState::QUERY_CANCELED,
+                        "canceling statement due to user request",
+                    )
+                    .await
+                }
+                PeekResponse::Error(text) => {
+                    self.error(session, SqlState::INTERNAL_ERROR, text).await
+                }
+                PeekResponse::Rows(rows) => self.copy_to(session, rows, desc, params).await,
+            },
             ExecuteResponse::AbortedTransaction => command_complete!("ROLLBACK"),
             ExecuteResponse::Tailing { rx } => {
                 let row_desc =
@@ -869,7 +1138,7 @@ where
         &mut self,
         session: Session,
         row_desc: RelationDesc,
-        mut rx: comm::mpsc::Receiver<Vec<Update>>,
+        mut rx: comm::mpsc::Receiver<Vec<TailEvent>>,
     ) -> Result<State, comm::Error> {
         let typ = row_desc.typ();
         let column_formats = iter::repeat(pgrepr::Format::Text)
@@ -885,14 +1154,19 @@ where
         loop {
             match time::timeout(Duration::from_secs(1), rx.next()).await {
                 Ok(None) => break,
-                Ok(Some(updates)) => {
-                    let updates = updates?;
-                    count += updates.len();
-                    for update in updates {
-                        self.send(BackendMessage::CopyData(message::encode_update(
-                            update, typ,
-                        )))
-                        .await?;
+                Ok(Some(events)) => {
+                    for event in events? {
+                        match event {
+                            TailEvent::Update(_) => count += 1,
+                            TailEvent::Progress(_) => (),
+                            TailEvent::Error(msg) => {
+                                return self
+                                    .error(session, SqlState::PROGRAM_LIMIT_EXCEEDED, msg)
+                                    .await;
+                            }
+                        }
+                        self.send(BackendMessage::CopyData(message::encode_update(event, typ)))
+                            .await?;
                     }
                 }
                 Err(time::Elapsed { .. }) => {
@@ -926,6 +1200,132 @@ where
         Ok(State::Ready(session))
     }
 
+    async fn copy_from(
+        &mut self,
+        session: Session,
+        id: GlobalId,
+        columns: Vec<usize>,
+        desc: RelationDesc,
+        params: CopyParams,
+    ) -> Result<State, comm::Error> {
+        let column_formats = vec![pgrepr::Format::Text; columns.len()];
+        self.send(BackendMessage::CopyInResponse {
+            overall_format: pgrepr::Format::Text,
+            column_formats,
+        })
+        .await?;
+        self.conn.flush().await?;
+
+        // The rows are buffered in full before they are decoded, so that they
+        // can be inserted as a single atomic batch.
+        let mut data = vec![];
+        loop {
+            match self.recv().await? {
+                Some(FrontendMessage::CopyData(buf)) => data.extend(buf),
+                Some(FrontendMessage::CopyDone) => break,
+                Some(FrontendMessage::CopyFail { message }) => {
+                    return self
+                        .error(
+                            session,
+                            SqlState::QUERY_CANCELED,
+                            format!("COPY from stdin failed: {}", message),
+                        )
+                        .await;
+                }
+                // Clients that use the extended query protocol may send Flush
+                // and Sync messages while copying, which PostgreSQL ignores.
+                Some(FrontendMessage::Flush) | Some(FrontendMessage::Sync) => (),
+                Some(message) => {
+                    return self
+                        .error(
+                            session,
+                            SqlState::PROTOCOL_VIOLATION,
+                            format!(
+                                "unexpected message type during COPY from stdin: {}",
+                                message.name()
+                            ),
+                        )
+                        .await;
+                }
+                None => return Ok(State::Done),
+            }
+        }
+
+        let rows = match copy::decode_copy_rows(&data, &desc, &columns, &params) {
+            Ok(rows) => rows,
+            Err(message) => {
+                return self
+                    .error(session, SqlState::BAD_COPY_FILE_FORMAT, message)
+                    .await
+            }
+        };
+
+        let (tx, rx) = futures::channel::oneshot::channel();
+        self.cmdq_tx
+            .send(coord::Command::CopyRows {
+                id,
+                rows,
+                session,
+                tx,
+            })
+            .await?;
+        match rx.await? {
+            coord::Response {
+                result: Ok(count),
+                session,
+            } => {
+                let tag = format!("COPY {}", count);
+                self.send(BackendMessage::CommandComplete { tag }).await?;
+                Ok(State::Ready(session))
+            }
+            coord::Response {
+                result: Err(err),
+                session,
+            } => {
+                self.error(session, SqlState::INTERNAL_ERROR, err.to_string())
+                    .await
+            }
+        }
+    }
+
+    async fn copy_to(
+        &mut self,
+        session: Session,
+        rows: Vec<Row>,
+        desc: RelationDesc,
+        params: CopyParams,
+    ) -> Result<State, comm::Error> {
+        let format = match params.format {
+            CopyFormat::Binary => pgrepr::Format::Binary,
+            CopyFormat::Text | CopyFormat::Csv => pgrepr::Format::Text,
+        };
+        let column_formats = vec![format; desc.typ().column_types.len()];
+        self.send(BackendMessage::CopyOutResponse {
+            overall_format: format,
+            column_formats,
+        })
+        .await?;
+
+        if let Some(header) = copy::encode_copy_header(&desc, &params) {
+            self.send(BackendMessage::CopyData(header)).await?;
+        }
+        let count = rows.len();
+        let typ = desc.typ();
+        self.send_all(
+            rows.into_iter()
+                .map(|row| BackendMessage::CopyData(copy::encode_copy_row(row, typ, &params))),
+        )
+        .await?;
+        if let Some(trailer) = copy::encode_copy_trailer(&params) {
+            self.send(BackendMessage::CopyData(trailer)).await?;
+        }
+
+        let tag = format!("COPY {}", count);
+        self.send(BackendMessage::CopyDone).await?;
+        self.send(BackendMessage::CommandComplete { tag }).await?;
+        Ok(State::Ready(session))
+    }
+
     async fn recv(&mut self) -> Result<Option<FrontendMessage>, comm::Error> {
         let message = self.conn.try_next().await?;
         match &message {
@@ -18,8 +18,7 @@ use tokio::io::{self, AsyncRead, AsyncWrite, AsyncWriteExt};
 use tokio_openssl::SslStream;
 use tokio_util::codec::Framed;
 
-use coord::session::Session;
-
+use crate::auth::{self, AuthMethod};
 use crate::codec::{self, Codec, ACCEPT_SSL_ENCRYPTION, REJECT_ENCRYPTION};
 use crate::id_alloc::{IdAllocator, IdExhaustionError};
 use crate::message::FrontendStartupMessage;
@@ -30,18 +29,23 @@ pub struct Server {
     id_alloc: IdAllocator,
     secrets: SecretManager,
     tls: Option<SslAcceptor>,
+    auth_method: AuthMethod,
+    mock_auth_secret: Vec<u8>,
     cmdq_tx: futures::channel::mpsc::UnboundedSender<coord::Command>,
 }
 
 impl Server {
     pub fn new(
         tls: Option<SslAcceptor>,
+        auth_method: AuthMethod,
         cmdq_tx: futures::channel::mpsc::UnboundedSender<coord::Command>,
     ) -> Server {
         Server {
             id_alloc: IdAllocator::new(1, 1 << 16),
             secrets: SecretManager::new(),
             tls,
+            auth_method,
+            mock_auth_secret: auth::generate_mock_secret(),
             cmdq_tx,
         }
     }
@@ -62,13 +66,20 @@ impl Server {
                     };
                     self.secrets.generate(conn_id);
 
+                    let encrypted = match conn {
+                        Conn::Unencrypted(_) => false,
+                        Conn::Ssl(_) => true,
+                    };
                     let mut machine = StateMachine {
                         conn: &mut Framed::new(conn, Codec::new()).buffer(32),
                         conn_id,
                         secret_key: self.secrets.get(conn_id).unwrap(),
+                        encrypted,
+                        auth_method: self.auth_method,
+                        mock_auth_secret: &self.mock_auth_secret,
                         cmdq_tx: self.cmdq_tx.clone(),
                     };
-                    let res = machine.start(Session::new(conn_id), version, params).await;
+                    let res = machine.start(version, params).await;
 
                     // Clean up state tied to this specific connection.
                     self.cmdq_tx
@@ -47,7 +47,7 @@
 //! [bigdecimal]: https://crates.io/crates/bigdecimal
 //! [fixed-point arithmetic]: https://en.wikipedia.org/wiki/Fixed-point_arithmetic
 
-use std::cmp::PartialEq;
+use std::cmp::{Ordering, PartialEq};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::iter::Sum;
@@ -358,6 +358,38 @@ impl Decimal {
             scale: self.scale,
         }
     }
+
+    /// Adjusts this decimal to have scale `scale`, rounding half away from
+    /// zero if digits are lost, and returns the resulting significand.
+    ///
+    /// Returns an error if the result has more than `precision` digits.
+    pub fn rescale(&self, scale: u8, precision: u8) -> Result<Significand, failure::Error> {
+        let significand = match self.scale.cmp(&scale) {
+            Ordering::Less => 10_i128
+                .checked_pow(u32::from(scale - self.scale))
+                .and_then(|factor| self.significand.checked_mul(factor)),
+            Ordering::Equal => Some(self.significand),
+            Ordering::Greater => match 10_i128.checked_pow(u32::from(self.scale - scale)) {
+                Some(factor) => {
+                    let quotient = self.significand / factor;
+                    if (self.significand % factor).abs() >= factor / 2 {
+                        Some(quotient + self.significand.signum())
+                    } else {
+                        Some(quotient)
+                    }
+                }
+                // The factor exceeds any significand, so every digit is lost.
+                None => Some(0),
+            },
+        };
+        let limit = 10_i128.checked_pow(u32::from(precision));
+        match significand {
+            Some(d) if limit.map_or(true, |limit| d.checked_abs().map_or(false, |d| d < limit)) => {
+                Ok(Significand(d))
+            }
+            _ => bail!("numeric field overflow"),
+        }
+    }
 }
 
 impl FromStr for Decimal {
@@ -558,6 +590,27 @@ mod tests {
         assert_eq!(d("55.5555").round(-3), d("0.0000"));
     }
 
+    #[test]
+    fn test_rescale() {
+        let rescale = |s: &str, scale, precision| {
+            d(s).rescale(scale, precision)
+                .map(|d| d.as_i128())
+                .map_err(|e| e.to_string())
+        };
+        let overflow = Err("numeric field overflow".to_string());
+        assert_eq!(rescale("1.25", 3, 10), Ok(1250));
+        assert_eq!(rescale("1.25", 1, 10), Ok(13));
+        assert_eq!(rescale("-1.25", 1, 10), Ok(-13));
+        assert_eq!(rescale("1.24", 1, 10), Ok(12));
+        assert_eq!(rescale("1.24", 2, 10), Ok(124));
+        assert_eq!(rescale("99.94", 1, 3), Ok(999));
+        assert_eq!(rescale("99.95", 1, 3), overflow);
+        assert_eq!(rescale("-1000", 0, 3), overflow);
+        assert_eq!(rescale("12345678", 30, 38), Ok(12345678 * 10_i128.pow(30)));
+        assert_eq!(rescale("123456789", 30, 38), overflow);
+        assert_eq!(rescale("12345678901", 30, 38), overflow);
+    }
+
     #[test]
     fn test_parse_decimal() {
         assert_eq!(d("123.45"), Significand::new(12345).with_scale(2));
@@ -76,6 +76,30 @@ impl AstDisplay for AvroSchema {
 }
 impl_display!(AvroSchema);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProtobufSchema {
+    CsrUrl { url: String, seed: Option<CsrSeed> },
+    Schema(Schema),
+}
+
+impl AstDisplay for ProtobufSchema {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            Self::CsrUrl { url, seed } => {
+                f.write_str("CONFLUENT SCHEMA REGISTRY '");
+                f.write_node(&display::escape_single_quote_string(url));
+                f.write_str("'");
+                if let Some(seed) = seed {
+                    f.write_str(" ");
+                    f.write_node(seed);
+                }
+            }
+            Self::Schema(schema) => schema.fmt(f),
+        }
+    }
+}
+impl_display!(ProtobufSchema);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CsrSeed {
     pub key_schema: Option<String>,
@@ -103,7 +127,7 @@ pub enum Format {
     Avro(AvroSchema),
     Protobuf {
         message_name: String,
-        schema: Schema,
+        schema: ProtobufSchema,
     },
     Regex(String),
     Csv {
@@ -111,7 +135,11 @@ pub enum Format {
         n_cols: Option<usize>,
         delimiter: char,
     },
-    Json,
+    Json {
+        /// The columns to extract from each JSON object. If empty, each
+        /// message is decoded into a single `jsonb` column.
+        columns: Vec<ColumnDef>,
+    },
     Text,
 }
 
@@ -165,7 +193,7 @@ impl AstDisplay for Format {
                 f.write_str("PROTOBUF MESSAGE '");
                 f.write_node(&display::escape_single_quote_string(message_name));
                 f.write_str("' USING ");
-                f.write_str(schema);
+                f.write_node(schema);
             }
             Self::Regex(regex) => {
                 f.write_str("REGEX '");
@@ -177,11 +205,12 @@ impl AstDisplay for Format {
                 n_cols,
                 delimiter,
             } => {
-                f.write_str("CSV WITH ");
+                f.write_str("CSV");
                 if *header_row {
-                    f.write_str("HEADER");
-                } else {
-                    f.write_str(n_cols.unwrap());
+                    f.write_str(" WITH HEADER");
+                } else if let Some(n_cols) = n_cols {
+                    f.write_str(" WITH ");
+                    f.write_str(n_cols);
                     f.write_str(" COLUMNS");
                 }
                 if *delimiter != ',' {
@@ -190,7 +219,14 @@ impl AstDisplay for Format {
                     f.write_str("'");
                 }
             }
-            Self::Json => f.write_str("JSON"),
+            Self::Json { columns } => {
+                f.write_str("JSON");
+                if !columns.is_empty() {
+                    f.write_str(" (");
+                    f.write_node(&display::comma_separated(columns));
+                    f.write_str(")");
+                }
+            }
             Self::Text => f.write_str("TEXT"),
         }
     }
@@ -213,6 +249,9 @@ pub enum Connector {
     AvroOcf {
         path: String,
     },
+    Http {
+        url: String,
+    },
 }
 
 impl AstDisplay for Connector {
@@ -241,6 +280,11 @@ impl AstDisplay for Connector {
                 f.write_node(&display::escape_single_quote_string(path));
                 f.write_str("'");
             }
+            Connector::Http { url } => {
+                f.write_str("HTTP '");
+                f.write_node(&display::escape_single_quote_string(url));
+                f.write_str("'");
+            }
         }
     }
 }
@@ -271,6 +315,22 @@ impl AstDisplay for AlterTableOperation {
 }
 impl_display!(AlterTableOperation);
 
+/// An `ALTER SOURCE` (`Statement::AlterSource`) operation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AlterSourceOperation {
+    /// `REFRESH SCHEMA`
+    RefreshSchema,
+}
+
+impl AstDisplay for AlterSourceOperation {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            AlterSourceOperation::RefreshSchema => f.write_str("REFRESH SCHEMA"),
+        }
+    }
+}
+impl_display!(AlterSourceOperation);
+
 /// A table-level constraint, specified in a `CREATE TABLE` or an
 /// `ALTER TABLE ADD <constraint>` statement.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -29,6 +29,8 @@ use crate::ast::{Expr, FunctionArgs, Ident, ObjectName};
 pub struct Query {
     /// WITH (common table expressions, or CTEs)
     pub ctes: Vec<Cte>,
+    /// Whether the CTEs were introduced with `WITH RECURSIVE`
+    pub recursive: bool,
     /// SELECT or UNION / EXCEPT / INTECEPT
     pub body: SetExpr,
     /// ORDER BY
@@ -45,6 +47,9 @@ impl AstDisplay for Query {
     fn fmt(&self, f: &mut AstFormatter) {
         if !self.ctes.is_empty() {
             f.write_str("WITH ");
+            if self.recursive {
+                f.write_str("RECURSIVE ");
+            }
             f.write_node(&display::comma_separated(&self.ctes));
             f.write_str(" ");
         }
@@ -74,6 +79,7 @@ impl Query {
     pub fn select(select: Select) -> Query {
         Query {
             ctes: vec![],
+            recursive: false,
             body: SetExpr::Select(Box::new(select)),
             order_by: vec![],
             limit: None,
@@ -87,6 +93,7 @@ impl Query {
             self,
             Query {
                 ctes: vec![],
+                recursive: false,
                 order_by: vec![],
                 body: SetExpr::Values(Values(vec![])),
                 limit: None,
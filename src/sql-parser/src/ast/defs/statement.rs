@@ -20,8 +20,8 @@
 
 use crate::ast::display::{self, AstDisplay, AstFormatter};
 use crate::ast::{
-    AlterTableOperation, ColumnDef, Connector, Envelope, Expr, Format, Ident, ObjectName, Query,
-    TableConstraint, Value,
+    AlterSourceOperation, AlterTableOperation, ColumnDef, Connector, Envelope, Expr, Format, Ident,
+    ObjectName, Query, TableConstraint, Value,
 };
 
 /// A top-level statement (SELECT, INSERT, CREATE, etc.)
@@ -42,13 +42,14 @@ pub enum Statement {
         /// A SQL query that specifies what to insert
         source: Box<Query>,
     },
+    /// `COPY ... FROM STDIN` or `COPY ... TO STDOUT`
     Copy {
-        /// TABLE
-        table_name: ObjectName,
-        /// COLUMNS
-        columns: Vec<Ident>,
-        /// VALUES a vector of values to be copied
-        values: Vec<Option<String>>,
+        /// The table or query whose rows are copied
+        relation: CopyRelation,
+        /// Whether rows are copied from the client or to the client
+        direction: CopyDirection,
+        /// Options that describe the format of the data
+        options: Vec<CopyOption>,
     },
     /// `UPDATE`
     Update {
@@ -92,8 +93,10 @@ pub enum Statement {
         name: ObjectName,
         from: ObjectName,
         connector: Connector,
+        key: Option<Vec<Ident>>,
         with_options: Vec<SqlOption>,
         format: Option<Format>,
+        envelope: Option<Envelope>,
         with_snapshot: bool,
         as_of: Option<Expr>,
         if_not_exists: bool,
@@ -130,6 +133,21 @@ pub enum Statement {
         key_parts: Option<Vec<Expr>>,
         if_not_exists: bool,
     },
+    /// `CREATE ROLE`
+    CreateRole {
+        /// Whether this was actually a `CREATE USER` statement.
+        is_user: bool,
+        /// The role name.
+        name: Ident,
+        /// Any options that were attached, in the order they were presented.
+        options: Vec<RoleOption>,
+    },
+    /// `ALTER SOURCE`
+    AlterSource {
+        /// Source name
+        name: ObjectName,
+        operation: AlterSourceOperation,
+    },
     /// `ALTER TABLE`
     AlterTable {
         /// Table name
@@ -152,6 +170,31 @@ pub enum Statement {
         /// `RESTRICT` or no drop behavior at all was specified.
         cascade: bool,
     },
+    /// `DROP ROLE`
+    DropRole {
+        /// An optional `IF EXISTS` clause. (Non-standard.)
+        if_exists: bool,
+        /// One or more roles to drop.
+        names: Vec<Ident>,
+    },
+    /// `GRANT`
+    Grant {
+        /// The privileges to grant.
+        privileges: Privileges,
+        /// The objects on which to grant the privileges.
+        objects: GrantObjects,
+        /// The roles to which the privileges are granted.
+        grantees: Vec<Ident>,
+    },
+    /// `REVOKE`
+    Revoke {
+        /// The privileges to revoke.
+        privileges: Privileges,
+        /// The objects on which to revoke the privileges.
+        objects: GrantObjects,
+        /// The roles from which the privileges are revoked.
+        grantees: Vec<Ident>,
+    },
     /// `SET <variable>`
     ///
     /// Note: this is not a standard SQL statement, but it is supported by at
@@ -239,7 +282,9 @@ pub enum Statement {
     },
     /// `TAIL`
     Tail {
-        name: ObjectName,
+        relation: TailRelation,
+        /// Whether to report when timestamps are closed.
+        progress: bool,
         with_snapshot: bool,
         as_of: Option<Expr>,
     },
@@ -277,32 +322,19 @@ impl AstDisplay for Statement {
                 f.write_node(&source);
             }
             Statement::Copy {
-                table_name,
-                columns,
-                values,
+                relation,
+                direction,
+                options,
             } => {
                 f.write_str("COPY ");
-                f.write_node(&table_name);
-                if !columns.is_empty() {
-                    f.write_str("(");
-                    f.write_node(&display::comma_separated(columns));
+                f.write_node(relation);
+                f.write_str(" ");
+                f.write_node(direction);
+                if !options.is_empty() {
+                    f.write_str(" WITH (");
+                    f.write_node(&display::comma_separated(options));
                     f.write_str(")");
                 }
-                f.write_str(" FROM stdin; ");
-                if !values.is_empty() {
-                    f.write_str("\n");
-                    let mut delim = "";
-                    for v in values {
-                        f.write_str(delim);
-                        delim = "\t";
-                        if let Some(v) = v {
-                            f.write_str(v);
-                        } else {
-                            f.write_str("\\N");
-                        }
-                    }
-                }
-                f.write_str("\n\\.");
             }
             Statement::Update {
                 table_name,
@@ -396,8 +428,10 @@ impl AstDisplay for Statement {
                 name,
                 from,
                 connector,
+                key,
                 with_options,
                 format,
+                envelope,
                 with_snapshot,
                 as_of,
                 if_not_exists,
@@ -411,6 +445,11 @@ impl AstDisplay for Statement {
                 f.write_node(&from);
                 f.write_str(" INTO ");
                 f.write_node(connector);
+                if let Some(key) = key {
+                    f.write_str(" KEY (");
+                    f.write_node(&display::comma_separated(key));
+                    f.write_str(")");
+                }
                 if !with_options.is_empty() {
                     f.write_str(" WITH (");
                     f.write_node(&display::comma_separated(with_options));
@@ -420,6 +459,10 @@ impl AstDisplay for Statement {
                     f.write_str(" FORMAT ");
                     f.write_node(format);
                 }
+                if let Some(envelope) = envelope {
+                    f.write_str(" ENVELOPE ");
+                    f.write_node(envelope);
+                }
                 if *with_snapshot {
                     f.write_str(" WITH SNAPSHOT");
                 } else {
@@ -527,6 +570,29 @@ impl AstDisplay for Statement {
                     f.write_str(")");
                 }
             }
+            Statement::CreateRole {
+                is_user,
+                name,
+                options,
+            } => {
+                f.write_str("CREATE ");
+                if *is_user {
+                    f.write_str("USER ");
+                } else {
+                    f.write_str("ROLE ");
+                }
+                f.write_node(name);
+                for option in options {
+                    f.write_str(" ");
+                    f.write_node(option);
+                }
+            }
+            Statement::AlterSource { name, operation } => {
+                f.write_str("ALTER SOURCE ");
+                f.write_node(&name);
+                f.write_str(" ");
+                f.write_node(operation);
+            }
             Statement::AlterTable { name, operation } => {
                 f.write_str("ALTER TABLE ");
                 f.write_node(&name);
@@ -557,6 +623,37 @@ impl AstDisplay for Statement {
                     f.write_str(" CASCADE");
                 }
             }
+            Statement::DropRole { if_exists, names } => {
+                f.write_str("DROP ROLE ");
+                if *if_exists {
+                    f.write_str("IF EXISTS ");
+                }
+                f.write_node(&display::comma_separated(names));
+            }
+            Statement::Grant {
+                privileges,
+                objects,
+                grantees,
+            } => {
+                f.write_str("GRANT ");
+                f.write_node(privileges);
+                f.write_str(" ON ");
+                f.write_node(objects);
+                f.write_str(" TO ");
+                f.write_node(&display::comma_separated(grantees));
+            }
+            Statement::Revoke {
+                privileges,
+                objects,
+                grantees,
+            } => {
+                f.write_str("REVOKE ");
+                f.write_node(privileges);
+                f.write_str(" ON ");
+                f.write_node(objects);
+                f.write_str(" FROM ");
+                f.write_node(&display::comma_separated(grantees));
+            }
             Statement::SetVariable {
                 local,
                 variable,
@@ -696,12 +793,17 @@ impl AstDisplay for Statement {
                 }
             }
             Statement::Tail {
-                name,
+                relation,
+                progress,
                 with_snapshot,
                 as_of,
             } => {
                 f.write_str("TAIL ");
-                f.write_node(&name);
+                f.write_node(relation);
+
+                if *progress {
+                    f.write_str(" WITH (PROGRESS)");
+                }
 
                 if *with_snapshot {
                     f.write_str(" WITH SNAPSHOT");
@@ -755,6 +857,112 @@ impl AstDisplay for ObjectType {
 }
 impl_display!(ObjectType);
 
+/// An option in a `CREATE ROLE` statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RoleOption {
+    /// The `SUPERUSER` option.
+    SuperUser,
+    /// The `NOSUPERUSER` option.
+    NoSuperUser,
+    /// The `LOGIN` option.
+    Login,
+    /// The `NOLOGIN` option.
+    NoLogin,
+    /// The `PASSWORD` option. `None` indicates `PASSWORD NULL`.
+    Password(Option<String>),
+}
+
+impl AstDisplay for RoleOption {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            RoleOption::SuperUser => f.write_str("SUPERUSER"),
+            RoleOption::NoSuperUser => f.write_str("NOSUPERUSER"),
+            RoleOption::Login => f.write_str("LOGIN"),
+            RoleOption::NoLogin => f.write_str("NOLOGIN"),
+            RoleOption::Password(None) => f.write_str("PASSWORD NULL"),
+            RoleOption::Password(Some(password)) => {
+                f.write_str("PASSWORD '");
+                f.write_node(&display::escape_single_quote_string(password));
+                f.write_str("'");
+            }
+        }
+    }
+}
+impl_display!(RoleOption);
+
+/// The privileges in a `GRANT` or `REVOKE` statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Privileges {
+    /// `ALL [PRIVILEGES]`.
+    All,
+    /// An explicit list of privileges.
+    Actions(Vec<Privilege>),
+}
+
+impl AstDisplay for Privileges {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            Privileges::All => f.write_str("ALL"),
+            Privileges::Actions(actions) => f.write_node(&display::comma_separated(actions)),
+        }
+    }
+}
+impl_display!(Privileges);
+
+/// A privilege in a `GRANT` or `REVOKE` statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Privilege {
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Create,
+    Usage,
+}
+
+impl AstDisplay for Privilege {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str(match self {
+            Privilege::Select => "SELECT",
+            Privilege::Insert => "INSERT",
+            Privilege::Update => "UPDATE",
+            Privilege::Delete => "DELETE",
+            Privilege::Create => "CREATE",
+            Privilege::Usage => "USAGE",
+        })
+    }
+}
+impl_display!(Privilege);
+
+/// The objects in a `GRANT` or `REVOKE` statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GrantObjects {
+    /// `DATABASE <name> [, ...]`
+    Databases(Vec<Ident>),
+    /// `SCHEMA <name> [, ...]`
+    Schemas(Vec<ObjectName>),
+    /// `[TABLE | VIEW | SOURCE] <name> [, ...]`. The object type is optional
+    /// and is not recorded.
+    Items(Vec<ObjectName>),
+}
+
+impl AstDisplay for GrantObjects {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            GrantObjects::Databases(names) => {
+                f.write_str("DATABASE ");
+                f.write_node(&display::comma_separated(names));
+            }
+            GrantObjects::Schemas(names) => {
+                f.write_str("SCHEMA ");
+                f.write_node(&display::comma_separated(names));
+            }
+            GrantObjects::Items(names) => f.write_node(&display::comma_separated(names)),
+        }
+    }
+}
+impl_display!(GrantObjects);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ShowStatementFilter {
     Like(String),
@@ -794,6 +1002,72 @@ impl AstDisplay for SqlOption {
 }
 impl_display!(SqlOption);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CopyRelation {
+    Table {
+        name: ObjectName,
+        columns: Vec<Ident>,
+    },
+    Select(Box<Query>),
+}
+
+impl AstDisplay for CopyRelation {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            CopyRelation::Table { name, columns } => {
+                f.write_node(name);
+                if !columns.is_empty() {
+                    f.write_str(" (");
+                    f.write_node(&display::comma_separated(columns));
+                    f.write_str(")");
+                }
+            }
+            CopyRelation::Select(query) => {
+                f.write_str("(");
+                f.write_node(query);
+                f.write_str(")");
+            }
+        }
+    }
+}
+impl_display!(CopyRelation);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CopyDirection {
+    /// `FROM STDIN`
+    From,
+    /// `TO STDOUT`
+    To,
+}
+
+impl AstDisplay for CopyDirection {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str(match self {
+            CopyDirection::From => "FROM STDIN",
+            CopyDirection::To => "TO STDOUT",
+        })
+    }
+}
+impl_display!(CopyDirection);
+
+/// An option in a `COPY` statement, like `FORMAT csv` or `HEADER`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CopyOption {
+    pub name: Ident,
+    pub value: Option<Value>,
+}
+
+impl AstDisplay for CopyOption {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_node(&self.name);
+        if let Some(value) = &self.value {
+            f.write_str(" ");
+            f.write_node(value);
+        }
+    }
+}
+impl_display!(CopyOption);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TransactionMode {
     AccessMode(TransactionAccessMode),
@@ -931,6 +1205,27 @@ impl AstDisplay for Explainee {
 }
 impl_display!(Explainee);
 
+/// The relation that a `TAIL` statement watches.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TailRelation {
+    Name(ObjectName),
+    Query(Query),
+}
+
+impl AstDisplay for TailRelation {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            TailRelation::Name(name) => f.write_node(name),
+            TailRelation::Query(query) => {
+                f.write_str("(");
+                f.write_node(query);
+                f.write_str(")");
+            }
+        }
+    }
+}
+impl_display!(TailRelation);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IfExistsBehavior {
     Error,
@@ -170,6 +170,7 @@ define_keywords!(
     DEFAULT,
     DELETE,
     DELIMITED,
+    DELIMITER,
     DENSE_RANK,
     DEREF,
     DESC,
@@ -233,6 +234,7 @@ define_keywords!(
     HOLD,
     HOUR,
     HOURS,
+    HTTP,
     IDENTITY,
     IF,
     IMMEDIATE,
@@ -279,6 +281,7 @@ define_keywords!(
     LOCALTIME,
     LOCALTIMESTAMP,
     LOCATION,
+    LOGIN,
     LOWER,
     MATCH,
     MATERIALIZED,
@@ -305,8 +308,10 @@ define_keywords!(
     NEW,
     NEXT,
     NO,
+    NOLOGIN,
     NONE,
     NORMALIZE,
+    NOSUPERUSER,
     NOT,
     NTH_VALUE,
     NTILE,
@@ -334,6 +339,7 @@ define_keywords!(
     PARAMETER,
     PARQUET,
     PARTITION,
+    PASSWORD,
     PERCENT,
     PERCENT_RANK,
     PERCENTILE_CONT,
@@ -349,9 +355,12 @@ define_keywords!(
     PRECISION,
     PREPARE,
     PRIMARY,
+    PRIVILEGES,
     PROCEDURE,
+    PROGRESS,
     PROTOBUF,
     QUARTER,
+    QUOTE,
     RANGE,
     RANK,
     RAW,
@@ -363,6 +372,7 @@ define_keywords!(
     REF,
     REFERENCES,
     REFERENCING,
+    REFRESH,
     REGCLASS,
     REGEX,
     REGISTRY,
@@ -384,6 +394,7 @@ define_keywords!(
     RETURNS,
     REVOKE,
     RIGHT,
+    ROLE,
     ROLLBACK,
     ROLLUP,
     ROW,
@@ -425,6 +436,7 @@ define_keywords!(
     STDDEV_POP,
     STDDEV_SAMP,
     STDIN,
+    STDOUT,
     STORED,
     STRING,
     SUBMULTISET,
@@ -432,6 +444,7 @@ define_keywords!(
     SUBSTRING_REGEX,
     SUCCEEDS,
     SUM,
+    SUPERUSER,
     SYMMETRIC,
     SYSTEM,
     SYSTEM_TIME,
@@ -475,6 +488,7 @@ define_keywords!(
     UPDATE,
     UPSERT,
     UPPER,
+    USAGE,
     USER,
     USING,
     UUID,
@@ -201,6 +201,8 @@ impl Parser {
                     }
                     "CREATE" => Ok(self.parse_create()?),
                     "DROP" => Ok(self.parse_drop()?),
+                    "GRANT" => Ok(self.parse_grant()?),
+                    "REVOKE" => Ok(self.parse_revoke()?),
                     "DELETE" => Ok(self.parse_delete()?),
                     "INSERT" => Ok(self.parse_insert()?),
                     "UPDATE" => Ok(self.parse_update()?),
@@ -1011,12 +1013,6 @@ impl Parser {
         }
     }
 
-    /// Return the first unprocessed token, possibly whitespace.
-    fn next_token_no_skip(&mut self) -> Option<&Token> {
-        self.index += 1;
-        self.tokens.get(self.index - 1).map(|(token, _range)| token)
-    }
-
     /// Push back the last one non-whitespace token. Must be called after
     /// `next_token()`, otherwise might panic. OK to call after
     /// `next_token()` indicates an EOF.
@@ -1270,10 +1266,14 @@ impl Parser {
         } else if self.parse_keyword("INDEX") {
             self.prev_token();
             self.parse_create_index()
+        } else if self.parse_keyword("ROLE") {
+            self.parse_create_role(false)
+        } else if self.parse_keyword("USER") {
+            self.parse_create_role(true)
         } else {
             self.expected(
                 self.peek_range(),
-                "DATABASE, SCHEMA, [MATERIALIZED] VIEW, SOURCE, SINK, or INDEX after CREATE",
+                "DATABASE, SCHEMA, ROLE, [MATERIALIZED] VIEW, SOURCE, SINK, or INDEX after CREATE",
                 self.peek_token(),
             )
         }
@@ -1288,6 +1288,41 @@ impl Parser {
         })
     }
 
+    fn parse_create_role(&mut self, is_user: bool) -> Result<Statement, ParserError> {
+        let name = self.parse_identifier()?;
+        let _ = self.parse_keyword("WITH");
+        let mut options = vec![];
+        loop {
+            match self.parse_one_of_keywords(&[
+                "SUPERUSER",
+                "NOSUPERUSER",
+                "LOGIN",
+                "NOLOGIN",
+                "PASSWORD",
+            ]) {
+                None => break,
+                Some("SUPERUSER") => options.push(RoleOption::SuperUser),
+                Some("NOSUPERUSER") => options.push(RoleOption::NoSuperUser),
+                Some("LOGIN") => options.push(RoleOption::Login),
+                Some("NOLOGIN") => options.push(RoleOption::NoLogin),
+                Some("PASSWORD") => {
+                    if self.parse_keyword("NULL") {
+                        options.push(RoleOption::Password(None));
+                    } else {
+                        let password = self.parse_literal_string()?;
+                        options.push(RoleOption::Password(Some(password)));
+                    }
+                }
+                Some(_) => unreachable!(),
+            }
+        }
+        Ok(Statement::CreateRole {
+            is_user,
+            name,
+            options,
+        })
+    }
+
     fn parse_create_schema(&mut self) -> Result<Statement, ParserError> {
         let if_not_exists = self.parse_if_not_exists()?;
         let name = self.parse_object_name()?;
@@ -1305,7 +1340,7 @@ impl Parser {
             self.expect_keyword("MESSAGE")?;
             let message_name = self.parse_literal_string()?;
             self.expect_keyword("USING")?;
-            let schema = self.parse_schema()?;
+            let schema = self.parse_protobuf_schema()?;
             Format::Protobuf {
                 message_name,
                 schema,
@@ -1314,15 +1349,23 @@ impl Parser {
             let regex = self.parse_literal_string()?;
             Format::Regex(regex)
         } else if self.parse_keyword("CSV") {
-            self.expect_keyword("WITH")?;
-            let (header_row, n_cols) =
+            // The WITH clause is optional, as sinks determine their columns
+            // from the relation they write. Be careful not to consume the
+            // WITH of a following clause, like `WITH SNAPSHOT`.
+            let (header_row, n_cols) = if self.parse_keyword("WITH") {
                 if self.parse_keyword("HEADER") || self.parse_keyword("HEADERS") {
                     (true, None)
-                } else {
+                } else if let Some(Token::Number(_)) = self.peek_token() {
                     let n_cols = self.parse_literal_uint()? as usize;
                     self.expect_keyword("COLUMNS")?;
                     (false, Some(n_cols))
-                };
+                } else {
+                    self.prev_token();
+                    (false, None)
+                }
+            } else {
+                (false, None)
+            };
             let delimiter = if self.parse_keywords(vec!["DELIMITED", "BY"]) {
                 let s = self.parse_literal_string()?;
                 match s.len() {
@@ -1340,7 +1383,21 @@ impl Parser {
                 delimiter,
             }
         } else if self.parse_keyword("JSON") {
-            Format::Json
+            let columns = if self.consume_token(&Token::LParen) {
+                let columns = self.parse_comma_separated(|parser| {
+                    Ok(ColumnDef {
+                        name: parser.parse_identifier()?,
+                        data_type: parser.parse_data_type()?,
+                        collation: None,
+                        options: vec![],
+                    })
+                })?;
+                self.expect_token(&Token::RParen)?;
+                columns
+            } else {
+                vec![]
+            };
+            Format::Json { columns }
         } else if self.parse_keyword("TEXT") {
             Format::Text
         } else if self.parse_keyword("BYTES") {
@@ -1358,22 +1415,7 @@ impl Parser {
     fn parse_avro_schema(&mut self) -> Result<AvroSchema, ParserError> {
         let avro_schema = if self.parse_keywords(vec!["CONFLUENT", "SCHEMA", "REGISTRY"]) {
             let url = self.parse_literal_string()?;
-            let seed = if self.parse_keyword("SEED") {
-                let key_schema = if self.parse_keyword("KEY") {
-                    self.expect_keyword("SCHEMA")?;
-                    Some(self.parse_literal_string()?)
-                } else {
-                    None
-                };
-                self.expect_keywords(&["VALUE", "SCHEMA"])?;
-                let value_schema = self.parse_literal_string()?;
-                Some(CsrSeed {
-                    key_schema,
-                    value_schema,
-                })
-            } else {
-                None
-            };
+            let seed = self.parse_csr_seed()?;
             AvroSchema::CsrUrl { url, seed }
         } else if self.parse_keyword("SCHEMA") {
             self.prev_token();
@@ -1388,6 +1430,44 @@ impl Parser {
         Ok(avro_schema)
     }
 
+    fn parse_protobuf_schema(&mut self) -> Result<ProtobufSchema, ParserError> {
+        let protobuf_schema = if self.parse_keywords(vec!["CONFLUENT", "SCHEMA", "REGISTRY"]) {
+            let url = self.parse_literal_string()?;
+            let seed = self.parse_csr_seed()?;
+            ProtobufSchema::CsrUrl { url, seed }
+        } else if self.parse_keyword("SCHEMA") {
+            self.prev_token();
+            ProtobufSchema::Schema(self.parse_schema()?)
+        } else {
+            return self.expected(
+                self.peek_range(),
+                "CONFLUENT SCHEMA REGISTRY or SCHEMA",
+                self.peek_token(),
+            );
+        };
+        Ok(protobuf_schema)
+    }
+
+    fn parse_csr_seed(&mut self) -> Result<Option<CsrSeed>, ParserError> {
+        let seed = if self.parse_keyword("SEED") {
+            let key_schema = if self.parse_keyword("KEY") {
+                self.expect_keyword("SCHEMA")?;
+                Some(self.parse_literal_string()?)
+            } else {
+                None
+            };
+            self.expect_keywords(&["VALUE", "SCHEMA"])?;
+            let value_schema = self.parse_literal_string()?;
+            Some(CsrSeed {
+                key_schema,
+                value_schema,
+            })
+        } else {
+            None
+        };
+        Ok(seed)
+    }
+
     fn parse_schema(&mut self) -> Result<Schema, ParserError> {
         self.expect_keyword("SCHEMA")?;
         let schema = if self.parse_keyword("FILE") {
@@ -1459,6 +1539,11 @@ impl Parser {
         let from = self.parse_object_name()?;
         self.expect_keyword("INTO")?;
         let connector = self.parse_connector()?;
+        let key = if self.parse_keyword("KEY") {
+            Some(self.parse_parenthesized_column_list(Mandatory)?)
+        } else {
+            None
+        };
         let mut with_options = vec![];
         if self.parse_keyword("WITH") {
             if let Some(Token::LParen) = self.next_token() {
@@ -1472,6 +1557,11 @@ impl Parser {
         } else {
             None
         };
+        let envelope = if self.parse_keyword("ENVELOPE") {
+            Some(self.parse_envelope()?)
+        } else {
+            None
+        };
         let with_snapshot = if self.parse_keyword("WITH") {
             self.expect_keyword("SNAPSHOT")?;
             true
@@ -1488,8 +1578,10 @@ impl Parser {
             name,
             from,
             connector,
+            key,
             with_options,
             format,
+            envelope,
             with_snapshot,
             as_of,
             if_not_exists,
@@ -1497,7 +1589,7 @@ impl Parser {
     }
 
     fn parse_connector(&mut self) -> Result<Connector, ParserError> {
-        match self.expect_one_of_keywords(&["FILE", "KAFKA", "KINESIS", "AVRO"])? {
+        match self.expect_one_of_keywords(&["FILE", "KAFKA", "KINESIS", "AVRO", "HTTP"])? {
             "FILE" => {
                 let path = self.parse_literal_string()?;
                 Ok(Connector::File { path })
@@ -1519,6 +1611,10 @@ impl Parser {
                 let path = self.parse_literal_string()?;
                 Ok(Connector::AvroOcf { path })
             }
+            "HTTP" => {
+                let url = self.parse_literal_string()?;
+                Ok(Connector::Http { url })
+            }
             _ => unreachable!(),
         }
     }
@@ -1619,7 +1715,7 @@ impl Parser {
 
     fn parse_drop(&mut self) -> Result<Statement, ParserError> {
         let object_type = match self.parse_one_of_keywords(&[
-            "DATABASE", "SCHEMA", "TABLE", "VIEW", "SOURCE", "SINK", "INDEX",
+            "DATABASE", "SCHEMA", "ROLE", "USER", "TABLE", "VIEW", "SOURCE", "SINK", "INDEX",
         ]) {
             Some("DATABASE") => {
                 return Ok(Statement::DropDatabase {
@@ -1627,6 +1723,12 @@ impl Parser {
                     name: self.parse_identifier()?,
                 });
             }
+            Some("ROLE") | Some("USER") => {
+                return Ok(Statement::DropRole {
+                    if_exists: self.parse_if_exists()?,
+                    names: self.parse_comma_separated(Parser::parse_identifier)?,
+                });
+            }
             Some("SCHEMA") => ObjectType::Schema,
             Some("TABLE") => ObjectType::Table,
             Some("VIEW") => ObjectType::View,
@@ -1636,7 +1738,7 @@ impl Parser {
             _ => {
                 return self.expected(
                     self.peek_range(),
-                    "DATABASE, SCHEMA, TABLE, VIEW, SOURCE, SINK, or INDEX after DROP",
+                    "DATABASE, SCHEMA, ROLE, TABLE, VIEW, SOURCE, SINK, or INDEX after DROP",
                     self.peek_token(),
                 )
             }
@@ -1663,6 +1765,70 @@ impl Parser {
         })
     }
 
+    fn parse_grant(&mut self) -> Result<Statement, ParserError> {
+        let privileges = self.parse_privileges()?;
+        let objects = self.parse_grant_objects()?;
+        self.expect_keyword("TO")?;
+        let grantees = self.parse_comma_separated(Parser::parse_identifier)?;
+        Ok(Statement::Grant {
+            privileges,
+            objects,
+            grantees,
+        })
+    }
+
+    fn parse_revoke(&mut self) -> Result<Statement, ParserError> {
+        let privileges = self.parse_privileges()?;
+        let objects = self.parse_grant_objects()?;
+        self.expect_keyword("FROM")?;
+        let grantees = self.parse_comma_separated(Parser::parse_identifier)?;
+        Ok(Statement::Revoke {
+            privileges,
+            objects,
+            grantees,
+        })
+    }
+
+    fn parse_privileges(&mut self) -> Result<Privileges, ParserError> {
+        if self.parse_keyword("ALL") {
+            let _ = self.parse_keyword("PRIVILEGES");
+            return Ok(Privileges::All);
+        }
+        let actions = self.parse_comma_separated(|parser| {
+            match parser
+                .parse_one_of_keywords(&["SELECT", "INSERT", "UPDATE", "DELETE", "CREATE", "USAGE"])
+            {
+                Some("SELECT") => Ok(Privilege::Select),
+                Some("INSERT") => Ok(Privilege::Insert),
+                Some("UPDATE") => Ok(Privilege::Update),
+                Some("DELETE") => Ok(Privilege::Delete),
+                Some("CREATE") => Ok(Privilege::Create),
+                Some("USAGE") => Ok(Privilege::Usage),
+                _ => parser.expected(
+                    parser.peek_range(),
+                    "ALL, SELECT, INSERT, UPDATE, DELETE, CREATE, or USAGE",
+                    parser.peek_token(),
+                ),
+            }
+        })?;
+        Ok(Privileges::Actions(actions))
+    }
+
+    fn parse_grant_objects(&mut self) -> Result<GrantObjects, ParserError> {
+        self.expect_keyword("ON")?;
+        match self.parse_one_of_keywords(&["DATABASE", "SCHEMA", "TABLE", "VIEW", "SOURCE"]) {
+            Some("DATABASE") => Ok(GrantObjects::Databases(
+                self.parse_comma_separated(Parser::parse_identifier)?,
+            )),
+            Some("SCHEMA") => Ok(GrantObjects::Schemas(
+                self.parse_comma_separated(Parser::parse_object_name)?,
+            )),
+            _ => Ok(GrantObjects::Items(
+                self.parse_comma_separated(Parser::parse_object_name)?,
+            )),
+        }
+    }
+
     fn parse_create_table(&mut self) -> Result<Statement, ParserError> {
         let if_not_exists = self.parse_if_not_exists()?;
         let table_name = self.parse_object_name()?;
@@ -1842,7 +2008,9 @@ impl Parser {
     }
 
     fn parse_alter(&mut self) -> Result<Statement, ParserError> {
-        self.expect_keyword("TABLE")?;
+        if self.expect_one_of_keywords(&["SOURCE", "TABLE"])? == "SOURCE" {
+            return self.parse_alter_source();
+        }
         let _ = self.parse_keyword("ONLY");
         let table_name = self.parse_object_name()?;
         let operation = if self.parse_keyword("ADD") {
@@ -1868,60 +2036,106 @@ impl Parser {
         })
     }
 
+    fn parse_alter_source(&mut self) -> Result<Statement, ParserError> {
+        let name = self.parse_object_name()?;
+        let operation = if self.parse_keywords(vec!["REFRESH", "SCHEMA"]) {
+            AlterSourceOperation::RefreshSchema
+        } else {
+            return self.expected(
+                self.peek_range(),
+                "REFRESH SCHEMA after ALTER SOURCE",
+                self.peek_token(),
+            );
+        };
+        Ok(Statement::AlterSource { name, operation })
+    }
+
     /// Parse a copy statement
     fn parse_copy(&mut self) -> Result<Statement, ParserError> {
-        let table_name = self.parse_object_name()?;
-        let columns = self.parse_parenthesized_column_list(Optional)?;
-        self.expect_keywords(&["FROM", "STDIN"])?;
-        self.expect_token(&Token::SemiColon)?;
-        let values = self.parse_tsv()?;
+        let relation = if self.consume_token(&Token::LParen) {
+            let query = self.parse_query()?;
+            self.expect_token(&Token::RParen)?;
+            CopyRelation::Select(Box::new(query))
+        } else {
+            let name = self.parse_object_name()?;
+            let columns = self.parse_parenthesized_column_list(Optional)?;
+            CopyRelation::Table { name, columns }
+        };
+        let direction = match relation {
+            // Query results can only be copied out.
+            CopyRelation::Select(_) => {
+                self.expect_keywords(&["TO", "STDOUT"])?;
+                CopyDirection::To
+            }
+            CopyRelation::Table { .. } => {
+                if self.parse_keyword("FROM") {
+                    self.expect_keyword("STDIN")?;
+                    CopyDirection::From
+                } else {
+                    self.expect_keywords(&["TO", "STDOUT"])?;
+                    CopyDirection::To
+                }
+            }
+        };
+        let with = self.parse_keyword("WITH");
+        let options = if self.consume_token(&Token::LParen) {
+            let options = self.parse_comma_separated(Parser::parse_copy_option)?;
+            self.expect_token(&Token::RParen)?;
+            options
+        } else {
+            let options = self.parse_legacy_copy_options()?;
+            if with && options.is_empty() {
+                return self.expected(self.peek_range(), "copy options", self.peek_token());
+            }
+            options
+        };
         Ok(Statement::Copy {
-            table_name,
-            columns,
-            values,
+            relation,
+            direction,
+            options,
         })
     }
 
-    /// Parse a tab separated values in
-    /// COPY payload
-    fn parse_tsv(&mut self) -> Result<Vec<Option<String>>, ParserError> {
-        let values = self.parse_tab_value()?;
-        Ok(values)
+    /// Parse an option in the parenthesized option list of a copy
+    /// statement, like `FORMAT csv` or `HEADER`.
+    fn parse_copy_option(&mut self) -> Result<CopyOption, ParserError> {
+        let name = self.parse_identifier()?;
+        let value = match self.peek_token() {
+            None | Some(Token::Comma) | Some(Token::RParen) => None,
+            Some(Token::Word(w)) if w.keyword != "TRUE" && w.keyword != "FALSE" => {
+                Some(Value::String(self.parse_identifier()?.as_str().to_owned()))
+            }
+            Some(_) => Some(self.parse_value()?),
+        };
+        Ok(CopyOption { name, value })
     }
 
-    fn parse_tab_value(&mut self) -> Result<Vec<Option<String>>, ParserError> {
-        let mut values = vec![];
-        let mut content = String::from("");
-        while let Some(t) = self.next_token_no_skip() {
-            match t {
-                Token::Whitespace(Whitespace::Tab) => {
-                    values.push(Some(content.to_string()));
-                    content.clear();
-                }
-                Token::Whitespace(Whitespace::Newline) => {
-                    values.push(Some(content.to_string()));
-                    content.clear();
-                }
-                Token::Backslash => {
-                    if self.consume_token(&Token::Period) {
-                        return Ok(values);
-                    }
-                    if let Some(token) = self.next_token() {
-                        if let Token::Word(Word { value: v, .. }) = token {
-                            if v == "N" {
-                                values.push(None);
-                            }
-                        }
-                    } else {
-                        continue;
-                    }
-                }
-                _ => {
-                    content.push_str(&t.to_string());
-                }
+    /// Parse the unparenthesized options that PostgreSQL accepts in copy
+    /// statements for backwards compatibility, like `CSV HEADER`. This is the
+    /// syntax that `psql`'s `\copy` command generates.
+    fn parse_legacy_copy_options(&mut self) -> Result<Vec<CopyOption>, ParserError> {
+        let mut options = vec![];
+        loop {
+            let option = |name: &str, value: Option<Value>| CopyOption {
+                name: Ident::new(name),
+                value,
+            };
+            if self.parse_keyword("BINARY") {
+                options.push(option("format", Some(Value::String("binary".into()))));
+            } else if self.parse_keyword("CSV") {
+                options.push(option("format", Some(Value::String("csv".into()))));
+            } else if self.parse_keyword("HEADER") {
+                options.push(option("header", None));
+            } else if let Some(name) =
+                self.parse_one_of_keywords(&["DELIMITER", "NULL", "QUOTE", "ESCAPE"])
+            {
+                let _ = self.parse_keyword("AS");
+                let value = self.parse_literal_string()?;
+                options.push(option(&name.to_lowercase(), Some(Value::String(value))));
+            } else {
+                return Ok(options);
             }
         }
-        Ok(values)
     }
 
     /// Parse a literal value (numbers, strings, date/time, booleans)
@@ -2235,11 +2449,11 @@ impl Parser {
     /// expect the initial keyword to be already consumed
     fn parse_query(&mut self) -> Result<Query, ParserError> {
         self.check_descent(|parser| {
-            let ctes = if parser.parse_keyword("WITH") {
-                // TODO: optional RECURSIVE
-                parser.parse_comma_separated(Parser::parse_cte)?
+            let (ctes, recursive) = if parser.parse_keyword("WITH") {
+                let recursive = parser.parse_keyword("RECURSIVE");
+                (parser.parse_comma_separated(Parser::parse_cte)?, recursive)
             } else {
-                vec![]
+                (vec![], false)
             };
 
             let body = parser.parse_query_body(Precedence::Zero)?;
@@ -2270,6 +2484,7 @@ impl Parser {
 
             Ok(Query {
                 ctes,
+                recursive,
                 body,
                 limit,
                 order_by,
@@ -2603,10 +2818,19 @@ impl Parser {
         let mut joins = vec![];
         loop {
             let join = if self.parse_keyword("CROSS") {
-                self.expect_keyword("JOIN")?;
+                let join_operator = match self.expect_one_of_keywords(&["JOIN", "APPLY"])? {
+                    "JOIN" => JoinOperator::CrossJoin,
+                    "APPLY" => JoinOperator::CrossApply,
+                    _ => unreachable!(),
+                };
+                Join {
+                    relation: self.parse_table_factor()?,
+                    join_operator,
+                }
+            } else if self.parse_keywords(vec!["OUTER", "APPLY"]) {
                 Join {
                     relation: self.parse_table_factor()?,
-                    join_operator: JoinOperator::CrossJoin,
+                    join_operator: JoinOperator::OuterApply,
                 }
             } else {
                 let natural = self.parse_keyword("NATURAL");
@@ -3007,7 +3231,27 @@ impl Parser {
     }
 
     fn parse_tail(&mut self) -> Result<Statement, ParserError> {
-        let name = self.parse_object_name()?;
+        let relation = if self.consume_token(&Token::LParen) {
+            let query = self.parse_query()?;
+            self.expect_token(&Token::RParen)?;
+            TailRelation::Query(query)
+        } else {
+            TailRelation::Name(self.parse_object_name()?)
+        };
+
+        // Be careful not to consume the WITH of a following `WITH SNAPSHOT`.
+        let progress = if self.parse_keyword("WITH") {
+            if self.consume_token(&Token::LParen) {
+                self.expect_keyword("PROGRESS")?;
+                self.expect_token(&Token::RParen)?;
+                true
+            } else {
+                self.prev_token();
+                false
+            }
+        } else {
+            false
+        };
 
         let with_snapshot = if self.parse_keyword("WITH") {
             self.expect_keyword("SNAPSHOT")?;
@@ -3022,7 +3266,8 @@ impl Parser {
         };
         let as_of = self.parse_optional_as_of()?;
         Ok(Statement::Tail {
-            name,
+            relation,
+            progress,
             with_snapshot,
             as_of,
         })
@@ -111,9 +111,9 @@ pub enum Token {
     Colon,
     /// DoubleColon `::` (used for casting in postgresql)
     DoubleColon,
-    /// SemiColon `;` used as separator for COPY and payload
+    /// SemiColon `;`
     SemiColon,
-    /// Backslash `\` used in terminating the COPY payload with `\.`
+    /// Backslash `\`
     Backslash,
     /// Left bracket `[`
     LBracket,
@@ -16,6 +16,7 @@ use std::time::SystemTime;
 
 use expr::{GlobalId, ScalarExpr};
 use repr::RelationDesc;
+use serde::{Deserialize, Serialize};
 
 use crate::names::{DatabaseSpecifier, FullName, PartialName};
 use crate::plan::PlanContext;
@@ -27,9 +28,9 @@ use crate::plan::PlanContext;
 ///
 /// The SQL standard mandates a catalog hierarchy of exactly three layers. A
 /// catalog contains databases, databases contain schemas, and schemas contain
-/// catalog items, like sources, sinks, view, and indexes.
+/// catalog items, like sources, sinks, tables, views, and indexes.
 ///
-/// There are two classes of operations provided by a catalog:
+/// There are three classes of operations provided by a catalog:
 ///
 ///   * Resolution operations, like [`resolve_item`]. These fill in missing name
 ///     components based upon connection defaults, e.g., resolving the partial
@@ -41,9 +42,14 @@ use crate::plan::PlanContext;
 ///     or was constructed based on the output of a prior lookup operation).
 ///     These functions panic if called with invalid input.
 ///
+///   * Access control operations, like [`check_privilege`]. These determine
+///     whether the user on whose behalf the catalog is being consulted is
+///     permitted to access a catalog entity.
+///
 /// [`list_databases`]: Catalog::list_databases
 /// [`get_item`]: Catalog::resolve_item
 /// [`resolve_item`]: Catalog::resolve_item
+/// [`check_privilege`]: Catalog::check_privilege
 pub trait Catalog: fmt::Debug {
     /// Returns the time at which the catalog booted.
     ///
@@ -71,6 +77,13 @@ pub trait Catalog: fmt::Debug {
     /// existence check for the named database.
     fn resolve_database(&self, database_name: &str) -> Result<(), failure::Error>;
 
+    /// Resolves the named role.
+    ///
+    /// If `role_name` exists in the catalog, it returns `Ok`; otherwise it
+    /// returns an error. Like databases, roles have no notion of a partial
+    /// name, so this function amounts to an existence check.
+    fn resolve_role(&self, role_name: &str) -> Result<(), failure::Error>;
+
     /// Resolves a partially-specified schema name.
     ///
     /// If `database_name` is provided, it searches the named database for a
@@ -128,6 +141,23 @@ pub trait Catalog: fmt::Debug {
     ///
     /// Panics if `id` does not specify a valid item.
     fn get_item_by_id(&self, id: &GlobalId) -> &dyn CatalogItem;
+
+    /// Checks whether the current user holds `privilege` on `object`.
+    ///
+    /// The owner of an object implicitly holds all privileges on it. It
+    /// returns an error if the privilege is not held, or if `object` does not
+    /// exist.
+    fn check_privilege(
+        &self,
+        object: &ObjectRef,
+        privilege: Privilege,
+    ) -> Result<(), failure::Error>;
+
+    /// Checks whether the current user owns `object`.
+    ///
+    /// It returns an error if the current user is not the owner, or if
+    /// `object` does not exist.
+    fn check_owner(&self, object: &ObjectRef) -> Result<(), failure::Error>;
 }
 
 /// An item in a [`Catalog`].
@@ -176,6 +206,8 @@ pub enum CatalogItemType {
     Source,
     /// A sink.
     Sink,
+    /// A table.
+    Table,
     /// A view.
     View,
     /// An index.
@@ -187,12 +219,60 @@ impl fmt::Display for CatalogItemType {
         match self {
             CatalogItemType::Source => f.write_str("source"),
             CatalogItemType::Sink => f.write_str("sink"),
+            CatalogItemType::Table => f.write_str("table"),
             CatalogItemType::View => f.write_str("view"),
             CatalogItemType::Index => f.write_str("index"),
         }
     }
 }
 
+/// The name of the pseudo-role that implicitly includes every role.
+///
+/// Privileges granted to this role are held by all roles.
+pub const PUBLIC_ROLE: &str = "public";
+
+/// A privilege that can be granted on a database, schema, or catalog item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Privilege {
+    /// Permits reading from a source, table, or view.
+    Select,
+    /// Permits inserting rows into a table.
+    Insert,
+    /// Permits updating rows in a table.
+    Update,
+    /// Permits deleting rows from a table.
+    Delete,
+    /// Permits creating schemas in a database, or items in a schema.
+    Create,
+    /// Permits referring to the objects in a database.
+    Usage,
+}
+
+impl fmt::Display for Privilege {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Privilege::Select => f.write_str("SELECT"),
+            Privilege::Insert => f.write_str("INSERT"),
+            Privilege::Update => f.write_str("UPDATE"),
+            Privilege::Delete => f.write_str("DELETE"),
+            Privilege::Create => f.write_str("CREATE"),
+            Privilege::Usage => f.write_str("USAGE"),
+        }
+    }
+}
+
+/// A reference to an object in a [`Catalog`] that is subject to access
+/// control.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ObjectRef {
+    /// The named database.
+    Database(String),
+    /// The named schema in the specified database.
+    Schema(DatabaseSpecifier, String),
+    /// The catalog item with the given ID.
+    Item(GlobalId),
+}
+
 /// A dummy [`Catalog`] implementation.
 ///
 /// This implementation is suitable for use in tests that plan queries which are
@@ -217,6 +297,10 @@ impl Catalog for DummyCatalog {
         unimplemented!();
     }
 
+    fn resolve_role(&self, _: &str) -> Result<(), failure::Error> {
+        unimplemented!();
+    }
+
     fn resolve_schema(
         &self,
         _: Option<String>,
@@ -252,4 +336,12 @@ impl Catalog for DummyCatalog {
     fn get_item_by_id(&self, _: &GlobalId) -> &dyn CatalogItem {
         unimplemented!();
     }
+
+    fn check_privilege(&self, _: &ObjectRef, _: Privilege) -> Result<(), failure::Error> {
+        unimplemented!();
+    }
+
+    fn check_owner(&self, _: &ObjectRef) -> Result<(), failure::Error> {
+        unimplemented!();
+    }
 }
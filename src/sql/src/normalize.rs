@@ -16,8 +16,8 @@ use repr::ColumnName;
 use sql_parser::ast::display::AstDisplay;
 use sql_parser::ast::visit_mut::{self, VisitMut};
 use sql_parser::ast::{
-    Function, FunctionArgs, Ident, IfExistsBehavior, ObjectName, SqlOption, Statement, TableFactor,
-    Value,
+    Function, FunctionArgs, Ident, IfExistsBehavior, ObjectName, Query, SqlOption, Statement,
+    TableFactor, Value,
 };
 
 use crate::names::{DatabaseSpecifier, FullName, PartialName};
@@ -77,7 +77,7 @@ pub fn unresolve(name: FullName) -> ObjectName {
     ObjectName(out)
 }
 
-/// Normalizes a `CREATE { SOURCE | VIEW | INDEX | SINK }` statement so that the
+/// Normalizes a `CREATE { SOURCE | TABLE | VIEW | INDEX | SINK }` statement so that the
 /// statement does not depend upon any session parameters, nor specify any
 /// non-default options (like `MATERIALIZED`, `IF NOT EXISTS`, etc).
 ///
@@ -104,6 +104,8 @@ pub fn create_statement(
 
     struct QueryNormalizer<'a> {
         scx: &'a StatementContext<'a>,
+        /// The names of the common table expressions that are in scope.
+        ctes: Vec<String>,
         err: Option<failure::Error>,
     }
 
@@ -124,6 +126,36 @@ pub fn create_statement(
             }
         }
 
+        fn visit_query_mut(&mut self, query: &'ast mut Query) {
+            // Each common table expression is visible in the body of the query
+            // and in the common table expressions that follow it, but not in
+            // its own definition unless the query is `WITH RECURSIVE`.
+            let n = self.ctes.len();
+            for cte in &mut query.ctes {
+                let name = ident(cte.alias.name.clone());
+                if query.recursive {
+                    self.ctes.push(name.clone());
+                }
+                self.visit_query_mut(&mut cte.query);
+                self.visit_table_alias_mut(&mut cte.alias);
+                self.ctes.push(name);
+            }
+            self.visit_set_expr_mut(&mut query.body);
+            for order_by in &mut query.order_by {
+                self.visit_order_by_expr_mut(order_by);
+            }
+            if let Some(limit) = &mut query.limit {
+                self.visit_expr_mut(limit);
+            }
+            if let Some(offset) = &mut query.offset {
+                self.visit_expr_mut(offset);
+            }
+            if let Some(fetch) = &mut query.fetch {
+                self.visit_fetch_mut(fetch);
+            }
+            self.ctes.truncate(n);
+        }
+
         fn visit_table_factor_mut(&mut self, table_factor: &'ast mut TableFactor) {
             match table_factor {
                 TableFactor::Table {
@@ -133,8 +165,13 @@ pub fn create_statement(
                     with_hints,
                 } => {
                     // Only attempt to resolve the name if it is not a table
-                    // function (i.e., there are no arguments).
-                    if args.is_none() {
+                    // function (i.e., there are no arguments) or a reference
+                    // to a common table expression.
+                    let is_cte = match &name.0[..] {
+                        [item] => self.ctes.contains(&ident(item.clone())),
+                        _ => false,
+                    };
+                    if args.is_none() && !is_cte {
                         self.visit_object_name_mut(name)
                     }
                     match args {
@@ -196,8 +233,10 @@ pub fn create_statement(
             name,
             from,
             connector: _,
+            key: _,
             with_options: _,
             format: _,
+            envelope: _,
             with_snapshot: _,
             as_of: _,
             if_not_exists,
@@ -207,6 +246,17 @@ pub fn create_statement(
             *if_not_exists = false;
         }
 
+        Statement::CreateTable {
+            name,
+            columns: _,
+            constraints: _,
+            with_options: _,
+            if_not_exists,
+        } => {
+            *name = allocate_name(name)?;
+            *if_not_exists = false;
+        }
+
         Statement::CreateView {
             name,
             columns: _,
@@ -222,7 +272,11 @@ pub fn create_statement(
                 allocate_name(name)?
             };
             {
-                let mut normalizer = QueryNormalizer { scx, err: None };
+                let mut normalizer = QueryNormalizer {
+                    scx,
+                    ctes: vec![],
+                    err: None,
+                };
                 normalizer.visit_query_mut(query);
                 if let Some(err) = normalizer.err {
                     return Err(err);
@@ -239,7 +293,11 @@ pub fn create_statement(
             if_not_exists,
         } => {
             *on_name = resolve_item(on_name)?;
-            let mut normalizer = QueryNormalizer { scx, err: None };
+            let mut normalizer = QueryNormalizer {
+                scx,
+                ctes: vec![],
+                err: None,
+            };
             if let Some(key_parts) = key_parts {
                 for key_part in key_parts {
                     normalizer.visit_expr_mut(key_part);
@@ -34,7 +34,7 @@ use dataflow_types::{PeekWhen, SinkConnectorBuilder, SourceConnector, Timestamp}
 use repr::{ColumnName, RelationDesc, Row, ScalarType};
 
 use crate::ast::{ExplainOptions, ExplainStage, ObjectType, Statement};
-use crate::catalog::Catalog;
+use crate::catalog::{Catalog, ObjectRef, Privilege};
 use crate::names::{DatabaseSpecifier, FullName};
 
 pub(crate) mod decorrelate;
@@ -71,6 +71,13 @@ pub enum Plan {
         if_not_exists: bool,
         materialized: bool,
     },
+    /// Refreshes the schema of the source `id` by purifying and planning
+    /// `stmt`, a copy of the statement that created the source from which
+    /// the schemas fetched from the registry have been removed.
+    AlterSourceRefreshSchema {
+        id: GlobalId,
+        stmt: Statement,
+    },
     CreateSink {
         name: FullName,
         sink: Sink,
@@ -80,7 +87,7 @@ pub enum Plan {
     },
     CreateTable {
         name: FullName,
-        desc: RelationDesc,
+        table: Table,
         if_not_exists: bool,
     },
     CreateView {
@@ -97,6 +104,14 @@ pub enum Plan {
         index: Index,
         if_not_exists: bool,
     },
+    CreateRole {
+        name: String,
+        superuser: bool,
+        login: bool,
+        /// The role's password, in plain text. `None` indicates that the role
+        /// has no password and so cannot authenticate with one.
+        password: Option<String>,
+    },
     DropDatabase {
         name: String,
     },
@@ -108,6 +123,17 @@ pub enum Plan {
         items: Vec<GlobalId>,
         ty: ObjectType,
     },
+    DropRoles {
+        names: Vec<String>,
+    },
+    Grant {
+        objects: Vec<(ObjectRef, Vec<Privilege>)>,
+        grantees: Vec<String>,
+    },
+    Revoke {
+        objects: Vec<(ObjectRef, Vec<Privilege>)>,
+        grantees: Vec<String>,
+    },
     EmptyQuery,
     ShowAllVariables,
     ShowVariable(String),
@@ -125,7 +151,9 @@ pub enum Plan {
         materialize: bool,
     },
     Tail {
-        id: GlobalId,
+        from: TailFrom,
+        /// Whether to report when timestamps are closed.
+        progress: bool,
         with_snapshot: bool,
         ts: Option<Timestamp>,
     },
@@ -143,6 +171,38 @@ pub enum Plan {
         affected_rows: usize,
         kind: MutationKind,
     },
+    Insert {
+        id: GlobalId,
+        values: ::expr::RelationExpr,
+    },
+    /// Inserts rows streamed from the client into a table.
+    ///
+    /// `columns` holds the index of the table column that each column of the
+    /// streamed rows feeds. Table columns that are not listed are null.
+    CopyFrom {
+        id: GlobalId,
+        columns: Vec<usize>,
+        params: CopyParams,
+    },
+    /// Streams the results of a one-off query to the client, like `Peek`,
+    /// but encoded as described by `params`.
+    CopyTo {
+        source: ::expr::RelationExpr,
+        finishing: RowSetFinishing,
+        desc: RelationDesc,
+        params: CopyParams,
+    },
+    /// Reads the rows of a table selected by `selection` and writes the
+    /// corresponding diffs back to the table.
+    ///
+    /// For deletes, `selection` produces the rows to retract. For updates,
+    /// `selection` produces each selected row followed by its replacement, so
+    /// its arity is twice that of the table.
+    ReadThenWrite {
+        id: GlobalId,
+        selection: ::expr::RelationExpr,
+        kind: MutationKind,
+    },
     ShowViews {
         ids: Vec<(String, GlobalId)>,
         full: bool,
@@ -158,6 +218,12 @@ pub struct Source {
     pub desc: RelationDesc,
 }
 
+#[derive(Clone, Debug)]
+pub struct Table {
+    pub create_sql: String,
+    pub desc: RelationDesc,
+}
+
 #[derive(Clone, Debug)]
 pub struct Sink {
     pub create_sql: String,
@@ -173,6 +239,16 @@ pub struct View {
     pub temporary: bool,
 }
 
+/// The relation that a `TAIL` statement watches.
+#[derive(Debug)]
+pub enum TailFrom {
+    /// A source, table, or view in the catalog.
+    Id(GlobalId),
+    /// An ad-hoc query, which must be maintained by a transient dataflow for
+    /// the life of the `TAIL`.
+    Query(::expr::RelationExpr),
+}
+
 #[derive(Clone, Debug)]
 pub struct Index {
     pub create_sql: String,
@@ -180,6 +256,36 @@ pub struct Index {
     pub keys: Vec<::expr::ScalarExpr>,
 }
 
+/// The encoding of the data transferred by a `COPY` statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// PostgreSQL's tab-separated text format.
+    Text,
+    /// Comma-separated values.
+    Csv,
+    /// PostgreSQL's binary format, in which each value uses the binary
+    /// encoding of its type.
+    Binary,
+}
+
+/// Describes the data transferred by a `COPY` statement.
+#[derive(Debug, Clone)]
+pub struct CopyParams {
+    pub format: CopyFormat,
+    /// The byte that separates the columns of a row.
+    pub delimiter: u8,
+    /// The string that represents a null value.
+    pub null: String,
+    /// Whether the data begins with a header line. Only applies to the CSV
+    /// format.
+    pub header: bool,
+    /// The byte that quotes values in the CSV format.
+    pub quote: u8,
+    /// The byte that escapes quote bytes within quoted values in the CSV
+    /// format.
+    pub escape: u8,
+}
+
 #[derive(Debug)]
 pub enum MutationKind {
     Insert,
@@ -87,6 +87,15 @@ impl RelationExpr {
     /// This requires rewriting all correlated subqueries (nested `RelationExpr`s) into flat queries
     pub fn decorrelate(mut self) -> expr::RelationExpr {
         let mut id_gen = expr::IdGen::default();
+        // The local identifiers bound by common table expressions were chosen
+        // during planning. Reassign them from `id_gen` so that they cannot
+        // collide with the identifiers allocated during decorrelation.
+        let mut local_ids = HashMap::new();
+        self.visit_local_ids(&mut |id| {
+            *id = *local_ids
+                .entry(*id)
+                .or_insert_with(|| expr::LocalId::new(id_gen.allocate_id()));
+        });
         transform_expr::split_subquery_predicates(&mut self);
         transform_expr::try_simplify_quantified_comparisons(&mut self);
         expr::RelationExpr::constant(vec![vec![]], RelationType::new(vec![]))
@@ -132,9 +141,40 @@ impl RelationExpr {
                 })
             }
             Get { id, typ } => {
-                // Get statements are only to external sources, and are not correlated with `get_outer`.
+                // Get statements are only to external sources or to the values of `Let` and
+                // `LetRec` bindings, and are not correlated with `get_outer`.
                 get_outer.product(SR::Get { id, typ })
             }
+            Let { id, value, body } => {
+                // The value of a `Let` is never correlated, so it is computed
+                // just once, against a single empty outer row. The body then
+                // refers to it like any other uncorrelated `Get`.
+                let value = SR::constant(vec![vec![]], RelationType::new(vec![]))
+                    .let_in(id_gen, |id_gen, get_unit| {
+                        value.applied_to(id_gen, get_unit, &ColumnMap::empty())
+                    });
+                let body = body.applied_to(id_gen, get_outer, col_map);
+                SR::Let {
+                    id,
+                    value: Box::new(value),
+                    body: Box::new(body),
+                }
+            }
+            LetRec { id, value, body } => {
+                // As with `Let`, the value is computed against a single empty
+                // outer row, which leaves the recursive `Get`s in the value
+                // with the same type as they have in the body.
+                let value = SR::constant(vec![vec![]], RelationType::new(vec![]))
+                    .let_in(id_gen, |id_gen, get_unit| {
+                        value.applied_to(id_gen, get_unit, &ColumnMap::empty())
+                    });
+                let body = body.applied_to(id_gen, get_outer, col_map);
+                SR::LetRec {
+                    id,
+                    value: Box::new(value),
+                    body: Box::new(body),
+                }
+            }
             Project { input, outputs } => {
                 // Projections should be applied to the decorrelated `inner`, and to its columns,
                 // which means rebasing `outputs` to start `get_outer.arity()` columns later.
@@ -214,6 +254,61 @@ impl RelationExpr {
                 right,
                 on,
                 kind,
+                lateral: true,
+            } => {
+                // The right input of a lateral join may refer to the columns of the
+                // left input, so we treat it as a subquery of the left input: it is
+                // computed once for each distinct assignment to the columns it refers
+                // to, and joined back onto the rows of the left input, exactly as
+                // `branch` does for subqueries. The `on` predicate is then applied as
+                // a filter, and for a left join the rows of the left input without
+                // any match are padded with nulls. Planning rejects right and full
+                // lateral joins.
+                let oa = get_outer.arity();
+                let left = left.applied_to(id_gen, get_outer, col_map);
+                let la = left.arity() - oa;
+                left.let_in(id_gen, |id_gen, get_left| {
+                    let mut join = branch(
+                        id_gen,
+                        get_left.clone(),
+                        col_map,
+                        *right,
+                        |id_gen, right, get_left, col_map| {
+                            right.applied_to(id_gen, get_left, col_map)
+                        },
+                    );
+                    let old_arity = join.arity();
+                    let on = on.applied_to(id_gen, col_map, &mut join);
+                    join = join.filter(vec![on]);
+                    let new_arity = join.arity();
+                    if old_arity != new_arity {
+                        // this means we added some columns to handle subqueries, and now we need to get rid of them
+                        join = join.project((0..old_arity).collect());
+                    }
+                    match kind {
+                        JoinKind::Inner => join,
+                        JoinKind::LeftOuter => {
+                            let default = join
+                                .typ()
+                                .column_types
+                                .into_iter()
+                                .skip(oa + la)
+                                .map(|typ| (Datum::Null, typ.nullable(true)))
+                                .collect();
+                            get_left.lookup(id_gen, join, default)
+                        }
+                        JoinKind::RightOuter | JoinKind::FullOuter => unreachable!(
+                            "right and full lateral joins are rejected during planning"
+                        ),
+                    }
+                })
+            }
+            Join {
+                left,
+                right,
+                on,
+                kind,
+                lateral: false,
             } => {
                 // Both join expressions should be decorrelated, and then joined by their
                 // leading columns to form only those pairs corresponding to the same row
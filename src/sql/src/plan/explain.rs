@@ -141,6 +141,10 @@ impl RelationExpr {
                     | Threshold { .. }
                     | Distinct { .. } => false,
                     Join { .. } | Union { .. } => true,
+                    Let { value, .. } | LetRec { value, .. } => {
+                        // only the value child goes in a different chain
+                        (node.expr as *const RelationExpr) == ((&**value) as *const RelationExpr)
+                    }
                     Constant { .. } | Get { .. } => unreachable!(), // these don't have children
                 },
             };
@@ -155,6 +159,8 @@ impl RelationExpr {
             match &node.expr {
                 Constant { .. }
                 | Get { .. }
+                | Let { .. }
+                | LetRec { .. }
                 | Project { .. }
                 | Distinct { .. }
                 | Negate { .. }
@@ -211,9 +217,7 @@ impl RelationExpr {
                     write!(pretty, "Constant {}", Separated(" ", rows.clone())).unwrap();
                 }
                 Get { id, .. } => match id {
-                    Id::Local(_) => {
-                        unimplemented!("sql::RelationExpr::Get can't contain LocalId yet")
-                    }
+                    Id::Local(local_id) => write!(pretty, "Get {}", local_id).unwrap(),
                     Id::Global(_) => write!(
                         pretty,
                         "Get {} ({})",
@@ -224,6 +228,12 @@ impl RelationExpr {
                     )
                     .unwrap(),
                 },
+                Let { id, value, .. } => {
+                    write!(pretty, "Let {} = %{}", id, expr_chain(value)).unwrap()
+                }
+                LetRec { id, value, .. } => {
+                    write!(pretty, "LetRec {} = %{}", id, expr_chain(value)).unwrap()
+                }
                 Project { outputs, .. } => {
                     write!(pretty, "Project {}", Bracketed("(", ")", Indices(outputs))).unwrap()
                 }
@@ -272,11 +282,13 @@ impl RelationExpr {
                     right,
                     on,
                     kind,
+                    lateral,
                 } => {
                     write!(
                         pretty,
-                        "{}Join %{} %{} on {}",
+                        "{}Join{} %{} %{} on {}",
                         kind,
+                        if *lateral { " lateral" } else { "" },
                         expr_chain(left),
                         expr_chain(right),
                         on.fmt_with(&mut subqueries),
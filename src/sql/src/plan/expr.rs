@@ -27,6 +27,7 @@ use crate::plan::Params;
 // these happen to be unchanged at the moment, but there might be additions later
 pub use expr::{
     AggregateFunc, BinaryFunc, ColumnOrder, NullaryFunc, TableFunc, UnaryFunc, VariadicFunc,
+    WindowFrame, WindowFunc,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -40,12 +41,21 @@ pub enum RelationExpr {
         id: expr::Id,
         typ: RelationType,
     },
-    // only needed for CTEs
-    // Let {
-    //     name: Name,
-    //     value: Box<RelationExpr>,
-    //     body: Box<RelationExpr>,
-    // },
+    /// Binds `value` to `id` for the evaluation of `body`. Used to plan common
+    /// table expressions, whose `value` is never correlated.
+    Let {
+        id: expr::LocalId,
+        value: Box<RelationExpr>,
+        body: Box<RelationExpr>,
+    },
+    /// Like `Let`, but `id` is also bound in `value`, where it refers to the
+    /// result of the previous iteration. Used to plan recursive common table
+    /// expressions.
+    LetRec {
+        id: expr::LocalId,
+        value: Box<RelationExpr>,
+        body: Box<RelationExpr>,
+    },
     Project {
         input: Box<RelationExpr>,
         outputs: Vec<usize>,
@@ -70,6 +80,10 @@ pub enum RelationExpr {
         right: Box<RelationExpr>,
         on: ScalarExpr,
         kind: JoinKind,
+        /// Whether `right` may refer to the columns of `left`, as though it
+        /// were a subquery of `left`: column references at level 1 in `right`
+        /// refer to `left`, and those at higher levels to the outer relations.
+        lateral: bool,
     },
     /// Unlike expr::RelationExpr, when `key` is empty AND `input` is empty this returns
     /// a single row with the aggregates evaluated over empty groups, rather than returning zero
@@ -277,6 +291,9 @@ impl RelationExpr {
         match self {
             RelationExpr::Constant { typ, .. } => typ.clone(),
             RelationExpr::Get { typ, .. } => typ.clone(),
+            RelationExpr::Let { body, .. } | RelationExpr::LetRec { body, .. } => {
+                body.typ(outers, params)
+            }
             RelationExpr::Project { input, outputs } => {
                 let input_typ = input.typ(outers, params);
                 RelationType::new(
@@ -307,15 +324,27 @@ impl RelationExpr {
                 input.typ(outers, params)
             }
             RelationExpr::Join {
-                left, right, kind, ..
+                left,
+                right,
+                kind,
+                lateral,
+                ..
             } => {
                 let left_nullable = *kind == JoinKind::RightOuter || *kind == JoinKind::FullOuter;
                 let right_nullable = *kind == JoinKind::LeftOuter || *kind == JoinKind::FullOuter;
-                let lt = left.typ(outers, params).column_types.into_iter().map(|t| {
+                let left_typ = left.typ(outers, params);
+                let right_typ = if *lateral {
+                    let mut outers = outers.to_vec();
+                    outers.push(left_typ.clone());
+                    right.typ(&outers, params)
+                } else {
+                    right.typ(outers, params)
+                };
+                let lt = left_typ.column_types.into_iter().map(|t| {
                     let nullable = t.nullable || left_nullable;
                     t.nullable(nullable)
                 });
-                let rt = right.typ(outers, params).column_types.into_iter().map(|t| {
+                let rt = right_typ.column_types.into_iter().map(|t| {
                     let nullable = t.nullable || right_nullable;
                     t.nullable(nullable)
                 });
@@ -362,6 +391,7 @@ impl RelationExpr {
         match self {
             RelationExpr::Constant { typ, .. } => typ.column_types.len(),
             RelationExpr::Get { typ, .. } => typ.column_types.len(),
+            RelationExpr::Let { body, .. } | RelationExpr::LetRec { body, .. } => body.arity(),
             RelationExpr::Project { outputs, .. } => outputs.len(),
             RelationExpr::Map { input, scalars } => input.arity() + scalars.len(),
             RelationExpr::FlatMap { input, func, .. } => input.arity() + func.output_arity(),
@@ -429,6 +459,7 @@ impl RelationExpr {
                 right: Box::new(right),
                 on: ScalarExpr::literal_true(),
                 kind: JoinKind::Inner,
+                lateral: false,
             }
         }
     }
@@ -515,6 +546,10 @@ impl RelationExpr {
     {
         match self {
             RelationExpr::Constant { .. } | RelationExpr::Get { .. } => (),
+            RelationExpr::Let { value, body, .. } | RelationExpr::LetRec { value, body, .. } => {
+                f(value);
+                f(body);
+            }
             RelationExpr::Project { input, .. } => {
                 f(input);
             }
@@ -567,6 +602,10 @@ impl RelationExpr {
     {
         match self {
             RelationExpr::Constant { .. } | RelationExpr::Get { .. } => (),
+            RelationExpr::Let { value, body, .. } | RelationExpr::LetRec { value, body, .. } => {
+                f(value);
+                f(body);
+            }
             RelationExpr::Project { input, .. } => {
                 f(input);
             }
@@ -614,7 +653,21 @@ impl RelationExpr {
     where
         F: FnMut(usize, &mut ColumnRef),
     {
-        self.visit_mut(&mut |e| match e {
+        match self {
+            RelationExpr::Join {
+                left,
+                right,
+                on,
+                lateral: true,
+                ..
+            } => {
+                // The right input of a lateral join is nested one level deeper,
+                // as it may refer to the columns of the left input.
+                left.visit_columns(depth, f);
+                right.visit_columns(depth + 1, f);
+                on.visit_columns(depth, f);
+                return;
+            }
             RelationExpr::Join { on, .. } => on.visit_columns(depth, f),
             RelationExpr::Map { scalars, .. } => {
                 for scalar in scalars {
@@ -638,13 +691,16 @@ impl RelationExpr {
             }
             RelationExpr::Constant { .. }
             | RelationExpr::Get { .. }
+            | RelationExpr::Let { .. }
+            | RelationExpr::LetRec { .. }
             | RelationExpr::Project { .. }
             | RelationExpr::Distinct { .. }
             | RelationExpr::TopK { .. }
             | RelationExpr::Negate { .. }
             | RelationExpr::Threshold { .. }
             | RelationExpr::Union { .. } => (),
-        })
+        }
+        self.visit1_mut(|e| e.visit_columns(depth, f));
     }
 
     /// Replaces any parameter references in the expression with the
@@ -674,6 +730,54 @@ impl RelationExpr {
             }
             RelationExpr::Constant { .. }
             | RelationExpr::Get { .. }
+            | RelationExpr::Let { .. }
+            | RelationExpr::LetRec { .. }
+            | RelationExpr::Project { .. }
+            | RelationExpr::Distinct { .. }
+            | RelationExpr::TopK { .. }
+            | RelationExpr::Negate { .. }
+            | RelationExpr::Threshold { .. }
+            | RelationExpr::Union { .. } => (),
+        })
+    }
+
+    /// Visits the identifiers of the local bindings introduced by `Let` and
+    /// `LetRec` expressions, and of the local references in `Get` expressions,
+    /// in this relation expression and in any of its subqueries.
+    pub fn visit_local_ids<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut expr::LocalId),
+    {
+        self.visit_mut(&mut |e| match e {
+            RelationExpr::Get {
+                id: expr::Id::Local(id),
+                ..
+            }
+            | RelationExpr::Let { id, .. }
+            | RelationExpr::LetRec { id, .. } => f(id),
+            RelationExpr::Join { on, .. } => on.visit_local_ids(f),
+            RelationExpr::Map { scalars, .. } => {
+                for scalar in scalars {
+                    scalar.visit_local_ids(f);
+                }
+            }
+            RelationExpr::FlatMap { exprs, .. } => {
+                for expr in exprs {
+                    expr.visit_local_ids(f);
+                }
+            }
+            RelationExpr::Filter { predicates, .. } => {
+                for predicate in predicates {
+                    predicate.visit_local_ids(f);
+                }
+            }
+            RelationExpr::Reduce { aggregates, .. } => {
+                for aggregate in aggregates {
+                    aggregate.expr.visit_local_ids(f);
+                }
+            }
+            RelationExpr::Constant { .. }
+            | RelationExpr::Get { .. }
             | RelationExpr::Project { .. }
             | RelationExpr::Distinct { .. }
             | RelationExpr::TopK { .. }
@@ -683,6 +787,38 @@ impl RelationExpr {
         })
     }
 
+    /// Reports whether this relation expression, or any of its subqueries,
+    /// contains a `LetRec`.
+    pub fn contains_let_rec(&mut self) -> bool {
+        let mut found = false;
+        self.visit_mut(&mut |e| match e {
+            RelationExpr::LetRec { .. } => found = true,
+            RelationExpr::Join { on, .. } => found |= on.contains_let_rec(),
+            RelationExpr::Map { scalars, .. } => {
+                found |= scalars.iter_mut().any(|s| s.contains_let_rec());
+            }
+            RelationExpr::FlatMap { exprs, .. } => {
+                found |= exprs.iter_mut().any(|e| e.contains_let_rec());
+            }
+            RelationExpr::Filter { predicates, .. } => {
+                found |= predicates.iter_mut().any(|p| p.contains_let_rec());
+            }
+            RelationExpr::Reduce { aggregates, .. } => {
+                found |= aggregates.iter_mut().any(|a| a.expr.contains_let_rec());
+            }
+            RelationExpr::Constant { .. }
+            | RelationExpr::Get { .. }
+            | RelationExpr::Let { .. }
+            | RelationExpr::Project { .. }
+            | RelationExpr::Distinct { .. }
+            | RelationExpr::TopK { .. }
+            | RelationExpr::Negate { .. }
+            | RelationExpr::Threshold { .. }
+            | RelationExpr::Union { .. } => (),
+        });
+        found
+    }
+
     /// Constructs a constant collection from specific rows and schema.
     pub fn constant(rows: Vec<Vec<Datum>>, typ: RelationType) -> Self {
         let mut row_packer = repr::RowPacker::new();
@@ -749,6 +885,31 @@ impl ScalarExpr {
         }
     }
 
+    /// Visits the identifiers of the local bindings and references in the
+    /// subqueries of this scalar expression.
+    pub fn visit_local_ids<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&mut expr::LocalId),
+    {
+        self.visit_mut(&mut |e| {
+            if let ScalarExpr::Exists(expr) | ScalarExpr::Select(expr) = e {
+                expr.visit_local_ids(f);
+            }
+        })
+    }
+
+    /// Reports whether any subquery of this scalar expression contains a
+    /// `LetRec`.
+    pub fn contains_let_rec(&mut self) -> bool {
+        let mut found = false;
+        self.visit_mut(&mut |e| {
+            if let ScalarExpr::Exists(expr) | ScalarExpr::Select(expr) = e {
+                found |= expr.contains_let_rec();
+            }
+        });
+        found
+    }
+
     pub fn literal(datum: Datum, column_type: ColumnType) -> ScalarExpr {
         let row = Row::pack(&[datum]);
         ScalarExpr::Literal(row, column_type)
@@ -24,7 +24,7 @@ use sql_parser::ast::{BinaryOperator, Expr, UnaryOperator};
 
 use super::expr::{
     AggregateFunc, BinaryFunc, CoercibleScalarExpr, NullaryFunc, ScalarExpr, TableFunc, UnaryFunc,
-    VariadicFunc,
+    VariadicFunc, WindowFunc,
 };
 use super::query::{self, ExprContext, QueryLifetime};
 use super::typeconv::{self, rescale_decimal, CastTo, CoerceTo};
@@ -1524,3 +1524,83 @@ pub fn select_aggregate_func(
 
     ArgImplementationMatcher::select_implementation(ident, func_err_string, ecx, impls, cexprs)
 }
+
+lazy_static! {
+    /// Correlates a built-in window function name to its implementations.
+    static ref BUILTIN_WINDOW_IMPLS: HashMap<&'static str, Vec<FuncImpl<(Vec<ScalarExpr>, WindowFunc)>>> = {
+        use ParamType::*;
+        use ScalarType::*;
+        impls! {
+            "dense_rank" => {
+                params!() => nullary_op(|_ecx| Ok((vec![], WindowFunc::DenseRank)))
+            },
+            "first_value" => {
+                params!(Any) => unary_op(|_ecx, e| Ok((vec![e], WindowFunc::FirstValue)))
+            },
+            "lag" => {
+                params!(Any) => plan_lag_lead(WindowFunc::Lag),
+                params!(Any, Int32) => plan_lag_lead(WindowFunc::Lag),
+                params!(Any, Int32, Any) => plan_lag_lead(WindowFunc::Lag)
+            },
+            "lead" => {
+                params!(Any) => plan_lag_lead(WindowFunc::Lead),
+                params!(Any, Int32) => plan_lag_lead(WindowFunc::Lead),
+                params!(Any, Int32, Any) => plan_lag_lead(WindowFunc::Lead)
+            },
+            "rank" => {
+                params!() => nullary_op(|_ecx| Ok((vec![], WindowFunc::Rank)))
+            },
+            "row_number" => {
+                params!() => nullary_op(|_ecx| Ok((vec![], WindowFunc::RowNumber)))
+            }
+        }
+    };
+}
+
+/// Plans `lag` or `lead`, whose offset defaults to one and whose default value
+/// defaults to `NULL`.
+fn plan_lag_lead(func: WindowFunc) -> Operation<(Vec<ScalarExpr>, WindowFunc)> {
+    variadic_op(move |ecx, exprs| {
+        let mut exprs = exprs.into_iter();
+        let value = exprs.next().unwrap();
+        let typ = ecx.scalar_type(&value);
+        let offset = exprs.next().unwrap_or_else(|| {
+            ScalarExpr::literal(Datum::Int32(1), ColumnType::new(ScalarType::Int32))
+        });
+        let default = match exprs.next() {
+            Some(ScalarExpr::Literal(row, _)) if row.unpack_first().is_null() => {
+                ScalarExpr::literal_null(typ)
+            }
+            Some(default) => {
+                typeconv::plan_cast(&func.to_string(), ecx, default, CastTo::Implicit(typ))?
+            }
+            None => ScalarExpr::literal_null(typ),
+        };
+        Ok((vec![value, offset, default], func.clone()))
+    })
+}
+
+pub fn is_window_func(ident: &str) -> bool {
+    BUILTIN_WINDOW_IMPLS.get(ident).is_some()
+}
+
+/// Plans a built-in window function, returning its arguments along with the
+/// function itself.
+pub fn select_window_func(
+    ecx: &ExprContext,
+    ident: &str,
+    args: &[Expr],
+) -> Result<(Vec<ScalarExpr>, WindowFunc), failure::Error> {
+    let impls = match BUILTIN_WINDOW_IMPLS.get(ident) {
+        Some(i) => i,
+        None => unsupported!(ident),
+    };
+
+    let mut cexprs = Vec::new();
+    for arg in args {
+        let cexpr = query::plan_expr(ecx, arg)?;
+        cexprs.push(cexpr);
+    }
+
+    ArgImplementationMatcher::select_implementation(ident, func_err_string, ecx, impls, cexprs)
+}
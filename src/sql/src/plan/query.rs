@@ -20,7 +20,7 @@
 //! To deal with this, whenever we see a SQL GROUP BY we look ahead for aggregates and precompute them in the `RelationExpr::Reduce`. When we reach the same aggregates during normal planning later on, we look them up in an `ExprContext` to find the precomputed versions.
 
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::iter;
 use std::rc::Rc;
@@ -28,22 +28,25 @@ use std::rc::Rc;
 use failure::{bail, ensure, format_err, ResultExt};
 use sql_parser::ast::visit::{self, Visit};
 use sql_parser::ast::{
-    BinaryOperator, DataType, Expr, Function, FunctionArgs, Ident, JoinConstraint, JoinOperator,
-    ObjectName, Query, Select, SelectItem, SetExpr, SetOperator, ShowStatementFilter, TableAlias,
-    TableFactor, TableWithJoins, Value, Values,
+    Assignment, BinaryOperator, DataType, Expr, Function, FunctionArgs, Ident, JoinConstraint,
+    JoinOperator, ObjectName, Query, Select, SelectItem, SetExpr, SetOperator, ShowStatementFilter,
+    TableAlias, TableFactor, TableWithJoins, Value, Values, WindowFrameBound, WindowFrameUnits,
+    WindowSpec,
 };
 
-use ::expr::{Id, RowSetFinishing};
+use ::expr::{GlobalId, Id, IdGen, LocalId, RowSetFinishing};
 use dataflow_types::Timestamp;
 use repr::adt::decimal::{Decimal, MAX_DECIMAL_PRECISION};
 use repr::{
     strconv, ColumnName, ColumnType, Datum, RelationDesc, RelationType, RowArena, ScalarType,
 };
 
+use crate::catalog::{CatalogItem, CatalogItemType, ObjectRef, Privilege};
 use crate::names::PartialName;
 use crate::plan::expr::{
     AggregateExpr, AggregateFunc, BinaryFunc, CoercibleScalarExpr, ColumnOrder, ColumnRef,
-    JoinKind, RelationExpr, ScalarExpr, ScalarTypeable, UnaryFunc, VariadicFunc,
+    JoinKind, RelationExpr, ScalarExpr, ScalarTypeable, TableFunc, UnaryFunc, VariadicFunc,
+    WindowFrame, WindowFunc,
 };
 use crate::plan::func;
 use crate::plan::scope::{Scope, ScopeItem, ScopeItemName};
@@ -78,14 +81,299 @@ pub fn plan_root_query(
     );
 
     let desc = RelationDesc::new(typ, scope.column_names());
-    let mut param_types = vec![];
-    for (i, (n, typ)) in qcx.unwrap_param_types().into_iter().enumerate() {
-        if n != i + 1 {
-            bail!("unable to infer type for parameter ${}", i + 1);
+    let param_types = qcx.finish_param_types()?;
+    Ok((expr, desc, finishing, param_types))
+}
+
+/// Plans the source of an `INSERT` statement, returning the ID of the target
+/// table, an expression that produces the rows to insert, and the types of the
+/// parameters in the statement, if any were present.
+///
+/// The returned expression produces rows with exactly the columns of the
+/// target table, in order. Any columns not named in `columns` are filled in
+/// with `NULL`.
+pub fn plan_insert_query(
+    scx: &StatementContext,
+    table_name: ObjectName,
+    columns: Vec<Ident>,
+    mut source: Query,
+) -> Result<(GlobalId, RelationExpr, Vec<ScalarType>), failure::Error> {
+    let table = resolve_table(scx, table_name, Privilege::Insert)?;
+    let desc = table.desc()?;
+    let target_columns = resolve_target_columns(table, columns)?;
+    let target_types: Vec<_> = target_columns
+        .iter()
+        .map(|i| desc.typ().column_types[*i].scalar_type.clone())
+        .collect();
+
+    transform_ast::transform_query(&mut source)?;
+    let qcx = QueryContext::root(scx, QueryLifetime::OneShot);
+    let expr = match &source.body {
+        // Plan VALUES lists directly against the types of the target columns,
+        // so that literals are coerced the way they would be in PostgreSQL.
+        SetExpr::Values(Values(values))
+            if source.ctes.is_empty()
+                && source.order_by.is_empty()
+                && source.limit.is_none()
+                && source.offset.is_none() =>
+        {
+            let ecx = &ExprContext {
+                qcx: &qcx,
+                name: "INSERT",
+                scope: &Scope::empty(Some(qcx.outer_scope.clone())),
+                relation_type: &RelationType::empty(),
+                allow_aggregates: false,
+                allow_subqueries: true,
+            };
+            let mut out: Option<RelationExpr> = None;
+            for row in values {
+                check_insert_arity(row.len(), target_types.len())?;
+                let mut exprs = vec![];
+                for (e, ty) in row.iter().zip(&target_types) {
+                    let expr = plan_expr(ecx, e)?;
+                    let expr = typeconv::plan_coerce(ecx, expr, CoerceTo::Plain(ty.clone()))?;
+                    exprs.push(typeconv::plan_cast(
+                        "INSERT",
+                        ecx,
+                        expr,
+                        CastTo::Explicit(ty.clone()),
+                    )?);
+                }
+                let row = RelationExpr::constant(vec![vec![]], RelationType::new(vec![]));
+                let row = row.map(exprs);
+                out = Some(match out {
+                    None => row,
+                    Some(out) => out.union(row),
+                });
+            }
+            out.ok_or_else(|| format_err!("Can't infer a type for empty VALUES expression"))?
+        }
+        _ => {
+            let (expr, _scope) = plan_subquery(&qcx, &source)?;
+            let typ = qcx.relation_type(&expr);
+            check_insert_arity(typ.column_types.len(), target_types.len())?;
+            let ecx = &ExprContext {
+                qcx: &qcx,
+                name: "INSERT",
+                scope: &Scope::empty(Some(qcx.outer_scope.clone())),
+                relation_type: &typ,
+                allow_aggregates: false,
+                allow_subqueries: false,
+            };
+            let mut exprs = vec![];
+            for (i, ty) in target_types.iter().enumerate() {
+                let expr = ScalarExpr::Column(ColumnRef {
+                    level: 0,
+                    column: i,
+                });
+                exprs.push(typeconv::plan_cast(
+                    "INSERT",
+                    ecx,
+                    expr,
+                    CastTo::Explicit(ty.clone()),
+                )?);
+            }
+            let arity = typ.column_types.len();
+            expr.map(exprs).project((arity..arity * 2).collect())
+        }
+    };
+
+    // Reorder the planned columns to match the table, filling in any columns
+    // that were not specified with nulls.
+    let mut nulls = vec![];
+    let mut project = vec![];
+    for (i, typ) in desc.typ().column_types.iter().enumerate() {
+        match target_columns.iter().position(|c| *c == i) {
+            Some(pos) => project.push(pos),
+            None => {
+                project.push(target_columns.len() + nulls.len());
+                nulls.push(ScalarExpr::literal_null(typ.scalar_type.clone()));
+            }
         }
-        param_types.push(typ);
     }
-    Ok((expr, desc, finishing, param_types))
+    let expr = expr.map(nulls).project(project);
+
+    let param_types = qcx.finish_param_types()?;
+    Ok((table.id(), expr, param_types))
+}
+
+fn check_insert_arity(actual: usize, expected: usize) -> Result<(), failure::Error> {
+    if actual > expected {
+        bail!("INSERT has more expressions than target columns");
+    } else if actual < expected {
+        bail!("INSERT has more target columns than expressions");
+    }
+    Ok(())
+}
+
+/// Plans the selection of an `UPDATE` or `DELETE` statement, returning the ID
+/// of the target table, an expression that produces the affected rows, and the
+/// types of the parameters in the statement, if any were present.
+///
+/// If `assignments` is empty, the returned expression produces the rows of the
+/// table that match `selection`. Otherwise, each matching row is followed by
+/// the columns of its replacement row.
+///
+/// A `selection` reveals which rows the table contains, so it additionally
+/// requires the privilege to read from the table.
+pub fn plan_mutation_query(
+    scx: &StatementContext,
+    table_name: ObjectName,
+    assignments: Vec<Assignment>,
+    selection: Option<Expr>,
+) -> Result<(GlobalId, RelationExpr, Vec<ScalarType>), failure::Error> {
+    let privilege = if assignments.is_empty() {
+        Privilege::Delete
+    } else {
+        Privilege::Update
+    };
+    let table = resolve_table(scx, table_name, privilege)?;
+    if selection.is_some() {
+        scx.catalog
+            .check_privilege(&ObjectRef::Item(table.id()), Privilege::Select)?;
+    }
+    let desc = table.desc()?;
+    let qcx = QueryContext::root(scx, QueryLifetime::OneShot);
+
+    let mut expr = RelationExpr::Get {
+        id: Id::Global(table.id()),
+        typ: desc.typ().clone(),
+    };
+    let scope = plan_table_alias(
+        &qcx,
+        None,
+        Some(table.name().clone().into()),
+        desc.iter_names().map(|n| n.cloned()).collect(),
+    )?;
+
+    if let Some(mut selection) = selection {
+        transform_ast::transform_expr(&mut selection)?;
+        let ecx = &ExprContext {
+            qcx: &qcx,
+            name: "WHERE clause",
+            scope: &scope,
+            relation_type: &qcx.relation_type(&expr),
+            allow_aggregates: false,
+            allow_subqueries: true,
+        };
+        let predicate = plan_expr(ecx, &selection)?.type_as(ecx, ScalarType::Bool)?;
+        expr = expr.filter(vec![predicate]);
+    }
+
+    if !assignments.is_empty() {
+        let mut sets = BTreeMap::new();
+        for Assignment { id, mut value } in assignments {
+            let name = normalize::column_name(id);
+            let i = desc
+                .iter_names()
+                .position(|n| n == Some(&name))
+                .ok_or_else(|| {
+                    format_err!(
+                        "column \"{}\" of relation \"{}\" does not exist",
+                        name,
+                        table.name()
+                    )
+                })?;
+            transform_ast::transform_expr(&mut value)?;
+            if sets.insert(i, value).is_some() {
+                bail!("multiple assignments to same column \"{}\"", name);
+            }
+        }
+        let typ = qcx.relation_type(&expr);
+        let ecx = &ExprContext {
+            qcx: &qcx,
+            name: "SET clause",
+            scope: &scope,
+            relation_type: &typ,
+            allow_aggregates: false,
+            allow_subqueries: true,
+        };
+        let mut exprs = vec![];
+        for (i, column_type) in desc.typ().column_types.iter().enumerate() {
+            exprs.push(match sets.get(&i) {
+                Some(value) => {
+                    let ty = column_type.scalar_type.clone();
+                    let value = plan_expr(ecx, value)?;
+                    let value = typeconv::plan_coerce(ecx, value, CoerceTo::Plain(ty.clone()))?;
+                    typeconv::plan_cast("UPDATE", ecx, value, CastTo::Explicit(ty))?
+                }
+                None => ScalarExpr::Column(ColumnRef {
+                    level: 0,
+                    column: i,
+                }),
+            });
+        }
+        expr = expr.map(exprs);
+    }
+
+    let param_types = qcx.finish_param_types()?;
+    Ok((table.id(), expr, param_types))
+}
+
+/// Plans the target of a `COPY ... FROM` statement.
+///
+/// Returns the ID of the table and the index of the table column that each
+/// column of the copied data feeds.
+pub fn plan_copy_from(
+    scx: &StatementContext,
+    table_name: ObjectName,
+    columns: Vec<Ident>,
+) -> Result<(GlobalId, Vec<usize>), failure::Error> {
+    let table = resolve_table(scx, table_name, Privilege::Insert)?;
+    let target_columns = resolve_target_columns(table, columns)?;
+    Ok((table.id(), target_columns))
+}
+
+/// Determines the index of the column of `table` named by each of
+/// `columns`. An empty list of columns names every column of the table.
+fn resolve_target_columns(
+    table: &dyn CatalogItem,
+    columns: Vec<Ident>,
+) -> Result<Vec<usize>, failure::Error> {
+    let desc = table.desc()?;
+    if columns.is_empty() {
+        return Ok((0..desc.typ().column_types.len()).collect());
+    }
+    let mut target_columns = vec![];
+    for column in columns {
+        let name = normalize::column_name(column);
+        let i = desc
+            .iter_names()
+            .position(|n| n == Some(&name))
+            .ok_or_else(|| {
+                format_err!(
+                    "column \"{}\" of relation \"{}\" does not exist",
+                    name,
+                    table.name()
+                )
+            })?;
+        if target_columns.contains(&i) {
+            bail!("column \"{}\" specified more than once", name);
+        }
+        target_columns.push(i);
+    }
+    Ok(target_columns)
+}
+
+/// Resolves `name` to a table that can be written to, checking that the
+/// current user holds `privilege` on it.
+fn resolve_table<'a>(
+    scx: &'a StatementContext,
+    name: ObjectName,
+    privilege: Privilege,
+) -> Result<&'a dyn CatalogItem, failure::Error> {
+    let name = scx.resolve_item(name)?;
+    let item = scx.catalog.get_item(&name);
+    if item.item_type() != CatalogItemType::Table {
+        bail!(
+            "cannot mutate {} '{}' because it is not a table",
+            item.item_type(),
+            name
+        );
+    }
+    scx.catalog
+        .check_privilege(&ObjectRef::Item(item.id()), privilege)?;
+    Ok(item)
 }
 
 /// Plans a SHOW statement that might have a WHERE or LIKE clause attached to it. A LIKE clause is
@@ -249,9 +537,8 @@ fn plan_query(
     qcx: &QueryContext,
     q: &Query,
 ) -> Result<(RelationExpr, Scope, RowSetFinishing), failure::Error> {
-    if !q.ctes.is_empty() {
-        unsupported!(2617, "CTEs");
-    }
+    let (qcx, ctes) = plan_ctes(qcx, q)?;
+    let qcx = &qcx;
     let limit = match &q.limit {
         None => None,
         Some(Expr::Value(Value::Number(x))) => Some(x.parse()?),
@@ -305,7 +592,142 @@ fn plan_query(
         project: (0..output_typ.column_types.len()).collect(),
         offset,
     };
-    Ok((expr.map(map_exprs), scope, finishing))
+    let mut expr = expr.map(map_exprs);
+    for (id, value, recursive) in ctes.into_iter().rev() {
+        let value = Box::new(value);
+        let body = Box::new(expr);
+        expr = if recursive {
+            RelationExpr::LetRec { id, value, body }
+        } else {
+            RelationExpr::Let { id, value, body }
+        };
+    }
+    Ok((expr, scope, finishing))
+}
+
+/// Plans the common table expressions in the `WITH` clause of `q`, returning
+/// a query context in which they are in scope along with their values, and
+/// whether each value refers to itself.
+///
+/// In a `WITH RECURSIVE` clause, a CTE of the form `non_recursive_term UNION
+/// [ALL] recursive_term` is in scope in its own definition, with the column
+/// names and types of `non_recursive_term`.
+fn plan_ctes<'a>(
+    qcx: &QueryContext<'a>,
+    q: &Query,
+) -> Result<(QueryContext<'a>, Vec<(LocalId, RelationExpr, bool)>), failure::Error> {
+    let mut cte_qcx = QueryContext {
+        scx: qcx.scx,
+        lifetime: qcx.lifetime,
+        outer_scope: qcx.outer_scope.clone(),
+        outer_relation_types: qcx.outer_relation_types.clone(),
+        param_types: qcx.param_types.clone(),
+        ctes: qcx.ctes.clone(),
+        id_gen: qcx.id_gen.clone(),
+    };
+    let mut ctes = vec![];
+    let mut names = HashSet::new();
+    for cte in &q.ctes {
+        let name = normalize::ident(cte.alias.name.clone());
+        if !names.insert(name.clone()) {
+            bail!("WITH query name \"{}\" specified more than once", name);
+        }
+        let id = LocalId::new(cte_qcx.id_gen.borrow_mut().allocate_id());
+        let column_names = |cte_qcx: &QueryContext, scope: Scope| {
+            let column_names = scope.column_names().map(|n| n.cloned()).collect();
+            Ok::<_, failure::Error>(
+                plan_table_alias(cte_qcx, Some(&cte.alias), None, column_names)?
+                    .column_names()
+                    .map(|n| n.cloned())
+                    .collect(),
+            )
+        };
+        let mut recursive = false;
+        if let Some(non_recursive_term) = recursive_cte_base(q, &cte.query) {
+            let (base, scope) = plan_set_expr(&cte_qcx, non_recursive_term)?;
+            // Whether later iterations produce nulls is not known until the
+            // whole CTE is planned, so the recursive references conservatively
+            // admit nulls in every column.
+            let typ = RelationType::new(
+                cte_qcx
+                    .relation_type(&base)
+                    .column_types
+                    .into_iter()
+                    .map(|typ| typ.nullable(true))
+                    .collect(),
+            );
+            cte_qcx.ctes.insert(
+                name.clone(),
+                Cte {
+                    id,
+                    column_names: column_names(&cte_qcx, scope)?,
+                    typ,
+                },
+            );
+            recursive = true;
+        }
+        let (mut value, scope) = plan_subquery(&cte_qcx, &cte.query)?;
+        if recursive {
+            let mut references = 0;
+            value.visit_local_ids(&mut |local_id| {
+                if *local_id == id {
+                    references += 1;
+                }
+            });
+            if references > 1 {
+                bail!(
+                    "recursive reference to query \"{}\" must not appear more than once",
+                    name
+                );
+            }
+            if references > 0 && value.contains_let_rec() {
+                unsupported!("nested recursive CTEs");
+            }
+            recursive = references > 0;
+        }
+        let mut correlated = false;
+        value.visit_columns(0, &mut |depth, col| {
+            if col.level > depth {
+                correlated = true;
+            }
+        });
+        if correlated {
+            unsupported!("correlated CTEs");
+        }
+        let column_names = column_names(&cte_qcx, scope)?;
+        let typ = cte_qcx.relation_type(&value);
+        cte_qcx.ctes.insert(
+            name,
+            Cte {
+                id,
+                column_names,
+                typ,
+            },
+        );
+        ctes.push((id, value, recursive));
+    }
+    Ok((cte_qcx, ctes))
+}
+
+/// Returns the non-recursive term of `cte_query`, if `cte_query` is a
+/// candidate for recursion in the `WITH` clause of `q`.
+fn recursive_cte_base<'a>(q: &Query, cte_query: &'a Query) -> Option<&'a SetExpr> {
+    match &cte_query.body {
+        SetExpr::SetOperation {
+            op: SetOperator::Union,
+            left,
+            ..
+        } if q.recursive
+            && cte_query.ctes.is_empty()
+            && cte_query.order_by.is_empty()
+            && cte_query.limit.is_none()
+            && cte_query.offset.is_none()
+            && cte_query.fetch.is_none() =>
+        {
+            Some(&**left)
+        }
+        _ => None,
+    }
 }
 
 fn plan_subquery(qcx: &QueryContext, q: &Query) -> Result<(RelationExpr, Scope), failure::Error> {
@@ -503,7 +925,7 @@ fn plan_view_select(
     }
 
     // Step 3. Handle GROUP BY clause.
-    let (group_scope, select_all_mapping) = {
+    let (mut group_scope, select_all_mapping) = {
         // gather group columns
         let ecx = &ExprContext {
             qcx,
@@ -605,7 +1027,24 @@ fn plan_view_select(
         relation_expr = relation_expr.filter(vec![expr]);
     }
 
-    // Step 5. Handle projections.
+    // Step 5. Handle window functions.
+    let mut window_visitor = WindowFuncVisitor::new();
+    for p in &s.projection {
+        window_visitor.visit_select_item(p);
+    }
+    for sql_function in window_visitor.into_result() {
+        relation_expr = plan_window_function(qcx, relation_expr, &group_scope, sql_function)?;
+        group_scope.items.push(ScopeItem {
+            names: vec![ScopeItemName {
+                table_name: None,
+                column_name: Some(sql_function.name.to_string().into()),
+            }],
+            expr: Some(Expr::Function(sql_function.clone())),
+            nameable: true,
+        });
+    }
+
+    // Step 6. Handle projections.
     let project_scope = {
         let mut project_exprs = vec![];
         let mut project_key = vec![];
@@ -633,7 +1072,7 @@ fn plan_view_select(
         project_scope
     };
 
-    // Step 6. Handle DISTINCT.
+    // Step 7. Handle DISTINCT.
     if s.distinct {
         relation_expr = relation_expr.distinct();
     }
@@ -690,17 +1129,65 @@ fn plan_table_factor<'a>(
                     allow_aggregates: false,
                     allow_subqueries: true,
                 };
-                plan_table_function(ecx, left, &name, alias.as_ref(), args)
+                if let JoinOperator::CrossJoin | JoinOperator::CrossApply = join_operator {
+                    return plan_table_function(ecx, left, &name, alias.as_ref(), args);
+                }
+                // Under any other join operator, the table function is planned
+                // on its own, so that the join constraint can be applied. It
+                // may still refer to the columns of the left input as a
+                // lateral subquery, except in right and full joins, where
+                // such references are not permitted.
+                let lateral = match join_operator {
+                    JoinOperator::RightOuter(_) | JoinOperator::FullOuter(_) => false,
+                    _ => true,
+                };
+                let (expr, mut scope) = {
+                    let lateral_qcx;
+                    let qcx = if lateral {
+                        lateral_qcx = ecx.derived_query_context();
+                        &lateral_qcx
+                    } else {
+                        qcx
+                    };
+                    let (identity, identity_scope) = plan_join_identity(qcx);
+                    let ecx = &ExprContext {
+                        qcx,
+                        name: "FROM table function",
+                        scope: &identity_scope,
+                        relation_type: &qcx.relation_type(&identity),
+                        allow_aggregates: false,
+                        allow_subqueries: true,
+                    };
+                    plan_table_function(ecx, identity, &name, alias.as_ref(), args)?
+                };
+                scope.outer_scope = Some(Box::new(qcx.outer_scope.clone()));
+                plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope, lateral)
+            } else if let Some((cte_name, cte)) = resolve_cte(qcx, name) {
+                let expr = RelationExpr::Get {
+                    id: Id::Local(cte.id),
+                    typ: cte.typ.clone(),
+                };
+                let name = PartialName {
+                    database: None,
+                    schema: None,
+                    item: cte_name,
+                };
+                let scope =
+                    plan_table_alias(qcx, alias.as_ref(), Some(name), cte.column_names.clone())?;
+                plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope, false)
             } else {
                 let name = qcx.scx.resolve_item(name.clone())?;
                 let item = qcx.scx.catalog.get_item(&name);
+                qcx.scx
+                    .catalog
+                    .check_privilege(&ObjectRef::Item(item.id()), Privilege::Select)?;
                 let expr = RelationExpr::Get {
                     id: Id::Global(item.id()),
                     typ: item.desc()?.typ().clone(),
                 };
                 let column_names = item.desc()?.iter_names().map(|n| n.cloned()).collect();
                 let scope = plan_table_alias(qcx, alias.as_ref(), Some(name.into()), column_names)?;
-                plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope)
+                plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope, false)
             }
         }
         TableFactor::Derived {
@@ -708,14 +1195,33 @@ fn plan_table_factor<'a>(
             subquery,
             alias,
         } => {
-            if *lateral {
-                unsupported!(3111, "LATERAL derived tables");
-            }
-            let (expr, scope) = plan_subquery(&qcx, &subquery)?;
+            // A lateral subquery may refer to the columns of the tables to its
+            // left, and so is planned as though it were a subquery of them.
+            // There is nothing to refer to if it is the first table. The
+            // right-hand side of an APPLY is always lateral.
+            let lateral = (*lateral
+                || matches!(
+                    join_operator,
+                    JoinOperator::CrossApply | JoinOperator::OuterApply
+                ))
+                && !left.is_join_identity();
+            let (expr, scope) = if lateral {
+                let ecx = &ExprContext {
+                    qcx,
+                    name: "LATERAL subquery",
+                    scope: &left_scope,
+                    relation_type: &qcx.relation_type(&left),
+                    allow_aggregates: false,
+                    allow_subqueries: true,
+                };
+                plan_subquery(&ecx.derived_query_context(), &subquery)?
+            } else {
+                plan_subquery(&qcx, &subquery)?
+            };
             let table_name = None;
             let column_names = scope.column_names().map(|n| n.cloned()).collect();
             let scope = plan_table_alias(qcx, alias.as_ref(), table_name, column_names)?;
-            plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope)
+            plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope, lateral)
         }
         TableFactor::NestedJoin(table_with_joins) => {
             let (identity, identity_scope) = plan_join_identity(qcx);
@@ -726,8 +1232,21 @@ fn plan_table_factor<'a>(
                 &JoinOperator::CrossJoin,
                 table_with_joins,
             )?;
-            plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope)
+            plan_join_operator(qcx, &join_operator, left, left_scope, expr, scope, false)
+        }
+    }
+}
+
+/// Looks up the common table expression, if any, to which the unqualified
+/// table reference `name` refers.
+fn resolve_cte<'a>(qcx: &'a QueryContext, name: &ObjectName) -> Option<(String, &'a Cte)> {
+    match &name.0[..] {
+        [item] => {
+            let item = normalize::ident(item.clone());
+            let cte = qcx.ctes.get(&item)?;
+            Some((item, cte))
         }
+        _ => None,
     }
 }
 
@@ -934,6 +1453,10 @@ fn plan_select_item<'a>(
     }
 }
 
+/// Plans the join of `left` and `right` according to `operator`.
+///
+/// If `lateral` is set, `right` was planned as a subquery of `left`, and may
+/// refer to its columns.
 fn plan_join_operator(
     qcx: &QueryContext,
     operator: &JoinOperator,
@@ -941,6 +1464,7 @@ fn plan_join_operator(
     left_scope: Scope,
     right: RelationExpr,
     right_scope: Scope,
+    lateral: bool,
 ) -> Result<(RelationExpr, Scope), failure::Error> {
     match operator {
         JoinOperator::Inner(constraint) => plan_join_constraint(
@@ -951,6 +1475,7 @@ fn plan_join_operator(
             right,
             right_scope,
             JoinKind::Inner,
+            lateral,
         ),
         JoinOperator::LeftOuter(constraint) => plan_join_constraint(
             qcx,
@@ -960,6 +1485,7 @@ fn plan_join_operator(
             right,
             right_scope,
             JoinKind::LeftOuter,
+            lateral,
         ),
         JoinOperator::RightOuter(constraint) => plan_join_constraint(
             qcx,
@@ -969,6 +1495,7 @@ fn plan_join_operator(
             right,
             right_scope,
             JoinKind::RightOuter,
+            lateral,
         ),
         JoinOperator::FullOuter(constraint) => plan_join_constraint(
             qcx,
@@ -978,13 +1505,33 @@ fn plan_join_operator(
             right,
             right_scope,
             JoinKind::FullOuter,
+            lateral,
         ),
-        JoinOperator::CrossJoin => Ok((left.product(right), left_scope.product(right_scope))),
-        // The remaining join types are MSSQL-specific. We are unlikely to
-        // ever support them. The standard SQL equivalent is LATERAL, which
-        // we are not capable of even parsing at the moment.
-        JoinOperator::CrossApply => unsupported!("CROSS APPLY"),
-        JoinOperator::OuterApply => unsupported!("OUTER APPLY"),
+        // CROSS APPLY and OUTER APPLY are the MSSQL spellings of
+        // `CROSS JOIN LATERAL` and `LEFT JOIN LATERAL ... ON true`.
+        JoinOperator::CrossJoin | JoinOperator::CrossApply if lateral => {
+            let joined = RelationExpr::Join {
+                left: Box::new(left),
+                right: Box::new(right),
+                on: ScalarExpr::literal_true(),
+                kind: JoinKind::Inner,
+                lateral,
+            };
+            Ok((joined, left_scope.product(right_scope)))
+        }
+        JoinOperator::CrossJoin | JoinOperator::CrossApply => {
+            Ok((left.product(right), left_scope.product(right_scope)))
+        }
+        JoinOperator::OuterApply => {
+            let joined = RelationExpr::Join {
+                left: Box::new(left),
+                right: Box::new(right),
+                on: ScalarExpr::literal_true(),
+                kind: JoinKind::LeftOuter,
+                lateral,
+            };
+            Ok((joined, left_scope.product(right_scope)))
+        }
     }
 }
 
@@ -997,7 +1544,11 @@ fn plan_join_constraint<'a>(
     right: RelationExpr,
     right_scope: Scope,
     kind: JoinKind,
+    lateral: bool,
 ) -> Result<(RelationExpr, Scope), failure::Error> {
+    if lateral && (kind == JoinKind::RightOuter || kind == JoinKind::FullOuter) {
+        bail!("the combining JOIN type must be INNER or LEFT for a LATERAL reference");
+    }
     let (expr, scope) = match constraint {
         JoinConstraint::On(expr) => {
             let mut product_scope = left_scope.product(right_scope);
@@ -1009,7 +1560,7 @@ fn plan_join_constraint<'a>(
                     qcx.relation_type(&left)
                         .column_types
                         .into_iter()
-                        .chain(qcx.relation_type(&right).column_types)
+                        .chain(qcx.join_right_type(&left, &right, lateral).column_types)
                         .collect(),
                 ),
                 allow_aggregates: false,
@@ -1041,6 +1592,7 @@ fn plan_join_constraint<'a>(
                 right: Box::new(right),
                 on,
                 kind,
+                lateral,
             };
             (joined, product_scope)
         }
@@ -1055,6 +1607,7 @@ fn plan_join_constraint<'a>(
             right,
             right_scope,
             kind,
+            lateral,
         )?,
         JoinConstraint::Natural => {
             let mut column_names = vec![];
@@ -1078,6 +1631,7 @@ fn plan_join_constraint<'a>(
                 right,
                 right_scope,
                 kind,
+                lateral,
             )?
         }
     };
@@ -1094,6 +1648,7 @@ fn plan_using_constraint(
     right: RelationExpr,
     right_scope: Scope,
     kind: JoinKind,
+    lateral: bool,
 ) -> Result<(RelationExpr, Scope), failure::Error> {
     let mut join_exprs = vec![];
     let mut map_exprs = vec![];
@@ -1123,7 +1678,7 @@ fn plan_using_constraint(
             ),
         };
         let l_type = &qcx.relation_type(&left).column_types[l];
-        let r_type = &qcx.relation_type(&right).column_types[r];
+        let r_type = &qcx.join_right_type(&left, &right, lateral).column_types[r];
         if l_type.scalar_type != r_type.scalar_type {
             bail!(
                 "{:?} and {:?} are not comparable (in NATURAL/USING join on {})",
@@ -1192,6 +1747,7 @@ fn plan_using_constraint(
                 }
             }),
         kind,
+        lateral,
     }
     .map(map_exprs)
     .project(project_key);
@@ -1381,10 +1937,6 @@ fn plan_aggregate(ecx: &ExprContext, sql_func: &Function) -> Result<AggregateExp
     let name = normalize::function_name(sql_func.name.clone())?;
     assert!(func::is_aggregate_func(&name));
 
-    if sql_func.over.is_some() {
-        unsupported!(213, "window functions");
-    }
-
     // We follow PostgreSQL's rule here for mapping `count(*)` into the
     // generalized function selection framework. The rule is simple: the user
     // must type `count(*)`, but the function selection framework sees an empty
@@ -1494,6 +2046,160 @@ fn plan_identifier(ecx: &ExprContext, names: &[Ident]) -> Result<ScalarExpr, fai
     bail!("column \"{}\" does not exist", col_name);
 }
 
+/// Plans a call to a window function, appending a column containing its value to
+/// `relation_expr`, whose columns are described by `scope`.
+///
+/// The rows are grouped by the partition key, and each partition is reduced to
+/// a list of its rows paired with the function's value at that row. Flattening
+/// that list recovers the original rows, now with the function's value.
+fn plan_window_function(
+    qcx: &QueryContext,
+    relation_expr: RelationExpr,
+    scope: &Scope,
+    sql_func: &Function,
+) -> Result<RelationExpr, failure::Error> {
+    let name = normalize::function_name(sql_func.name.clone())?;
+    let window_spec = sql_func.over.as_ref().unwrap();
+    let ecx = &ExprContext {
+        qcx,
+        name: "window function",
+        scope,
+        relation_type: &qcx.relation_type(&relation_expr),
+        allow_aggregates: true,
+        allow_subqueries: true,
+    };
+
+    if sql_func.distinct {
+        unsupported!("DISTINCT in window functions");
+    }
+    let frame = plan_window_frame(window_spec)?;
+    let (args, func) = if func::is_window_func(&name) {
+        if sql_func.filter.is_some() {
+            bail!(
+                "FILTER specified but {}() is not an aggregate function",
+                name
+            );
+        }
+        let args = match &sql_func.args {
+            FunctionArgs::Star => {
+                bail!("* argument is invalid with non-aggregate function {}", name)
+            }
+            FunctionArgs::Args(args) => args,
+        };
+        func::select_window_func(ecx, &name, args)?
+    } else if func::is_aggregate_func(&name) {
+        let aggregate = plan_aggregate(ecx, sql_func)?;
+        let func = WindowFunc::Aggregate {
+            func: Box::new(aggregate.func),
+            frame,
+        };
+        (vec![*aggregate.expr], func)
+    } else {
+        bail!(
+            "OVER specified, but {}() is not a window function nor an aggregate function",
+            name
+        );
+    };
+
+    let arity = relation_expr.arity();
+    let mut map_exprs = vec![];
+    let mut group_key = vec![];
+    for expr in &window_spec.partition_by {
+        let expr = plan_expr(ecx, expr)?.type_as_any(ecx)?;
+        group_key.push(arity + map_exprs.len());
+        map_exprs.push(expr);
+    }
+    let mut order_exprs = vec![];
+    let mut order_by = vec![];
+    for obe in &window_spec.order_by {
+        order_by.push(ColumnOrder {
+            column: order_exprs.len(),
+            desc: match obe.asc {
+                None => false,
+                Some(asc) => !asc,
+            },
+        });
+        order_exprs.push(plan_expr(ecx, &obe.expr)?.type_as_any(ecx)?);
+    }
+
+    let record = |exprs: Vec<ScalarExpr>| ScalarExpr::CallVariadic {
+        func: VariadicFunc::RecordCreate {
+            field_names: (0..exprs.len())
+                .map(|i| ColumnName::from(format!("f{}", i + 1)))
+                .collect(),
+        },
+        exprs,
+    };
+    let row = (0..arity)
+        .map(|column| ScalarExpr::Column(ColumnRef { level: 0, column }))
+        .collect();
+    let aggregate = AggregateExpr {
+        func: AggregateFunc::Window { func, order_by },
+        expr: Box::new(record(vec![record(row), record(order_exprs), record(args)])),
+        distinct: false,
+    };
+
+    let list_column = group_key.len();
+    let relation_expr = relation_expr
+        .map(map_exprs)
+        .reduce(group_key, vec![aggregate]);
+    let typ = qcx.relation_type(&relation_expr);
+    let el_typ = match &typ.column_types[list_column].scalar_type {
+        ScalarType::List(el_typ) => (**el_typ).clone(),
+        _ => unreachable!("window function does not produce a list"),
+    };
+    let element = ScalarExpr::Column(ColumnRef {
+        level: 0,
+        column: list_column + 1,
+    });
+    let mut map_exprs: Vec<_> = (0..arity)
+        .map(|i| {
+            element
+                .clone()
+                .call_unary(UnaryFunc::RecordGet(0))
+                .call_unary(UnaryFunc::RecordGet(i))
+        })
+        .collect();
+    map_exprs.push(element.call_unary(UnaryFunc::RecordGet(1)));
+    let relation_expr = RelationExpr::FlatMap {
+        input: Box::new(relation_expr),
+        func: TableFunc::UnnestList(el_typ),
+        exprs: vec![ScalarExpr::Column(ColumnRef {
+            level: 0,
+            column: list_column,
+        })],
+    };
+    let start = list_column + 2;
+    Ok(relation_expr
+        .map(map_exprs)
+        .project((start..start + arity + 1).collect()))
+}
+
+/// Determines which rows of a partition a windowed aggregate covers.
+///
+/// Only frames that start at the beginning of the partition are supported.
+fn plan_window_frame(window_spec: &WindowSpec) -> Result<WindowFrame, failure::Error> {
+    let frame = match &window_spec.window_frame {
+        // The default frame extends through the peers of the current row,
+        // which are all rows of the partition if there is no ORDER BY.
+        None => return Ok(WindowFrame::ThroughPeers),
+        Some(frame) => frame,
+    };
+    Ok(match (&frame.start_bound, &frame.end_bound) {
+        (WindowFrameBound::Preceding(None), Some(WindowFrameBound::Following(None))) => {
+            WindowFrame::Partition
+        }
+        (WindowFrameBound::Preceding(None), None)
+        | (WindowFrameBound::Preceding(None), Some(WindowFrameBound::CurrentRow)) => {
+            match frame.units {
+                WindowFrameUnits::Rows => WindowFrame::ThroughRow,
+                WindowFrameUnits::Range | WindowFrameUnits::Groups => WindowFrame::ThroughPeers,
+            }
+        }
+        _ => unsupported!("window frames that do not start at UNBOUNDED PRECEDING"),
+    })
+}
+
 fn plan_function<'a>(
     ecx: &ExprContext,
     sql_func: &'a Function,
@@ -1501,6 +2207,10 @@ fn plan_function<'a>(
     let name = normalize::function_name(sql_func.name.clone())?;
     let ident = &*name;
 
+    if sql_func.over.is_some() {
+        bail!("window functions are not allowed in {}", ecx.name);
+    }
+
     if func::is_aggregate_func(&name) {
         if ecx.allow_aggregates {
             // should already have been caught by `scope.resolve_expr` in `plan_expr`
@@ -1518,9 +2228,6 @@ fn plan_function<'a>(
         );
     }
 
-    if sql_func.over.is_some() {
-        unsupported!(213, "window functions");
-    }
     if sql_func.filter.is_some() {
         bail!(
             "FILTER specified but {}() is not an aggregate function",
@@ -1757,7 +2464,7 @@ impl<'ast> AggregateFuncVisitor<'ast> {
 impl<'ast> Visit<'ast> for AggregateFuncVisitor<'ast> {
     fn visit_function(&mut self, func: &'ast Function) {
         if let Ok(name) = normalize::function_name(func.name.clone()) {
-            if func::is_aggregate_func(&name) {
+            if func::is_aggregate_func(&name) && func.over.is_none() {
                 if self.within_aggregate {
                     self.err = Some(format_err!("nested aggregate functions are not allowed"));
                     return;
@@ -1788,6 +2495,41 @@ impl<'ast> Visit<'ast> for AggregateFuncVisitor<'ast> {
     }
 }
 
+struct WindowFuncVisitor<'ast> {
+    funcs: Vec<&'ast Function>,
+}
+
+impl<'ast> WindowFuncVisitor<'ast> {
+    fn new() -> WindowFuncVisitor<'ast> {
+        WindowFuncVisitor { funcs: Vec::new() }
+    }
+
+    fn into_result(self) -> Vec<&'ast Function> {
+        // dedup window functions while preserving the order, as for aggregates
+        let mut seen = HashSet::new();
+        self.funcs
+            .into_iter()
+            .filter(move |func| seen.insert(&**func))
+            .collect()
+    }
+}
+
+impl<'ast> Visit<'ast> for WindowFuncVisitor<'ast> {
+    fn visit_function(&mut self, func: &'ast Function) {
+        if func.over.is_some() {
+            // Window functions nested within this one are rejected when it is
+            // planned.
+            self.funcs.push(func);
+            return;
+        }
+        visit::visit_function(self, func);
+    }
+
+    fn visit_query(&mut self, _query: &'ast Query) {
+        // Don't go into subqueries.
+    }
+}
+
 /// Specifies how long a query will live. This impacts whether the query is
 /// allowed to reason about the time at which it is running, e.g., by calling
 /// the `now()` function.
@@ -1813,6 +2555,22 @@ pub struct QueryContext<'a> {
     /// The types of the parameters in the query. This is filled in as planning
     /// occurs.
     pub param_types: Rc<RefCell<BTreeMap<usize, ScalarType>>>,
+    /// The common table expressions that are in scope, keyed by name.
+    pub ctes: HashMap<String, Cte>,
+    /// Allocates the identifiers of the `Let` bindings for common table
+    /// expressions, which must be unique across the entire query.
+    pub id_gen: Rc<RefCell<IdGen>>,
+}
+
+/// A common table expression that is in scope of a query.
+#[derive(Debug, Clone)]
+pub struct Cte {
+    /// The identifier of the `Let` binding that holds the value of the CTE.
+    pub id: LocalId,
+    /// The names of the columns of the CTE.
+    pub column_names: Vec<Option<ColumnName>>,
+    /// The type of the value of the CTE.
+    pub typ: RelationType,
 }
 
 impl<'a> QueryContext<'a> {
@@ -1823,6 +2581,8 @@ impl<'a> QueryContext<'a> {
             outer_scope: Scope::empty(None),
             outer_relation_types: vec![],
             param_types: Rc::new(RefCell::new(BTreeMap::new())),
+            ctes: HashMap::new(),
+            id_gen: Rc::new(RefCell::new(IdGen::default())),
         }
     }
 
@@ -1830,9 +2590,39 @@ impl<'a> QueryContext<'a> {
         Rc::try_unwrap(self.param_types).unwrap().into_inner()
     }
 
+    /// Consumes the context, returning the types of the parameters that were
+    /// encountered during planning, in order.
+    fn finish_param_types(self) -> Result<Vec<ScalarType>, failure::Error> {
+        let mut param_types = vec![];
+        for (i, (n, typ)) in self.unwrap_param_types().into_iter().enumerate() {
+            if n != i + 1 {
+                bail!("unable to infer type for parameter ${}", i + 1);
+            }
+            param_types.push(typ);
+        }
+        Ok(param_types)
+    }
+
     fn relation_type(&self, expr: &RelationExpr) -> RelationType {
         expr.typ(&self.outer_relation_types, &self.param_types.borrow())
     }
+
+    /// Computes the type of `right` as the right input of a join with `left`.
+    /// The right input of a lateral join may refer to the columns of the left.
+    fn join_right_type(
+        &self,
+        left: &RelationExpr,
+        right: &RelationExpr,
+        lateral: bool,
+    ) -> RelationType {
+        if lateral {
+            let mut outers = self.outer_relation_types.clone();
+            outers.push(self.relation_type(left));
+            right.typ(&outers, &self.param_types.borrow())
+        } else {
+            self.relation_type(right)
+        }
+    }
 }
 
 /// A bundle of unrelated things that we need for planning `Expr`s.
@@ -1888,6 +2678,8 @@ impl<'a> ExprContext<'a> {
                 .cloned()
                 .collect(),
             param_types: self.qcx.param_types.clone(),
+            ctes: self.qcx.ctes.clone(),
+            id_gen: self.qcx.id_gen.clone(),
         }
     }
 }
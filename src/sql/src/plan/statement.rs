@@ -22,25 +22,34 @@ use url::Url;
 
 use dataflow_types::{
     AvroEncoding, AvroOcfSinkConnectorBuilder, Consistency, CsvEncoding, DataEncoding, Envelope,
-    ExternalSourceConnector, FileSourceConnector, KafkaSinkConnectorBuilder, KafkaSourceConnector,
-    KinesisSourceConnector, PeekWhen, ProtobufEncoding, SinkConnectorBuilder, SourceConnector,
+    ExternalSourceConnector, FileSinkConnectorBuilder, FileSinkFormat, FileSourceConnector,
+    HttpSinkConnectorBuilder, JsonEncoding, KafkaSinkConnectorBuilder, KafkaSinkFormatBuilder,
+    KafkaSourceConnector, KinesisSinkConnectorBuilder, KinesisSinkFormat, KinesisSourceConnector,
+    PeekWhen, ProtobufEncoding, QueueFullBehavior, SinkConnectorBuilder, SinkEnvelope,
+    SinkQueueConfig, SourceConnector,
 };
 use expr::{like_pattern, GlobalId, RowSetFinishing};
 use interchange::avro::{DebeziumDeduplicationStrategy, Encoder};
+use interchange::protobuf;
 use ore::collections::CollectionExt;
 use repr::strconv;
 use repr::{ColumnType, Datum, RelationDesc, RelationType, Row, RowArena, ScalarType};
 use sql_parser::ast::{
-    AvroSchema, Connector, ExplainOptions, ExplainStage, Explainee, Expr, Format, Ident,
-    IfExistsBehavior, ObjectName, ObjectType, Query, SetVariableValue, ShowStatementFilter,
-    SqlOption, Statement, Value,
+    AlterSourceOperation, Assignment, AvroSchema, ColumnOption, Connector, CopyDirection,
+    CopyOption, CopyRelation, ExplainOptions, ExplainStage, Explainee, Expr, Format, GrantObjects,
+    Ident, IfExistsBehavior, ObjectName, ObjectType, Privileges, ProtobufSchema, Query, RoleOption,
+    Select, SelectItem, SetExpr, SetVariableValue, ShowStatementFilter, SqlOption, Statement,
+    TableFactor, TableWithJoins, TailRelation, Value,
 };
 
-use crate::catalog::{Catalog, CatalogItemType};
+use crate::catalog::{Catalog, CatalogItem, CatalogItemType, ObjectRef, Privilege, PUBLIC_ROLE};
 use crate::kafka_util;
 use crate::names::{DatabaseSpecifier, FullName, PartialName};
 use crate::plan::query::QueryLifetime;
-use crate::plan::{query, Index, Params, Plan, PlanContext, Sink, Source, View};
+use crate::plan::{
+    query, CopyFormat, CopyParams, Index, MutationKind, Params, Plan, PlanContext, Sink, Source,
+    Table, TailFrom, View,
+};
 use crate::pure::Schema;
 use crate::{normalize, unsupported};
 
@@ -102,14 +111,20 @@ pub fn describe_statement(
     let pcx = &PlanContext::default();
     let scx = &StatementContext { catalog, pcx };
     Ok(match stmt {
-        Statement::CreateDatabase { .. }
+        Statement::AlterSource { .. }
+        | Statement::CreateDatabase { .. }
         | Statement::CreateSchema { .. }
         | Statement::CreateIndex { .. }
         | Statement::CreateSource { .. }
         | Statement::CreateSink { .. }
+        | Statement::CreateTable { .. }
         | Statement::CreateView { .. }
+        | Statement::CreateRole { .. }
         | Statement::DropDatabase { .. }
         | Statement::DropObjects { .. }
+        | Statement::DropRole { .. }
+        | Statement::Grant { .. }
+        | Statement::Revoke { .. }
         | Statement::SetVariable { .. }
         | Statement::StartTransaction { .. }
         | Statement::Rollback { .. }
@@ -214,11 +229,18 @@ pub fn describe_statement(
             }
         }
 
-        Statement::Tail { name, .. } => {
-            let name = scx.resolve_item(name)?;
-            let sql_object = scx.catalog.get_item(&name);
-            (Some(sql_object.desc()?.clone()), vec![])
-        }
+        Statement::Tail { relation, .. } => match relation {
+            TailRelation::Name(name) => {
+                let name = scx.resolve_item(name)?;
+                let sql_object = scx.catalog.get_item(&name);
+                (Some(sql_object.desc()?.clone()), vec![])
+            }
+            TailRelation::Query(query) => {
+                let (_relation_expr, desc, _finishing, param_types) =
+                    query::plan_root_query(scx, query, QueryLifetime::Static)?;
+                (Some(desc), param_types)
+            }
+        },
 
         Statement::Select { query, .. } => {
             // TODO(benesch): ideally we'd save `relation_expr` and `finishing`
@@ -229,10 +251,45 @@ pub fn describe_statement(
                 query::plan_root_query(scx, *query, QueryLifetime::OneShot)?;
             (Some(desc), param_types)
         }
-        Statement::CreateTable { .. } => bail!(
-            "CREATE TABLE statements are not supported. \
-             Try CREATE SOURCE or CREATE [MATERIALIZED] VIEW instead."
-        ),
+
+        Statement::Insert {
+            table_name,
+            columns,
+            source,
+        } => {
+            let (_id, _expr, param_types) =
+                query::plan_insert_query(scx, table_name, columns, *source)?;
+            (None, param_types)
+        }
+
+        Statement::Copy {
+            relation,
+            direction,
+            options,
+        } => {
+            handle_copy(scx, relation, direction, options)?;
+            (None, vec![])
+        }
+
+        Statement::Update {
+            table_name,
+            assignments,
+            selection,
+        } => {
+            let (_id, _expr, param_types) =
+                query::plan_mutation_query(scx, table_name, assignments, selection)?;
+            (None, param_types)
+        }
+
+        Statement::Delete {
+            table_name,
+            selection,
+        } => {
+            let (_id, _expr, param_types) =
+                query::plan_mutation_query(scx, table_name, vec![], selection)?;
+            (None, param_types)
+        }
+
         _ => unsupported!(format!("{:?}", stmt)),
     })
 }
@@ -246,10 +303,11 @@ pub fn handle_statement(
     let scx = &StatementContext { pcx, catalog };
     match stmt {
         Statement::Tail {
-            name,
+            relation,
+            progress,
             with_snapshot,
             as_of,
-        } => handle_tail(scx, name, with_snapshot, as_of),
+        } => handle_tail(scx, relation, progress, with_snapshot, as_of, params),
         Statement::StartTransaction { .. } => Ok(Plan::StartTransaction),
         Statement::Commit { .. } => Ok(Plan::CommitTransaction),
         Statement::Rollback { .. } => Ok(Plan::AbortTransaction),
@@ -262,9 +320,16 @@ pub fn handle_statement(
             if_not_exists,
         } => handle_create_schema(scx, name, if_not_exists),
         Statement::CreateSource { .. } => handle_create_source(scx, stmt),
+        Statement::AlterSource { name, operation } => handle_alter_source(scx, name, operation),
+        Statement::CreateTable { .. } => handle_create_table(scx, stmt),
         Statement::CreateView { .. } => handle_create_view(scx, stmt, params),
         Statement::CreateSink { .. } => handle_create_sink(scx, stmt),
         Statement::CreateIndex { .. } => handle_create_index(scx, stmt),
+        Statement::CreateRole {
+            is_user,
+            name,
+            options,
+        } => handle_create_role(scx, is_user, name, options),
         Statement::DropDatabase { name, if_exists } => handle_drop_database(scx, name, if_exists),
         Statement::DropObjects {
             object_type,
@@ -272,6 +337,42 @@ pub fn handle_statement(
             names,
             cascade,
         } => handle_drop_objects(scx, object_type, if_exists, names, cascade),
+        Statement::DropRole { if_exists, names } => handle_drop_role(scx, if_exists, names),
+        Statement::Grant {
+            privileges,
+            objects,
+            grantees,
+        } => {
+            let (objects, grantees) = plan_privilege_change(scx, privileges, objects, grantees)?;
+            Ok(Plan::Grant { objects, grantees })
+        }
+        Statement::Revoke {
+            privileges,
+            objects,
+            grantees,
+        } => {
+            let (objects, grantees) = plan_privilege_change(scx, privileges, objects, grantees)?;
+            Ok(Plan::Revoke { objects, grantees })
+        }
+        Statement::Insert {
+            table_name,
+            columns,
+            source,
+        } => handle_insert(scx, table_name, columns, *source, params),
+        Statement::Copy {
+            relation,
+            direction,
+            options,
+        } => handle_copy(scx, relation, direction, options),
+        Statement::Update {
+            table_name,
+            assignments,
+            selection,
+        } => handle_update(scx, table_name, assignments, selection, params),
+        Statement::Delete {
+            table_name,
+            selection,
+        } => handle_delete(scx, table_name, selection, params),
         Statement::Select { query, as_of } => handle_select(scx, *query, as_of, params),
         Statement::SetVariable {
             local,
@@ -342,26 +443,47 @@ fn handle_show_variable(_: &StatementContext, variable: Ident) -> Result<Plan, f
 
 fn handle_tail(
     scx: &StatementContext,
-    from: ObjectName,
+    relation: TailRelation,
+    progress: bool,
     with_snapshot: bool,
     as_of: Option<sql_parser::ast::Expr>,
+    params: &Params,
 ) -> Result<Plan, failure::Error> {
-    let from = scx.resolve_item(from)?;
-    let entry = scx.catalog.get_item(&from);
     let ts = as_of.map(|e| query::eval_as_of(scx, e)).transpose()?;
+    let from = match relation {
+        TailRelation::Name(name) => {
+            let name = scx.resolve_item(name)?;
+            let entry = scx.catalog.get_item(&name);
+            scx.catalog
+                .check_privilege(&ObjectRef::Item(entry.id()), Privilege::Select)?;
+            match entry.item_type() {
+                CatalogItemType::Source | CatalogItemType::Table | CatalogItemType::View => {
+                    TailFrom::Id(entry.id())
+                }
+                CatalogItemType::Index | CatalogItemType::Sink => bail!(
+                    "'{}' cannot be tailed because it is a {}",
+                    name,
+                    entry.item_type(),
+                ),
+            }
+        }
+        TailRelation::Query(query) => {
+            // The query is maintained for as long as the TAIL runs, so, like a
+            // view, it cannot depend on the time at which it is planned.
+            let (mut relation_expr, _desc, finishing, _param_types) =
+                query::plan_root_query(scx, query, QueryLifetime::Static)?;
+            relation_expr.bind_parameters(&params);
+            relation_expr.finish(finishing);
+            TailFrom::Query(relation_expr.decorrelate())
+        }
+    };
 
-    match entry.item_type() {
-        CatalogItemType::Source | CatalogItemType::View => Ok(Plan::Tail {
-            id: entry.id(),
-            ts,
-            with_snapshot,
-        }),
-        CatalogItemType::Index | CatalogItemType::Sink => bail!(
-            "'{}' cannot be tailed because it is a {}",
-            from,
-            entry.item_type(),
-        ),
-    }
+    Ok(Plan::Tail {
+        from,
+        ts,
+        progress,
+        with_snapshot,
+    })
 }
 
 fn finish_show_where(
@@ -504,6 +626,7 @@ fn handle_show_indexes(
     let from_entry = scx.catalog.get_item(&from_name);
     if from_entry.item_type() != CatalogItemType::View
         && from_entry.item_type() != CatalogItemType::Source
+        && from_entry.item_type() != CatalogItemType::Table
     {
         bail!(
             "cannot show indexes on {} because it is a {}",
@@ -576,9 +699,12 @@ fn handle_show_columns(
 
     let arena = RowArena::new();
     let table_name = scx.resolve_item(table_name)?;
-    let rows: Vec<_> = scx
-        .catalog
-        .get_item(&table_name)
+    let entry = scx.catalog.get_item(&table_name);
+    // The columns of an index are those of the relation that it indexes.
+    let id = entry.index_details().map_or(entry.id(), |(_, on)| on);
+    scx.catalog
+        .check_privilege(&ObjectRef::Item(id), Privilege::Select)?;
+    let rows: Vec<_> = entry
         .desc()?
         .iter()
         .map(|(name, typ)| {
@@ -601,6 +727,7 @@ fn handle_show_create_view(
     let name = scx.resolve_item(name)?;
     let entry = scx.catalog.get_item(&name);
     if let CatalogItemType::View = entry.item_type() {
+        check_definition_privilege(scx, entry)?;
         Ok(Plan::SendRows(vec![Row::pack(&[
             Datum::String(&name.to_string()),
             Datum::String(entry.create_sql()),
@@ -617,6 +744,7 @@ fn handle_show_create_source(
     let name = scx.resolve_item(name)?;
     let entry = scx.catalog.get_item(&name);
     if let CatalogItemType::Source = entry.item_type() {
+        check_definition_privilege(scx, entry)?;
         Ok(Plan::SendRows(vec![Row::pack(&[
             Datum::String(&name.to_string()),
             Datum::String(entry.create_sql()),
@@ -633,6 +761,7 @@ fn handle_show_create_sink(
     let name = scx.resolve_item(name)?;
     let entry = scx.catalog.get_item(&name);
     if let CatalogItemType::Sink = entry.item_type() {
+        check_definition_privilege(scx, entry)?;
         Ok(Plan::SendRows(vec![Row::pack(&[
             Datum::String(&name.to_string()),
             Datum::String(entry.create_sql()),
@@ -649,6 +778,7 @@ fn handle_show_create_index(
     let name = scx.resolve_item(name)?;
     let entry = scx.catalog.get_item(&name);
     if let CatalogItemType::Index = entry.item_type() {
+        check_definition_privilege(scx, entry)?;
         Ok(Plan::SendRows(vec![Row::pack(&[
             Datum::String(&name.to_string()),
             Datum::String(entry.create_sql()),
@@ -658,22 +788,107 @@ fn handle_show_create_index(
     }
 }
 
+/// Checks that the current user may inspect the `CREATE` statement for
+/// `entry`.
+///
+/// Reading the definition of a table or view requires the privilege to read
+/// from it, and an index is treated like the relation that it indexes. Source
+/// and sink definitions can contain credentials for external systems, so they
+/// are only visible to their owners.
+fn check_definition_privilege(
+    scx: &StatementContext,
+    entry: &dyn CatalogItem,
+) -> Result<(), failure::Error> {
+    match entry.item_type() {
+        CatalogItemType::Table | CatalogItemType::View => scx
+            .catalog
+            .check_privilege(&ObjectRef::Item(entry.id()), Privilege::Select),
+        CatalogItemType::Index => {
+            let (_, on) = entry.index_details().expect("index without details");
+            scx.catalog
+                .check_privilege(&ObjectRef::Item(on), Privilege::Select)
+        }
+        CatalogItemType::Source | CatalogItemType::Sink => {
+            scx.catalog.check_owner(&ObjectRef::Item(entry.id()))
+        }
+    }
+}
+
 fn kafka_sink_builder(
     format: Option<Format>,
     with_options: Vec<SqlOption>,
     mut broker: String,
     topic_prefix: String,
+    key_indices: Option<Vec<usize>>,
+    envelope: SinkEnvelope,
     desc: RelationDesc,
     topic_suffix: String,
 ) -> Result<SinkConnectorBuilder, failure::Error> {
-    let schema_registry_url = match format {
+    let format = match format {
         Some(Format::Avro(AvroSchema::CsrUrl { url, seed })) => {
             if seed.is_some() {
                 bail!("SEED option does not make sense with sinks");
             }
-            url.parse()?
+            let key_schema = key_indices.as_ref().map(|key_indices| {
+                Encoder::new_key(&desc, key_indices)
+                    .row_schema()
+                    .canonical_form()
+            });
+            let encoder = Encoder::new(desc);
+            let value_schema = match envelope {
+                SinkEnvelope::Debezium => encoder.writer_schema().canonical_form(),
+                SinkEnvelope::Upsert => encoder.row_schema().canonical_form(),
+            };
+            KafkaSinkFormatBuilder::Avro {
+                schema_registry_url: url.parse()?,
+                value_schema,
+                key_schema,
+            }
+        }
+        Some(Format::Protobuf {
+            message_name,
+            schema: ProtobufSchema::CsrUrl { url, seed },
+        }) => {
+            if seed.is_some() {
+                bail!("SEED option does not make sense with sinks");
+            }
+            let key_schema = match &key_indices {
+                Some(key_indices) => Some(
+                    protobuf::Encoder::new_key(&desc, key_indices, &message_name)?.row_schema(),
+                ),
+                None => None,
+            };
+            let encoder = protobuf::Encoder::new(desc, &message_name)?;
+            let value_schema = match envelope {
+                SinkEnvelope::Debezium => encoder.writer_schema(),
+                SinkEnvelope::Upsert => encoder.row_schema(),
+            };
+            KafkaSinkFormatBuilder::Protobuf {
+                schema_registry_url: url.parse()?,
+                message_name,
+                value_schema,
+                key_schema,
+            }
+        }
+        Some(Format::Json { columns }) => {
+            if !columns.is_empty() {
+                bail!("JSON sinks cannot specify columns");
+            }
+            KafkaSinkFormatBuilder::Json
+        }
+        Some(Format::Csv {
+            header_row,
+            n_cols,
+            delimiter,
+        }) => KafkaSinkFormatBuilder::Csv {
+            delimiter: csv_sink_delimiter(header_row, n_cols, delimiter)?,
+        },
+        Some(Format::Avro(_)) => unsupported!("non-confluent schema registry avro sinks"),
+        Some(Format::Protobuf { .. }) => {
+            unsupported!("non-confluent schema registry protobuf sinks")
         }
-        _ => unsupported!("non-confluent schema registry avro sinks"),
+        Some(_) => bail!("Kafka sinks only support the AVRO, PROTOBUF, JSON, and CSV formats"),
+        None => bail!("Kafka sinks must specify a FORMAT"),
     };
 
     if !broker.contains(':') {
@@ -681,9 +896,6 @@ fn kafka_sink_builder(
     }
     let broker_url = broker.parse()?;
 
-    let encoder = Encoder::new(desc);
-    let value_schema = encoder.writer_schema().canonical_form();
-
     let mut with_options = normalize::with_options(&with_options);
 
     // Use the user supplied value for replication factor, or default to 1
@@ -697,20 +909,339 @@ fn kafka_sink_builder(
         bail!("replication factor for sink topics has to be greater than zero");
     }
 
+    let reuse_topic = match with_options.remove("reuse_topic") {
+        None => false,
+        Some(Value::Boolean(b)) => b,
+        Some(_) => bail!("reuse_topic must be a boolean"),
+    };
+    if reuse_topic {
+        if let KafkaSinkFormatBuilder::Protobuf { .. } = format {
+            unsupported!("reuse_topic with protobuf sinks");
+        }
+    }
+
+    // Kafka sinks spill by default, rather than stalling the worker, since
+    // Kafka is routinely slower than the dataflows that feed it.
+    let queue = get_sink_queue_config(
+        &mut with_options,
+        SinkQueueConfig {
+            on_full: QueueFullBehavior::Spill,
+            ..Default::default()
+        },
+    )?;
+
     Ok(SinkConnectorBuilder::Kafka(KafkaSinkConnectorBuilder {
         broker_url,
-        schema_registry_url,
-        value_schema,
+        format,
+        key_indices,
+        envelope,
         topic_prefix,
         topic_suffix,
+        reuse_topic,
         replication_factor,
         fuel: 10000,
+        queue,
     }))
 }
 
+fn file_sink_builder(
+    format: Option<Format>,
+    with_options: Vec<SqlOption>,
+    key_indices: Option<Vec<usize>>,
+    envelope: SinkEnvelope,
+    path: String,
+    file_name_suffix: String,
+) -> Result<SinkConnectorBuilder, failure::Error> {
+    let format = match format {
+        Some(Format::Json { columns }) => {
+            if !columns.is_empty() {
+                bail!("JSON sinks cannot specify columns");
+            }
+            FileSinkFormat::Json
+        }
+        Some(Format::Csv {
+            header_row,
+            n_cols,
+            delimiter,
+        }) => FileSinkFormat::Csv {
+            delimiter: csv_sink_delimiter(header_row, n_cols, delimiter)?,
+        },
+        Some(_) => bail!("file sinks only support the JSON and CSV formats"),
+        None => bail!("file sinks must specify a FORMAT"),
+    };
+
+    if key_indices.is_some() {
+        bail!("file sinks cannot specify a key");
+    }
+
+    if envelope != SinkEnvelope::Debezium {
+        bail!("file sinks only support the DEBEZIUM envelope");
+    }
+
+    let mut with_options = normalize::with_options(&with_options);
+
+    let rotate_bytes = match with_options.remove("rotate_bytes") {
+        None => None,
+        Some(Value::Number(n)) => match n.parse::<u64>() {
+            Ok(n) if n > 0 => Some(n),
+            _ => bail!("rotate_bytes must be a positive integer"),
+        },
+        Some(_) => bail!("rotate_bytes must be a positive integer"),
+    };
+
+    let rotate_interval = match with_options.remove("rotate_interval_ms") {
+        None => None,
+        Some(Value::Number(n)) => match n.parse::<u64>() {
+            Ok(n) if n > 0 => Some(Duration::from_millis(n)),
+            _ => bail!("rotate_interval_ms must be a positive integer"),
+        },
+        Some(_) => bail!("rotate_interval_ms must be a positive integer"),
+    };
+
+    if let Some(option) = with_options.keys().next() {
+        bail!("file sinks do not support WITH option {}", option);
+    }
+
+    let path = PathBuf::from(path);
+
+    if path.is_dir() {
+        bail!("file sink cannot write to a directory");
+    }
+
+    Ok(SinkConnectorBuilder::File(FileSinkConnectorBuilder {
+        path,
+        file_name_suffix,
+        format,
+        rotate_bytes,
+        rotate_interval,
+    }))
+}
+
+fn kinesis_sink_builder(
+    format: Option<Format>,
+    with_options: Vec<SqlOption>,
+    arn: String,
+    key_indices: Option<Vec<usize>>,
+    envelope: SinkEnvelope,
+) -> Result<SinkConnectorBuilder, failure::Error> {
+    let format = match format {
+        Some(Format::Json { columns }) => {
+            if !columns.is_empty() {
+                bail!("JSON sinks cannot specify columns");
+            }
+            KinesisSinkFormat::Json
+        }
+        Some(Format::Csv {
+            header_row,
+            n_cols,
+            delimiter,
+        }) => KinesisSinkFormat::Csv {
+            delimiter: csv_sink_delimiter(header_row, n_cols, delimiter)?,
+        },
+        Some(_) => bail!("Kinesis sinks only support the JSON and CSV formats"),
+        None => bail!("Kinesis sinks must specify a FORMAT"),
+    };
+
+    // Kinesis streams are not compacted, so an upsert envelope is of no use.
+    if envelope != SinkEnvelope::Debezium {
+        bail!("Kinesis sinks only support the DEBEZIUM envelope");
+    }
+
+    let mut with_options = normalize::with_options(&with_options);
+    let (stream_name, region) = kinesis_stream(&arn, &mut with_options)?;
+    let (access_key_id, secret_access_key, token) = aws_credentials(&mut with_options)?;
+
+    Ok(SinkConnectorBuilder::Kinesis(KinesisSinkConnectorBuilder {
+        stream_name,
+        region,
+        access_key_id,
+        secret_access_key,
+        token,
+        format,
+        partition_key_indices: key_indices,
+    }))
+}
+
+fn http_sink_builder(
+    format: Option<Format>,
+    with_options: Vec<SqlOption>,
+    url: String,
+    key_indices: Option<Vec<usize>>,
+    envelope: SinkEnvelope,
+    file_name_suffix: String,
+) -> Result<SinkConnectorBuilder, failure::Error> {
+    match format {
+        Some(Format::Json { columns }) => {
+            if !columns.is_empty() {
+                bail!("JSON sinks cannot specify columns");
+            }
+        }
+        Some(_) => bail!("HTTP sinks only support the JSON format"),
+        None => bail!("HTTP sinks must specify a FORMAT"),
+    }
+
+    if key_indices.is_some() {
+        bail!("HTTP sinks cannot specify a key");
+    }
+
+    if envelope != SinkEnvelope::Debezium {
+        bail!("HTTP sinks only support the DEBEZIUM envelope");
+    }
+
+    let url: Url = url.parse()?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        bail!("HTTP sinks require an http or https URL");
+    }
+
+    let mut with_options = normalize::with_options(&with_options);
+
+    let dead_letter_path = match with_options.remove("dead_letter_path") {
+        None => None,
+        Some(Value::String(path)) => Some(PathBuf::from(path)),
+        Some(_) => bail!("dead_letter_path must be a string"),
+    };
+
+    let max_retry = match with_options.remove("max_retry_ms") {
+        None => Duration::from_secs(60),
+        Some(Value::Number(n)) => match n.parse::<u64>() {
+            Ok(n) if n > 0 => Duration::from_millis(n),
+            _ => bail!("max_retry_ms must be a positive integer"),
+        },
+        Some(_) => bail!("max_retry_ms must be a positive integer"),
+    };
+
+    let queue = get_sink_queue_config(&mut with_options, SinkQueueConfig::default())?;
+
+    Ok(SinkConnectorBuilder::Http(HttpSinkConnectorBuilder {
+        url,
+        dead_letter_path,
+        file_name_suffix,
+        max_retry,
+        queue,
+    }))
+}
+
+/// Removes the options that bound a sink's queue from `with_options`,
+/// overriding the corresponding settings in `queue`.
+fn get_sink_queue_config(
+    with_options: &mut HashMap<String, Value>,
+    mut queue: SinkQueueConfig,
+) -> Result<SinkQueueConfig, failure::Error> {
+    match with_options.remove("max_queued_bytes") {
+        None => (),
+        Some(Value::Number(n)) => match n.parse::<usize>() {
+            Ok(n) if n > 0 => queue.max_bytes = n,
+            _ => bail!("max_queued_bytes must be a positive integer"),
+        },
+        Some(_) => bail!("max_queued_bytes must be a positive integer"),
+    }
+    match with_options.remove("on_queue_full") {
+        None => (),
+        Some(Value::String(s)) => match QueueFullBehavior::from_name(&s) {
+            Some(on_full) => queue.on_full = on_full,
+            None => bail!("on_queue_full must be one of 'block', 'error', or 'spill'"),
+        },
+        Some(_) => bail!("on_queue_full must be one of 'block', 'error', or 'spill'"),
+    }
+    Ok(queue)
+}
+
+/// Extracts the name and region of the Kinesis stream identified by `arn`.
+///
+/// If the ARN names a custom region, the `endpoint` option is removed from
+/// `with_options` and used as the region's endpoint.
+fn kinesis_stream(
+    arn: &str,
+    with_options: &mut HashMap<String, Value>,
+) -> Result<(String, Region), failure::Error> {
+    let arn: ARN = match arn.parse() {
+        Ok(arn) => arn,
+        Err(e) => bail!("Unable to parse provided ARN: {:#?}", e),
+    };
+    let stream_name = match arn.resource {
+        Resource::Path(path) => {
+            if path.starts_with("stream/") {
+                String::from(&path["stream/".len()..])
+            } else {
+                bail!("Unable to parse stream name from resource path: {}", path);
+            }
+        }
+        _ => unsupported!(format!("AWS Resource type: {:#?}", arn.resource)),
+    };
+
+    let region: Region = match arn.region {
+        Some(region) => match region.parse() {
+            Ok(region) => region,
+            Err(e) => {
+                // Region's fromstr doesn't support parsing custom regions.
+                // If a Kinesis stream's ARN indicates it exists in a custom
+                // region, support it iff a valid endpoint for the stream
+                // is also provided.
+                match with_options.remove("endpoint") {
+                    Some(Value::String(endpoint)) => Region::Custom {
+                        name: region,
+                        endpoint,
+                    },
+                    _ => bail!(
+                        "Unable to parse AWS region: {}. If providing a custom \
+                         region, an `endpoint` option must also be provided",
+                        e
+                    ),
+                }
+            }
+        },
+        None => bail!("Provided ARN does not include an AWS region"),
+    };
+
+    Ok((stream_name, region))
+}
+
+/// Removes the AWS access key ID, secret access key, and session token from
+/// `with_options`.
+fn aws_credentials(
+    with_options: &mut HashMap<String, Value>,
+) -> Result<(Option<String>, Option<String>, Option<String>), failure::Error> {
+    // todo@jldlaughlin: We should support all (?) variants of AWS authentication.
+    // https://github.com/materializeinc/materialize/issues/1991
+    let access_key_id = match with_options.remove("access_key_id") {
+        Some(Value::String(access_key_id)) => Some(access_key_id),
+        Some(_) => bail!("access_key_id must be a string"),
+        _ => None,
+    };
+    let secret_access_key = match with_options.remove("secret_access_key") {
+        Some(Value::String(secret_access_key)) => Some(secret_access_key),
+        Some(_) => bail!("secret_access_key must be a string"),
+        _ => None,
+    };
+    let token = match with_options.remove("token") {
+        Some(Value::String(token)) => Some(token),
+        Some(_) => bail!("token must be a string"),
+        _ => None,
+    };
+    Ok((access_key_id, secret_access_key, token))
+}
+
+/// Validates the options of a `FORMAT CSV` clause for a sink, returning the
+/// delimiter.
+fn csv_sink_delimiter(
+    header_row: bool,
+    n_cols: Option<usize>,
+    delimiter: char,
+) -> Result<u8, failure::Error> {
+    if header_row || n_cols.is_some() {
+        bail!("CSV sinks cannot specify WITH HEADER or WITH n COLUMNS");
+    }
+    match delimiter as u32 {
+        0..=127 => Ok(delimiter as u8),
+        _ => bail!("CSV delimiter must be an ASCII character"),
+    }
+}
+
 fn avro_ocf_sink_builder(
     format: Option<Format>,
     with_options: Vec<SqlOption>,
+    key_indices: Option<Vec<usize>>,
+    envelope: SinkEnvelope,
     path: String,
     file_name_suffix: String,
 ) -> Result<SinkConnectorBuilder, failure::Error> {
@@ -718,8 +1249,31 @@ fn avro_ocf_sink_builder(
         bail!("avro ocf sinks cannot specify a format");
     }
 
-    if !with_options.is_empty() {
-        bail!("avro ocf sinks do not support WITH options");
+    if key_indices.is_some() {
+        bail!("avro ocf sinks cannot specify a key");
+    }
+
+    if envelope != SinkEnvelope::Debezium {
+        bail!("avro ocf sinks only support the DEBEZIUM envelope");
+    }
+
+    let mut with_options = normalize::with_options(&with_options);
+
+    let codec = match with_options.remove("codec") {
+        None => avro::Codec::Null,
+        Some(Value::String(codec)) => match codec.parse() {
+            Ok(codec) => codec,
+            Err(_) => bail!(
+                "unknown avro ocf codec '{}': expected one of null, deflate, zstandard, \
+                 bzip2, or xz",
+                codec
+            ),
+        },
+        Some(_) => bail!("codec must be a string"),
+    };
+
+    if let Some(option) = with_options.keys().next() {
+        bail!("avro ocf sinks do not support WITH option {}", option);
     }
 
     let path = PathBuf::from(path);
@@ -731,37 +1285,55 @@ fn avro_ocf_sink_builder(
     Ok(SinkConnectorBuilder::AvroOcf(AvroOcfSinkConnectorBuilder {
         path,
         file_name_suffix,
+        codec,
     }))
 }
 
 fn handle_create_sink(scx: &StatementContext, stmt: Statement) -> Result<Plan, failure::Error> {
     let create_sql = normalize::create_statement(scx, stmt.clone())?;
-    let (name, from, connector, with_options, format, with_snapshot, as_of, if_not_exists) =
-        match stmt {
-            Statement::CreateSink {
-                name,
-                from,
-                connector,
-                with_options,
-                format,
-                with_snapshot,
-                as_of,
-                if_not_exists,
-            } => (
-                name,
-                from,
-                connector,
-                with_options,
-                format,
-                with_snapshot,
-                as_of,
-                if_not_exists,
-            ),
-            _ => unreachable!(),
-        };
+    let (
+        name,
+        from,
+        connector,
+        key,
+        with_options,
+        format,
+        envelope,
+        with_snapshot,
+        as_of,
+        if_not_exists,
+    ) = match stmt {
+        Statement::CreateSink {
+            name,
+            from,
+            connector,
+            key,
+            with_options,
+            format,
+            envelope,
+            with_snapshot,
+            as_of,
+            if_not_exists,
+        } => (
+            name,
+            from,
+            connector,
+            key,
+            with_options,
+            format,
+            envelope,
+            with_snapshot,
+            as_of,
+            if_not_exists,
+        ),
+        _ => unreachable!(),
+    };
 
     let name = scx.allocate_name(normalize::object_name(name)?);
+    scx.check_create(&name)?;
     let from = scx.catalog.get_item(&scx.resolve_item(from)?);
+    scx.catalog
+        .check_privilege(&ObjectRef::Item(from.id()), Privilege::Select)?;
     let suffix = format!(
         "{}-{}",
         scx.catalog
@@ -772,18 +1344,83 @@ fn handle_create_sink(scx: &StatementContext, stmt: Statement) -> Result<Plan, f
     );
 
     let as_of = as_of.map(|e| query::eval_as_of(scx, e)).transpose()?;
+    let desc = from.desc()?;
+
+    let envelope = match envelope {
+        None | Some(sql_parser::ast::Envelope::Debezium) => SinkEnvelope::Debezium,
+        Some(sql_parser::ast::Envelope::Upsert(None)) => SinkEnvelope::Upsert,
+        Some(sql_parser::ast::Envelope::Upsert(Some(_))) => {
+            bail!("upsert sinks cannot specify a key format")
+        }
+        Some(sql_parser::ast::Envelope::None) => unsupported!("ENVELOPE NONE for sinks"),
+    };
+
+    let key_indices = match key {
+        Some(key) => {
+            let mut key_names = vec![];
+            let mut key_indices = vec![];
+            for column in key {
+                let name = normalize::column_name(column);
+                let i = match desc.get_by_name(&name) {
+                    Some((i, _)) => i,
+                    None => bail!("column \"{}\" does not exist", name),
+                };
+                if key_indices.contains(&i) {
+                    bail!("column \"{}\" specified more than once", name);
+                }
+                key_names.push(name);
+                key_indices.push(i);
+            }
+            // The key must determine the value, or consumers that retain only
+            // the latest message for each key will lose data. Kinesis streams
+            // retain every record, so the key of a Kinesis sink only chooses
+            // the shard for each record, and need not be unique.
+            if !matches!(connector, Connector::Kinesis { .. })
+                && !desc
+                    .typ()
+                    .keys
+                    .iter()
+                    .any(|k| k.iter().all(|i| key_indices.contains(i)))
+            {
+                bail!(
+                    "KEY ({}) is not a unique key of {}",
+                    key_names.iter().join(", "),
+                    from.name()
+                );
+            }
+            Some(key_indices)
+        }
+        None => {
+            if envelope == SinkEnvelope::Upsert {
+                bail!("upsert sinks must specify a KEY");
+            }
+            None
+        }
+    };
+
     let connector_builder = match connector {
-        Connector::File { .. } => unsupported!("file sinks"),
+        Connector::File { path } => {
+            file_sink_builder(format, with_options, key_indices, envelope, path, suffix)?
+        }
         Connector::Kafka { broker, topic } => kafka_sink_builder(
             format,
             with_options,
             broker,
             topic,
-            from.desc()?.clone(),
+            key_indices,
+            envelope,
+            desc.clone(),
             suffix,
         )?,
-        Connector::Kinesis { .. } => unsupported!("Kinesis sinks"),
-        Connector::AvroOcf { path } => avro_ocf_sink_builder(format, with_options, path, suffix)?,
+        Connector::Kinesis { arn } => {
+            kinesis_sink_builder(format, with_options, arn, key_indices, envelope)?
+        }
+        Connector::AvroOcf { path } => {
+            avro_ocf_sink_builder(format, with_options, key_indices, envelope, path, suffix)?
+        }
+        Connector::Http { url } => {
+            http_sink_builder(format, with_options, url, key_indices, envelope, suffix)?
+        }
     };
 
     Ok(Plan::CreateSink {
@@ -814,9 +1451,12 @@ fn handle_create_index(
     };
     let on_name = scx.resolve_item(on_name.clone())?;
     let catalog_entry = scx.catalog.get_item(&on_name);
+    scx.catalog
+        .check_owner(&ObjectRef::Item(catalog_entry.id()))?;
 
     if CatalogItemType::View != catalog_entry.item_type()
         && CatalogItemType::Source != catalog_entry.item_type()
+        && CatalogItemType::Table != catalog_entry.item_type()
     {
         bail!(
             "index cannot be created on {} because it is a {}",
@@ -926,6 +1566,47 @@ fn handle_create_database(
     })
 }
 
+fn handle_create_role(
+    _scx: &StatementContext,
+    is_user: bool,
+    name: Ident,
+    options: Vec<RoleOption>,
+) -> Result<Plan, failure::Error> {
+    let mut superuser = None;
+    let mut login = None;
+    let mut password = None;
+    for option in options {
+        match option {
+            RoleOption::SuperUser | RoleOption::NoSuperUser => {
+                if superuser.is_some() {
+                    bail!("conflicting or redundant options");
+                }
+                superuser = Some(option == RoleOption::SuperUser);
+            }
+            RoleOption::Login | RoleOption::NoLogin => {
+                if login.is_some() {
+                    bail!("conflicting or redundant options");
+                }
+                login = Some(option == RoleOption::Login);
+            }
+            RoleOption::Password(pw) => {
+                if password.is_some() {
+                    bail!("conflicting or redundant options");
+                }
+                password = Some(pw);
+            }
+        }
+    }
+    Ok(Plan::CreateRole {
+        name: normalize::ident(name),
+        superuser: superuser.unwrap_or(false),
+        // `CREATE USER` is an alias for `CREATE ROLE` that defaults to `LOGIN`
+        // rather than `NOLOGIN`.
+        login: login.unwrap_or(is_user),
+        password: password.unwrap_or(None),
+    })
+}
+
 fn handle_create_schema(
     scx: &StatementContext,
     mut name: ObjectName,
@@ -940,9 +1621,14 @@ fn handle_create_schema(
             .expect("names always have at least one component"),
     );
     let database_name = match name.0.pop() {
-        None => DatabaseSpecifier::Name(scx.catalog.default_database().into()),
-        Some(n) => DatabaseSpecifier::Name(normalize::ident(n)),
+        None => scx.catalog.default_database().to_owned(),
+        Some(n) => normalize::ident(n),
     };
+    scx.catalog.check_privilege(
+        &ObjectRef::Database(database_name.clone()),
+        Privilege::Create,
+    )?;
+    let database_name = DatabaseSpecifier::Name(database_name);
     Ok(Plan::CreateSchema {
         database_name,
         schema_name,
@@ -950,6 +1636,64 @@ fn handle_create_schema(
     })
 }
 
+fn handle_create_table(scx: &StatementContext, stmt: Statement) -> Result<Plan, failure::Error> {
+    let create_sql = normalize::create_statement(scx, stmt.clone())?;
+    let (name, columns, constraints, with_options, if_not_exists) = match stmt {
+        Statement::CreateTable {
+            name,
+            columns,
+            constraints,
+            with_options,
+            if_not_exists,
+        } => (name, columns, constraints, with_options, if_not_exists),
+        _ => unreachable!(),
+    };
+    if !with_options.is_empty() {
+        unsupported!("WITH options");
+    }
+    // Tables do not yet enforce any constraints beyond nullability. Refuse
+    // the others rather than silently ignoring them.
+    if !constraints.is_empty() {
+        unsupported!("CREATE TABLE with constraints");
+    }
+
+    let mut names = vec![];
+    let mut column_types = vec![];
+    for column in columns {
+        if column.collation.is_some() {
+            unsupported!("COLLATE");
+        }
+        let name = normalize::column_name(column.name);
+        if names.contains(&Some(name.clone())) {
+            bail!("column \"{}\" specified more than once", name);
+        }
+        let mut nullable = true;
+        for option in column.options {
+            match option.option {
+                ColumnOption::Null => nullable = true,
+                ColumnOption::NotNull => nullable = false,
+                ColumnOption::Default(_) => unsupported!("DEFAULT column values"),
+                ColumnOption::Unique { is_primary: true } => unsupported!("PRIMARY KEY"),
+                ColumnOption::Unique { is_primary: false } => unsupported!("UNIQUE"),
+                ColumnOption::ForeignKey { .. } => unsupported!("foreign keys"),
+                ColumnOption::Check(_) => unsupported!("CHECK constraints"),
+            }
+        }
+        let ty = query::scalar_type_from_sql(&column.data_type)?;
+        names.push(Some(name));
+        column_types.push(ColumnType::new(ty).nullable(nullable));
+    }
+
+    let name = scx.allocate_name(normalize::object_name(name)?);
+    scx.check_create(&name)?;
+    let desc = RelationDesc::new(RelationType::new(column_types), names);
+    Ok(Plan::CreateTable {
+        name,
+        table: Table { create_sql, desc },
+        if_not_exists,
+    })
+}
+
 fn handle_create_view(
     scx: &StatementContext,
     mut stmt: Statement,
@@ -984,6 +1728,7 @@ fn handle_create_view(
     } else {
         scx.allocate_name(normalize::object_name(name.to_owned())?)
     };
+    scx.check_create(&name)?;
     let replace = if *if_exists == IfExistsBehavior::Replace
         && scx.catalog.resolve_item(&name.clone().into()).is_ok()
     {
@@ -1116,16 +1861,25 @@ fn handle_create_source(scx: &StatementContext, stmt: Statement) -> Result<Plan,
                         message_name,
                         schema,
                     } => {
-                        let descriptors = match schema {
-                            sql_parser::ast::Schema::Inline(bytes) => strconv::parse_bytes(&bytes)?,
-                            sql_parser::ast::Schema::File(_) => {
+                        let (descriptors, confluent_wire_format) = match schema {
+                            ProtobufSchema::Schema(sql_parser::ast::Schema::Inline(bytes)) => {
+                                (strconv::parse_bytes(&bytes)?, false)
+                            }
+                            ProtobufSchema::Schema(sql_parser::ast::Schema::File(_)) => {
                                 unreachable!("File schema should already have been inlined")
                             }
+                            ProtobufSchema::CsrUrl { seed, .. } => match seed {
+                                Some(seed) => (strconv::parse_bytes(&seed.value_schema)?, true),
+                                None => unreachable!(
+                                    "CSR seed resolution should already have been called"
+                                ),
+                            },
                         };
 
                         DataEncoding::Protobuf(ProtobufEncoding {
                             descriptors,
                             message_name: message_name.to_owned(),
+                            confluent_wire_format,
                         })
                     }
                     Format::Regex(regex) => {
@@ -1155,7 +1909,23 @@ fn handle_create_source(scx: &StatementContext, stmt: Statement) -> Result<Plan,
                             },
                         })
                     }
-                    Format::Json => unsupported!("JSON sources"),
+                    Format::Json { columns } => {
+                        let mut json_columns: Vec<(String, ScalarType)> = vec![];
+                        for column in columns {
+                            let name = normalize::column_name(column.name.clone()).to_string();
+                            if json_columns.iter().any(|(n, _)| *n == name) {
+                                bail!("column \"{}\" specified more than once", name);
+                            }
+                            let ty = query::scalar_type_from_sql(&column.data_type)?;
+                            if let ScalarType::List(_) = ty {
+                                unsupported!("list columns in JSON sources");
+                            }
+                            json_columns.push((name, ty));
+                        }
+                        DataEncoding::Json(JsonEncoding {
+                            columns: json_columns,
+                        })
+                    }
                     Format::Text => DataEncoding::Text,
                 })
             };
@@ -1215,62 +1985,9 @@ fn handle_create_source(scx: &StatementContext, stmt: Statement) -> Result<Plan,
                     (connector, encoding)
                 }
                 Connector::Kinesis { arn, .. } => {
-                    let arn: ARN = match arn.parse() {
-                        Ok(arn) => arn,
-                        Err(e) => bail!("Unable to parse provided ARN: {:#?}", e),
-                    };
-                    let stream_name = match arn.resource {
-                        Resource::Path(path) => {
-                            if path.starts_with("stream/") {
-                                String::from(&path["stream/".len()..])
-                            } else {
-                                bail!("Unable to parse stream name from resource path: {}", path);
-                            }
-                        }
-                        _ => unsupported!(format!("AWS Resource type: {:#?}", arn.resource)),
-                    };
-
-                    let region: Region = match arn.region {
-                        Some(region) => match region.parse() {
-                            Ok(region) => region,
-                            Err(e) => {
-                                // Region's fromstr doesn't support parsing custom regions.
-                                // If a Kinesis stream's ARN indicates it exists in a custom
-                                // region, support it iff a valid endpoint for the stream
-                                // is also provided.
-                                match with_options.remove("endpoint") {
-                                    Some(Value::String(endpoint)) => Region::Custom {
-                                        name: region,
-                                        endpoint,
-                                    },
-                                    _ => bail!(
-                                        "Unable to parse AWS region: {}. If providing a custom \
-                                         region, an `endpoint` option must also be provided",
-                                        e
-                                    ),
-                                }
-                            }
-                        },
-                        None => bail!("Provided ARN does not include an AWS region"),
-                    };
-
-                    // todo@jldlaughlin: We should support all (?) variants of AWS authentication.
-                    // https://github.com/materializeinc/materialize/issues/1991
-                    let access_key_id = match with_options.remove("access_key_id") {
-                        Some(Value::String(access_key_id)) => Some(access_key_id),
-                        Some(_) => bail!("access_key_id must be a string"),
-                        _ => None,
-                    };
-                    let secret_access_key = match with_options.remove("secret_access_key") {
-                        Some(Value::String(secret_access_key)) => Some(secret_access_key),
-                        Some(_) => bail!("secret_access_key must be a string"),
-                        _ => None,
-                    };
-                    let token = match with_options.remove("token") {
-                        Some(Value::String(token)) => Some(token),
-                        Some(_) => bail!("token must be a string"),
-                        _ => None,
-                    };
+                    let (stream_name, region) = kinesis_stream(&arn, &mut with_options)?;
+                    let (access_key_id, secret_access_key, token) =
+                        aws_credentials(&mut with_options)?;
 
                     let connector = ExternalSourceConnector::Kinesis(KinesisSourceConnector {
                         stream_name,
@@ -1296,8 +2013,26 @@ fn handle_create_source(scx: &StatementContext, stmt: Statement) -> Result<Plan,
                     max_ts_batch = extract_batch_size_option(&mut with_options)?;
                     ts_frequency = extract_timestamp_frequency_option(&mut with_options)?;
 
+                    // With `glob`, the path is a pattern that names a set of
+                    // files. Without it, glob metacharacters in the path are
+                    // taken literally.
+                    let glob = match with_options.remove("glob") {
+                        None => false,
+                        Some(Value::Boolean(b)) => b,
+                        Some(_) => bail!("glob must be a boolean"),
+                    };
+                    if glob {
+                        if let Err(e) = glob::Pattern::new(path) {
+                            bail!("invalid glob pattern '{}': {}", path, e);
+                        }
+                        if let Consistency::BringYourOwn(_) = consistency {
+                            bail!("file sources with glob patterns cannot specify consistency");
+                        }
+                    }
+
                     let connector = ExternalSourceConnector::File(FileSourceConnector {
                         path: path.clone().into(),
+                        glob,
                         tail,
                     });
                     let encoding = get_encoding(format)?;
@@ -1320,6 +2055,7 @@ fn handle_create_source(scx: &StatementContext, stmt: Statement) -> Result<Plan,
 
                     let connector = ExternalSourceConnector::AvroOcf(FileSourceConnector {
                         path: path.clone().into(),
+                        glob: false,
                         tail,
                     });
                     if format.is_some() {
@@ -1335,6 +2071,7 @@ fn handle_create_source(scx: &StatementContext, stmt: Statement) -> Result<Plan,
                     let encoding = DataEncoding::AvroOcf { reader_schema };
                     (connector, encoding)
                 }
+                Connector::Http { .. } => unsupported!("HTTP sources"),
             };
 
             // TODO (materialize#2537): cleanup format validation
@@ -1438,6 +2175,7 @@ fn handle_create_source(scx: &StatementContext, stmt: Statement) -> Result<Plan,
             let if_not_exists = *if_not_exists;
             let materialized = *materialized;
             let name = scx.allocate_name(normalize::object_name(name.clone())?);
+            scx.check_create(&name)?;
             let create_sql = normalize::create_statement(&scx, stmt)?;
 
             let source = Source {
@@ -1493,6 +2231,70 @@ fn maybe_rename_columns(
     Ok(desc.with_names(new_names))
 }
 
+fn handle_alter_source(
+    scx: &StatementContext,
+    name: ObjectName,
+    operation: AlterSourceOperation,
+) -> Result<Plan, failure::Error> {
+    let name = scx.resolve_item(name)?;
+    let entry = scx.catalog.get_item(&name);
+    if entry.item_type() != CatalogItemType::Source {
+        bail!("{} is not a source", name);
+    }
+    scx.catalog.check_owner(&ObjectRef::Item(entry.id()))?;
+    match operation {
+        AlterSourceOperation::RefreshSchema => {
+            let mut stmt = crate::parse::parse(entry.create_sql().to_owned())?.into_element();
+            let mut refreshable = false;
+            if let Statement::CreateSource {
+                format, envelope, ..
+            } = &mut stmt
+            {
+                // Forget the schemas that were fetched from the registry when
+                // the source was created, so that purification fetches the
+                // latest ones.
+                let mut formats = vec![format];
+                if let sql_parser::ast::Envelope::Upsert(format) = envelope {
+                    formats.push(format);
+                }
+                for format in formats {
+                    if let Some(Format::Avro(AvroSchema::CsrUrl { seed, .. })) = format {
+                        *seed = None;
+                        refreshable = true;
+                    }
+                }
+            }
+            if !refreshable {
+                bail!(
+                    "cannot refresh the schema of {}: only Avro sources that use a \
+                     Confluent Schema Registry can refresh their schema",
+                    name
+                );
+            }
+
+            // The encoding of a sink is fixed when the sink is created, so it
+            // cannot follow the source to a new schema.
+            let mut todo = entry.used_by().to_vec();
+            while let Some(id) = todo.pop() {
+                let dep = scx.catalog.get_item_by_id(&id);
+                if dep.item_type() == CatalogItemType::Sink {
+                    bail!(
+                        "cannot refresh the schema of {}: depended upon by sink '{}'",
+                        name,
+                        dep.name()
+                    );
+                }
+                todo.extend(dep.used_by());
+            }
+
+            Ok(Plan::AlterSourceRefreshSchema {
+                id: entry.id(),
+                stmt,
+            })
+        }
+    }
+}
+
 fn handle_drop_database(
     scx: &StatementContext,
     name: Ident,
@@ -1510,9 +2312,140 @@ fn handle_drop_database(
         }
         Err(err) => return Err(err),
     };
+    if !name.is_empty() {
+        scx.catalog
+            .check_owner(&ObjectRef::Database(name.clone()))?;
+    }
     Ok(Plan::DropDatabase { name })
 }
 
+fn handle_drop_role(
+    scx: &StatementContext,
+    if_exists: bool,
+    names: Vec<Ident>,
+) -> Result<Plan, failure::Error> {
+    let mut out = vec![];
+    for name in names {
+        let name = normalize::ident(name);
+        match scx.catalog.resolve_role(&name) {
+            Ok(()) => out.push(name),
+            // TODO: generate a notice indicating that the role does not
+            // exist.
+            Err(_) if if_exists => (),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(Plan::DropRoles { names: out })
+}
+
+/// Plans the privileges, objects, and grantees of a `GRANT` or `REVOKE`
+/// statement, which share their syntax.
+///
+/// Returns each object along with the privileges to grant on or revoke from
+/// it. `ALL` refers to every privilege that is valid for the object's type.
+fn plan_privilege_change(
+    scx: &StatementContext,
+    privileges: Privileges,
+    objects: GrantObjects,
+    grantees: Vec<Ident>,
+) -> Result<(Vec<(ObjectRef, Vec<Privilege>)>, Vec<String>), failure::Error> {
+    let mut targets = vec![];
+    match objects {
+        GrantObjects::Databases(names) => {
+            for name in names {
+                targets.push((
+                    ObjectRef::Database(scx.resolve_database_ident(name)?),
+                    "database".to_owned(),
+                    &[Privilege::Usage, Privilege::Create][..],
+                ));
+            }
+        }
+        GrantObjects::Schemas(names) => {
+            for name in names {
+                let (database_spec, schema_name) = scx.resolve_schema(name)?;
+                if let DatabaseSpecifier::Ambient = database_spec {
+                    bail!(
+                        "cannot change privileges on system schema '{}'",
+                        schema_name
+                    );
+                }
+                targets.push((
+                    ObjectRef::Schema(database_spec, schema_name),
+                    "schema".to_owned(),
+                    &[Privilege::Create][..],
+                ));
+            }
+        }
+        GrantObjects::Items(names) => {
+            for name in names {
+                let name = scx.resolve_item(name)?;
+                let entry = scx.catalog.get_item(&name);
+                let valid_privileges = match entry.item_type() {
+                    CatalogItemType::Table => &[
+                        Privilege::Select,
+                        Privilege::Insert,
+                        Privilege::Update,
+                        Privilege::Delete,
+                    ][..],
+                    CatalogItemType::Source | CatalogItemType::View => &[Privilege::Select][..],
+                    CatalogItemType::Sink | CatalogItemType::Index => {
+                        bail!("cannot change privileges on {} {}", entry.item_type(), name)
+                    }
+                };
+                if let DatabaseSpecifier::Ambient = name.database {
+                    bail!(
+                        "cannot change privileges on system or temporary item {}",
+                        name
+                    );
+                }
+                targets.push((
+                    ObjectRef::Item(entry.id()),
+                    entry.item_type().to_string(),
+                    valid_privileges,
+                ));
+            }
+        }
+    }
+
+    let mut out = vec![];
+    for (object, object_type, valid_privileges) in targets {
+        scx.catalog.check_owner(&object)?;
+        let privileges = match &privileges {
+            Privileges::All => valid_privileges.to_vec(),
+            Privileges::Actions(actions) => {
+                let mut privileges = vec![];
+                for action in actions {
+                    let privilege = match action {
+                        sql_parser::ast::Privilege::Select => Privilege::Select,
+                        sql_parser::ast::Privilege::Insert => Privilege::Insert,
+                        sql_parser::ast::Privilege::Update => Privilege::Update,
+                        sql_parser::ast::Privilege::Delete => Privilege::Delete,
+                        sql_parser::ast::Privilege::Create => Privilege::Create,
+                        sql_parser::ast::Privilege::Usage => Privilege::Usage,
+                    };
+                    if !valid_privileges.contains(&privilege) {
+                        bail!("invalid privilege type {} for {}", privilege, object_type);
+                    }
+                    privileges.push(privilege);
+                }
+                privileges
+            }
+        };
+        out.push((object, privileges));
+    }
+
+    let mut names = vec![];
+    for grantee in grantees {
+        let grantee = normalize::ident(grantee);
+        if grantee != PUBLIC_ROLE {
+            scx.catalog.resolve_role(&grantee)?;
+        }
+        names.push(grantee);
+    }
+
+    Ok((out, names))
+}
+
 fn handle_drop_objects(
     scx: &StatementContext,
     object_type: ObjectType,
@@ -1522,10 +2455,11 @@ fn handle_drop_objects(
 ) -> Result<Plan, failure::Error> {
     match object_type {
         ObjectType::Schema => handle_drop_schema(scx, if_exists, names, cascade),
-        ObjectType::Source | ObjectType::View | ObjectType::Index | ObjectType::Sink => {
-            handle_drop_items(scx, object_type, if_exists, names, cascade)
-        }
-        _ => unsupported!(format!("DROP {}", object_type)),
+        ObjectType::Source
+        | ObjectType::Table
+        | ObjectType::View
+        | ObjectType::Index
+        | ObjectType::Sink => handle_drop_items(scx, object_type, if_exists, names, cascade),
     }
 }
 
@@ -1546,6 +2480,10 @@ fn handle_drop_schema(
                     schema_name
                 );
             }
+            scx.catalog.check_owner(&ObjectRef::Schema(
+                database_spec.clone(),
+                schema_name.clone(),
+            ))?;
             let mut items = scx.catalog.list_items(&database_spec, &schema_name);
             if !cascade && items.next().is_some() {
                 bail!(
@@ -1617,11 +2555,28 @@ fn handle_drop_item(
     if object_type != catalog_entry.item_type() {
         bail!("{} is not of type {}", name, object_type);
     }
+    scx.catalog
+        .check_owner(&ObjectRef::Item(catalog_entry.id()))?;
+    if let Some((_keys, on)) = catalog_entry.index_details() {
+        let on = scx.catalog.get_item_by_id(&on);
+        if on.item_type() == CatalogItemType::Table
+            && table_primary_index(scx, on) == Some(catalog_entry.id())
+        {
+            bail!(
+                "cannot drop {}: it holds the contents of table '{}'",
+                name,
+                on.name()
+            );
+        }
+    }
     if !cascade {
         for id in catalog_entry.used_by() {
             let dep = scx.catalog.get_item_by_id(id);
             match dep.item_type() {
-                CatalogItemType::Source | CatalogItemType::View | CatalogItemType::Sink => {
+                CatalogItemType::Source
+                | CatalogItemType::Table
+                | CatalogItemType::View
+                | CatalogItemType::Sink => {
                     bail!(
                         "cannot drop {}: still depended upon by catalog item '{}'",
                         catalog_entry.name(),
@@ -1635,6 +2590,244 @@ fn handle_drop_item(
     Ok(Some(catalog_entry.id()))
 }
 
+/// Returns the ID of the index that holds the contents of `table`.
+///
+/// Every table is created alongside a primary index, which is where the
+/// table's data lives. It is always the oldest index on the table.
+fn table_primary_index(scx: &StatementContext, table: &dyn CatalogItem) -> Option<GlobalId> {
+    table
+        .used_by()
+        .iter()
+        .filter(|id| scx.catalog.get_item_by_id(*id).item_type() == CatalogItemType::Index)
+        .min()
+        .cloned()
+}
+
+fn handle_insert(
+    scx: &StatementContext,
+    table_name: ObjectName,
+    columns: Vec<Ident>,
+    source: Query,
+    params: &Params,
+) -> Result<Plan, failure::Error> {
+    let (id, mut expr, _param_types) = query::plan_insert_query(scx, table_name, columns, source)?;
+    expr.bind_parameters(&params);
+    Ok(Plan::Insert {
+        id,
+        values: expr.decorrelate(),
+    })
+}
+
+fn handle_copy(
+    scx: &StatementContext,
+    relation: CopyRelation,
+    direction: CopyDirection,
+    options: Vec<CopyOption>,
+) -> Result<Plan, failure::Error> {
+    let copy_params = plan_copy_params(options)?;
+    match (relation, direction) {
+        (CopyRelation::Table { name, columns }, CopyDirection::From) => {
+            if copy_params.format == CopyFormat::Binary {
+                unsupported!("COPY FROM binary format");
+            }
+            let (id, columns) = query::plan_copy_from(scx, name, columns)?;
+            Ok(Plan::CopyFrom {
+                id,
+                columns,
+                params: copy_params,
+            })
+        }
+        (CopyRelation::Select(_), CopyDirection::From) => {
+            unreachable!("parser does not permit COPY (query) FROM STDIN")
+        }
+        (relation, CopyDirection::To) => {
+            let query = match relation {
+                CopyRelation::Table { name, columns } => copy_table_query(name, columns),
+                CopyRelation::Select(query) => *query,
+            };
+            let (expr, desc, finishing, param_types) =
+                query::plan_root_query(scx, query, QueryLifetime::OneShot)?;
+            if !param_types.is_empty() {
+                bail!("COPY does not support parameters");
+            }
+            Ok(Plan::CopyTo {
+                source: expr.decorrelate(),
+                finishing,
+                desc,
+                params: copy_params,
+            })
+        }
+    }
+}
+
+/// Constructs the query `SELECT columns FROM name`, to which
+/// `COPY name (columns) TO STDOUT` is equivalent. If `columns` is empty, every
+/// column is selected.
+fn copy_table_query(name: ObjectName, columns: Vec<Ident>) -> Query {
+    let projection = if columns.is_empty() {
+        vec![SelectItem::Wildcard]
+    } else {
+        columns
+            .into_iter()
+            .map(|column| SelectItem::Expr {
+                expr: Expr::Identifier(vec![column]),
+                alias: None,
+            })
+            .collect()
+    };
+    Query {
+        ctes: vec![],
+        recursive: false,
+        body: SetExpr::Select(Box::new(Select {
+            distinct: false,
+            projection,
+            from: vec![TableWithJoins {
+                relation: TableFactor::Table {
+                    name,
+                    args: None,
+                    alias: None,
+                    with_hints: vec![],
+                },
+                joins: vec![],
+            }],
+            selection: None,
+            group_by: vec![],
+            having: None,
+        })),
+        order_by: vec![],
+        limit: None,
+        offset: None,
+        fetch: None,
+    }
+}
+
+/// Validates the options of a `COPY` statement, filling in the defaults for
+/// the requested format the way PostgreSQL does.
+fn plan_copy_params(options: Vec<CopyOption>) -> Result<CopyParams, failure::Error> {
+    let mut values = HashMap::new();
+    for option in options {
+        let name = normalize::ident(option.name);
+        if values.insert(name, option.value).is_some() {
+            bail!("conflicting or redundant options");
+        }
+    }
+
+    let string_option = |name: &str, value: Option<Value>| match value {
+        Some(Value::String(s)) => Ok(s),
+        _ => bail!("COPY {} must be a string", name),
+    };
+    let byte_option = |name: &str, value: Option<Value>| {
+        let s = string_option(name, value)?;
+        match s.as_bytes() {
+            [b] => Ok(*b),
+            _ => bail!("COPY {} must be a single one-byte character", name),
+        }
+    };
+
+    let format = match values.remove("format") {
+        None => CopyFormat::Text,
+        Some(value) => match string_option("format", value)?.to_lowercase().as_str() {
+            "text" => CopyFormat::Text,
+            "csv" => CopyFormat::Csv,
+            "binary" => CopyFormat::Binary,
+            other => bail!("COPY format \"{}\" not recognized", other),
+        },
+    };
+    let csv = format == CopyFormat::Csv;
+    let binary = format == CopyFormat::Binary;
+    let mut params = CopyParams {
+        format,
+        delimiter: if csv { b',' } else { b'\t' },
+        null: if csv { "".into() } else { "\\N".into() },
+        header: false,
+        quote: b'"',
+        escape: b'"',
+    };
+
+    if let Some(value) = values.remove("delimiter") {
+        if binary {
+            bail!("cannot specify DELIMITER in BINARY mode");
+        }
+        params.delimiter = byte_option("delimiter", value)?;
+    }
+    if let Some(value) = values.remove("null") {
+        if binary {
+            bail!("cannot specify NULL in BINARY mode");
+        }
+        params.null = string_option("null", value)?;
+    }
+    if let Some(value) = values.remove("header") {
+        if !csv {
+            bail!("COPY HEADER available only in CSV mode");
+        }
+        params.header = match value {
+            None => true,
+            Some(Value::Boolean(b)) => b,
+            Some(Value::String(s)) => strconv::parse_bool(&s)?,
+            Some(_) => bail!("COPY header must be a boolean"),
+        };
+    }
+    if let Some(value) = values.remove("quote") {
+        if !csv {
+            bail!("COPY quote available only in CSV mode");
+        }
+        params.quote = byte_option("quote", value)?;
+    }
+    match values.remove("escape") {
+        Some(_) if !csv => bail!("COPY escape available only in CSV mode"),
+        Some(value) => params.escape = byte_option("escape", value)?,
+        // The escape character defaults to the quote character.
+        None => params.escape = params.quote,
+    }
+    if let Some(name) = values.keys().next() {
+        bail!("option \"{}\" not recognized", name);
+    }
+
+    if params.delimiter == b'\n' || params.delimiter == b'\r' {
+        bail!("COPY delimiter cannot be newline or carriage return");
+    }
+    if params.null.contains(|c| c == '\n' || c == '\r') {
+        bail!("COPY null representation cannot use newline or carriage return");
+    }
+    if csv && params.delimiter == params.quote {
+        bail!("COPY delimiter and quote must be different");
+    }
+    Ok(params)
+}
+
+fn handle_update(
+    scx: &StatementContext,
+    table_name: ObjectName,
+    assignments: Vec<Assignment>,
+    selection: Option<Expr>,
+    params: &Params,
+) -> Result<Plan, failure::Error> {
+    let (id, mut expr, _param_types) =
+        query::plan_mutation_query(scx, table_name, assignments, selection)?;
+    expr.bind_parameters(&params);
+    Ok(Plan::ReadThenWrite {
+        id,
+        selection: expr.decorrelate(),
+        kind: MutationKind::Update,
+    })
+}
+
+fn handle_delete(
+    scx: &StatementContext,
+    table_name: ObjectName,
+    selection: Option<Expr>,
+    params: &Params,
+) -> Result<Plan, failure::Error> {
+    let (id, mut expr, _param_types) =
+        query::plan_mutation_query(scx, table_name, vec![], selection)?;
+    expr.bind_parameters(&params);
+    Ok(Plan::ReadThenWrite {
+        id,
+        selection: expr.decorrelate(),
+        kind: MutationKind::Delete,
+    })
+}
+
 fn handle_select(
     scx: &StatementContext,
     query: Query,
@@ -1731,14 +2924,11 @@ fn handle_query(
 /// Whether a SQL object type can be interpreted as matching the type of the given catalog item.
 /// For example, if `v` is a view, `DROP SOURCE v` should not work, since Source and View
 /// are non-matching types.
-///
-/// For now tables are treated as a special kind of source in Materialize, so just
-/// allow `TABLE` to refer to either.
 impl PartialEq<ObjectType> for CatalogItemType {
     fn eq(&self, other: &ObjectType) -> bool {
         match (self, other) {
             (CatalogItemType::Source, ObjectType::Source)
-            | (CatalogItemType::Source, ObjectType::Table)
+            | (CatalogItemType::Table, ObjectType::Table)
             | (CatalogItemType::Sink, ObjectType::Sink)
             | (CatalogItemType::View, ObjectType::View)
             | (CatalogItemType::Index, ObjectType::Index) => true,
@@ -1829,8 +3019,31 @@ impl<'a> StatementContext<'a> {
         Ok((database_spec, schema_name))
     }
 
+    /// Resolves an item name, checking that the current user may use the
+    /// database that contains the item.
     pub fn resolve_item(&self, name: ObjectName) -> Result<FullName, failure::Error> {
         let name = normalize::object_name(name)?;
-        Ok(self.catalog.resolve_item(&name)?)
+        let name = self.catalog.resolve_item(&name)?;
+        if let DatabaseSpecifier::Name(database) = &name.database {
+            self.catalog
+                .check_privilege(&ObjectRef::Database(database.clone()), Privilege::Usage)?;
+        }
+        Ok(name)
+    }
+
+    /// Checks that the current user may create an item named `name`.
+    ///
+    /// Temporary items are exempt, as they are only visible to the session
+    /// that creates them.
+    pub fn check_create(&self, name: &FullName) -> Result<(), failure::Error> {
+        if let DatabaseSpecifier::Name(database) = &name.database {
+            self.catalog
+                .check_privilege(&ObjectRef::Database(database.clone()), Privilege::Usage)?;
+            self.catalog.check_privilege(
+                &ObjectRef::Schema(name.database.clone(), name.schema.clone()),
+                Privilege::Create,
+            )?;
+        }
+        Ok(())
     }
 }
@@ -154,7 +154,23 @@ pub fn split_subquery_predicates(expr: &mut RelationExpr) {
 /// M. Elhemali, et al.
 pub fn try_simplify_quantified_comparisons(expr: &mut RelationExpr) {
     fn walk_relation(expr: &mut RelationExpr, outers: &[RelationType]) {
-        expr.visit_mut(&mut |expr| match expr {
+        if let RelationExpr::Join {
+            left,
+            right,
+            lateral: true,
+            ..
+        } = expr
+        {
+            // The right input of a lateral join may refer to the columns of
+            // the left input, as though it were a subquery.
+            walk_relation(left, outers);
+            let mut right_outers = outers.to_vec();
+            right_outers.push(left.typ(outers, &NO_PARAMS));
+            walk_relation(right, &right_outers);
+            return;
+        }
+        expr.visit1_mut(|expr| walk_relation(expr, outers));
+        match expr {
             RelationExpr::Map { scalars, input }
             | RelationExpr::FlatMap {
                 exprs: scalars,
@@ -175,7 +191,7 @@ pub fn try_simplify_quantified_comparisons(expr: &mut RelationExpr) {
                 }
             }
             _ => (),
-        })
+        }
     }
 
     fn walk_scalar(expr: &mut ScalarExpr, outers: &[RelationType], mut in_filter: bool) {
@@ -9,13 +9,15 @@
 
 //! Statement purification.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use failure::{bail, ResultExt};
 use tokio::io::AsyncBufReadExt;
 
 use repr::strconv;
-use sql_parser::ast::{AvroSchema, Connector, CsrSeed, Format, Ident, Statement};
+use sql_parser::ast::{
+    AvroSchema, Connector, CsrSeed, Format, Ident, ProtobufSchema, Statement, Value,
+};
 
 use crate::kafka_util;
 use crate::normalize;
@@ -62,10 +64,27 @@ pub async fn purify(mut stmt: Statement) -> Result<Statement, failure::Error> {
                     });
                 }
             }
-            // Report an error if a file cannot be opened.
+            // Report an error if a file cannot be opened. A glob pattern
+            // need not match any files yet, but its first match, if any,
+            // stands in for the file when inferring the format.
             Connector::File { path, .. } => {
-                let path = path.clone();
-                file = Some(tokio::fs::File::open(path).await?);
+                let glob = match with_options_map.get("glob") {
+                    None => false,
+                    Some(Value::Boolean(b)) => *b,
+                    Some(_) => bail!("glob must be a boolean"),
+                };
+                if glob {
+                    let mut paths = glob::glob(path)
+                        .with_context(|e| format!("invalid glob pattern '{}': {}", path, e))?
+                        .collect::<Result<Vec<_>, _>>()?;
+                    paths.sort();
+                    if let Some(path) = paths.into_iter().next() {
+                        file = Some(tokio::fs::File::open(path).await?);
+                    }
+                } else {
+                    let path = path.clone();
+                    file = Some(tokio::fs::File::open(path).await?);
+                }
             }
             _ => (),
         }
@@ -116,14 +135,34 @@ async fn purify_format(
             }
             _ => {}
         },
-        Some(Format::Protobuf { schema, .. }) => {
-            if let sql_parser::ast::Schema::File(path) = schema {
+        Some(Format::Protobuf { schema, .. }) => match schema {
+            ProtobufSchema::CsrUrl { url, seed } => {
+                let topic = if let Connector::Kafka { topic, .. } = connector {
+                    topic
+                } else {
+                    bail!("Confluent Schema Registry is only supported with Kafka sources")
+                };
+                if seed.is_none() {
+                    let url = url.parse()?;
+
+                    let ccsr_config =
+                        kafka_util::generate_ccsr_client_config(url, &specified_options)?;
+
+                    let value_schema = get_remote_protobuf_schema(ccsr_config, topic).await?;
+                    *seed = Some(CsrSeed {
+                        key_schema: None,
+                        value_schema,
+                    });
+                }
+            }
+            ProtobufSchema::Schema(sql_parser::ast::Schema::File(path)) => {
                 let descriptors = tokio::fs::read(path).await?;
                 let mut buf = String::new();
                 strconv::format_bytes(&mut buf, &descriptors);
-                *schema = sql_parser::ast::Schema::Inline(buf);
+                *schema = ProtobufSchema::Schema(sql_parser::ast::Schema::Inline(buf));
             }
-        }
+            _ => {}
+        },
         Some(Format::Csv {
             header_row,
             delimiter,
@@ -141,6 +180,12 @@ async fn purify_format(
                         }
                         None => bail!("CSV file expected header line, but is empty"),
                     }
+                } else if let Connector::File { path, .. } = connector {
+                    bail!(
+                        "CSV format with headers requires a file to read the header from, \
+                         but no files match '{}'",
+                        path
+                    )
                 } else {
                     bail!("CSV format with headers only works with file connectors")
                 }
@@ -182,3 +227,49 @@ async fn get_remote_avro_schema(
         schema_registry_config: Some(schema_registry_config),
     })
 }
+
+/// Fetches the latest `.proto` schema for `topic`'s values from the registry,
+/// and compiles it into descriptors, formatted as a byte string literal like
+/// an inlined schema file.
+async fn get_remote_protobuf_schema(
+    schema_registry_config: ccsr::ClientConfig,
+    topic: &str,
+) -> Result<String, failure::Error> {
+    let ccsr_client = schema_registry_config.build();
+
+    let value_schema_name = format!("{}-value", topic);
+    let value_schema = ccsr_client
+        .get_schema_by_subject(&value_schema_name)
+        .await
+        .with_context(|err| {
+            format!(
+                "fetching latest schema for subject '{}' from registry: {}",
+                value_schema_name, err
+            )
+        })?;
+    // Fetch the files that the schema imports, and the files that they import
+    // in turn, so that the schema can be compiled.
+    let mut imports = vec![];
+    let mut seen = HashSet::new();
+    let mut pending = value_schema.references;
+    while let Some(reference) = pending.pop() {
+        if !seen.insert(reference.name.clone()) {
+            continue;
+        }
+        let schema = ccsr_client
+            .get_schema_by_subject_and_version(&reference.subject, reference.version)
+            .await
+            .with_context(|err| {
+                format!(
+                    "fetching version {} of subject '{}', imported as '{}', from registry: {}",
+                    reference.version, reference.subject, reference.name, err
+                )
+            })?;
+        pending.extend(schema.references);
+        imports.push((reference.name, schema.raw));
+    }
+    let descriptors = interchange::protobuf::compile_proto_schema(&value_schema.raw, &imports)?;
+    let mut buf = String::new();
+    strconv::format_bytes(&mut buf, &descriptors);
+    Ok(buf)
+}
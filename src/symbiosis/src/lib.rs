@@ -40,7 +40,7 @@ use repr::{ColumnType, Datum, RelationDesc, RelationType, Row, RowPacker, Scalar
 use sql::catalog::Catalog;
 use sql::names::FullName;
 use sql::normalize;
-use sql::plan::{scalar_type_from_sql, MutationKind, Plan, PlanContext, StatementContext};
+use sql::plan::{scalar_type_from_sql, MutationKind, Plan, PlanContext, StatementContext, Table};
 
 pub struct Postgres {
     client: tokio_postgres::Client,
@@ -112,7 +112,10 @@ END $$;
     pub fn can_handle(&self, stmt: &Statement) -> bool {
         match stmt {
             Statement::CreateTable { .. }
-            | Statement::DropObjects { .. }
+            | Statement::DropObjects {
+                object_type: ObjectType::Table,
+                ..
+            }
             | Statement::Delete { .. }
             | Statement::Insert { .. }
             | Statement::Update { .. } => true,
@@ -199,13 +202,14 @@ END $$;
                 }
 
                 self.client.execute(&*stmt.to_string(), &[]).await?;
+                let create_sql = normalize::create_statement(&scx, stmt.clone())?;
                 let name = scx.allocate_name(normalize::object_name(name.clone())?);
                 let desc = RelationDesc::new(typ, names);
                 self.table_types
                     .insert(name.clone(), (sql_types, desc.clone()));
                 Plan::CreateTable {
                     name,
-                    desc,
+                    table: Table { create_sql, desc },
                     if_not_exists: *if_not_exists,
                 }
             }
@@ -173,9 +173,10 @@ impl Action for VerifyAction {
             let reader = Reader::new(file).map_err(|e| format!("creating avro reader: {}", e))?;
             let schema = reader.writer_schema().clone();
             let actual = reader
+                .map(|v| v.map(|v| (None, Some(v))))
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| format!("reading avro values from file: {}", e))?;
-            avro::validate_sink(&schema, &self.expected, &actual)
+            avro::validate_sink(None, &schema, &self.expected, &actual)
         })
     }
 }
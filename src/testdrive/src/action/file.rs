@@ -7,12 +7,18 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::path;
+use std::ffi::OsString;
+use std::os::unix::ffi::OsStringExt;
+use std::path::{self, PathBuf};
+use std::time::Duration;
 
 use async_trait::async_trait;
+use serde_json::Value as JsonValue;
 use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
 
+use ore::retry;
+
 use crate::action::{Action, State};
 use crate::parser::BuiltinCommand;
 
@@ -22,7 +28,11 @@ pub struct AppendAction {
 }
 
 fn build_path(cmd: &mut BuiltinCommand) -> Result<String, String> {
-    let path = cmd.args.string("path")?;
+    build_path_arg(cmd, "path")
+}
+
+fn build_path_arg(cmd: &mut BuiltinCommand, name: &str) -> Result<String, String> {
+    let path = cmd.args.string(name)?;
     if path.contains(path::MAIN_SEPARATOR) {
         // The goal isn't security, but preventing mistakes.
         Err("separators in paths are forbidden".into())
@@ -86,3 +96,161 @@ impl Action for DeleteAction {
             .map_err(|e| e.to_string())
     }
 }
+
+pub struct RenameAction {
+    path: String,
+    to: String,
+}
+
+pub fn build_rename(mut cmd: BuiltinCommand) -> Result<RenameAction, String> {
+    let path = build_path(&mut cmd)?;
+    let to = build_path_arg(&mut cmd, "to")?;
+    cmd.args.done()?;
+    Ok(RenameAction { path, to })
+}
+
+#[async_trait]
+impl Action for RenameAction {
+    async fn undo(&self, _: &mut State) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn redo(&self, state: &mut State) -> Result<(), String> {
+        let from = state.temp_dir.path().join(&self.path);
+        let to = state.temp_dir.path().join(&self.to);
+        println!("Renaming file {} to {}", from.display(), to.display());
+        tokio::fs::rename(&from, &to)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+pub enum SinkFormat {
+    Json,
+    Csv,
+}
+
+pub struct VerifyAction {
+    sink: String,
+    format: SinkFormat,
+    files: Option<usize>,
+    expected: Vec<String>,
+}
+
+pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, String> {
+    let sink = cmd.args.string("sink")?;
+    let format = match cmd.args.string("format")?.as_str() {
+        "json" => SinkFormat::Json,
+        "csv" => SinkFormat::Csv,
+        f => return Err(format!("unknown format: {}", f)),
+    };
+    let files = cmd
+        .args
+        .opt_string("files")
+        .map(|files| files.parse().map_err(|e| format!("parsing files: {}", e)))
+        .transpose()?;
+    let expected = cmd
+        .input
+        .iter()
+        .map(|line| normalize_record(&format, line, false))
+        .collect::<Result<_, _>>()?;
+    cmd.args.done()?;
+    Ok(VerifyAction {
+        sink,
+        format,
+        files,
+        expected,
+    })
+}
+
+#[async_trait]
+impl Action for VerifyAction {
+    async fn undo(&self, _state: &mut State) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn redo(&self, state: &mut State) -> Result<(), String> {
+        let path = retry::retry_for(Duration::from_secs(8), |_| async {
+            let row = state
+                .pgclient
+                .query_one(
+                    "SELECT path FROM mz_catalog_names NATURAL JOIN mz_file_sinks \
+                     WHERE name = $1",
+                    &[&self.sink],
+                )
+                .await
+                .map_err(|e| format!("querying materialize: {}", e.to_string()))?;
+            let bytes: Vec<u8> = row.get("path");
+            Ok::<_, String>(PathBuf::from(OsString::from_vec(bytes)))
+        })
+        .await
+        .map_err(|e| format!("retrieving path: {:?}", e))?;
+
+        println!("Verifying results in files starting at {}", path.display());
+
+        // The sink writes its files asynchronously, so retry until they
+        // contain the expected records.
+        retry::retry_for(Duration::from_secs(8), |_| async {
+            let mut actual = vec![];
+            let mut files = 0;
+            let mut file_path = path.clone();
+            loop {
+                let contents = match tokio::fs::read_to_string(&file_path).await {
+                    Ok(contents) => contents,
+                    Err(e) if files > 0 && e.kind() == std::io::ErrorKind::NotFound => break,
+                    Err(e) => return Err(format!("reading {}: {}", file_path.display(), e)),
+                };
+                for line in contents.lines() {
+                    actual.push(normalize_record(&self.format, line, true)?);
+                }
+                files += 1;
+                let mut next_path = path.clone().into_os_string();
+                next_path.push(format!(".{}", files));
+                file_path = PathBuf::from(next_path);
+            }
+            if actual != self.expected {
+                return Err(format!(
+                    "records did not match\nexpected:\n{}\n\nactual:\n{}",
+                    self.expected.join("\n"),
+                    actual.join("\n")
+                ));
+            }
+            match self.files {
+                Some(expected) if expected != files => {
+                    Err(format!("expected {} files, but found {}", expected, files))
+                }
+                _ => Ok(()),
+            }
+        })
+        .await
+    }
+}
+
+/// Normalizes a record written by a file or Kinesis sink, or an expected
+/// record, so that the two can be compared.
+///
+/// JSON records are rendered compactly. As the timestamp of each update is not
+/// deterministic, it is removed from records written by the sink, and should
+/// be omitted from expected records.
+pub fn normalize_record(format: &SinkFormat, record: &str, actual: bool) -> Result<String, String> {
+    match format {
+        SinkFormat::Json => {
+            let mut value: JsonValue = serde_json::from_str(record)
+                .map_err(|e| format!("parsing record as json: {}", e))?;
+            if actual {
+                if let JsonValue::Object(object) = &mut value {
+                    object.remove("timestamp");
+                }
+            }
+            Ok(value.to_string())
+        }
+        SinkFormat::Csv if actual => {
+            // Strip the leading timestamp field.
+            match record.find(|c: char| !c.is_ascii_digit()) {
+                Some(i) => Ok(record[i + 1..].to_owned()),
+                None => Err(format!("record is missing its timestamp: {}", record)),
+            }
+        }
+        SinkFormat::Csv => Ok(record.to_owned()),
+    }
+}
@@ -16,6 +16,8 @@ use futures::stream::{FuturesUnordered, StreamExt};
 use rdkafka::producer::FutureRecord;
 use serde::de::DeserializeOwned;
 
+use ccsr::SchemaType;
+
 use crate::action::{Action, State};
 use crate::format::avro::{self, Schema};
 use crate::format::protobuf::{self, ToMessage};
@@ -39,9 +41,17 @@ enum Format {
 }
 
 enum Transcoder {
-    Avro { schema: Schema, schema_id: i32 },
-    Protobuf { message: protobuf::MessageType },
-    Bytes { terminator: Option<u8> },
+    Avro {
+        schema: Schema,
+        schema_id: i32,
+    },
+    Protobuf {
+        message: protobuf::MessageType,
+        schema_id: Option<i32>,
+    },
+    Bytes {
+        terminator: Option<u8>,
+    },
 }
 
 impl Transcoder {
@@ -81,7 +91,7 @@ impl Transcoder {
                     return Ok(None);
                 }
             }
-            Transcoder::Protobuf { message } => {
+            Transcoder::Protobuf { message, schema_id } => {
                 let val: protobuf::DynMessage = match message {
                     protobuf::MessageType::Batch => {
                         let decoded = Self::decode_json::<_, protobuf::native::Batch>(row)?;
@@ -100,6 +110,24 @@ impl Transcoder {
                         }
                     }
                 };
+                if let Some(schema_id) = schema_id {
+                    // Messages whose schema is published to the registry are
+                    // framed like Avro messages, followed by the path to the
+                    // message type within the schema, as a count and indexes
+                    // in zigzag varint encoding. The path to the first message
+                    // type is abbreviated to a single 0.
+                    //
+                    // https://docs.confluent.io/current/schema-registry/serdes-develop/index.html#wire-format
+                    out.write_u8(0).unwrap();
+                    out.write_i32::<NetworkEndian>(*schema_id).unwrap();
+                    match message.index() {
+                        0 => out.write_u8(0).unwrap(),
+                        i => {
+                            write_zigzag_varint(&mut out, 1);
+                            write_zigzag_varint(&mut out, i);
+                        }
+                    }
+                }
                 val.write_to_vec(&mut out).map_err(|e| e.to_string())?;
             }
             Transcoder::Bytes { terminator } => match terminator {
@@ -116,6 +144,15 @@ impl Transcoder {
     }
 }
 
+fn write_zigzag_varint(out: &mut Vec<u8>, n: i32) {
+    let mut n = ((n << 1) ^ (n >> 31)) as u32;
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
 pub fn build_ingest(mut cmd: BuiltinCommand) -> Result<IngestAction, String> {
     let topic_prefix = format!("testdrive-{}", cmd.args.string("topic")?);
     let partition = cmd.args.opt_parse::<i32>("partition")?.unwrap_or(0);
@@ -197,7 +234,7 @@ impl Action for IngestAction {
                     let schema_id = if self.publish {
                         let ccsr_subject = format!("{}-{}", topic_name, typ);
                         let schema_id = ccsr_client
-                            .publish_schema(&ccsr_subject, &schema)
+                            .publish_schema(&ccsr_subject, &schema, SchemaType::Avro)
                             .await
                             .map_err(|e| format!("schema registry error: {}", e))?;
                         schema_id
@@ -208,7 +245,19 @@ impl Action for IngestAction {
                         .map_err(|e| format!("parsing avro schema: {}", e))?;
                     Ok::<_, String>(Transcoder::Avro { schema, schema_id })
                 }
-                Format::Protobuf { message } => Ok(Transcoder::Protobuf { message }),
+                Format::Protobuf { message } => {
+                    let schema_id = if self.publish {
+                        let ccsr_subject = format!("{}-{}", topic_name, typ);
+                        let schema_id = ccsr_client
+                            .publish_schema(&ccsr_subject, message.schema(), SchemaType::Protobuf)
+                            .await
+                            .map_err(|e| format!("schema registry error: {}", e))?;
+                        Some(schema_id)
+                    } else {
+                        None
+                    };
+                    Ok(Transcoder::Protobuf { message, schema_id })
+                }
                 Format::Bytes { terminator } => Ok(Transcoder::Bytes { terminator }),
             }
         };
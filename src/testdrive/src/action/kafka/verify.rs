@@ -14,6 +14,10 @@ use byteorder::{BigEndian, ByteOrder};
 use rdkafka::config::ClientConfig;
 use rdkafka::consumer::{Consumer, StreamConsumer};
 use rdkafka::message::Message;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use serde_protobuf::de::Deserializer;
+use serde_protobuf::descriptor::Descriptors;
 use tokio::stream::StreamExt;
 
 use ore::retry;
@@ -22,17 +26,34 @@ use crate::action::{Action, State};
 use crate::format::avro;
 use crate::parser::BuiltinCommand;
 
+enum SinkFormat {
+    Avro,
+    Protobuf { message: String },
+    Json,
+    Csv,
+}
+
 pub struct VerifyAction {
+    format: SinkFormat,
     sink: String,
     expected_messages: Vec<String>,
 }
 
 pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, String> {
-    let _format = cmd.args.string("format")?;
+    let format = match cmd.args.string("format")?.as_str() {
+        "avro" => SinkFormat::Avro,
+        "protobuf" => SinkFormat::Protobuf {
+            message: format!(".{}", cmd.args.string("message")?),
+        },
+        "json" => SinkFormat::Json,
+        "csv" => SinkFormat::Csv,
+        f => return Err(format!("unknown format: {}", f)),
+    };
     let sink = cmd.args.string("sink")?;
     let expected_messages = cmd.input;
     cmd.args.done()?;
     Ok(VerifyAction {
+        format,
         sink,
         expected_messages,
     })
@@ -61,22 +82,11 @@ impl Action for VerifyAction {
 
         println!("Verifying results in Kafka topic {}", topic);
 
-        let schema = state
-            .ccsr_client
-            .get_schema_by_subject(&format!("{}-value", topic))
-            .await
-            .map_err(|e| format!("fetching schema: {}", e))?
-            .raw;
-
         let mut config = ClientConfig::new();
         config.set("bootstrap.servers", &state.kafka_url);
         config.set("auto.offset.reset", "earliest");
         config.set("group.id", "materialize-testdrive");
 
-        let schema =
-            avro::parse_schema(&schema).map_err(|e| format!("parsing avro schema: {}", e))?;
-        let schema = &schema;
-
         let consumer: StreamConsumer = config
             .create()
             .map_err(|e| format!("creating kafka consumer: {}", e))?;
@@ -88,7 +98,7 @@ impl Action for VerifyAction {
             .take(self.expected_messages.len())
             .timeout(Duration::from_secs(15));
 
-        let mut actual_messages = vec![];
+        let mut messages = vec![];
 
         // Collect all messages that arrive without timing out. If we trip
         // the timeout, suppress the error and return what we have. This
@@ -97,34 +107,337 @@ impl Action for VerifyAction {
         // were missing.
         while let Some(Ok(message)) = message_stream.next().await {
             let message = message.map_err(|e| e.to_string())?;
+            messages.push((
+                message.key().map(|k| k.to_vec()),
+                message.payload().map(|p| p.to_vec()),
+            ));
+        }
 
-            let mut bytes = match message.payload() {
+        match self.format {
+            SinkFormat::Avro => self.verify_avro(state, &topic, messages).await,
+            SinkFormat::Protobuf { ref message } => {
+                self.verify_protobuf(state, &topic, message, messages).await
+            }
+            SinkFormat::Json | SinkFormat::Csv => {
+                let actual_messages = messages
+                    .iter()
+                    .map(|(key, payload)| self.render_text_message(key, payload))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let expected_messages = self
+                    .expected_messages
+                    .iter()
+                    .map(|m| self.normalize_expected(m))
+                    .collect::<Result<Vec<_>, _>>()?;
+                validate_text_messages(&expected_messages, &actual_messages)
+            }
+        }
+    }
+}
+
+impl VerifyAction {
+    async fn verify_avro(
+        &self,
+        state: &mut State,
+        topic: &str,
+        messages: Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>,
+    ) -> Result<(), String> {
+        let value_schema = state
+            .ccsr_client
+            .get_schema_by_subject(&format!("{}-value", topic))
+            .await
+            .map_err(|e| format!("fetching schema: {}", e))?
+            .raw;
+        let value_schema =
+            avro::parse_schema(&value_schema).map_err(|e| format!("parsing avro schema: {}", e))?;
+
+        // Keyed sinks publish a key schema alongside the value schema.
+        let key_schema = match state
+            .ccsr_client
+            .get_schema_by_subject(&format!("{}-key", topic))
+            .await
+        {
+            Ok(schema) => Some(
+                avro::parse_schema(&schema.raw)
+                    .map_err(|e| format!("parsing avro key schema: {}", e))?,
+            ),
+            Err(ccsr::GetBySubjectError::SubjectNotFound) => None,
+            Err(e) => return Err(format!("fetching key schema: {}", e)),
+        };
+
+        let mut actual_messages = vec![];
+        for (key, payload) in messages {
+            let key = match (&key_schema, key) {
+                (Some(schema), Some(bytes)) => Some(decode_avro(schema, &bytes)?),
+                (Some(_), None) => return Err("empty message key".into()),
+                (None, _) => None,
+            };
+
+            // Upsert sinks represent deletions as messages without a payload.
+            let value = match payload {
+                None if key_schema.is_some() => None,
                 None => return Err("empty message payload".into()),
-                Some(bytes) => bytes,
+                Some(bytes) => Some(decode_avro(&value_schema, &bytes)?),
             };
 
-            if bytes.len() < 5 {
-                return Err(format!(
-                    "avro datum is too few bytes: expected at least 5 bytes, got {}",
-                    bytes.len()
-                ));
+            actual_messages.push((key, value));
+        }
+
+        avro::validate_sink(
+            key_schema.as_ref(),
+            &value_schema,
+            &self.expected_messages,
+            &actual_messages,
+        )
+    }
+
+    /// Verifies Protobuf-encoded messages by rendering them as JSON, with
+    /// the key preceding the payload, and comparing them against the expected
+    /// messages.
+    ///
+    /// Protobuf sinks represent null values by omitting their fields, so
+    /// fields with null values are ignored in both the actual and the expected
+    /// messages.
+    async fn verify_protobuf(
+        &self,
+        state: &mut State,
+        topic: &str,
+        message: &str,
+        messages: Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>,
+    ) -> Result<(), String> {
+        // Materialize does not publish schemas with references.
+        let compile = |schema: &str| {
+            interchange::protobuf::compile_proto_schema(schema, &[])
+                .and_then(|d| interchange::protobuf::decode_descriptors(&d))
+                .map_err(|e| format!("compiling protobuf schema: {}", e))
+        };
+
+        let value_descriptors = compile(
+            &state
+                .ccsr_client
+                .get_schema_by_subject(&format!("{}-value", topic))
+                .await
+                .map_err(|e| format!("fetching schema: {}", e))?
+                .raw,
+        )?;
+
+        // Keyed sinks publish a key schema alongside the value schema.
+        let key_descriptors = match state
+            .ccsr_client
+            .get_schema_by_subject(&format!("{}-key", topic))
+            .await
+        {
+            Ok(schema) => Some(compile(&schema.raw)?),
+            Err(ccsr::GetBySubjectError::SubjectNotFound) => None,
+            Err(e) => return Err(format!("fetching key schema: {}", e)),
+        };
+
+        let mut actual_messages = vec![];
+        for (key, payload) in messages {
+            let mut values = vec![];
+            match (&key_descriptors, key) {
+                (Some(descriptors), Some(bytes)) => {
+                    values.push(decode_protobuf(descriptors, message, &bytes)?)
+                }
+                (Some(_), None) => return Err("empty message key".into()),
+                (None, _) => (),
+            }
+
+            // Upsert sinks represent deletions as messages without a payload.
+            values.push(match payload {
+                None if key_descriptors.is_some() => JsonValue::Null,
+                None => return Err("empty message payload".into()),
+                Some(bytes) => decode_protobuf(&value_descriptors, message, &bytes)?,
+            });
+            actual_messages.push(render_json(&values));
+        }
+
+        let expected_messages = self
+            .expected_messages
+            .iter()
+            .map(|m| {
+                let values = serde_json::Deserializer::from_str(m)
+                    .into_iter::<JsonValue>()
+                    .map(|v| v.map(strip_null_fields))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("parsing expected message as json: {}", e))?;
+                Ok(render_json(&values))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        validate_text_messages(&expected_messages, &actual_messages)
+    }
+
+    /// Renders a JSON- or CSV-encoded message as a line of text that can be
+    /// compared against the expected messages.
+    ///
+    /// For keyed messages, the key precedes the payload. JSON messages are
+    /// rendered compactly, with the nondeterministic `timestamp` field of
+    /// Debezium-style updates removed; CSV messages are rendered as is, with
+    /// the key and payload separated by ` => `. A missing payload is rendered
+    /// as `null` or `<null>`, respectively.
+    fn render_text_message(
+        &self,
+        key: &Option<Vec<u8>>,
+        payload: &Option<Vec<u8>>,
+    ) -> Result<String, String> {
+        let decode = |bytes: &Vec<u8>| {
+            String::from_utf8(bytes.clone())
+                .map_err(|e| format!("decoding message as utf-8: {}", e))
+        };
+        let key = key.as_ref().map(decode).transpose()?;
+        let payload = payload.as_ref().map(decode).transpose()?;
+        match self.format {
+            SinkFormat::Json => {
+                let mut values = vec![];
+                if let Some(key) = key {
+                    values.push(parse_json(&key)?);
+                }
+                values.push(match payload {
+                    None => JsonValue::Null,
+                    Some(payload) => {
+                        let mut value = parse_json(&payload)?;
+                        if let JsonValue::Object(object) = &mut value {
+                            if object.contains_key("before") && object.contains_key("after") {
+                                object.remove("timestamp");
+                            }
+                        }
+                        value
+                    }
+                });
+                Ok(render_json(&values))
             }
-            let magic = bytes[0];
-            let _schema_id = BigEndian::read_i32(&bytes[1..5]);
-            bytes = &bytes[5..];
-
-            if magic != 0 {
-                return Err(format!(
-                    "wrong avro serialization magic: expected 0, got {}",
-                    bytes[0]
-                ));
+            SinkFormat::Csv => {
+                let payload = payload.unwrap_or_else(|| "<null>".into());
+                match key {
+                    None => Ok(payload),
+                    Some(key) => Ok(format!("{} => {}", key, payload)),
+                }
             }
+            SinkFormat::Avro | SinkFormat::Protobuf { .. } => unreachable!(),
+        }
+    }
 
-            let datum = avro::from_avro_datum(schema, &mut bytes)
-                .map_err(|e| format!("from_avro_datum: {}", e.to_string()))?;
-            actual_messages.push(datum);
+    /// Normalizes an expected message so that it is comparable with the
+    /// output of `render_text_message`.
+    fn normalize_expected(&self, expected: &str) -> Result<String, String> {
+        match self.format {
+            SinkFormat::Json => {
+                let values = serde_json::Deserializer::from_str(expected)
+                    .into_iter::<JsonValue>()
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("parsing expected message as json: {}", e))?;
+                Ok(render_json(&values))
+            }
+            SinkFormat::Csv => Ok(expected.to_owned()),
+            SinkFormat::Avro | SinkFormat::Protobuf { .. } => unreachable!(),
         }
+    }
+}
+
+fn parse_json(s: &str) -> Result<JsonValue, String> {
+    serde_json::from_str(s).map_err(|e| format!("parsing message as json: {}", e))
+}
+
+fn render_json(values: &[JsonValue]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-        avro::validate_sink(schema, &self.expected_messages, &actual_messages)
+fn validate_text_messages(expected: &[String], actual: &[String]) -> Result<(), String> {
+    let mut expected = expected.iter();
+    let mut actual = actual.iter();
+    for (i, (e, a)) in (&mut expected).zip(&mut actual).enumerate() {
+        if e != a {
+            return Err(format!(
+                "record {} did not match\nexpected:\n{}\n\nactual:\n{}",
+                i, e, a
+            ));
+        }
+    }
+    let expected: Vec<_> = expected.cloned().collect();
+    let actual: Vec<_> = actual.cloned().collect();
+    if !expected.is_empty() {
+        Err(format!("missing records:\n{}", expected.join("\n")))
+    } else if !actual.is_empty() {
+        Err(format!("extra records:\n{}", actual.join("\n")))
+    } else {
+        Ok(())
+    }
+}
+
+/// Decodes a Confluent-framed Avro datum from `bytes`.
+fn decode_avro(schema: &avro::Schema, mut bytes: &[u8]) -> Result<avro::Value, String> {
+    if bytes.len() < 5 {
+        return Err(format!(
+            "avro datum is too few bytes: expected at least 5 bytes, got {}",
+            bytes.len()
+        ));
+    }
+    let magic = bytes[0];
+    let _schema_id = BigEndian::read_i32(&bytes[1..5]);
+    bytes = &bytes[5..];
+
+    if magic != 0 {
+        return Err(format!(
+            "wrong avro serialization magic: expected 0, got {}",
+            magic
+        ));
+    }
+
+    avro::from_avro_datum(schema, &mut bytes)
+        .map_err(|e| format!("from_avro_datum: {}", e.to_string()))
+}
+
+/// Decodes a Confluent-framed Protobuf message of type `message` from `bytes`,
+/// as JSON.
+fn decode_protobuf(
+    descriptors: &Descriptors,
+    message: &str,
+    bytes: &[u8],
+) -> Result<JsonValue, String> {
+    // Sinks write messages of the first type in their schema, whose path is
+    // abbreviated to a single 0 following the magic byte and schema ID.
+    if bytes.len() < 6 {
+        return Err(format!(
+            "protobuf message is too few bytes: expected at least 6 bytes, got {}",
+            bytes.len()
+        ));
+    }
+    if bytes[0] != 0 {
+        return Err(format!(
+            "wrong protobuf serialization magic: expected 0, got {}",
+            bytes[0]
+        ));
+    }
+    if bytes[5] != 0 {
+        return Err(format!(
+            "unexpected protobuf message index: expected 0, got {}",
+            bytes[5]
+        ));
+    }
+    let input_stream = protobuf::CodedInputStream::from_bytes(&bytes[6..]);
+    let mut deserializer = Deserializer::for_named_message(descriptors, message, input_stream)
+        .map_err(|e| format!("decoding protobuf message: {}", e))?;
+    let value = JsonValue::deserialize(&mut deserializer)
+        .map_err(|e| format!("decoding protobuf message: {}", e))?;
+    Ok(strip_null_fields(value))
+}
+
+/// Removes the fields with null values from the objects in `value`.
+fn strip_null_fields(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(object) => JsonValue::Object(
+            object
+                .into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, strip_null_fields(v)))
+                .collect(),
+        ),
+        JsonValue::Array(array) => {
+            JsonValue::Array(array.into_iter().map(strip_null_fields).collect())
+        }
+        value => value,
     }
 }
@@ -9,31 +9,49 @@
 
 use std::collections::{HashSet, VecDeque};
 use std::str;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use itertools::Itertools;
 use rusoto_kinesis::{GetRecordsInput, Kinesis, KinesisClient};
 
 use aws_util::kinesis::{get_shard_ids, get_shard_iterator};
+use ore::retry;
 
+use crate::action::file::{normalize_record, SinkFormat};
 use crate::action::{Action, State};
 use crate::parser::BuiltinCommand;
 use crate::util::kinesis::DEFAULT_KINESIS_TIMEOUT;
 
 pub struct VerifyAction {
     stream_prefix: String,
+    /// The format of the records, if they were written by a sink.
+    format: Option<SinkFormat>,
     expected_records: HashSet<String>,
 }
 
 pub fn build_verify(mut cmd: BuiltinCommand) -> Result<VerifyAction, String> {
     let stream_prefix = cmd.args.string("stream")?;
-    let expected_records: HashSet<String> = cmd.input.into_iter().collect();
+    let format = match cmd.args.opt_string("format").as_deref() {
+        None | Some("bytes") => None,
+        Some("json") => Some(SinkFormat::Json),
+        Some("csv") => Some(SinkFormat::Csv),
+        Some(f) => return Err(format!("unknown format: {}", f)),
+    };
+    let expected_records = match &format {
+        None => cmd.input.into_iter().collect(),
+        Some(format) => cmd
+            .input
+            .iter()
+            .map(|record| normalize_record(format, record, false))
+            .collect::<Result<_, _>>()?,
+    };
 
     cmd.args.done()?;
 
     Ok(VerifyAction {
         stream_prefix,
+        format,
         expected_records,
     })
 }
@@ -47,59 +65,78 @@ impl Action for VerifyAction {
     async fn redo(&self, state: &mut State) -> Result<(), String> {
         let stream_name = format!("testdrive-{}-{}", self.stream_prefix, state.seed);
 
-        let mut shard_iterators = get_shard_iterators(&state.kinesis_client, &stream_name).await?;
-        let timer = Instant::now();
-        let mut records: HashSet<String> = HashSet::new();
-        while let Some(iterator) = shard_iterators.pop_front() {
-            if let Some(iterator) = &iterator {
-                let output = state
-                    .kinesis_client
-                    .get_records(GetRecordsInput {
-                        limit: None,
-                        shard_iterator: iterator.clone(),
-                    })
-                    .await
-                    .map_err(|e| format!("getting Kinesis records: {}", e))?;
-                for record in output.records {
-                    records.insert(
-                        String::from_utf8(record.data.to_vec()).map_err(|e| {
-                            format!("converting Kinesis record bytes to utf8: {}", e)
-                        })?,
-                    );
-                }
-                match output.millis_behind_latest {
-                    // Test hack!
-                    // Assume all records have already been written to the stream. Once you've
-                    // caught up, you're done with that shard.
-                    // NOTE: this is not true for real Kinesis streams as data could still be
-                    // arriving.
-                    Some(0) => (),
-                    _ => shard_iterators.push_back(output.next_shard_iterator),
-                };
-                if timer.elapsed() > DEFAULT_KINESIS_TIMEOUT {
-                    // Unable to read all Kinesis records in the default
-                    // time allotted -- fail.
-                    return Err(format!(
-                        "timeout reading from Kinesis stream: {}",
-                        stream_name
-                    ));
-                }
+        // Sinks write to the stream asynchronously, so retry until the stream
+        // contains the expected records.
+        retry::retry_for(Duration::from_secs(8), |_| async {
+            let records = read_records(&state.kinesis_client, &stream_name).await?;
+            let records = match &self.format {
+                None => records,
+                Some(format) => records
+                    .iter()
+                    .map(|record| normalize_record(format, record, true))
+                    .collect::<Result<_, _>>()?,
+            };
+
+            // For now, we don't guarantee any type of ordering!
+            if records != self.expected_records {
+                let missing_records = &self.expected_records - &records;
+                let extra_records = &records - &self.expected_records;
+                return Err(format!(
+                    "kinesis records did not match:\nmissing:\n{}\nextra:\n{}",
+                    missing_records.iter().join("\n"),
+                    extra_records.iter().join("\n")
+                ));
             }
-        }
 
-        // For now, we don't guarantee any type of ordering!
-        if records != self.expected_records {
-            let missing_records = &self.expected_records - &records;
-            let extra_records = &records - &self.expected_records;
-            return Err(format!(
-                "kinesis records did not match:\nmissing:\n{}\nextra:\n{}",
-                missing_records.iter().join("\n"),
-                extra_records.iter().join("\n")
-            ));
-        }
+            Ok(())
+        })
+        .await
+    }
+}
 
-        Ok(())
+/// Reads all of the records in the Kinesis stream named `stream_name`.
+async fn read_records(
+    kinesis_client: &KinesisClient,
+    stream_name: &str,
+) -> Result<HashSet<String>, String> {
+    let mut shard_iterators = get_shard_iterators(kinesis_client, stream_name).await?;
+    let timer = Instant::now();
+    let mut records: HashSet<String> = HashSet::new();
+    while let Some(iterator) = shard_iterators.pop_front() {
+        if let Some(iterator) = &iterator {
+            let output = kinesis_client
+                .get_records(GetRecordsInput {
+                    limit: None,
+                    shard_iterator: iterator.clone(),
+                })
+                .await
+                .map_err(|e| format!("getting Kinesis records: {}", e))?;
+            for record in output.records {
+                records.insert(
+                    String::from_utf8(record.data.to_vec())
+                        .map_err(|e| format!("converting Kinesis record bytes to utf8: {}", e))?,
+                );
+            }
+            match output.millis_behind_latest {
+                // Test hack!
+                // Assume all records have already been written to the stream. Once you've
+                // caught up, you're done with that shard.
+                // NOTE: this is not true for real Kinesis streams as data could still be
+                // arriving.
+                Some(0) => (),
+                _ => shard_iterators.push_back(output.next_shard_iterator),
+            };
+            if timer.elapsed() > DEFAULT_KINESIS_TIMEOUT {
+                // Unable to read all Kinesis records in the default
+                // time allotted -- fail.
+                return Err(format!(
+                    "timeout reading from Kinesis stream: {}",
+                    stream_name
+                ));
+            }
+        }
     }
+    Ok(records)
 }
 
 async fn get_shard_iterators(
@@ -89,6 +89,13 @@ impl Action for SqlAction {
                 )
                 .await
             }
+            Statement::CreateRole { name, .. } => {
+                self.try_drop(
+                    &mut state.pgclient,
+                    &format!("DROP ROLE IF EXISTS {}", name),
+                )
+                .await
+            }
             _ => Ok(()),
         }
     }
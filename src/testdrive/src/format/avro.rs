@@ -70,6 +70,12 @@ pub fn from_json(json: &JsonValue, schema: SchemaNode) -> Result<Value, String>
                 .map(|x| from_json(x, schema.step(&**inner)))
                 .collect::<Result<_, _>>()?,
         )),
+        (JsonValue::Object(items), SchemaPiece::Map(inner)) => Ok(Value::Map(
+            items
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), from_json(v, schema.step(&**inner))?)))
+                .collect::<Result<_, String>>()?,
+        )),
         (JsonValue::String(s), SchemaPiece::String) => Ok(Value::String(s.clone())),
         (
             JsonValue::Array(items),
@@ -132,7 +138,17 @@ pub fn from_json(json: &JsonValue, schema: SchemaNode) -> Result<Value, String>
     }
 }
 
-pub fn validate_sink<I>(schema: &Schema, expected: I, actual: &[Value]) -> Result<(), String>
+/// Validates the messages written by a sink against the expected messages.
+///
+/// If the sink has a key schema, each expected message is a JSON key followed
+/// by a JSON value, where a `null` value indicates a tombstone. Otherwise each
+/// expected message is just a JSON value.
+pub fn validate_sink<I>(
+    key_schema: Option<&Schema>,
+    value_schema: &Schema,
+    expected: I,
+    actual: &[(Option<Value>, Option<Value>)],
+) -> Result<(), String>
 where
     I: IntoIterator,
     I::Item: AsRef<str>,
@@ -140,9 +156,25 @@ where
     let expected = expected
         .into_iter()
         .map(|v| {
-            let json = &serde_json::from_str(v.as_ref())
-                .map_err(|e| format!("parsing expected avro datum as json: {}", e.to_string()))?;
-            Ok(from_json(json, schema.top_node())?)
+            let mut deserializer =
+                serde_json::Deserializer::from_str(v.as_ref()).into_iter::<JsonValue>();
+            let mut next_json = || -> Result<JsonValue, String> {
+                match deserializer.next() {
+                    None => Err("missing expected avro datum".into()),
+                    Some(json) => json.map_err(|e| {
+                        format!("parsing expected avro datum as json: {}", e.to_string())
+                    }),
+                }
+            };
+            let key = match key_schema {
+                None => None,
+                Some(schema) => Some(from_json(&next_json()?, schema.top_node())?),
+            };
+            let value = match next_json()? {
+                JsonValue::Null if key_schema.is_some() => None,
+                json => Some(from_json(&json, value_schema.top_node())?),
+            };
+            Ok((key, value))
         })
         .collect::<Result<Vec<_>, String>>()?;
     let mut expected = expected.iter();
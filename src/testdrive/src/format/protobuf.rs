@@ -38,6 +38,25 @@ pub enum MessageType {
     Struct,
 }
 
+impl MessageType {
+    /// Returns the text of the `.proto` file that defines the message type.
+    pub fn schema(self) -> &'static str {
+        match self {
+            MessageType::Batch => include_str!("protobuf/billing.proto"),
+            MessageType::Struct => include_str!("protobuf/simple.proto"),
+        }
+    }
+
+    /// Returns the index of the message type among the top-level messages of
+    /// the file returned by [`MessageType::schema`].
+    pub fn index(self) -> i32 {
+        match self {
+            MessageType::Batch => 2,
+            MessageType::Struct => 0,
+        }
+    }
+}
+
 impl FromStr for MessageType {
     type Err = String;
 
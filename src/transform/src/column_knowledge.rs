@@ -75,6 +75,12 @@ impl ColumnKnowledge {
                 }
                 body_knowledge
             }
+            RelationExpr::LetRec { value, body, .. } => {
+                // Knowledge about a recursive value depends on knowledge of
+                // the value itself, so we only rely on its type in `body`.
+                ColumnKnowledge::harvest(value, knowledge)?;
+                ColumnKnowledge::harvest(body, knowledge)?
+            }
             RelationExpr::Project { input, outputs } => {
                 let input_knowledge = ColumnKnowledge::harvest(input, knowledge)?;
                 outputs
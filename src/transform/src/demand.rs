@@ -73,6 +73,19 @@ impl Demand {
 
                 self.action(value, needs, gets);
             }
+            RelationExpr::LetRec { id, value, body } => {
+                // The value of a recursive binding reads its own columns in
+                // ways we do not track, so all of its columns are demanded.
+                let id = Id::Local(*id);
+                let prior = gets.insert(id, HashSet::new());
+                self.action(body, columns, gets);
+                let arity = value.arity();
+                self.action(value, (0..arity).collect(), gets);
+                gets.remove(&id);
+                if let Some(prior) = prior {
+                    gets.insert(id, prior);
+                }
+            }
             RelationExpr::Project { input, outputs } => {
                 self.action(
                     input,
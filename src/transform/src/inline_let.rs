@@ -74,6 +74,19 @@ impl InlineLet {
             *relation = body.take_safely();
             // might be another Let in the body so have to recur here
             self.action(relation, lets);
+        } else if let RelationExpr::LetRec { value, body, .. } = relation {
+            // Bindings in a recursive value may refer to the recursive binding,
+            // so they are lifted only to the top of the value.
+            let mut value_lets = vec![];
+            self.action(value, &mut value_lets);
+            for (id, let_value) in value_lets.into_iter().rev() {
+                **value = RelationExpr::Let {
+                    id,
+                    value: Box::new(let_value),
+                    body: Box::new(value.take_safely()),
+                };
+            }
+            self.action(body, lets);
         } else {
             relation.visit1_mut(|child| self.action(child, lets));
         }
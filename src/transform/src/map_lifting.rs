@@ -139,6 +139,15 @@ impl LiteralLifting {
                 gets.remove(&id);
                 result
             }
+            RelationExpr::LetRec { value, body, .. } => {
+                // Literals cannot be lifted out of a recursive value, as the
+                // value must continue to match the type of its `Get`s.
+                let literals = self.action(value, gets);
+                if !literals.is_empty() {
+                    **value = value.take_dangerous().map(literals);
+                }
+                self.action(body, gets)
+            }
             RelationExpr::Project { input, outputs } => {
                 // We do not want to lift literals around projections.
                 // Projections are the highest lifted operator and lifting
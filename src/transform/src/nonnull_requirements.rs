@@ -76,6 +76,18 @@ impl NonNullRequirements {
                     self.action(value, need, gets);
                 }
             }
+            RelationExpr::LetRec { id, value, body } => {
+                // Requirements cannot be pushed at a recursive value, as its
+                // `Get`s would observe the filtered collection.
+                let id = Id::Local(*id);
+                let prior = gets.insert(id, Vec::new());
+                self.action(body, columns, gets);
+                self.action(value, HashSet::new(), gets);
+                gets.remove(&id);
+                if let Some(prior) = prior {
+                    gets.insert(id, prior);
+                }
+            }
             RelationExpr::Project { input, outputs } => {
                 self.action(
                     input,
@@ -117,7 +129,8 @@ impl NonNullRequirements {
                     | TableFunc::JsonbArrayElements { .. }
                     | TableFunc::GenerateSeries(_)
                     | TableFunc::RegexpExtract(_)
-                    | TableFunc::CsvExtract(_) => {
+                    | TableFunc::CsvExtract(_)
+                    | TableFunc::UnnestList(_) => {
                         for expr in exprs {
                             expr.non_null_requirements(&mut columns);
                         }
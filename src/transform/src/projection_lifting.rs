@@ -63,6 +63,12 @@ impl ProjectionLifting {
                 self.action(body, gets);
                 gets.remove(&id);
             }
+            RelationExpr::LetRec { value, body, .. } => {
+                // Projections cannot be lifted out of a recursive value, as
+                // the value must continue to match the type of its `Get`s.
+                self.action(value, gets);
+                self.action(body, gets);
+            }
             RelationExpr::Project { input, outputs } => {
                 self.action(input, gets);
                 if let RelationExpr::Project {
@@ -41,11 +41,18 @@ impl ReduceElision {
             aggregates,
         } = relation
         {
+            // Window functions produce a list of records even for a single
+            // row, and so are not the identity on singleton groups.
+            let has_window = aggregates
+                .iter()
+                .any(|a| matches!(a.func, expr::AggregateFunc::Window { .. }));
             let input_type = input.typ();
-            if input_type.keys.iter().any(|keys| {
-                keys.iter()
-                    .all(|k| group_key.contains(&crate::ScalarExpr::Column(*k)))
-            }) {
+            if !has_window
+                && input_type.keys.iter().any(|keys| {
+                    keys.iter()
+                        .all(|k| group_key.contains(&crate::ScalarExpr::Column(*k)))
+                })
+            {
                 use expr::{AggregateFunc, UnaryFunc};
                 use repr::Datum;
                 let map_scalars = aggregates
@@ -42,6 +42,7 @@ impl FoldConstants {
             RelationExpr::Constant { .. } => { /* handled after match */ }
             RelationExpr::Get { .. } => {}
             RelationExpr::Let { .. } => { /* constant prop done in InlineLet */ }
+            RelationExpr::LetRec { .. } => {}
             RelationExpr::Reduce {
                 input,
                 group_key,
@@ -58,6 +58,33 @@ impl UpdateLet {
                 }
                 *id = new_id;
             }
+            RelationExpr::LetRec { id, value, body } => {
+                // Unlike `Let`, the binding is visible in `value`, where its
+                // `Get`s retain their declared type.
+                let mut declared = None;
+                value.visit(&mut |e| {
+                    if let RelationExpr::Get {
+                        id: Id::Local(get_id),
+                        typ,
+                    } = e
+                    {
+                        if get_id == id {
+                            declared = Some(typ.clone());
+                        }
+                    }
+                });
+                let new_id = LocalId::new(id_gen.allocate_id());
+                let declared = declared.unwrap_or_else(|| value.typ());
+                let prev = remap.insert(id.clone(), (new_id, declared));
+                self.action(value, remap, id_gen);
+                remap.insert(id.clone(), (new_id, value.typ()));
+                self.action(body, remap, id_gen);
+                remap.remove(id);
+                if let Some(prev_stuff) = prev {
+                    remap.insert(id.clone(), prev_stuff);
+                }
+                *id = new_id;
+            }
             RelationExpr::Get { id, typ } => {
                 if let Id::Local(local_id) = id {
                     if let Some((new_id, new_type)) = remap.get(local_id) {